@@ -0,0 +1,94 @@
+//! Constructors of the auction calls, so callers don't have to spell out the runtime's call
+//! enums.
+
+use auction_traits::auction::{AuctionParameters, AuctionUpdate, NewAuction, TerminalCapacity};
+use node_auction_runtime::{
+    auction, AccountId, AuctionId, Balance, BlockNumber, Call, GeneralInformationContainer,
+};
+
+type Info = GeneralInformationContainer;
+
+/// Puts the slot of `terminal` up for auction between blocks `start` and `end`.
+pub fn new_auction(
+    terminal: AccountId,
+    num_con: Info,
+    num_teu: Info,
+    timestamp: Info,
+    start: BlockNumber,
+    end: BlockNumber,
+) -> Call {
+    Call::AuctionModule(auction::Call::ext_new_auction(
+        terminal, num_con, num_teu, timestamp, start, end,
+    ))
+}
+
+/// Changes the given details of an auction, `None` keeps the current value.
+pub fn update_auction(
+    id: AuctionId,
+    timestamp: Option<Info>,
+    num_con: Option<Info>,
+    num_teu: Option<Info>,
+    start: Option<BlockNumber>,
+    end: Option<BlockNumber>,
+) -> Call {
+    Call::AuctionModule(auction::Call::ext_update_auction(
+        id, timestamp, num_con, num_teu, start, end,
+    ))
+}
+
+/// Creates every auction of `auctions`, or none of them if one can't be created.
+pub fn new_auctions_batch(auctions: Vec<NewAuction<AccountId, BlockNumber, Info>>) -> Call {
+    Call::AuctionModule(auction::Call::ext_new_auctions_batch(auctions))
+}
+
+/// Applies every update of `updates` in order, or none of them if one fails.
+pub fn update_auctions_batch(updates: Vec<AuctionUpdate<AuctionId, BlockNumber, Info>>) -> Call {
+    Call::AuctionModule(auction::Call::ext_update_auctions_batch(updates))
+}
+
+pub fn remove_auction(id: AuctionId) -> Call {
+    Call::AuctionModule(auction::Call::ext_remove_auction(id))
+}
+
+pub fn bid(id: AuctionId, value: Balance) -> Call {
+    Call::AuctionModule(auction::Call::bid(id, value))
+}
+
+/// Confirms winning auction `id`, needed when the runtime sets a confirmation period.
+pub fn confirm_win(id: AuctionId) -> Call {
+    Call::AuctionModule(auction::Call::confirm_win(id))
+}
+
+/// Offers won slot `slot` for a fixed `price`.
+pub fn list_slot(slot: AuctionId, price: Balance) -> Call {
+    Call::AuctionModule(auction::Call::list_slot(slot, price))
+}
+
+/// Puts won slot `slot` up for auction again between blocks `start` and `end`.
+pub fn relist_slot(slot: AuctionId, start: BlockNumber, end: BlockNumber) -> Call {
+    Call::AuctionModule(auction::Call::relist_slot(slot, start, end))
+}
+
+pub fn unlist_slot(slot: AuctionId) -> Call {
+    Call::AuctionModule(auction::Call::unlist_slot(slot))
+}
+
+/// Buys won slot `slot`, `price` has to match the listed price.
+pub fn buy_slot(slot: AuctionId, price: Balance) -> Call {
+    Call::AuctionModule(auction::Call::buy_slot(slot, price))
+}
+
+/// Declares the berth capacity of the signing terminal, `None` withdraws it.
+pub fn set_terminal_capacity(capacity: Option<TerminalCapacity<Info>>) -> Call {
+    Call::AuctionModule(auction::Call::set_terminal_capacity(capacity))
+}
+
+/// Replaces the auction parameters. Needs root, see `sudo`.
+pub fn set_parameters(parameters: AuctionParameters<Balance, BlockNumber>) -> Call {
+    Call::AuctionModule(auction::Call::set_parameters(parameters))
+}
+
+/// Dispatches `call` as root, signed by the sudo key.
+pub fn sudo(call: Call) -> Call {
+    Call::Sudo(sudo::Call::sudo(Box::new(call)))
+}
@@ -0,0 +1,65 @@
+//! Errors of the client.
+
+use jsonrpc_core_client::RpcError;
+use node_auction_runtime::Hash;
+use sp_runtime::DispatchError;
+
+/// Result type of the client.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong talking to a node.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+    /// The node couldn't be reached or refused the request.
+    #[display(fmt = "RPC error: {}", _0)]
+    Rpc(RpcError),
+    /// The node answered with data that doesn't decode into the runtime types.
+    #[display(fmt = "Decoding failed: {}", _0)]
+    Codec(codec::Error),
+    /// The node's storage proof doesn't hold against the trusted header.
+    #[display(fmt = "{}", _0)]
+    Proof(auction_proof::Error),
+    /// The secret URI doesn't describe a key.
+    #[from(ignore)]
+    #[display(fmt = "Invalid secret URI `{}`", _0)]
+    InvalidSuri(String),
+    /// The extrinsic was included, but its dispatch failed.
+    #[display(fmt = "Dispatch failed in block {:?}: {:?}", block, error)]
+    Dispatch { block: Hash, error: DispatchError },
+    /// The transaction pool threw the extrinsic out.
+    #[display(fmt = "The extrinsic was dropped from the pool")]
+    Dropped,
+    /// The transaction pool found the extrinsic invalid.
+    #[display(fmt = "The extrinsic is invalid")]
+    Invalid,
+    /// Another extrinsic with the same sender and nonce took its place.
+    #[from(ignore)]
+    #[display(fmt = "The extrinsic was replaced by {:?}", _0)]
+    Usurped(Hash),
+    /// The block holding the extrinsic wasn't finalized in time.
+    #[from(ignore)]
+    #[display(fmt = "Block {:?} wasn't finalized in time", _0)]
+    FinalityTimeout(Hash),
+    /// The node stopped reporting on the extrinsic before it reached the awaited state.
+    #[display(fmt = "The extrinsic subscription closed early")]
+    SubscriptionClosed,
+    /// The node doesn't know a block it reported itself.
+    #[from(ignore)]
+    #[display(fmt = "Block {:?} is unknown to the node", _0)]
+    UnknownBlock(Hash),
+    /// The extrinsic isn't in the block the node reported it in.
+    #[from(ignore)]
+    #[display(fmt = "The extrinsic isn't part of block {:?}", _0)]
+    ExtrinsicNotFound(Hash),
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Rpc(e) => Some(e),
+            Error::Codec(e) => Some(e),
+            Error::Proof(e) => Some(e),
+            _ => None,
+        }
+    }
+}
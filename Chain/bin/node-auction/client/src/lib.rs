@@ -0,0 +1,463 @@
+//! Typed client for `node-auction`.
+//!
+//! Wraps the `auctionInformation_*` RPC methods, builds and signs auction extrinsics and follows
+//! them through the transaction pool until they're included or finalized, decoding the events
+//! they emitted on the way.
+//!
+//! `Client::proven_auction` and `Client::proven_queued_bids` read auction state along with a
+//! storage proof and check it against a header the caller trusts, rather than trusting the node.
+//!
+//! ```no_run
+//! use node_auction_client::{calls, Client, Signer, WaitFor};
+//!
+//! let client = Client::connect("ws://127.0.0.1:9944").unwrap();
+//! let barge = Signer::from_suri("//Bob").unwrap();
+//! if let Some(auction) = client.auction(0, None).unwrap() {
+//!     let call = calls::bid(0, auction.bid.map_or(1, |(_, bid)| bid + 1));
+//!     let events = client.submit_and_watch(&barge, call, WaitFor::InBlock).unwrap();
+//!     println!("{:?}", events.auction_events().collect::<Vec<_>>());
+//! }
+//! ```
+
+pub mod calls;
+mod error;
+mod signer;
+#[cfg(test)]
+mod tests;
+
+pub use error::{Error, Result};
+pub use signer::{sign, Signer};
+
+use auction_traits::auction::{
+    AuctionInfo, AuctionParameters, BerthWindow, QueuedBid, UIAuctionInfo,
+};
+use codec::{Decode, Encode};
+use futures01::{sync::oneshot, Future, Stream};
+use jsonrpc_core_client::{transports::ws, RpcChannel, RpcError};
+use node_auction_runtime::{
+    auction, AccountId, AuctionId, Balance, BlockNumber, Call, Event, GeneralInformationContainer,
+    Hash, Header, Index, Runtime, SignedBlock, UncheckedExtrinsic,
+};
+use sc_rpc_api::{author::AuthorClient, chain::ChainClient, state::StateClient};
+use sp_core::storage::StorageKey;
+use sp_core::{twox_128, Bytes};
+use sp_rpc::{list::ListOrValue, number::NumberOrHex};
+use sp_runtime::traits::Header as HeaderT;
+use sp_transaction_pool::TransactionStatus;
+use sp_version::RuntimeVersion;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use system::Phase;
+use tokio01::runtime::Runtime as Executor;
+
+pub type AuctionInfoOf = AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+pub type UIAuctionInfoOf = UIAuctionInfo<AccountId, BlockNumber, GeneralInformationContainer>;
+pub type AuctionParametersOf = AuctionParameters<Balance, BlockNumber>;
+/// Auction parameters as the RPC returns them, their balances converted for display.
+pub type DisplayedParametersOf = AuctionParameters<u64, BlockNumber>;
+pub type BerthWindowOf = BerthWindow<GeneralInformationContainer>;
+pub type QueuedBidOf = QueuedBid<AccountId, Balance, AuctionId>;
+pub type EventRecordOf = system::EventRecord<Event, Hash>;
+pub type AuctionEvent = auction::Event<Runtime>;
+
+type AuctionClient = auction_rpc::gen_client::Client<
+    Hash,
+    AccountId,
+    AuctionId,
+    Balance,
+    BlockNumber,
+    GeneralInformationContainer,
+>;
+type SystemClient = system_rpc::SystemClient<Hash, AccountId, Index>;
+
+/// When `Client::submit_and_watch` returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitFor {
+    /// As soon as the extrinsic is in a block of the best chain.
+    InBlock,
+    /// Once the block holding the extrinsic is finalized.
+    Finalized,
+}
+
+/// An extrinsic included in a block, along with the events it emitted.
+#[derive(Debug, Clone)]
+pub struct ExtrinsicEvents {
+    /// Hash of the extrinsic.
+    pub extrinsic: Hash,
+    /// Hash of the block holding it.
+    pub block: Hash,
+    /// Position of the extrinsic in the block.
+    pub index: u32,
+    /// Events emitted while applying the extrinsic, in order.
+    pub events: Vec<Event>,
+}
+
+impl ExtrinsicEvents {
+    /// The events of the auction pallet, leaving out every other pallet's.
+    pub fn auction_events(&self) -> impl Iterator<Item = &AuctionEvent> {
+        self.events.iter().filter_map(|event| match event {
+            Event::auction(event) => Some(event),
+            _ => None,
+        })
+    }
+}
+
+/// A storage value checked against the state root of a trusted header.
+#[derive(Debug, Clone)]
+pub struct Proven<T> {
+    /// Hash of the block the value was read at.
+    pub at: Hash,
+    pub value: T,
+    /// The trie nodes proving the value, as the node returned them.
+    pub proof: Vec<Bytes>,
+}
+
+/// A connection to a node over its WebSocket RPC endpoint.
+///
+/// Calls block the current thread until the node answers. Nonces are tracked per signer, so
+/// extrinsics of the same account can be submitted back to back without waiting for inclusion.
+pub struct Client {
+    executor: Executor,
+    auction: AuctionClient,
+    author: AuthorClient<Hash, Hash>,
+    chain: ChainClient<BlockNumber, Hash, Header, SignedBlock>,
+    state: StateClient<Hash>,
+    system: SystemClient,
+    genesis: Hash,
+    nonces: Mutex<HashMap<AccountId, Index>>,
+}
+
+impl Client {
+    /// Connects to the node at `url`, e.g. `ws://127.0.0.1:9944`.
+    pub fn connect(url: &str) -> Result<Self> {
+        let mut executor = Executor::new().map_err(|e| RpcError::Other(e.into()))?;
+        let channel: RpcChannel = executor.block_on(ws::try_connect(url)?)?;
+
+        let mut client = Client {
+            executor,
+            auction: channel.clone().into(),
+            author: channel.clone().into(),
+            chain: channel.clone().into(),
+            state: channel.clone().into(),
+            system: channel.into(),
+            genesis: Default::default(),
+            nonces: Default::default(),
+        };
+        client.genesis = client
+            .block_hash(0)?
+            .ok_or(Error::UnknownBlock(Default::default()))?;
+        Ok(client)
+    }
+
+    /// Drives `future` on the client's executor and waits for its outcome.
+    fn wait<F>(&self, future: F) -> Result<F::Item>
+    where
+        F: Future<Error = RpcError> + Send + 'static,
+        F::Item: Send + 'static,
+    {
+        oneshot::spawn(future, &self.executor.executor())
+            .wait()
+            .map_err(Into::into)
+    }
+
+    /// Whether the auction `id` exists at block `at`, the best block if `None`.
+    pub fn auction_exists(&self, id: AuctionId, at: Option<Hash>) -> Result<bool> {
+        self.wait(self.auction.auction_exists(at, id))
+    }
+
+    /// The full state of an auction.
+    pub fn auction(&self, id: AuctionId, at: Option<Hash>) -> Result<Option<AuctionInfoOf>> {
+        found(self.wait(self.auction.auction_query_informal(at, id)))
+    }
+
+    pub fn auctions(&self, at: Option<Hash>) -> Result<Vec<AuctionInfoOf>> {
+        let auctions = self.wait(self.auction.auction_query_informal_all(at, None))?;
+        Ok(auctions.unwrap_or_default())
+    }
+
+    /// The auctions which are running, if `active`, or yet to start.
+    pub fn auctions_with_status(
+        &self,
+        active: bool,
+        at: Option<Hash>,
+    ) -> Result<Vec<AuctionInfoOf>> {
+        let auctions = self.wait(
+            self.auction
+                .auction_query_informal_all_status(at, active, None),
+        )?;
+        Ok(auctions.unwrap_or_default())
+    }
+
+    /// The public summary of an auction, as shown to users.
+    pub fn auction_summary(
+        &self,
+        id: AuctionId,
+        at: Option<Hash>,
+    ) -> Result<Option<UIAuctionInfoOf>> {
+        found(self.wait(self.auction.auction_query_formal(at, id)))
+    }
+
+    pub fn auction_summaries(&self, at: Option<Hash>) -> Result<Vec<UIAuctionInfoOf>> {
+        let auctions = self.wait(self.auction.auction_query_formal_all(at, None))?;
+        Ok(auctions.unwrap_or_default())
+    }
+
+    pub fn auction_summaries_with_status(
+        &self,
+        active: bool,
+        at: Option<Hash>,
+    ) -> Result<Vec<UIAuctionInfoOf>> {
+        let auctions = self.wait(
+            self.auction
+                .auction_query_formal_all_status(at, active, None),
+        )?;
+        Ok(auctions.unwrap_or_default())
+    }
+
+    /// The auction parameters in force.
+    pub fn parameters(&self, at: Option<Hash>) -> Result<DisplayedParametersOf> {
+        self.wait(self.auction.auction_parameters(at))
+    }
+
+    /// The largest values governance may set the parameters to.
+    pub fn parameter_bounds(&self, at: Option<Hash>) -> Result<DisplayedParametersOf> {
+        self.wait(self.auction.auction_parameter_bounds(at))
+    }
+
+    /// The berth windows of `terminal`'s slots overlapping the period from `from` up to `to`, by
+    /// the auction each slot was first offered in.
+    pub fn terminal_occupancy(
+        &self,
+        terminal: AccountId,
+        from: GeneralInformationContainer,
+        to: GeneralInformationContainer,
+        at: Option<Hash>,
+    ) -> Result<Vec<(AuctionId, BerthWindowOf)>> {
+        self.wait(self.auction.terminal_occupancy(at, terminal, from, to))
+    }
+
+    /// The auction `id` at the block of `header`, proven against the header's state root.
+    ///
+    /// The value is only as trustworthy as `header`, which should come from a source other than
+    /// this node, e.g. a finalized header checked by a light client.
+    pub fn proven_auction(
+        &self,
+        id: AuctionId,
+        header: &Header,
+    ) -> Result<Proven<Option<AuctionInfoOf>>> {
+        let proof = self.read_proof(auction_proof::auction_key(&id), header)?;
+        let value = auction_proof::verify_auction(header, &id, raw_nodes(&proof))?;
+        Ok(Proven {
+            at: header.hash(),
+            value,
+            proof,
+        })
+    }
+
+    /// The bids queued for auction `id` at the block of `header`, proven against the header's
+    /// state root.
+    pub fn proven_queued_bids(
+        &self,
+        id: AuctionId,
+        header: &Header,
+    ) -> Result<Proven<Vec<QueuedBidOf>>> {
+        let proof = self.read_proof(auction_proof::queued_bids_key(&id), header)?;
+        let value = auction_proof::verify_queued_bids(header, &id, raw_nodes(&proof))?;
+        Ok(Proven {
+            at: header.hash(),
+            value,
+            proof,
+        })
+    }
+
+    /// Asks the node for the proof of `key` at the block of `header`.
+    fn read_proof(&self, key: Vec<u8>, header: &Header) -> Result<Vec<Bytes>> {
+        let proof = self.wait(
+            self.state
+                .read_proof(vec![StorageKey(key)], Some(header.hash())),
+        )?;
+        Ok(proof.proof)
+    }
+
+    pub fn genesis_hash(&self) -> Hash {
+        self.genesis
+    }
+
+    /// Header of the best block.
+    pub fn best_header(&self) -> Result<Header> {
+        let header = self.wait(self.chain.header(None))?;
+        header.ok_or_else(|| Error::UnknownBlock(Default::default()))
+    }
+
+    pub fn block_hash(&self, number: BlockNumber) -> Result<Option<Hash>> {
+        let number = ListOrValue::Value(NumberOrHex::Number(number.into()));
+        match self.wait(self.chain.block_hash(Some(number)))? {
+            ListOrValue::Value(hash) => Ok(hash),
+            ListOrValue::List(_) => Ok(None),
+        }
+    }
+
+    /// Version of the runtime at the best block.
+    pub fn runtime_version(&self) -> Result<RuntimeVersion> {
+        self.wait(self.state.runtime_version(None))
+    }
+
+    /// Next nonce of `who` as seen by the node, counting the transactions in its pool.
+    pub fn next_index(&self, who: &AccountId) -> Result<Index> {
+        self.wait(self.system.nonce(who.clone()))
+    }
+
+    /// Every event emitted in block `at`.
+    pub fn events(&self, at: Hash) -> Result<Vec<EventRecordOf>> {
+        // `System::Events` isn't public, its key is built by hand.
+        let mut key = twox_128(b"System").to_vec();
+        key.extend_from_slice(&twox_128(b"Events"));
+
+        match self.wait(self.state.storage(StorageKey(key), Some(at)))? {
+            Some(data) => Ok(Decode::decode(&mut &data.0[..])?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Signs `call` with the next nonce of `signer`.
+    pub fn sign(&self, signer: &Signer, call: Call) -> Result<UncheckedExtrinsic> {
+        let version = self.runtime_version()?;
+        let mut nonces = self.nonces.lock().expect("Nonce lock isn't poisoned; qed");
+        let nonce = match nonces.get(signer.account()) {
+            Some(nonce) => *nonce,
+            None => self.next_index(signer.account())?,
+        };
+        nonces.insert(signer.account().clone(), nonce + 1);
+
+        Ok(signer.sign(call, nonce, self.genesis, &version))
+    }
+
+    /// Forgets the nonce tracked for `who`, the next extrinsic asks the node again.
+    pub fn reset_nonce(&self, who: &AccountId) {
+        self.nonces
+            .lock()
+            .expect("Nonce lock isn't poisoned; qed")
+            .remove(who);
+    }
+
+    /// Signs and submits `call` without waiting for it, returns the extrinsic hash.
+    pub fn submit(&self, signer: &Signer, call: Call) -> Result<Hash> {
+        let xt = self.sign(signer, call)?;
+        self.wait(self.author.submit_extrinsic(Bytes(xt.encode())))
+            .map_err(|e| {
+                self.reset_nonce(signer.account());
+                e
+            })
+    }
+
+    /// Signs and submits `call`, then waits for the extrinsic to reach the `wait_for` state.
+    ///
+    /// Fails with `Error::Dispatch` if the extrinsic was included but its dispatch failed.
+    pub fn submit_and_watch(
+        &self,
+        signer: &Signer,
+        call: Call,
+        wait_for: WaitFor,
+    ) -> Result<ExtrinsicEvents> {
+        let xt = self.sign(signer, call)?;
+        let hash = extrinsic_hash(&xt);
+        let result = self
+            .wait(self.author.watch_extrinsic(Bytes(xt.encode())))
+            .and_then(|updates| self.follow(updates.wait(), wait_for));
+
+        match result {
+            Ok(block) => self.extrinsic_events(hash, block),
+            Err(e) => {
+                // The nonce was never used, so the tracked one is ahead of the chain.
+                if let Error::Rpc(_) | Error::Dropped | Error::Invalid = e {
+                    self.reset_nonce(signer.account());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Follows the status updates of an extrinsic and returns the block it ended up in.
+    fn follow<I>(&self, updates: I, wait_for: WaitFor) -> Result<Hash>
+    where
+        I: Iterator<Item = std::result::Result<TransactionStatus<Hash, Hash>, RpcError>>,
+    {
+        for status in updates {
+            match status? {
+                TransactionStatus::InBlock(block) if wait_for == WaitFor::InBlock => {
+                    return Ok(block)
+                }
+                TransactionStatus::Finalized(block) => return Ok(block),
+                TransactionStatus::FinalityTimeout(block) => {
+                    return Err(Error::FinalityTimeout(block))
+                }
+                TransactionStatus::Usurped(by) => return Err(Error::Usurped(by)),
+                TransactionStatus::Dropped => return Err(Error::Dropped),
+                TransactionStatus::Invalid => return Err(Error::Invalid),
+                status => log::debug!("Extrinsic status: {:?}", status),
+            }
+        }
+        Err(Error::SubscriptionClosed)
+    }
+
+    /// Looks the extrinsic `hash` up in `block` and collects the events it emitted.
+    fn extrinsic_events(&self, hash: Hash, block: Hash) -> Result<ExtrinsicEvents> {
+        let body = self
+            .wait(self.chain.block(Some(block)))?
+            .ok_or(Error::UnknownBlock(block))?;
+        let index = body
+            .block
+            .extrinsics
+            .iter()
+            .position(|xt| extrinsic_hash(xt) == hash)
+            .ok_or(Error::ExtrinsicNotFound(block))? as u32;
+
+        let events = events_of(self.events(block)?, index);
+        for event in &events {
+            if let Event::system(system::RawEvent::ExtrinsicFailed(error, _)) = event {
+                return Err(Error::Dispatch {
+                    block,
+                    error: *error,
+                });
+            }
+        }
+
+        Ok(ExtrinsicEvents {
+            extrinsic: hash,
+            block,
+            index,
+            events,
+        })
+    }
+}
+
+/// The events emitted while applying the `index`th extrinsic of a block.
+pub fn events_of(records: Vec<EventRecordOf>, index: u32) -> Vec<Event> {
+    records
+        .into_iter()
+        .filter(|record| record.phase == Phase::ApplyExtrinsic(index))
+        .map(|record| record.event)
+        .collect()
+}
+
+/// The auction a single auction query answered with, `None` if the node reports it doesn't exist.
+fn found<T>(answer: Result<T>) -> Result<Option<T>> {
+    match answer {
+        Ok(auction) => Ok(Some(auction)),
+        Err(Error::Rpc(RpcError::JsonRpcError(ref error)))
+            if error.code.code() == auction_rpc::error::AUCTION_NOT_FOUND =>
+        {
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+fn raw_nodes(proof: &[Bytes]) -> Vec<Vec<u8>> {
+    proof.iter().map(|node| node.0.clone()).collect()
+}
+
+/// Hash of an extrinsic as reported by the transaction pool.
+pub fn extrinsic_hash<E: Encode>(xt: &E) -> Hash {
+    xt.using_encoded(|encoded| sp_core::blake2_256(encoded).into())
+}
@@ -0,0 +1,84 @@
+//! Signing of runtime calls.
+
+use crate::error::{Error, Result};
+use node_auction_runtime::{AccountId, Call, Hash, Index, SignedExtra, UncheckedExtrinsic};
+use sp_core::{sr25519, Pair};
+use sp_runtime::generic::{Era, SignedPayload};
+use sp_runtime::traits::IdentifyAccount;
+use sp_runtime::MultiSigner;
+use sp_version::RuntimeVersion;
+
+/// An sr25519 key pair along with the account it controls.
+#[derive(Clone)]
+pub struct Signer {
+    pair: sr25519::Pair,
+    account: AccountId,
+}
+
+impl Signer {
+    pub fn new(pair: sr25519::Pair) -> Self {
+        let account = MultiSigner::from(pair.public()).into_account();
+        Signer { pair, account }
+    }
+
+    /// Derives the key pair of a secret URI such as `//Alice`.
+    pub fn from_suri(suri: &str) -> Result<Self> {
+        sr25519::Pair::from_string(suri, None)
+            .map(Self::new)
+            .map_err(|_| Error::InvalidSuri(suri.into()))
+    }
+
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    pub fn pair(&self) -> &sr25519::Pair {
+        &self.pair
+    }
+
+    /// Signs `call` as the `nonce`th transaction of the account.
+    pub fn sign(
+        &self,
+        call: Call,
+        nonce: Index,
+        genesis: Hash,
+        version: &RuntimeVersion,
+    ) -> UncheckedExtrinsic {
+        sign(&self.pair, call, nonce, genesis, version)
+    }
+}
+
+/// Signs `call` as the `nonce`th transaction of `pair`. Transactions are immortal, so they don't
+/// depend on any block but the genesis block.
+pub fn sign(
+    pair: &sr25519::Pair,
+    call: Call,
+    nonce: Index,
+    genesis: Hash,
+    version: &RuntimeVersion,
+) -> UncheckedExtrinsic {
+    let extra: SignedExtra = (
+        system::CheckSpecVersion::new(),
+        system::CheckTxVersion::new(),
+        system::CheckGenesis::new(),
+        system::CheckEra::from(Era::Immortal),
+        system::CheckNonce::from(nonce),
+        system::CheckWeight::new(),
+        transaction_payment::ChargeTransactionPayment::from(0),
+    );
+    let additional = (
+        version.spec_version,
+        version.transaction_version,
+        genesis,
+        genesis,
+        (),
+        (),
+        (),
+    );
+    let payload = SignedPayload::from_raw(call, extra, additional);
+    let signature = payload.using_encoded(|payload| pair.sign(payload));
+    let (call, extra, _) = payload.deconstruct();
+    let account = MultiSigner::from(pair.public()).into_account();
+
+    UncheckedExtrinsic::new_signed(call, account, signature.into(), extra)
+}
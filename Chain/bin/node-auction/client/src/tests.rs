@@ -0,0 +1,154 @@
+use crate::*;
+use sp_core::H256;
+use sp_runtime::generic::SignedPayload;
+use sp_runtime::traits::Verify;
+
+fn version() -> RuntimeVersion {
+    node_auction_runtime::VERSION
+}
+
+fn record(phase: Phase, event: Event) -> EventRecordOf {
+    system::EventRecord {
+        phase,
+        event,
+        topics: Vec::new(),
+    }
+}
+
+#[test]
+fn signed_extrinsics_verify() {
+    let signer = Signer::from_suri("//Alice").unwrap();
+    let genesis = H256::repeat_byte(7);
+    let xt = signer.sign(calls::remove_auction(3), 5, genesis, &version());
+
+    let (account, signature, extra) = xt.signature.clone().unwrap();
+    assert_eq!(&account, signer.account());
+    assert_eq!(extra.4, system::CheckNonce::from(5));
+
+    let version = version();
+    let additional = (
+        version.spec_version,
+        version.transaction_version,
+        genesis,
+        genesis,
+        (),
+        (),
+        (),
+    );
+    let payload = SignedPayload::from_raw(xt.function.clone(), extra, additional);
+    assert!(payload.using_encoded(|payload| signature.verify(payload, &account)));
+
+    // The genesis hash is part of the payload, a different chain can't replay the extrinsic.
+    let (call, extra, _) = payload.deconstruct();
+    let replayed = SignedPayload::from_raw(call, extra, {
+        let mut additional = additional;
+        additional.2 = H256::repeat_byte(8);
+        additional.3 = H256::repeat_byte(8);
+        additional
+    });
+    assert!(!replayed.using_encoded(|payload| signature.verify(payload, &account)));
+}
+
+#[test]
+fn invalid_suris_are_rejected() {
+    assert!(matches!(
+        Signer::from_suri("not a key"),
+        Err(Error::InvalidSuri(_))
+    ));
+}
+
+#[test]
+fn events_are_split_by_extrinsic() {
+    let alice = Signer::from_suri("//Alice").unwrap().account().clone();
+    let deleted = Event::auction(auction::RawEvent::AuctionDeleted(4));
+    let records = vec![
+        record(
+            Phase::ApplyExtrinsic(0),
+            Event::system(system::RawEvent::ExtrinsicSuccess(Default::default())),
+        ),
+        record(
+            Phase::ApplyExtrinsic(1),
+            Event::system(system::RawEvent::NewAccount(alice)),
+        ),
+        record(Phase::ApplyExtrinsic(1), deleted.clone()),
+        record(Phase::Finalization, deleted.clone()),
+    ];
+
+    let events = ExtrinsicEvents {
+        extrinsic: H256::zero(),
+        block: H256::zero(),
+        index: 1,
+        events: events_of(records, 1),
+    };
+    assert_eq!(events.events.len(), 2);
+    assert_eq!(
+        events.auction_events().collect::<Vec<_>>(),
+        vec![&auction::RawEvent::AuctionDeleted(4)]
+    );
+}
+
+#[test]
+fn proof_keys_match_the_runtime_storage() {
+    use frame_support::storage::StorageMap;
+
+    assert_eq!(
+        auction_proof::auction_key(&7u64),
+        <auction::Auctions<Runtime>>::hashed_key_for(7u64)
+    );
+    assert_eq!(
+        auction_proof::queued_bids_key(&7u64),
+        <auction::QueuedBids<Runtime>>::hashed_key_for(7u64)
+    );
+}
+
+/// Runs against a development node, e.g. `node-auction --dev --tmp`, reachable at the address
+/// in `NODE_AUCTION_WS`.
+#[test]
+#[ignore]
+fn auction_round_trip_on_dev_node() {
+    let url = std::env::var("NODE_AUCTION_WS").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
+    let client = Client::connect(&url).unwrap();
+    let terminal = Signer::from_suri("//Alice").unwrap();
+    let barge = Signer::from_suri("//Bob").unwrap();
+
+    let best = client.block_hash(0).unwrap();
+    assert_eq!(best, Some(client.genesis_hash()));
+    let parameters = client.parameters(None).unwrap();
+    let start = client.best_header().unwrap().number + 2;
+
+    let created = client
+        .submit_and_watch(
+            &barge,
+            calls::new_auction(
+                terminal.account().clone(),
+                1,
+                2,
+                0,
+                start,
+                start + parameters.min_duration.max(1) + 20,
+            ),
+            WaitFor::InBlock,
+        )
+        .unwrap();
+    let id = created
+        .auction_events()
+        .find_map(|event| match event {
+            auction::RawEvent::AuctionCreated(id, ..) => Some(*id),
+            _ => None,
+        })
+        .expect("Creating an auction emits AuctionCreated");
+
+    assert!(client.auction_exists(id, Some(created.block)).unwrap());
+    assert_eq!(
+        client.auction(id, None).unwrap().unwrap().creator,
+        *barge.account()
+    );
+    let header = client.best_header().unwrap();
+    let proven = client.proven_auction(id, &header).unwrap();
+    assert_eq!(proven.value.unwrap().creator, *barge.account());
+
+    client
+        .submit_and_watch(&barge, calls::remove_auction(id), WaitFor::InBlock)
+        .unwrap();
+    assert!(!client.auction_exists(id, None).unwrap());
+}
@@ -0,0 +1,130 @@
+//! Structs to compose the `auction` sub-command of the CLI.
+
+use sc_cli::{ImportParams, SharedParams};
+use std::path::PathBuf;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// The `auction` command used to inspect the auctions stored in the local database.
+#[derive(Debug, StructOpt)]
+pub struct AuctionCmd {
+    #[allow(missing_docs)]
+    #[structopt(subcommand)]
+    pub command: AuctionSubCmd,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: SharedParams,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub import_params: ImportParams,
+}
+
+/// The possible auction sub-commands.
+#[derive(Debug, StructOpt)]
+pub enum AuctionSubCmd {
+    /// List every stored auction.
+    List {
+        /// Only list auctions which are still open at the inspected block.
+        #[structopt(long)]
+        active: bool,
+
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        block: BlockParams,
+
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        output: OutputParams,
+    },
+    /// Show a single auction, including its queued bids.
+    Show {
+        /// Id of the auction to show.
+        id: u64,
+
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        block: BlockParams,
+
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        output: OutputParams,
+    },
+    /// Show every change of a single auction over a range of blocks. The state of every block in
+    /// the range has to be available, run the node with `--pruning archive` to trace blocks older
+    /// than the pruning window.
+    History {
+        /// Id of the auction to trace.
+        id: u64,
+
+        /// First block of the range, defaults to 256 blocks before the last one, or the oldest
+        /// block whose state is kept if that's more recent.
+        #[structopt(long, value_name = "NUMBER")]
+        from: Option<u32>,
+
+        /// Last block of the range, defaults to the best block.
+        #[structopt(long, value_name = "NUMBER")]
+        to: Option<u32>,
+
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        output: OutputParams,
+    },
+    /// Export every stored auction along with its queued bids.
+    Export {
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        block: BlockParams,
+
+        #[allow(missing_docs)]
+        #[structopt(flatten)]
+        output: OutputParams,
+    },
+}
+
+/// Parameters selecting the block whose state is inspected.
+#[derive(Debug, StructOpt)]
+pub struct BlockParams {
+    /// Block to read the auction state from, defaults to the best block.
+    ///
+    /// Can be either a block hash (with or without 0x prefix) or a block number.
+    #[structopt(long, value_name = "HASH or NUMBER")]
+    pub at: Option<String>,
+}
+
+/// Parameters controlling how results are written.
+#[derive(Debug, StructOpt)]
+pub struct OutputParams {
+    /// Output format, either `json` or `csv`.
+    #[structopt(long, value_name = "FORMAT", default_value = "json")]
+    pub format: OutputFormat,
+
+    /// File to write the output to, defaults to stdout.
+    #[structopt(long, short = "o", parse(from_os_str))]
+    pub output: Option<PathBuf>,
+}
+
+/// Formats the `auction` sub-commands can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Pretty printed JSON.
+    Json,
+    /// Comma separated values, one row per record with a header row.
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            other => Err(format!(
+                "Unknown output format `{}`, expected json or csv",
+                other
+            )),
+        }
+    }
+}
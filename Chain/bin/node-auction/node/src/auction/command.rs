@@ -0,0 +1,112 @@
+//! Command ran by the `auction` sub-command of the CLI.
+
+use super::cli::{AuctionCmd, AuctionSubCmd, OutputFormat, OutputParams};
+use super::{history_range, AuctionInspector, AuctionRecord, CsvRecord};
+use node_auction_runtime::{opaque::Block, RuntimeApi};
+use sc_cli::{CliConfiguration, ImportParams, Result, SharedParams};
+use sc_service::{new_full_client, Configuration};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+
+impl AuctionCmd {
+    /// Run the auction command against the database described by `config`.
+    pub fn run(&self, config: Configuration) -> Result<()> {
+        let client = new_full_client::<Block, RuntimeApi, crate::service::Executor>(&config)?;
+        let inspect = AuctionInspector::new(client);
+
+        match &self.command {
+            AuctionSubCmd::List {
+                active,
+                block,
+                output,
+            } => {
+                let at = inspect.block(block.at.as_deref())?;
+                let auctions = inspect
+                    .auctions(at)?
+                    .into_iter()
+                    .filter(|auction| !active || auction.live)
+                    .collect::<Vec<_>>();
+                write_records(output, &auctions)
+            }
+            AuctionSubCmd::Show { id, block, output } => {
+                let at = inspect.block(block.at.as_deref())?;
+                let auction = inspect
+                    .auction(at, *id)?
+                    .ok_or_else(|| format!("Auction {} does not exist at block {}", id, at.0))?;
+                write_records::<AuctionRecord>(output, &[auction])
+            }
+            AuctionSubCmd::History {
+                id,
+                from,
+                to,
+                output,
+            } => {
+                let (best, _) = inspect.block(None)?;
+                let oldest = inspect.oldest_state(&config.pruning);
+                let (from, to) = history_range(*from, to.unwrap_or(best), oldest)?;
+                let history = inspect.history(*id, from, to)?;
+                write_records(output, &history)
+            }
+            AuctionSubCmd::Export { block, output } => {
+                let at = inspect.block(block.at.as_deref())?;
+                write_records(output, &inspect.auctions(at)?)
+            }
+        }
+    }
+}
+
+impl CliConfiguration for AuctionCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn import_params(&self) -> Option<&ImportParams> {
+        Some(&self.import_params)
+    }
+}
+
+/// Writes `records` in the requested format to the requested destination.
+fn write_records<R: Serialize + CsvRecord>(params: &OutputParams, records: &[R]) -> Result<()> {
+    let out: Box<dyn Write> = match &params.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    Ok(render(params.format, records, out)?)
+}
+
+/// Writes `records` to `out` in the given format.
+pub(super) fn render<R, W>(format: OutputFormat, records: &[R], mut out: W) -> io::Result<()>
+where
+    R: Serialize + CsvRecord,
+    W: Write,
+{
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "{}", R::header().join(","))?;
+            for record in records {
+                let row = record
+                    .row()
+                    .iter()
+                    .map(|field| escape(field))
+                    .collect::<Vec<_>>();
+                writeln!(out, "{}", row.join(","))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Quotes a CSV field when it contains a separator, a quote or a line break.
+pub(super) fn escape(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
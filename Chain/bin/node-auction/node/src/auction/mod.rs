@@ -0,0 +1,462 @@
+//! Offline inspection of the auctioning pallet. The `auction` sub-command reads the `Auctions`,
+//! `AuctionEndTime` and `QueuedBids` storage items straight from the local database, so auctions
+//! can be audited without a running node or RPC access.
+
+mod cli;
+mod command;
+#[cfg(test)]
+mod tests;
+
+pub use cli::AuctionCmd;
+
+use auction_traits::auction::{AuctionInfo, QueuedBid};
+use codec::Decode;
+use frame_support::storage::{StorageDoubleMap, StorageMap, StoragePrefixedMap};
+use node_auction_runtime::{
+    auction, opaque::Block, AccountId, AuctionId, Balance, BlockNumber,
+    GeneralInformationContainer, Hash, Runtime, RuntimeApi,
+};
+use sc_client_api::StorageProvider;
+use sc_service::PruningMode;
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::generic::BlockId;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Number of blocks `auction history` traces when no first block is given.
+pub const HISTORY_WINDOW: BlockNumber = 256;
+
+type FullClient = sc_service::TFullClient<Block, RuntimeApi, crate::service::Executor>;
+type AuctionInfoOf = AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+type QueuedBidOf = QueuedBid<AccountId, Balance, AuctionId>;
+type Result<T> = std::result::Result<T, String>;
+
+/// A flattened view of a stored auction, written by the `auction` sub-commands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuctionRecord {
+    pub id: AuctionId,
+    pub creator: AccountId,
+    pub slot_origin: AccountId,
+    pub bidder: Option<AccountId>,
+    pub bid: Option<Balance>,
+    pub timestamp: GeneralInformationContainer,
+    pub num_con: GeneralInformationContainer,
+    pub num_teu: GeneralInformationContainer,
+    pub start: BlockNumber,
+    pub end: Option<BlockNumber>,
+    /// Block under which the auction is indexed in `AuctionEndTime`, `None` when the index has no
+    /// entry for this auction.
+    pub indexed_end: Option<BlockNumber>,
+    /// Whether the auction had started at the inspected block.
+    pub live: bool,
+    pub queued_bids: Vec<QueuedBidRecord>,
+}
+
+/// A bid waiting for its auction to start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueuedBidRecord {
+    pub bidder: AccountId,
+    pub amount: Balance,
+}
+
+/// The state of an auction right after the block in which it changed, `auction` is `None` once
+/// the auction has been removed. The `indexed_end` of the auction only reflects whether it's
+/// indexed under its `end`, the whole index isn't read for every block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryRecord {
+    pub block: BlockNumber,
+    pub hash: Hash,
+    pub auction: Option<AuctionRecord>,
+}
+
+/// Records which can be written as a CSV row.
+pub trait CsvRecord {
+    /// Names of the columns, in the order `row` returns them.
+    fn header() -> Vec<&'static str>;
+    /// The values of a single row.
+    fn row(&self) -> Vec<String>;
+}
+
+impl CsvRecord for AuctionRecord {
+    fn header() -> Vec<&'static str> {
+        vec![
+            "id",
+            "creator",
+            "slot_origin",
+            "bidder",
+            "bid",
+            "timestamp",
+            "num_con",
+            "num_teu",
+            "start",
+            "end",
+            "indexed_end",
+            "live",
+            "queued_bids",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        // Queued bids are folded into a single column as `bidder:amount` pairs.
+        let queued_bids = self
+            .queued_bids
+            .iter()
+            .map(|queued| format!("{}:{}", queued.bidder, queued.amount))
+            .collect::<Vec<_>>()
+            .join(";");
+
+        vec![
+            self.id.to_string(),
+            self.creator.to_string(),
+            self.slot_origin.to_string(),
+            optional(&self.bidder),
+            optional(&self.bid),
+            self.timestamp.to_string(),
+            self.num_con.to_string(),
+            self.num_teu.to_string(),
+            self.start.to_string(),
+            optional(&self.end),
+            optional(&self.indexed_end),
+            self.live.to_string(),
+            queued_bids,
+        ]
+    }
+}
+
+impl CsvRecord for HistoryRecord {
+    fn header() -> Vec<&'static str> {
+        let mut header = vec!["block", "hash", "removed"];
+        header.extend(AuctionRecord::header());
+        header
+    }
+
+    fn row(&self) -> Vec<String> {
+        let mut row = vec![
+            self.block.to_string(),
+            format!("{:?}", self.hash),
+            self.auction.is_none().to_string(),
+        ];
+        match &self.auction {
+            Some(auction) => row.extend(auction.row()),
+            None => row.extend(AuctionRecord::header().iter().map(|_| String::new())),
+        }
+        row
+    }
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+/// Reads the auctioning pallet's storage from a full client.
+pub struct AuctionInspector {
+    client: FullClient,
+}
+
+impl AuctionInspector {
+    pub fn new(client: FullClient) -> Self {
+        AuctionInspector { client }
+    }
+
+    /// Resolves a block hash or number into the number and hash of the block, `None` resolves
+    /// to the best block.
+    pub fn block(&self, input: Option<&str>) -> Result<(BlockNumber, Hash)> {
+        let info = self.client.info();
+        let id = match input {
+            None => return Ok((info.best_number, info.best_hash)),
+            Some(input) => match input.parse::<BlockNumber>() {
+                Ok(number) => BlockId::<Block>::Number(number),
+                Err(_) => Hash::from_str(input.trim_start_matches("0x"))
+                    .map(BlockId::Hash)
+                    .map_err(|e| format!("Invalid block `{}`: {:?}", input, e))?,
+            },
+        };
+
+        let hash = self
+            .client
+            .expect_block_hash_from_id(&id)
+            .map_err(|e| e.to_string())?;
+        let number = self
+            .client
+            .expect_block_number_from_id(&id)
+            .map_err(|e| e.to_string())?;
+
+        Ok((number, hash))
+    }
+
+    /// Every auction stored at the given block, ordered by id.
+    pub fn auctions(&self, at: (BlockNumber, Hash)) -> Result<Vec<AuctionRecord>> {
+        let id = BlockId::Hash(at.1);
+        let end_index = self.end_index(&id)?;
+
+        let mut auctions = self
+            .pairs::<AuctionInfoOf>(
+                &id,
+                <auction::Auctions<Runtime> as StoragePrefixedMap<AuctionInfoOf>>::final_prefix(),
+            )?
+            .into_iter()
+            .map(|(key, info)| {
+                let auction_id = decode_twox_64_concat::<AuctionId>(&mut &key[..])
+                    .ok_or("Undecodable key in `Auctions`")?;
+                let queued_bids = self.queued_bids(&id, auction_id)?;
+                Ok(record(
+                    auction_id,
+                    info,
+                    at.0,
+                    end_index.get(&auction_id).copied(),
+                    queued_bids,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Storage is ordered by the hashed keys, restore the order of the ids.
+        auctions.sort_by_key(|auction| auction.id);
+        Ok(auctions)
+    }
+
+    /// The oldest block whose state the database still holds. States of blocks which aren't
+    /// finalized yet are always kept, the pruning window counts back from the last finalized one.
+    pub fn oldest_state(&self, pruning: &PruningMode) -> BlockNumber {
+        match pruning {
+            PruningMode::Constrained(constraints) => self
+                .client
+                .info()
+                .finalized_number
+                .saturating_sub(constraints.max_blocks.unwrap_or(0)),
+            PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => 0,
+        }
+    }
+
+    /// A single auction stored at the given block.
+    pub fn auction(
+        &self,
+        at: (BlockNumber, Hash),
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionRecord>> {
+        let id = BlockId::Hash(at.1);
+        self.read_auction(at, auction_id, |_| {
+            Ok(self.end_index(&id)?.get(&auction_id).copied())
+        })
+    }
+
+    /// Reads a single auction, `indexed_end` finds the block the auction is indexed under.
+    fn read_auction<F>(
+        &self,
+        at: (BlockNumber, Hash),
+        auction_id: AuctionId,
+        indexed_end: F,
+    ) -> Result<Option<AuctionRecord>>
+    where
+        F: FnOnce(&AuctionInfoOf) -> Result<Option<BlockNumber>>,
+    {
+        let id = BlockId::Hash(at.1);
+        let info = self.get::<AuctionInfoOf>(
+            &id,
+            <auction::Auctions<Runtime> as StorageMap<AuctionId, AuctionInfoOf>>::hashed_key_for(
+                auction_id,
+            ),
+        )?;
+
+        match info {
+            Some(info) => {
+                let indexed_end = indexed_end(&info)?;
+                let queued_bids = self.queued_bids(&id, auction_id)?;
+                Ok(Some(record(
+                    auction_id,
+                    info,
+                    at.0,
+                    indexed_end,
+                    queued_bids,
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Every change of a single auction between `from` and `to`, both inclusive. The first
+    /// record holds the state at `from` if the auction existed at that point.
+    pub fn history(
+        &self,
+        auction_id: AuctionId,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<HistoryRecord>> {
+        let mut history = Vec::new();
+        let mut previous: Option<AuctionRecord> = None;
+
+        for number in from..=to {
+            let at = self.block(Some(&number.to_string()))?;
+            let id = BlockId::Hash(at.1);
+            let current = self
+                .read_auction(at, auction_id, |info| {
+                    self.indexed_at_end(&id, auction_id, info.end)
+                })
+                .map_err(|e| {
+                    format!(
+                        "{} (state of block {} unavailable? Run the node with `--pruning archive`)",
+                        e, number
+                    )
+                })?;
+
+            // `live` flips as soon as the start block passes, that alone is not a change.
+            let changed = match (&previous, &current) {
+                (Some(previous), Some(current)) => {
+                    AuctionRecord {
+                        live: current.live,
+                        ..previous.clone()
+                    } != *current
+                }
+                (None, None) => false,
+                _ => true,
+            };
+
+            if changed {
+                history.push(HistoryRecord {
+                    block: at.0,
+                    hash: at.1,
+                    auction: current.clone(),
+                });
+            }
+            previous = current;
+        }
+
+        Ok(history)
+    }
+
+    fn queued_bids(
+        &self,
+        id: &BlockId<Block>,
+        auction_id: AuctionId,
+    ) -> Result<Vec<QueuedBidRecord>> {
+        let queued = self.get::<Vec<QueuedBidOf>>(
+            id,
+            <auction::QueuedBids<Runtime> as StorageMap<AuctionId, Vec<QueuedBidOf>>>::hashed_key_for(
+                auction_id,
+            ),
+        )?;
+
+        Ok(queued
+            .unwrap_or_default()
+            .into_iter()
+            .map(|queued| QueuedBidRecord {
+                bidder: queued.bid.0,
+                amount: queued.bid.1,
+            })
+            .collect())
+    }
+
+    /// Maps every auction indexed in `AuctionEndTime` onto the block it's indexed under.
+    fn end_index(&self, id: &BlockId<Block>) -> Result<BTreeMap<AuctionId, BlockNumber>> {
+        self.pairs::<bool>(
+            id,
+            <auction::AuctionEndTime<Runtime> as StoragePrefixedMap<bool>>::final_prefix(),
+        )?
+        .into_iter()
+        .map(|(key, _)| {
+            let mut key = &key[..];
+            let block = decode_twox_64_concat::<BlockNumber>(&mut key);
+            let auction_id = decode_twox_64_concat::<AuctionId>(&mut key);
+            match (block, auction_id) {
+                (Some(block), Some(auction_id)) => Ok((auction_id, block)),
+                _ => Err("Undecodable key in `AuctionEndTime`".to_string()),
+            }
+        })
+        .collect()
+    }
+
+    /// `end` if the auction is indexed under it in `AuctionEndTime`. Unlike `end_index` this reads
+    /// a single entry, so an auction indexed under another block isn't found.
+    fn indexed_at_end(
+        &self,
+        id: &BlockId<Block>,
+        auction_id: AuctionId,
+        end: Option<BlockNumber>,
+    ) -> Result<Option<BlockNumber>> {
+        let end = match end {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        let key = <auction::AuctionEndTime<Runtime> as StorageDoubleMap<
+            BlockNumber,
+            AuctionId,
+            bool,
+        >>::hashed_key_for(end, auction_id);
+        Ok(self.get::<bool>(id, key)?.map(|_| end))
+    }
+
+    fn get<V: Decode>(&self, id: &BlockId<Block>, key: Vec<u8>) -> Result<Option<V>> {
+        self.client
+            .storage(id, &StorageKey(key))
+            .map_err(|e| e.to_string())?
+            .map(|data| V::decode(&mut &data.0[..]).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    /// Returns the decoded values under `prefix`, along with their keys stripped of the prefix.
+    fn pairs<V: Decode>(&self, id: &BlockId<Block>, prefix: [u8; 32]) -> Result<Vec<(Vec<u8>, V)>> {
+        self.client
+            .storage_pairs(id, &StorageKey(prefix.to_vec()))
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|(key, data)| {
+                let value = V::decode(&mut &data.0[..]).map_err(|e| e.to_string())?;
+                Ok((key.0[prefix.len()..].to_vec(), value))
+            })
+            .collect()
+    }
+}
+
+/// The range of blocks `auction history` traces. Without a first block the last `HISTORY_WINDOW`
+/// blocks up to `to` are traced, as far as their state is kept. A first block whose state has
+/// been pruned already is refused rather than failing halfway through the range.
+pub fn history_range(
+    from: Option<BlockNumber>,
+    to: BlockNumber,
+    oldest_state: BlockNumber,
+) -> Result<(BlockNumber, BlockNumber)> {
+    match from {
+        Some(from) if from < oldest_state => Err(format!(
+            "The state of block {} has been pruned, only the state of blocks from {} on is kept. \
+             Run the node with `--pruning archive` to trace older blocks",
+            from, oldest_state
+        )),
+        Some(from) if from > to => Err(format!("Block {} lies after block {}", from, to)),
+        Some(from) => Ok((from, to)),
+        None => Ok((to.saturating_sub(HISTORY_WINDOW).max(oldest_state), to)),
+    }
+}
+
+/// Strips the hash of a `twox_64_concat` key off `input` and decodes the key following it.
+fn decode_twox_64_concat<K: Decode>(input: &mut &[u8]) -> Option<K> {
+    if input.len() < 8 {
+        return None;
+    }
+    *input = &input[8..];
+    K::decode(input).ok()
+}
+
+fn record(
+    id: AuctionId,
+    info: AuctionInfoOf,
+    at: BlockNumber,
+    indexed_end: Option<BlockNumber>,
+    queued_bids: Vec<QueuedBidRecord>,
+) -> AuctionRecord {
+    AuctionRecord {
+        id,
+        bidder: info.bid.as_ref().map(|bid| bid.0.clone()),
+        bid: info.bid.map(|bid| bid.1),
+        creator: info.creator,
+        slot_origin: info.slot_origin,
+        timestamp: info.core.timestamp,
+        num_con: info.core.cargo.0,
+        num_teu: info.core.cargo.1,
+        start: info.start,
+        end: info.end,
+        indexed_end,
+        live: at >= info.start,
+        queued_bids,
+    }
+}
@@ -0,0 +1,179 @@
+use super::cli::OutputFormat;
+use super::command::render;
+use super::*;
+
+fn account(byte: u8) -> AccountId {
+    AccountId::from([byte; 32])
+}
+
+fn auction_record() -> AuctionRecord {
+    AuctionRecord {
+        id: 3,
+        creator: account(1),
+        slot_origin: account(2),
+        bidder: Some(account(3)),
+        bid: Some(500),
+        timestamp: 1594471764,
+        num_con: 22,
+        num_teu: 40,
+        start: 10,
+        end: Some(20),
+        indexed_end: Some(20),
+        live: true,
+        queued_bids: vec![
+            QueuedBidRecord {
+                bidder: account(4),
+                amount: 100,
+            },
+            QueuedBidRecord {
+                bidder: account(5),
+                amount: 200,
+            },
+        ],
+    }
+}
+
+fn rendered<R: Serialize + CsvRecord>(format: OutputFormat, records: &[R]) -> String {
+    let mut out = Vec::new();
+    render(format, records, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn auctions_as_json() {
+    let json = rendered(OutputFormat::Json, &[auction_record()]);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let auction = &value[0];
+    assert_eq!(auction["id"], 3);
+    assert_eq!(auction["creator"], account(1).to_string());
+    assert_eq!(auction["bid"], 500);
+    assert_eq!(auction["end"], 20);
+    assert_eq!(auction["live"], true);
+    assert_eq!(auction["queued_bids"][1]["amount"], 200);
+    assert!(json.ends_with("]\n"));
+
+    let unbid = AuctionRecord {
+        bidder: None,
+        bid: None,
+        end: None,
+        ..auction_record()
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(&rendered(OutputFormat::Json, &[unbid])).unwrap();
+    assert!(value[0]["bidder"].is_null());
+    assert!(value[0]["end"].is_null());
+}
+
+#[test]
+fn auctions_as_csv() {
+    let unbid = AuctionRecord {
+        id: 4,
+        bidder: None,
+        bid: None,
+        end: None,
+        indexed_end: None,
+        live: false,
+        queued_bids: Vec::new(),
+        ..auction_record()
+    };
+    let csv = rendered(OutputFormat::Csv, &[auction_record(), unbid]);
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        "id,creator,slot_origin,bidder,bid,timestamp,num_con,num_teu,start,end,indexed_end,live,queued_bids"
+    );
+    assert_eq!(
+        lines[1],
+        format!(
+            "3,{},{},{},500,1594471764,22,40,10,20,20,true,{}:100;{}:200",
+            account(1),
+            account(2),
+            account(3),
+            account(4),
+            account(5)
+        )
+    );
+    // Missing values are left empty.
+    assert_eq!(
+        lines[2],
+        format!(
+            "4,{},{},,,1594471764,22,40,10,,,false,",
+            account(1),
+            account(2)
+        )
+    );
+}
+
+#[test]
+fn history_as_csv() {
+    let history = vec![
+        HistoryRecord {
+            block: 5,
+            hash: Hash::repeat_byte(1),
+            auction: Some(auction_record()),
+        },
+        HistoryRecord {
+            block: 21,
+            hash: Hash::repeat_byte(2),
+            auction: None,
+        },
+    ];
+    let csv = rendered(OutputFormat::Csv, &history);
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("block,hash,removed,id,creator,"));
+    assert!(lines[1].starts_with(&format!("5,{:?},false,3,", Hash::repeat_byte(1))));
+    // A removed auction keeps every column, empty.
+    assert_eq!(
+        lines[2],
+        format!("21,{:?},true{}", Hash::repeat_byte(2), ",".repeat(13))
+    );
+    assert_eq!(lines[0].split(',').count(), lines[2].split(',').count());
+}
+
+#[test]
+fn history_as_json() {
+    let history = vec![HistoryRecord {
+        block: 21,
+        hash: Hash::repeat_byte(2),
+        auction: None,
+    }];
+    let value: serde_json::Value =
+        serde_json::from_str(&rendered(OutputFormat::Json, &history)).unwrap();
+    assert_eq!(value[0]["block"], 21);
+    assert_eq!(value[0]["hash"], format!("{:?}", Hash::repeat_byte(2)));
+    assert!(value[0]["auction"].is_null());
+}
+
+#[test]
+fn csv_fields_are_escaped() {
+    assert_eq!(command::escape("plain"), "plain");
+    assert_eq!(command::escape("a,b"), "\"a,b\"");
+    assert_eq!(command::escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+}
+
+#[test]
+fn output_format_parses() {
+    assert_eq!("JSON".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+    assert_eq!("csv".parse::<OutputFormat>(), Ok(OutputFormat::Csv));
+    assert!("xml".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn history_defaults_to_recent_window() {
+    assert_eq!(
+        history_range(None, 1000, 0),
+        Ok((1000 - HISTORY_WINDOW, 1000))
+    );
+    assert_eq!(history_range(None, 100, 0), Ok((0, 100)));
+    // The window doesn't reach past the oldest state kept.
+    assert_eq!(history_range(None, 1000, 900), Ok((900, 1000)));
+}
+
+#[test]
+fn history_refuses_pruned_blocks() {
+    assert_eq!(history_range(Some(900), 1000, 900), Ok((900, 1000)));
+    let error = history_range(Some(0), 1000, 744).unwrap_err();
+    assert!(error.contains("--pruning archive"));
+    assert!(history_range(Some(1001), 1000, 0).is_err());
+}
@@ -1,7 +1,8 @@
+use auction_traits::auction::{AuctionCoreInfo, GenesisAuction, GenesisTerminal, TerminalCapacity};
 use node_auction_runtime::{
-    AccountId, AuctionModuleConfig, AuraConfig, BalancesConfig, BlockNumber,
-    GeneralInformationContainer, GenesisConfig, GrandpaConfig, Signature, SudoConfig, SystemConfig,
-    WASM_BINARY,
+    AccountId, AuctionModuleConfig, AuraConfig, Balance, BalancesConfig, BlockNumber,
+    ExistentialDeposit, GeneralInformationContainer, GenesisConfig, GrandpaConfig, PlatformAccount,
+    Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -15,6 +16,14 @@ use sp_runtime::traits::{IdentifyAccount, Verify};
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
+/// A terminal of the auctioning module's genesis configuration.
+pub type Terminal = GenesisTerminal<AccountId, GeneralInformationContainer>;
+/// An auction of the auctioning module's genesis configuration.
+pub type Auction = GenesisAuction<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+
+// Balance of the pre-funded development accounts.
+const ENDOWMENT: Balance = 1 << 60;
+
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
     TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -54,7 +63,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
                 // Sudo account
                 get_account_id_from_seed::<sr25519::Public>("Alice"),
                 // Pre-funded accounts
-                vec![
+                endowed(vec![
                     // Begin Terminals //
                     get_account_id_from_seed::<sr25519::Public>("Alice"),
                     get_account_id_from_seed::<sr25519::Public>("Bob"),
@@ -69,41 +78,9 @@ pub fn development_config() -> Result<ChainSpec, String> {
                     get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
-                ],
-                vec![
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Alice"), // Terminal
-                        get_account_id_from_seed::<sr25519::Public>("Charlie"), // Barge
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Bob"),
-                        get_account_id_from_seed::<sr25519::Public>("Charlie"),
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Alice"),
-                        get_account_id_from_seed::<sr25519::Public>("Dave"),
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Bob"),
-                        get_account_id_from_seed::<sr25519::Public>("Dave"),
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Alice"),
-                        get_account_id_from_seed::<sr25519::Public>("Eve"),
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Bob"),
-                        get_account_id_from_seed::<sr25519::Public>("Eve"),
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Alice"),
-                        get_account_id_from_seed::<sr25519::Public>("Ferdie"),
-                    ),
-                    (
-                        get_account_id_from_seed::<sr25519::Public>("Bob"),
-                        get_account_id_from_seed::<sr25519::Public>("Ferdie"),
-                    ),
-                ],
+                ]),
+                development_terminals(),
+                development_auctions(),
                 true,
             )
         },
@@ -140,7 +117,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
                 // Sudo account
                 get_account_id_from_seed::<sr25519::Public>("Alice"),
                 // Pre-funded accounts
-                vec![
+                endowed(vec![
                     get_account_id_from_seed::<sr25519::Public>("Alice"),
                     get_account_id_from_seed::<sr25519::Public>("Bob"),
                     get_account_id_from_seed::<sr25519::Public>("Charlie"),
@@ -153,11 +130,9 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
                     get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
                     get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
-                ],
-                vec![(
-                    get_account_id_from_seed::<sr25519::Public>("Alice"),
-                    get_account_id_from_seed::<sr25519::Public>("Bob"),
-                )],
+                ]),
+                vec![],
+                vec![auction("Bob", "Alice", (503, 400, 400), 29, 388, vec![])],
                 true,
             )
         },
@@ -174,27 +149,101 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
     ))
 }
 
+fn endowed(accounts: Vec<AccountId>) -> Vec<(AccountId, Balance)> {
+    accounts.into_iter().map(|k| (k, ENDOWMENT)).collect()
+}
+
+/// An auction by the barge of `creator` for a slot of `terminal`, both given by their seeds. The
+/// slot is given as (timestamp, containers, TEU), the bids by the seeds of their bidders.
+fn auction(
+    creator: &str,
+    terminal: &str,
+    slot: (u64, u64, u64),
+    start: BlockNumber,
+    end: BlockNumber,
+    bids: Vec<(&str, Balance)>,
+) -> Auction {
+    Auction {
+        creator: get_account_id_from_seed::<sr25519::Public>(creator),
+        terminal: get_account_id_from_seed::<sr25519::Public>(terminal),
+        core: AuctionCoreInfo {
+            timestamp: slot.0,
+            cargo: (slot.1, slot.2),
+        },
+        start,
+        end,
+        bids: bids
+            .into_iter()
+            .map(|(bidder, value)| (get_account_id_from_seed::<sr25519::Public>(bidder), value))
+            .collect(),
+    }
+}
+
+// Alice's berths take up to four vessels at once, Bob approves every resale of his slots.
+fn development_terminals() -> Vec<Terminal> {
+    vec![
+        Terminal {
+            account: get_account_id_from_seed::<sr25519::Public>("Alice"),
+            capacity: Some(TerminalCapacity {
+                max_vessels: 4,
+                max_teu: 1000,
+                slot_duration: 3600,
+            }),
+            resale_approval_required: false,
+        },
+        Terminal {
+            account: get_account_id_from_seed::<sr25519::Public>("Bob"),
+            capacity: None,
+            resale_approval_required: true,
+        },
+    ]
+}
+
+// Test auctions for our Development environment, the terminals Alice and Bob each offer a slot to
+// every barge.
+fn development_auctions() -> Vec<Auction> {
+    vec![
+        // Barge, Terminal, (Timestamp, Containers, TEU), Start, End, Bids
+        auction(
+            "Charlie",
+            "Alice",
+            (503, 400, 400),
+            29,
+            388,
+            vec![("Dave//stash", 700)],
+        ),
+        auction("Charlie", "Bob", (884, 200, 200), 20, 200, vec![]),
+        auction("Dave", "Alice", (293, 10, 10), 0, 70, vec![]),
+        auction("Dave", "Bob", (2998, 500, 500), 100, 203, vec![]),
+        auction("Eve", "Alice", (948, 50, 50), 10, 500, vec![]),
+        auction("Eve", "Bob", (2040, 100, 100), 0, 100, vec![]),
+        auction("Ferdie", "Alice", (500, 100, 100), 0, 50, vec![]),
+        auction(
+            "Ferdie",
+            "Bob",
+            (39894, 600, 600),
+            0,
+            1000000,
+            vec![("Alice//stash", 1200), ("Charlie//stash", 900)],
+        ),
+    ]
+}
+
 /// Configure initial storage state for FRAME modules.
-fn testnet_genesis(
+pub fn testnet_genesis(
     wasm_binary: &[u8],
     initial_authorities: Vec<(AuraId, GrandpaId)>,
     root_key: AccountId,
-    endowed_accounts: Vec<AccountId>,
-    auction_initiators: Vec<(AccountId, AccountId)>,
+    mut endowed_accounts: Vec<(AccountId, Balance)>,
+    terminals: Vec<Terminal>,
+    auctions: Vec<Auction>,
     _enable_println: bool,
 ) -> GenesisConfig {
-    // Test auctions for our Development environment
-    let mut test_auctions = vec![
-        // Balance, Containers,  TEU, Start, End
-        (39894, 600, 600, 0, 1000000),
-        (500, 100, 100, 0, 50),
-        (2040, 100, 100, 0, 100),
-        (948, 50, 50, 10, 500),
-        (2998, 500, 500, 100, 203),
-        (293, 10, 10, 0, 70),
-        (884, 200, 200, 20, 200),
-        (503, 400, 400, 29, 388),
-    ];
+    // The platform account has to exist to receive platform fees below the existential deposit.
+    let platform = PlatformAccount::get();
+    if !endowed_accounts.iter().any(|(account, _)| *account == platform) {
+        endowed_accounts.push((platform, ExistentialDeposit::get()));
+    }
 
     GenesisConfig {
         system: Some(SystemConfig {
@@ -203,12 +252,7 @@ fn testnet_genesis(
             changes_trie_config: Default::default(),
         }),
         balances: Some(BalancesConfig {
-            // Configure endowed accounts with initial balance of 1 << 60.
-            balances: endowed_accounts
-                .iter()
-                .cloned()
-                .map(|k| (k, 1 << 60))
-                .collect(),
+            balances: endowed_accounts,
         }),
         aura: Some(AuraConfig {
             authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
@@ -225,31 +269,8 @@ fn testnet_genesis(
         }),
 
         auction: Some(AuctionModuleConfig {
-            _auctions: auction_initiators
-                .iter()
-                .cloned()
-                .map(|x| {
-                    // Make sure that the number of test_auctions == the number of test auction
-                    // initiators container in auction_initiators.
-                    if let Some(current_auction) = test_auctions.pop() {
-                        (
-                            x.0,
-                            x.1,
-                            vec![current_auction.0, current_auction.1, current_auction.2],
-                            current_auction.3,
-                            current_auction.4,
-                        )
-                    } else {
-                        (x.0, x.1, vec![2000, 200, 200], 0, 500)
-                    }
-                })
-                .collect::<Vec<(
-                    AccountId,
-                    AccountId,
-                    Vec<GeneralInformationContainer>,
-                    BlockNumber,
-                    BlockNumber,
-                )>>(),
+            terminals,
+            auctions,
         }),
     }
 }
@@ -1,4 +1,7 @@
-use sc_cli::{RunCmd, Subcommand};
+use crate::indexer::IndexerConfig;
+use crate::service::Sealing;
+use sc_cli::RunCmd;
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -8,4 +11,66 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Replace Aura and GRANDPA with instant sealing, a block is authored for every transaction
+	/// entering the pool or when requested through `engine_createBlock`. For development only.
+	#[structopt(long = "dev-instant-seal", conflicts_with = "manual-seal")]
+	pub instant_seal: bool,
+
+	/// Replace Aura and GRANDPA with manual sealing, blocks are only authored when requested
+	/// through `engine_createBlock`. For development only.
+	#[structopt(name = "manual-seal", long = "manual-seal")]
+	pub manual_seal: bool,
+
+	/// Index the auctions, bids, settlements and transfers of finalized blocks into the SQLite
+	/// database at the given path, which is created if needed.
+	#[structopt(long = "auction-index", value_name = "PATH", parse(from_os_str))]
+	pub auction_index: Option<PathBuf>,
+
+	/// Drop the auction index and rebuild it from genesis. The state of every block has to be
+	/// available, run the node with `--pruning archive`.
+	#[structopt(long = "auction-index-backfill", requires = "auction-index")]
+	pub auction_index_backfill: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Subcommand {
+	/// A set of base subcommands handled by `sc_cli`.
+	#[structopt(flatten)]
+	Base(sc_cli::Subcommand),
+
+	/// The auction subcommand for inspecting the auctions in the local database.
+	#[structopt(
+		name = "auction",
+		about = "List, show, trace and export auctions straight from the local database."
+	)]
+	Auction(crate::auction::AuctionCmd),
+
+	/// Write the polkadot.js type registry of the runtime.
+	#[structopt(
+		name = "export-types",
+		about = "Generate types.json for polkadot.js from the compiled runtime."
+	)]
+	ExportTypes(crate::types::ExportTypesCmd),
+}
+
+impl Cli {
+	/// The sealing mode requested on the command line, `None` when running Aura and GRANDPA.
+	pub fn sealing(&self) -> Option<Sealing> {
+		if self.instant_seal {
+			Some(Sealing::Instant)
+		} else if self.manual_seal {
+			Some(Sealing::Manual)
+		} else {
+			None
+		}
+	}
+
+	/// The auction indexer requested on the command line, if any.
+	pub fn indexer(&self) -> Option<IndexerConfig> {
+		self.auction_index.clone().map(|path| IndexerConfig {
+			path,
+			backfill: self.auction_index_backfill,
+		})
+	}
 }
@@ -16,7 +16,7 @@
 // limitations under the License.
 
 use crate::chain_spec;
-use crate::cli::Cli;
+use crate::cli::{Cli, Subcommand};
 use crate::service;
 use crate::service::new_partial;
 use sc_cli::{ChainSpec, RuntimeVersion, SubstrateCli};
@@ -67,7 +67,12 @@ pub fn run() -> sc_cli::Result<()> {
     let cli = Cli::from_args();
 
     match &cli.subcommand {
-        Some(subcommand) => {
+        Some(Subcommand::Auction(cmd)) => {
+            let runner = cli.create_runner(cmd)?;
+            runner.sync_run(|config| cmd.run(config))
+        }
+        Some(Subcommand::ExportTypes(cmd)) => cmd.run(),
+        Some(Subcommand::Base(subcommand)) => {
             let runner = cli.create_runner(subcommand)?;
             runner.run_subcommand(subcommand, |config| {
                 let PartialComponents {
@@ -82,8 +87,11 @@ pub fn run() -> sc_cli::Result<()> {
         }
         None => {
             let runner = cli.create_runner(&cli.run)?;
-            runner.run_node_until_exit(|config| match config.role {
-                _ => service::new_full(config),
+            let sealing = cli.sealing();
+            let indexer = cli.indexer();
+            runner.run_node_until_exit(|config| match sealing {
+                Some(sealing) => service::new_dev(config, sealing, indexer),
+                None => service::new_full(config, indexer),
             })
         }
     }
@@ -0,0 +1,477 @@
+//! The SQLite database written by the indexer.
+//!
+//! Amounts are stored as decimal text, balances don't fit SQLite's 64 bit integers. SQLite's
+//! arithmetic and aggregate functions still read them as numbers.
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, String>;
+
+const SCHEMA: &str = "
+    -- Every indexed block, the indexer resumes after the highest one.
+    CREATE TABLE IF NOT EXISTS blocks (
+        number INTEGER PRIMARY KEY,
+        hash TEXT NOT NULL,
+        -- Unix time in milliseconds, as set by the timestamp inherent.
+        timestamp INTEGER
+    );
+
+    CREATE TABLE IF NOT EXISTS auctions (
+        id INTEGER PRIMARY KEY,
+        creator TEXT NOT NULL,
+        terminal TEXT NOT NULL,
+        slot_timestamp INTEGER NOT NULL,
+        num_con INTEGER NOT NULL,
+        num_teu INTEGER NOT NULL,
+        start_block INTEGER NOT NULL,
+        end_block INTEGER,
+        created_in INTEGER NOT NULL REFERENCES blocks(number),
+        -- The won slot the auction resells, if any.
+        resale_of INTEGER,
+        removed_in INTEGER REFERENCES blocks(number)
+    );
+
+    -- kind: placed, queued, queued_placed or queued_failed.
+    CREATE TABLE IF NOT EXISTS bids (
+        block INTEGER NOT NULL REFERENCES blocks(number),
+        event_index INTEGER NOT NULL,
+        auction_id INTEGER NOT NULL,
+        bidder TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        PRIMARY KEY (block, event_index)
+    );
+
+    -- kind: confirmation_requested, won, defaulted, confirmation_missed or runner_up_promoted.
+    CREATE TABLE IF NOT EXISTS settlements (
+        block INTEGER NOT NULL REFERENCES blocks(number),
+        event_index INTEGER NOT NULL,
+        auction_id INTEGER NOT NULL,
+        bidder TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        PRIMARY KEY (block, event_index)
+    );
+
+    -- kind: creator, commission, platform_fee, burned or resale_fee.
+    CREATE TABLE IF NOT EXISTS payments (
+        block INTEGER NOT NULL REFERENCES blocks(number),
+        event_index INTEGER NOT NULL,
+        auction_id INTEGER NOT NULL,
+        account TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        PRIMARY KEY (block, event_index)
+    );
+
+    CREATE TABLE IF NOT EXISTS transfers (
+        block INTEGER NOT NULL REFERENCES blocks(number),
+        event_index INTEGER NOT NULL,
+        slot_id INTEGER NOT NULL,
+        seller TEXT NOT NULL,
+        buyer TEXT NOT NULL,
+        price TEXT NOT NULL,
+        PRIMARY KEY (block, event_index)
+    );
+
+    -- Signed calls into the auctioning pallet, including the failed ones.
+    CREATE TABLE IF NOT EXISTS calls (
+        block INTEGER NOT NULL REFERENCES blocks(number),
+        extrinsic_index INTEGER NOT NULL,
+        signer TEXT NOT NULL,
+        call TEXT NOT NULL,
+        auction_id INTEGER,
+        success INTEGER NOT NULL,
+        PRIMARY KEY (block, extrinsic_index)
+    );
+
+    CREATE INDEX IF NOT EXISTS auctions_terminal ON auctions (terminal);
+    CREATE INDEX IF NOT EXISTS bids_auction ON bids (auction_id);
+    CREATE INDEX IF NOT EXISTS bids_bidder ON bids (bidder);
+    CREATE INDEX IF NOT EXISTS settlements_auction ON settlements (auction_id);
+    CREATE INDEX IF NOT EXISTS calls_signer ON calls (signer);
+";
+
+const TABLES: [&str; 7] = [
+    "calls",
+    "transfers",
+    "payments",
+    "settlements",
+    "bids",
+    "auctions",
+    "blocks",
+];
+
+/// An auction as announced by its creation.
+pub struct NewAuction {
+    pub id: u64,
+    pub creator: String,
+    pub terminal: String,
+    pub slot_timestamp: u64,
+    pub num_con: u64,
+    pub num_teu: u64,
+    pub start: u32,
+    pub end: Option<u32>,
+}
+
+/// A row of the tables holding an account and an amount per auction event.
+pub struct Entry<'a> {
+    pub event_index: u32,
+    pub auction_id: u64,
+    pub account: String,
+    pub amount: u128,
+    pub kind: &'a str,
+}
+
+/// The indexer's database.
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path).map_err(|e| e.to_string())?;
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(|e| e.to_string())?;
+        Ok(Database { connection })
+    }
+
+    /// Drops everything indexed so far.
+    pub fn clear(&mut self) -> Result<()> {
+        let transaction = self.connection.transaction().map_err(|e| e.to_string())?;
+        for table in TABLES.iter() {
+            transaction
+                .execute(&format!("DELETE FROM {}", table), params![])
+                .map_err(|e| e.to_string())?;
+        }
+        transaction.commit().map_err(|e| e.to_string())
+    }
+
+    /// The number and hash of the highest indexed block.
+    pub fn last_block(&self) -> Result<Option<(u32, String)>> {
+        self.connection
+            .query_row(
+                "SELECT number, hash FROM blocks ORDER BY number DESC LIMIT 1",
+                params![],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Writes a block in a single transaction, a block is either indexed as a whole or not at
+    /// all.
+    pub fn block<F>(
+        &mut self,
+        number: u32,
+        hash: String,
+        timestamp: Option<u64>,
+        f: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&Writer) -> Result<()>,
+    {
+        let transaction = self.connection.transaction().map_err(|e| e.to_string())?;
+        transaction
+            .execute(
+                "INSERT INTO blocks (number, hash, timestamp) VALUES (?1, ?2, ?3)",
+                params![number, hash, timestamp.map(|t| t as i64)],
+            )
+            .map_err(|e| e.to_string())?;
+
+        let writer = Writer {
+            transaction: &transaction,
+            block: number,
+        };
+        f(&writer)?;
+
+        transaction.commit().map_err(|e| e.to_string())
+    }
+}
+
+/// Writes the rows of a single block.
+pub struct Writer<'a> {
+    transaction: &'a Transaction<'a>,
+    block: u32,
+}
+
+impl<'a> Writer<'a> {
+    pub fn auction_created(&self, auction: NewAuction) -> Result<()> {
+        self.execute(
+            "INSERT OR REPLACE INTO auctions (id, creator, terminal, slot_timestamp, num_con, \
+             num_teu, start_block, end_block, created_in) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                auction.id as i64,
+                auction.creator,
+                auction.terminal,
+                auction.slot_timestamp as i64,
+                auction.num_con as i64,
+                auction.num_teu as i64,
+                auction.start,
+                auction.end,
+                self.block,
+            ],
+        )
+    }
+
+    pub fn auction_updated(
+        &self,
+        id: u64,
+        slot_timestamp: u64,
+        num_con: u64,
+        num_teu: u64,
+        start: u32,
+        end: Option<u32>,
+    ) -> Result<()> {
+        self.execute(
+            "UPDATE auctions SET slot_timestamp = ?2, num_con = ?3, num_teu = ?4, \
+             start_block = ?5, end_block = ?6 WHERE id = ?1",
+            params![
+                id as i64,
+                slot_timestamp as i64,
+                num_con as i64,
+                num_teu as i64,
+                start,
+                end,
+            ],
+        )
+    }
+
+    pub fn auction_removed(&self, id: u64) -> Result<()> {
+        self.execute(
+            "UPDATE auctions SET removed_in = ?2 WHERE id = ?1",
+            params![id as i64, self.block],
+        )
+    }
+
+    pub fn auction_resells(&self, id: u64, slot_id: u64) -> Result<()> {
+        self.execute(
+            "UPDATE auctions SET resale_of = ?2 WHERE id = ?1",
+            params![id as i64, slot_id as i64],
+        )
+    }
+
+    pub fn bid(&self, entry: Entry) -> Result<()> {
+        self.entry("bids", "bidder", entry)
+    }
+
+    pub fn settlement(&self, entry: Entry) -> Result<()> {
+        self.entry("settlements", "bidder", entry)
+    }
+
+    pub fn payment(&self, entry: Entry) -> Result<()> {
+        self.entry("payments", "account", entry)
+    }
+
+    pub fn transfer(
+        &self,
+        event_index: u32,
+        slot_id: u64,
+        seller: String,
+        buyer: String,
+        price: u128,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO transfers (block, event_index, slot_id, seller, buyer, price) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.block,
+                event_index,
+                slot_id as i64,
+                seller,
+                buyer,
+                price.to_string(),
+            ],
+        )
+    }
+
+    pub fn call(
+        &self,
+        extrinsic_index: u32,
+        signer: String,
+        call: &str,
+        auction_id: Option<u64>,
+        success: bool,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO calls (block, extrinsic_index, signer, call, auction_id, success) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.block,
+                extrinsic_index,
+                signer,
+                call,
+                auction_id.map(|id| id as i64),
+                success,
+            ],
+        )
+    }
+
+    fn entry(&self, table: &str, account: &str, entry: Entry) -> Result<()> {
+        self.execute(
+            &format!(
+                "INSERT INTO {} (block, event_index, auction_id, {}, amount, kind) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                table, account
+            ),
+            params![
+                self.block,
+                entry.event_index,
+                entry.auction_id as i64,
+                entry.account,
+                entry.amount.to_string(),
+                entry.kind,
+            ],
+        )
+    }
+
+    fn execute(&self, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Result<()> {
+        self.transaction
+            .execute(sql, params)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir) -> Database {
+        Database::open(&dir.path().join("index.sqlite")).unwrap()
+    }
+
+    fn rows(db: &Database, table: &str) -> i64 {
+        db.connection
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {}", table),
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    fn auction(id: u64) -> NewAuction {
+        NewAuction {
+            id,
+            creator: "barge".into(),
+            terminal: "terminal".into(),
+            slot_timestamp: 1594471764,
+            num_con: 22,
+            num_teu: 40,
+            start: 10,
+            end: Some(20),
+        }
+    }
+
+    fn entry(event_index: u32, kind: &str) -> Entry {
+        Entry {
+            event_index,
+            auction_id: 1,
+            account: "bidder".into(),
+            amount: u128::max_value(),
+            kind,
+        }
+    }
+
+    /// Writes block `number` with an auction, one row in every other table of the block.
+    fn write_block(db: &mut Database, number: u32) {
+        db.block(number, format!("0x{:02x}", number), Some(6000), |writer| {
+            writer.auction_created(auction(number as u64))?;
+            writer.bid(entry(0, "placed"))?;
+            writer.settlement(entry(1, "won"))?;
+            writer.payment(entry(2, "creator"))?;
+            writer.transfer(3, 1, "seller".into(), "buyer".into(), 500)?;
+            writer.call(1, "bidder".into(), "bid", Some(1), true)
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn schema_has_every_table() {
+        let dir = TempDir::new().unwrap();
+        let db = open(&dir);
+        let tables = db
+            .connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .unwrap()
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        for table in TABLES.iter() {
+            assert!(
+                tables.iter().any(|name| name == table),
+                "{} is missing",
+                table
+            );
+        }
+
+        // Opening an existing database keeps its schema.
+        drop(db);
+        assert_eq!(rows(&open(&dir), "blocks"), 0);
+    }
+
+    #[test]
+    fn writes_a_block_as_a_whole() {
+        let dir = TempDir::new().unwrap();
+        let mut db = open(&dir);
+        write_block(&mut db, 0);
+        for table in TABLES.iter() {
+            assert_eq!(rows(&db, table), 1, "{}", table);
+        }
+        let amount: String = db
+            .connection
+            .query_row("SELECT amount FROM bids", params![], |row| row.get(0))
+            .unwrap();
+        assert_eq!(amount, u128::max_value().to_string());
+
+        // A block failing halfway leaves nothing behind.
+        let result = db.block(1, "0x01".into(), None, |writer| {
+            writer.auction_created(auction(1))?;
+            Err("failed".into())
+        });
+        assert_eq!(result, Err("failed".to_string()));
+        assert_eq!(rows(&db, "blocks"), 1);
+        assert_eq!(rows(&db, "auctions"), 1);
+        assert_eq!(db.last_block().unwrap(), Some((0, "0x00".into())));
+    }
+
+    #[test]
+    fn resumes_after_reopening() {
+        let dir = TempDir::new().unwrap();
+        let mut db = open(&dir);
+        assert_eq!(db.last_block().unwrap(), None);
+        write_block(&mut db, 0);
+        write_block(&mut db, 1);
+        drop(db);
+
+        let mut db = open(&dir);
+        assert_eq!(db.last_block().unwrap(), Some((1, "0x01".into())));
+        write_block(&mut db, 2);
+        assert_eq!(rows(&db, "blocks"), 3);
+        assert_eq!(db.last_block().unwrap(), Some((2, "0x02".into())));
+    }
+
+    #[test]
+    fn clear_drops_everything() {
+        let dir = TempDir::new().unwrap();
+        let mut db = open(&dir);
+        write_block(&mut db, 0);
+        write_block(&mut db, 1);
+        db.clear().unwrap();
+
+        assert_eq!(db.last_block().unwrap(), None);
+        for table in TABLES.iter() {
+            assert_eq!(rows(&db, table), 0, "{}", table);
+        }
+        // The schema survives, the chain is indexed again from genesis.
+        write_block(&mut db, 0);
+        assert_eq!(rows(&db, "blocks"), 1);
+    }
+}
@@ -0,0 +1,593 @@
+//! Indexes the auctioning pallet into a SQLite database for analysis, enabled by
+//! `--auction-index`.
+//!
+//! The indexer follows finalized blocks only, so the database never has to undo a block. Each
+//! block is written in a single transaction along with its number and hash, after a restart the
+//! indexer resumes after the highest block it wrote. It reads the events from the state of every
+//! block, indexing blocks which have been pruned fails, so backfilling an existing chain needs a
+//! node running with `--pruning archive`.
+//!
+//! Without a finality gadget, as on a `--manual-seal` node, blocks are indexed once they are
+//! finalized through `engine_finalizeBlock` or sealed with `finalize` set.
+
+mod db;
+
+use auction_traits::auction::AuctionInfo;
+use codec::{Decode, Encode};
+use db::{Database, Entry, NewAuction, Writer};
+use frame_support::{
+    dispatch::GetCallName,
+    storage::{StorageMap, StoragePrefixedMap},
+};
+use frame_system::{EventRecord, Phase};
+use futures::StreamExt;
+use node_auction_runtime::{
+    auction, opaque::Block, AccountId, Balance, BlockNumber, Call, Event,
+    GeneralInformationContainer, Hash, Runtime, RuntimeApi, UncheckedExtrinsic,
+};
+use sc_client_api::{BlockBackend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::Header as _;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+type FullClient = sc_service::TFullClient<Block, RuntimeApi, crate::service::Executor>;
+type AuctionInfoOf = AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+type AuctionEvent = auction::Event<Runtime>;
+type Result<T> = std::result::Result<T, String>;
+
+/// Where and how to index, as given on the command line.
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    /// Path of the SQLite database.
+    pub path: PathBuf,
+    /// Drop what has been indexed and replay the chain from genesis.
+    pub backfill: bool,
+}
+
+/// Writes the finalized blocks of a client into the database.
+pub struct Indexer {
+    client: Arc<FullClient>,
+    db: Database,
+}
+
+impl Indexer {
+    /// Opens the database of `config`, checking it belongs to the chain of `client`.
+    pub fn open(client: Arc<FullClient>, config: &IndexerConfig) -> Result<Self> {
+        let mut db = Database::open(&config.path)?;
+        if config.backfill {
+            db.clear()?;
+        }
+
+        if let Some((number, hash)) = db.last_block()? {
+            let canonical = client
+                .hash(number)
+                .map_err(|e| e.to_string())?
+                .map(|hash| format!("{:?}", hash));
+            if canonical.as_ref() != Some(&hash) {
+                return Err(format!(
+                    "The auction index at {} doesn't match the chain at block {}, rerun with \
+                     `--auction-index-backfill` to rebuild it",
+                    config.path.display(),
+                    number
+                ));
+            }
+        }
+
+        Ok(Indexer { client, db })
+    }
+
+    /// Indexes the finalized chain, then every block as it gets finalized.
+    pub async fn run(mut self) {
+        let mut finality = self.client.finality_notification_stream();
+
+        let mut target = self.client.info().finalized_number;
+        loop {
+            if let Err(e) = self.index_to(target) {
+                log::error!("Auction indexer stopped: {}", e);
+                return;
+            }
+            match finality.next().await {
+                Some(notification) => target = *notification.header.number(),
+                None => return,
+            }
+        }
+    }
+
+    /// Indexes every block after the last indexed one, up to the finalized block `target`.
+    fn index_to(&mut self, target: BlockNumber) -> Result<()> {
+        let next = match self.db.last_block()? {
+            Some((number, _)) => number + 1,
+            None => 0,
+        };
+
+        for number in next..=target {
+            let hash = self
+                .client
+                .hash(number)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Finalized block {} is missing", number))?;
+            self.index_block(number, hash)?;
+        }
+
+        Ok(())
+    }
+
+    fn index_block(&mut self, number: BlockNumber, hash: Hash) -> Result<()> {
+        let id = BlockId::Hash(hash);
+        let timestamp = self.get::<u64>(&id, storage_key(b"Timestamp", b"Now"))?;
+        let events = self
+            .get::<Vec<EventRecord<Event, Hash>>>(&id, storage_key(b"System", b"Events"))
+            .map_err(|e| format!("{} (state of block {} pruned?)", e, number))?
+            .unwrap_or_default();
+        let extrinsics = self
+            .client
+            .block_body(&id)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default();
+
+        // Auctions set up in the genesis configuration are never announced by an event.
+        let genesis = if number == 0 {
+            self.genesis_auctions(&id)?
+        } else {
+            Vec::new()
+        };
+        let mut batched = self.batched_auctions(&id, &events)?;
+
+        self.db
+            .block(number, format!("{:?}", hash), timestamp, |writer| {
+                for auction in genesis {
+                    writer.auction_created(auction)?;
+                }
+
+                let mut succeeded = BTreeSet::new();
+                for (index, record) in events.iter().enumerate() {
+                    match (&record.event, &record.phase) {
+                        (Event::auction(event), _) => {
+                            let batched = batched.remove(&index).unwrap_or_default();
+                            write_event(writer, index as u32, event, batched)?
+                        }
+                        (
+                            Event::system(frame_system::RawEvent::ExtrinsicSuccess(_)),
+                            Phase::ApplyExtrinsic(extrinsic),
+                        ) => {
+                            succeeded.insert(*extrinsic);
+                        }
+                        _ => {}
+                    }
+                }
+
+                for (index, extrinsic) in extrinsics.iter().enumerate() {
+                    // Opaque extrinsics encode just like the runtime's.
+                    let extrinsic = UncheckedExtrinsic::decode(&mut &extrinsic.encode()[..])
+                        .map_err(|e| e.to_string())?;
+                    let (signer, call) = match (extrinsic.signature, extrinsic.function) {
+                        (Some((signer, _, _)), Call::AuctionModule(call)) => (signer, call),
+                        _ => continue,
+                    };
+                    let index = index as u32;
+                    writer.call(
+                        index,
+                        signer.to_string(),
+                        call.get_call_name(),
+                        call_auction(&call),
+                        succeeded.contains(&index),
+                    )?;
+                }
+
+                Ok(())
+            })
+    }
+
+    fn genesis_auctions(&self, id: &BlockId<Block>) -> Result<Vec<NewAuction>> {
+        let prefix =
+            <auction::Auctions<Runtime> as StoragePrefixedMap<AuctionInfoOf>>::final_prefix();
+        self.client
+            .storage_pairs(id, &StorageKey(prefix.to_vec()))
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|(key, data)| {
+                // Keys are hashed with `twox_64_concat`, the id follows the 8 byte hash.
+                let auction_id = key
+                    .0
+                    .get(prefix.len() + 8..)
+                    .and_then(|mut id| Decode::decode(&mut id).ok())
+                    .ok_or("Undecodable key in `Auctions`")?;
+                let info = AuctionInfoOf::decode(&mut &data.0[..]).map_err(|e| e.to_string())?;
+                Ok(new_auction(auction_id, info))
+            })
+            .collect()
+    }
+
+    /// The auctions of the batches announced by `events`, read from the state of the block, by the
+    /// position of the announcing event.
+    ///
+    /// Batch events only carry the auction ids. An auction of a batch which was removed later in
+    /// the same block is gone from the block's state, and isn't indexed.
+    fn batched_auctions(
+        &self,
+        id: &BlockId<Block>,
+        events: &[EventRecord<Event, Hash>],
+    ) -> Result<BTreeMap<usize, Vec<NewAuction>>> {
+        let mut batched = BTreeMap::new();
+        for (index, record) in events.iter().enumerate() {
+            let ids = match &record.event {
+                Event::auction(auction::RawEvent::AuctionsCreated(_, first, last)) => {
+                    (*first..=*last).collect()
+                }
+                Event::auction(auction::RawEvent::AuctionsUpdated(_, ids)) => ids.clone(),
+                _ => continue,
+            };
+
+            let mut auctions = Vec::new();
+            for auction_id in ids {
+                let key = <auction::Auctions<Runtime>>::hashed_key_for(auction_id);
+                if let Some(info) = self.get::<AuctionInfoOf>(id, key)? {
+                    auctions.push(new_auction(auction_id, info));
+                }
+            }
+            batched.insert(index, auctions);
+        }
+        Ok(batched)
+    }
+
+    fn get<V: Decode>(&self, id: &BlockId<Block>, key: Vec<u8>) -> Result<Option<V>> {
+        self.client
+            .storage(id, &StorageKey(key))
+            .map_err(|e| e.to_string())?
+            .map(|data| V::decode(&mut &data.0[..]).map_err(|e| e.to_string()))
+            .transpose()
+    }
+}
+
+/// Writes an auction event, along with the auctions read for it if it announces a batch.
+fn write_event(
+    writer: &Writer,
+    index: u32,
+    event: &AuctionEvent,
+    batched: Vec<NewAuction>,
+) -> Result<()> {
+    use auction::RawEvent::*;
+
+    let entry = |auction_id: &u64, account: &AccountId, amount: &Balance, kind| Entry {
+        event_index: index,
+        auction_id: *auction_id,
+        account: account.to_string(),
+        amount: *amount,
+        kind,
+    };
+
+    match event {
+        AuctionCreated(id, creator, terminal, start, end, timestamp, num_con, num_teu) => writer
+            .auction_created(NewAuction {
+                id: *id,
+                creator: creator.to_string(),
+                terminal: terminal.to_string(),
+                slot_timestamp: *timestamp,
+                num_con: *num_con,
+                num_teu: *num_teu,
+                start: *start,
+                end: Some(*end),
+            }),
+        AuctionUpdated(id, _, _, _, _, _, timestamp, num_con, num_teu, start, end) => {
+            writer.auction_updated(*id, *timestamp, *num_con, *num_teu, *start, Some(*end))
+        }
+        AuctionsCreated(..) => batched
+            .into_iter()
+            .try_for_each(|auction| writer.auction_created(auction)),
+        AuctionsUpdated(..) => batched.into_iter().try_for_each(|auction| {
+            writer.auction_updated(
+                auction.id,
+                auction.slot_timestamp,
+                auction.num_con,
+                auction.num_teu,
+                auction.start,
+                auction.end,
+            )
+        }),
+        AuctionDeleted(id) => writer.auction_removed(*id),
+        SlotRelisted(slot_id, _, id) => writer.auction_resells(*id, *slot_id),
+
+        Bid(id, bidder, amount) => writer.bid(entry(id, bidder, amount, "placed")),
+        BidQueued(bidder, amount, id) => writer.bid(entry(id, bidder, amount, "queued")),
+        BidQueuedPlaced(id, bidder, amount) => {
+            writer.bid(entry(id, bidder, amount, "queued_placed"))
+        }
+        QueuedBidFailed(id, bidder, amount) => {
+            writer.bid(entry(id, bidder, amount, "queued_failed"))
+        }
+
+        ConfirmationRequested(id, winner, amount, _) => {
+            writer.settlement(entry(id, winner, amount, "confirmation_requested"))
+        }
+        SlotWon(id, winner, amount) => writer.settlement(entry(id, winner, amount, "won")),
+        WinnerDefaulted(id, winner, amount) => {
+            writer.settlement(entry(id, winner, amount, "defaulted"))
+        }
+        ConfirmationMissed(id, winner, amount) => {
+            writer.settlement(entry(id, winner, amount, "confirmation_missed"))
+        }
+        RunnerUpPromoted(id, bidder, amount) => {
+            writer.settlement(entry(id, bidder, amount, "runner_up_promoted"))
+        }
+
+        CreatorPaid(id, creator, amount) => writer.payment(entry(id, creator, amount, "creator")),
+        CommissionPaid(id, terminal, amount) => {
+            writer.payment(entry(id, terminal, amount, "commission"))
+        }
+        PlatformFeePaid(id, platform, amount) => {
+            writer.payment(entry(id, platform, amount, "platform_fee"))
+        }
+        BidBurned(id, winner, amount) => writer.payment(entry(id, winner, amount, "burned")),
+        ResaleFeePaid(slot_id, _, terminal, fee) => {
+            writer.payment(entry(slot_id, terminal, fee, "resale_fee"))
+        }
+
+        SlotTransferred(slot_id, seller, buyer, price) => writer.transfer(
+            index,
+            *slot_id,
+            seller.to_string(),
+            buyer.to_string(),
+            *price,
+        ),
+
+        _ => Ok(()),
+    }
+}
+
+/// The auction, or the won slot, a call of the auctioning pallet is about.
+fn call_auction(call: &auction::Call<Runtime>) -> Option<u64> {
+    use auction::Call::*;
+
+    match call {
+        bid(id, _)
+        | ext_update_auction(id, _, _, _, _, _)
+        | ext_remove_auction(id)
+        | confirm_win(id)
+        | approve_resale(id)
+        | list_slot(id, _)
+        | relist_slot(id, _, _)
+        | unlist_slot(id)
+        | buy_slot(id, _)
+        | clear_slot(id) => Some(*id),
+        _ => None,
+    }
+}
+
+fn new_auction(id: u64, info: AuctionInfoOf) -> NewAuction {
+    NewAuction {
+        id,
+        creator: info.creator.to_string(),
+        terminal: info.slot_origin.to_string(),
+        slot_timestamp: info.core.timestamp,
+        num_con: info.core.cargo.0,
+        num_teu: info.core.cargo.1,
+        start: info.start,
+        end: info.end,
+    }
+}
+
+fn storage_key(module: &[u8], item: &[u8]) -> Vec<u8> {
+    [twox_128(module), twox_128(item)].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{Executor, SlotTimestampProvider};
+    use futures::channel::{mpsc, oneshot};
+    use futures::{executor::block_on, SinkExt};
+    use node_auction_runtime::{SignedExtra, VERSION};
+    use rusqlite::{params, Connection};
+    use sc_consensus_manual_seal::{run_manual_seal, EngineCommand};
+    use sc_transaction_pool::{BasicPool, FullPool};
+    use sp_core::Pair;
+    use sp_runtime::{generic, BuildStorage};
+    use sp_transaction_pool::{TransactionPool, TransactionSource};
+    use substrate_test_client::{
+        AccountKeyring, ExecutionStrategy, GenesisInit, Storage, TestClientBuilder,
+    };
+    use tempfile::TempDir;
+
+    /// The genesis of the `--dev` chain.
+    #[derive(Default)]
+    struct DevGenesis;
+
+    impl GenesisInit for DevGenesis {
+        fn genesis_storage(&self) -> Storage {
+            crate::chain_spec::development_config()
+                .unwrap()
+                .build_storage()
+                .unwrap()
+        }
+    }
+
+    /// A development node sealing blocks on request, as started with `--manual-seal`.
+    struct Node {
+        client: Arc<FullClient>,
+        pool: Arc<FullPool<Block, FullClient>>,
+        commands: mpsc::Sender<EngineCommand<Hash>>,
+    }
+
+    impl Node {
+        fn start() -> Self {
+            let (client, select_chain) = TestClientBuilder::<
+                Block,
+                sc_service::TFullCallExecutor<Block, Executor>,
+                _,
+                DevGenesis,
+            >::with_default_backend()
+            .set_execution_strategy(ExecutionStrategy::NativeWhenPossible)
+            .build_with_native_executor::<RuntimeApi, _>(None);
+            let client = Arc::new(client);
+
+            let pool = BasicPool::new_full(
+                Default::default(),
+                None,
+                sp_core::testing::TaskExecutor::new(),
+                client.clone(),
+            );
+            let proposer =
+                sc_basic_authorship::ProposerFactory::new(client.clone(), pool.clone(), None);
+            let inherent_data_providers = sp_inherents::InherentDataProviders::new();
+            inherent_data_providers
+                .register_provider(SlotTimestampProvider::new(&client))
+                .unwrap();
+
+            let (commands, stream) = mpsc::channel(16);
+            let authorship = run_manual_seal(
+                Box::new(client.clone()),
+                proposer,
+                client.clone(),
+                pool.pool().clone(),
+                stream,
+                select_chain,
+                inherent_data_providers,
+            );
+            std::thread::spawn(move || block_on(authorship));
+
+            Node {
+                client,
+                pool,
+                commands,
+            }
+        }
+
+        /// Seals and finalizes a block with the transactions in the pool.
+        fn seal(&mut self) -> BlockNumber {
+            let (sender, receiver) = oneshot::channel();
+            block_on(async {
+                self.commands
+                    .send(EngineCommand::SealNewBlock {
+                        create_empty: true,
+                        finalize: true,
+                        parent_hash: None,
+                        sender: Some(sender),
+                    })
+                    .await
+                    .unwrap();
+                receiver.await.unwrap().unwrap();
+            });
+            self.client.info().finalized_number
+        }
+
+        /// Submits `call`, signed by Bob with an immortal era.
+        fn submit(&self, call: Call, nonce: u32) {
+            let genesis = self.client.info().genesis_hash;
+            let extra: SignedExtra = (
+                frame_system::CheckSpecVersion::new(),
+                frame_system::CheckTxVersion::new(),
+                frame_system::CheckGenesis::new(),
+                frame_system::CheckEra::from(generic::Era::Immortal),
+                frame_system::CheckNonce::from(nonce),
+                frame_system::CheckWeight::new(),
+                transaction_payment::ChargeTransactionPayment::from(0),
+            );
+            let additional = (
+                VERSION.spec_version,
+                VERSION.transaction_version,
+                genesis,
+                genesis,
+                (),
+                (),
+                (),
+            );
+            let payload = generic::SignedPayload::from_raw(call, extra, additional);
+            let signature =
+                payload.using_encoded(|payload| AccountKeyring::Bob.pair().sign(payload));
+            let (call, extra, _) = payload.deconstruct();
+            let extrinsic = UncheckedExtrinsic::new_signed(
+                call,
+                AccountKeyring::Bob.to_account_id(),
+                signature.into(),
+                extra,
+            );
+
+            let best = BlockId::Hash(self.client.info().best_hash);
+            let opaque = Decode::decode(&mut &extrinsic.encode()[..]).unwrap();
+            block_on(
+                self.pool
+                    .submit_one(&best, TransactionSource::External, opaque),
+            )
+            .unwrap();
+        }
+    }
+
+    fn count(index: &Connection, sql: &str) -> i64 {
+        index.query_row(sql, params![], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn indexes_a_manual_seal_node() {
+        let dir = TempDir::new().unwrap();
+        let mut config = IndexerConfig {
+            path: dir.path().join("index.sqlite"),
+            backfill: false,
+        };
+        let index = Connection::open(&config.path).unwrap();
+        let mut node = Node::start();
+
+        let mut indexer = Indexer::open(node.client.clone(), &config).unwrap();
+        indexer.index_to(0).unwrap();
+        let genesis_auctions = count(&index, "SELECT COUNT(*) FROM auctions");
+        assert!(genesis_auctions > 0);
+        // The development chain holds a single auction starting at block 100.
+        let queued: u64 = index
+            .query_row(
+                "SELECT id FROM auctions WHERE start_block = 100",
+                params![],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap() as u64;
+
+        node.submit(Call::AuctionModule(auction::Call::bid(queued, 300)), 0);
+        let finalized = node.seal();
+        indexer.index_to(finalized).unwrap();
+
+        let bob = AccountKeyring::Bob.to_account_id().to_string();
+        let (bidder, amount, kind): (String, String, String) = index
+            .query_row(
+                "SELECT bidder, amount, kind FROM bids WHERE block = 1 AND auction_id = ?1",
+                params![queued as i64],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (bidder.as_str(), amount.as_str(), kind.as_str()),
+            (bob.as_str(), "300", "queued")
+        );
+        assert_eq!(
+            count(
+                &index,
+                "SELECT COUNT(*) FROM calls WHERE call = 'bid' AND success = 1"
+            ),
+            1
+        );
+
+        // A restarted indexer resumes after the last block it wrote.
+        drop(indexer);
+        node.seal();
+        let finalized = node.seal();
+        let mut indexer = Indexer::open(node.client.clone(), &config).unwrap();
+        indexer.index_to(finalized).unwrap();
+        assert_eq!(count(&index, "SELECT COUNT(*) FROM blocks"), 4);
+        assert_eq!(count(&index, "SELECT COUNT(*) FROM bids"), 1);
+        assert_eq!(count(&index, "SELECT MAX(number) FROM blocks"), 3);
+
+        // Backfilling replays the chain from genesis into an emptied index.
+        drop(indexer);
+        config.backfill = true;
+        let mut indexer = Indexer::open(node.client.clone(), &config).unwrap();
+        assert_eq!(count(&index, "SELECT COUNT(*) FROM blocks"), 0);
+        indexer.index_to(finalized).unwrap();
+        assert_eq!(count(&index, "SELECT COUNT(*) FROM blocks"), 4);
+        assert_eq!(
+            count(&index, "SELECT COUNT(*) FROM auctions"),
+            genesis_auctions
+        );
+        assert_eq!(count(&index, "SELECT COUNT(*) FROM bids"), 1);
+    }
+}
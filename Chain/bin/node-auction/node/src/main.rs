@@ -1,12 +1,16 @@
 //! Substrate Node Auction CLI library.
 #![warn(missing_docs)]
 
+mod auction;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
 mod command;
+mod indexer;
+mod metrics;
 mod rpc;
+mod types;
 
 fn main() -> sc_cli::Result<()> {
     command::run()
@@ -0,0 +1,468 @@
+//! Prometheus metrics of the auctioning pallet.
+//!
+//! The metrics follow the finalized chain, so a reorg never counts an event twice: counters add up
+//! the events of every finalized block, gauges reflect the auctioning pallet's storage at the last
+//! finalized block. Counting starts at the block finalized as the node starts. Every node which
+//! exposes Prometheus metrics reports them, whether it authors blocks or not.
+//!
+//! The gauges scan whole storage maps, the metrics run as a blocking task to keep these reads off
+//! the async executor.
+
+use auction_traits::auction::{AuctionInfo, QueuedBid, WonSlot};
+use codec::Decode;
+use frame_support::storage::{StorageMap, StoragePrefixedMap};
+use frame_system::EventRecord;
+use futures::StreamExt;
+use node_auction_runtime::{
+    auction, opaque::Block, AccountId, AuctionId, Balance, BlockNumber, Event,
+    GeneralInformationContainer, Hash, Runtime,
+};
+use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, F64, U64};
+use sc_client_api::{backend::Backend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::Header as _;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+type AuctionInfoOf = AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+type QueuedBidOf = QueuedBid<AccountId, Balance, AuctionId>;
+type WonSlotOf = WonSlot<AccountId, Balance, AuctionId, GeneralInformationContainer>;
+type Result<T> = std::result::Result<T, String>;
+
+/// Auction metrics registered in the node's Prometheus registry.
+pub struct AuctionMetrics {
+    live_auctions: Gauge<U64>,
+    queued_auctions: Gauge<U64>,
+    reserved_volume: Gauge<F64>,
+    block_bids: Gauge<U64>,
+    bids: Counter<U64>,
+    settlements_succeeded: Counter<U64>,
+    settlements_failed: Counter<U64>,
+    queued_bid_failures: Counter<U64>,
+}
+
+impl AuctionMetrics {
+    /// Registers the auction metrics at `registry`.
+    pub fn register(registry: &Registry) -> std::result::Result<Self, PrometheusError> {
+        Ok(Self {
+            live_auctions: register(
+                Gauge::new(
+                    "auction_live_auctions",
+                    "Number of auctions which have started",
+                )?,
+                registry,
+            )?,
+            queued_auctions: register(
+                Gauge::new(
+                    "auction_queued_auctions",
+                    "Number of auctions which are yet to start",
+                )?,
+                registry,
+            )?,
+            reserved_volume: register(
+                Gauge::new(
+                    "auction_reserved_volume",
+                    "Total of the bids held in reserve: highest, queued, runner-up and \
+                     unconfirmed winning bids",
+                )?,
+                registry,
+            )?,
+            block_bids: register(
+                Gauge::new(
+                    "auction_block_bids",
+                    "Number of bids placed or queued in the last finalized block",
+                )?,
+                registry,
+            )?,
+            bids: register(
+                Counter::new(
+                    "auction_bids_total",
+                    "Total number of bids placed or queued",
+                )?,
+                registry,
+            )?,
+            settlements_succeeded: register(
+                Counter::new(
+                    "auction_settlements_succeeded_total",
+                    "Total number of auctions whose winner paid for the slot",
+                )?,
+                registry,
+            )?,
+            settlements_failed: register(
+                Counter::new(
+                    "auction_settlements_failed_total",
+                    "Total number of winners who defaulted or missed their confirmation",
+                )?,
+                registry,
+            )?,
+            queued_bid_failures: register(
+                Counter::new(
+                    "auction_queued_bid_failures_total",
+                    "Total number of queued bids which could not be placed as their auction \
+                     started",
+                )?,
+                registry,
+            )?,
+        })
+    }
+
+    /// Counts the events of every block after `counted` up to the finalized block `target`, then
+    /// updates the gauges from the state of `target`.
+    fn update<B, C>(
+        &self,
+        client: &C,
+        counted: BlockNumber,
+        target: BlockNumber,
+        hash: Hash,
+    ) -> Result<()>
+    where
+        B: Backend<Block>,
+        C: StorageProvider<Block, B> + HeaderBackend<Block>,
+    {
+        let state = |number: BlockNumber| -> Result<BlockState<B, C>> {
+            let hash = client
+                .hash(number)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Finalized block {} is missing", number))?;
+            Ok(BlockState::new(client, hash))
+        };
+        for number in counted + 1..=target {
+            self.count(&state(number)?, &state(number - 1)?)?;
+        }
+        self.measure(&BlockState::new(client, hash))
+    }
+
+    /// Adds the events of a block to the counters, `parent` is the state of the block's parent.
+    fn count(&self, state: &impl State, parent: &impl State) -> Result<()> {
+        let mut block_bids = 0;
+        for record in events(state)? {
+            match record.event {
+                Event::auction(auction::RawEvent::Bid(..))
+                | Event::auction(auction::RawEvent::BidQueued(..)) => block_bids += 1,
+                Event::auction(auction::RawEvent::SlotWon(..)) => self.settlements_succeeded.inc(),
+                // A slot bought at its fixed price changes hands as well, only a slot relisted in
+                // an auction is settled.
+                Event::auction(auction::RawEvent::SlotTransferred(slot_id, ..)) => {
+                    if in_resale_auction(parent, slot_id)? {
+                        self.settlements_succeeded.inc()
+                    }
+                }
+                Event::auction(auction::RawEvent::WinnerDefaulted(..))
+                | Event::auction(auction::RawEvent::ConfirmationMissed(..)) => {
+                    self.settlements_failed.inc()
+                }
+                Event::auction(auction::RawEvent::QueuedBidFailed(..)) => {
+                    self.queued_bid_failures.inc()
+                }
+                _ => {}
+            }
+        }
+        self.block_bids.set(block_bids);
+        self.bids.inc_by(block_bids);
+
+        Ok(())
+    }
+
+    /// Sets the gauges from the auctioning pallet's storage in `state`.
+    fn measure(&self, state: &impl State) -> Result<()> {
+        let now = state
+            .get(&storage_key(b"System", b"Number"))?
+            .and_then(|data| BlockNumber::decode(&mut &data[..]).ok())
+            .unwrap_or_default();
+
+        let auctions = values::<AuctionInfoOf>(
+            state,
+            <auction::Auctions<Runtime> as StoragePrefixedMap<AuctionInfoOf>>::final_prefix(),
+        )?;
+        let live = auctions.iter().filter(|info| info.start <= now).count() as u64;
+        self.live_auctions.set(live);
+        self.queued_auctions.set(auctions.len() as u64 - live);
+
+        let pending = values::<AuctionInfoOf>(
+            state,
+            <auction::PendingSettlements<Runtime> as StoragePrefixedMap<_>>::final_prefix(),
+        )?;
+        let queued = values::<Vec<QueuedBidOf>>(
+            state,
+            <auction::QueuedBids<Runtime> as StoragePrefixedMap<_>>::final_prefix(),
+        )?;
+        let runner_ups = values::<Vec<(AccountId, Balance)>>(
+            state,
+            <auction::RunnerUps<Runtime> as StoragePrefixedMap<_>>::final_prefix(),
+        )?;
+
+        let reserved = auctions
+            .iter()
+            .chain(pending.iter())
+            .filter_map(|info| info.bid.as_ref().map(|bid| bid.1))
+            .chain(queued.iter().flatten().map(|queued| queued.bid.1))
+            .chain(runner_ups.iter().flatten().map(|runner_up| runner_up.1))
+            .fold(0 as Balance, |total, bid| total.saturating_add(bid));
+        self.reserved_volume.set(reserved as f64);
+
+        Ok(())
+    }
+}
+
+/// Keeps `metrics` up to date with the finalized chain, until the client shuts down.
+pub async fn run<B, C>(client: Arc<C>, metrics: AuctionMetrics)
+where
+    B: Backend<Block>,
+    C: BlockchainEvents<Block> + StorageProvider<Block, B> + HeaderBackend<Block>,
+    C: Send + Sync + 'static,
+{
+    let mut finality = client.finality_notification_stream();
+
+    let mut counted = client.info().finalized_number;
+    while let Some(notification) = finality.next().await {
+        let target = *notification.header.number();
+        if let Err(e) = metrics.update(&*client, counted, target, notification.hash) {
+            log::warn!(
+                "Failed to update auction metrics at {}: {}",
+                notification.hash,
+                e
+            );
+        }
+        // Blocks which failed to be counted are skipped rather than retried, their state is
+        // likely pruned.
+        counted = counted.max(target);
+    }
+}
+
+/// Storage of a block, as far as the metrics read it.
+trait State {
+    /// Returns the value stored under `key`.
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the values of the keys starting with `prefix`.
+    fn values(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>>;
+}
+
+/// The state of a block the client knows.
+struct BlockState<'a, B, C> {
+    client: &'a C,
+    id: BlockId<Block>,
+    backend: PhantomData<B>,
+}
+
+impl<'a, B, C> BlockState<'a, B, C> {
+    fn new(client: &'a C, hash: Hash) -> Self {
+        Self {
+            client,
+            id: BlockId::Hash(hash),
+            backend: PhantomData,
+        }
+    }
+}
+
+impl<'a, B, C> State for BlockState<'a, B, C>
+where
+    B: Backend<Block>,
+    C: StorageProvider<Block, B>,
+{
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.client
+            .storage(&self.id, &StorageKey(key.to_vec()))
+            .map(|data| data.map(|data| data.0))
+            .map_err(|e| e.to_string())
+    }
+
+    fn values(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.client
+            .storage_pairs(&self.id, &StorageKey(prefix.to_vec()))
+            .map(|pairs| pairs.into_iter().map(|(_, data)| data.0).collect())
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn storage_key(module: &[u8], item: &[u8]) -> Vec<u8> {
+    [twox_128(module), twox_128(item)].concat()
+}
+
+fn events(state: &impl State) -> Result<Vec<EventRecord<Event, Hash>>> {
+    state
+        .get(&storage_key(b"System", b"Events"))?
+        .map(|data| Decode::decode(&mut &data[..]).map_err(|e| e.to_string()))
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+/// Returns whether slot `slot_id` is relisted in an auction in `state`.
+fn in_resale_auction(state: &impl State, slot_id: AuctionId) -> Result<bool> {
+    let key =
+        <auction::WonSlots<Runtime> as StorageMap<AuctionId, WonSlotOf>>::hashed_key_for(slot_id);
+    let slot = state
+        .get(&key)?
+        .map(|data| WonSlotOf::decode(&mut &data[..]).map_err(|e| e.to_string()))
+        .transpose()?;
+    Ok(slot.map_or(false, |slot| slot.resale.is_some()))
+}
+
+/// Returns the decoded values of the map under `prefix`.
+fn values<V: Decode>(state: &impl State, prefix: [u8; 32]) -> Result<Vec<V>> {
+    state
+        .values(&prefix)?
+        .into_iter()
+        .map(|data| V::decode(&mut &data[..]).map_err(|e| e.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auction_traits::auction::AuctionCoreInfo;
+    use codec::Encode;
+    use frame_system::Phase;
+    use std::collections::BTreeMap;
+
+    impl State for BTreeMap<Vec<u8>, Vec<u8>> {
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+            Ok(BTreeMap::get(self, key).cloned())
+        }
+
+        fn values(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>> {
+            Ok(self
+                .range(prefix.to_vec()..)
+                .take_while(|(key, _)| key.starts_with(prefix))
+                .map(|(_, value)| value.clone())
+                .collect())
+        }
+    }
+
+    fn metrics() -> AuctionMetrics {
+        AuctionMetrics::register(&Registry::new()).unwrap()
+    }
+
+    fn account(byte: u8) -> AccountId {
+        AccountId::from([byte; 32])
+    }
+
+    fn with_events(events: Vec<auction::Event<Runtime>>) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        let records = events
+            .into_iter()
+            .map(|event| EventRecord {
+                phase: Phase::Finalization,
+                event: Event::auction(event),
+                topics: Vec::<Hash>::new(),
+            })
+            .collect::<Vec<_>>();
+        let mut state = BTreeMap::new();
+        state.insert(storage_key(b"System", b"Events"), records.encode());
+        state
+    }
+
+    fn insert<S, V>(state: &mut BTreeMap<Vec<u8>, Vec<u8>>, id: AuctionId, value: V)
+    where
+        S: StorageMap<AuctionId, V>,
+        V: codec::FullCodec,
+    {
+        state.insert(S::hashed_key_for(id), value.encode());
+    }
+
+    fn slot(price: Option<Balance>, resale: Option<AuctionId>) -> WonSlotOf {
+        WonSlot {
+            holder: account(1),
+            slot_origin: account(2),
+            core: AuctionCoreInfo::default(),
+            price,
+            resale,
+            approved: false,
+        }
+    }
+
+    fn auction_info(start: BlockNumber, bid: Option<Balance>) -> AuctionInfoOf {
+        AuctionInfo {
+            creator: account(1),
+            slot_origin: account(2),
+            bid: bid.map(|bid| (account(3), bid)),
+            core: AuctionCoreInfo::default(),
+            start,
+            end: Some(start + 10),
+        }
+    }
+
+    #[test]
+    fn counts_the_events_of_a_block() {
+        let metrics = metrics();
+        let state = with_events(vec![
+            auction::RawEvent::Bid(1, account(3), 100),
+            auction::RawEvent::BidQueued(account(4), 50, 2),
+            auction::RawEvent::WinnerDefaulted(1, account(3), 100),
+            auction::RawEvent::ConfirmationMissed(3, account(5), 70),
+            auction::RawEvent::QueuedBidFailed(2, account(4), 50),
+        ]);
+        metrics.count(&state, &BTreeMap::new()).unwrap();
+
+        assert_eq!(metrics.block_bids.get(), 2);
+        assert_eq!(metrics.bids.get(), 2);
+        assert_eq!(metrics.settlements_failed.get(), 2);
+        assert_eq!(metrics.queued_bid_failures.get(), 1);
+        assert_eq!(metrics.settlements_succeeded.get(), 0);
+
+        // The gauge holds the bids of the last block only, the counter keeps adding up.
+        metrics
+            .count(
+                &with_events(vec![auction::RawEvent::Bid(1, account(4), 120)]),
+                &BTreeMap::new(),
+            )
+            .unwrap();
+        assert_eq!(metrics.block_bids.get(), 1);
+        assert_eq!(metrics.bids.get(), 3);
+    }
+
+    #[test]
+    fn counts_auction_settlements_only() {
+        let metrics = metrics();
+        let mut parent = BTreeMap::new();
+        insert::<auction::WonSlots<Runtime>, _>(&mut parent, 5, slot(None, Some(9)));
+        insert::<auction::WonSlots<Runtime>, _>(&mut parent, 6, slot(Some(80), None));
+        let state = with_events(vec![
+            auction::RawEvent::SlotWon(4, account(3), 100),
+            // Slot 5 is sold in its resale auction, slot 6 at its fixed price.
+            auction::RawEvent::SlotTransferred(5, account(1), account(3), 90),
+            auction::RawEvent::SlotTransferred(6, account(1), account(4), 80),
+        ]);
+        metrics.count(&state, &parent).unwrap();
+
+        assert_eq!(metrics.settlements_succeeded.get(), 2);
+        assert_eq!(metrics.settlements_failed.get(), 0);
+    }
+
+    #[test]
+    fn measures_the_auction_state() {
+        let metrics = metrics();
+        let mut state = BTreeMap::new();
+        state.insert(storage_key(b"System", b"Number"), 10u32.encode());
+        insert::<auction::Auctions<Runtime>, _>(&mut state, 1, auction_info(5, Some(100)));
+        insert::<auction::Auctions<Runtime>, _>(&mut state, 2, auction_info(10, None));
+        insert::<auction::Auctions<Runtime>, _>(&mut state, 3, auction_info(20, None));
+        insert::<auction::PendingSettlements<Runtime>, _>(&mut state, 4, auction_info(1, Some(50)));
+        insert::<auction::QueuedBids<Runtime>, _>(
+            &mut state,
+            3,
+            vec![
+                QueuedBid {
+                    bid: (account(4), 10),
+                    auction_id: 3,
+                },
+                QueuedBid {
+                    bid: (account(5), 20),
+                    auction_id: 3,
+                },
+            ],
+        );
+        insert::<auction::RunnerUps<Runtime>, _>(&mut state, 1, vec![(account(6), 5)]);
+        metrics.measure(&state).unwrap();
+
+        assert_eq!(metrics.live_auctions.get(), 2);
+        assert_eq!(metrics.queued_auctions.get(), 1);
+        assert_eq!(metrics.reserved_volume.get(), 185.0);
+
+        // An empty state clears the gauges.
+        metrics.measure(&BTreeMap::new()).unwrap();
+        assert_eq!(metrics.live_auctions.get(), 0);
+        assert_eq!(metrics.queued_auctions.get(), 0);
+        assert_eq!(metrics.reserved_volume.get(), 0.0);
+    }
+}
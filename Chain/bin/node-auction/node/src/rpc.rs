@@ -1,15 +1,17 @@
-use std::{fmt, sync::Arc};
+use std::sync::Arc;
 
+use futures::channel::mpsc;
 use node_auction_runtime::{
     opaque::Block, AccountId, AuctionId, Balance, BlockNumber, GeneralInformationContainer,
     Index, UncheckedExtrinsic,
 };
 use sc_client_api::backend::{Backend, StateBackend, StorageProvider};
+use sc_consensus_manual_seal::rpc::{EngineCommand, ManualSeal, ManualSealApi};
 use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
-use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT};
 use sp_transaction_pool::TransactionPool;
 
 /// Full client dependencies.
@@ -20,6 +22,8 @@ pub struct FullDeps<C, P> {
     pub pool: Arc<P>,
     /// Whether to deny unsafe calls
     pub deny_unsafe: DenyUnsafe,
+    /// Channel to the manual seal authorship task, only present on development nodes.
+    pub command_sink: Option<mpsc::Sender<EngineCommand<<Block as BlockT>::Hash>>>,
 }
 
 /// Instantiate all Full RPC extensions.
@@ -41,7 +45,7 @@ where
         BlockNumber,
         GeneralInformationContainer,
     >,
-    <C::Api as sp_api::ApiErrorExt>::Error: fmt::Debug,
+    C::Api: sp_api::ApiErrorExt<Error = BlockChainError>,
     P: TransactionPool<Block = Block> + 'static,
 {
     use payment_rpc::{TransactionPayment, TransactionPaymentApi};
@@ -53,6 +57,7 @@ where
         client,
         pool,
         deny_unsafe,
+        command_sink,
     } = deps;
 
     io.extend_with(SystemApi::to_delegate(FullSystem::new(
@@ -66,6 +71,22 @@ where
     io.extend_with(auction_rpc::AuctionInformationAPI::to_delegate(
         auction_rpc::AuctionInformation::new(client.clone()),
     ));
+    // The storage reads aren't tied to the runtime API, so the auction types are named here.
+    let storage: auction_rpc::FullAuctionStorage<BE, Block, C> =
+        auction_rpc::FullAuctionStorage::new(client.clone());
+    io.extend_with(auction_rpc::AuctionStorageApi::<
+        <Block as BlockT>::Hash,
+        AccountId,
+        AuctionId,
+        Balance,
+        BlockNumber,
+        GeneralInformationContainer,
+    >::to_delegate(storage));
+
+    if let Some(command_sink) = command_sink {
+        // Exposes `engine_createBlock` and `engine_finalizeBlock`.
+        io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+    }
 
     io
 }
@@ -1,16 +1,24 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_auction_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::ExecutorProvider;
+use crate::indexer::{Indexer, IndexerConfig};
+use codec::Decode;
+use futures::{channel::mpsc, Stream, StreamExt};
+use node_auction_runtime::{self, opaque::Block, RuntimeApi, SLOT_DURATION};
+use sc_client_api::{ExecutorProvider, StorageProvider};
+use sc_consensus_manual_seal::EngineCommand;
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sc_finality_grandpa::{
     FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState,
 };
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sp_blockchain::HeaderBackend;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::sync::Arc;
-use std::time::Duration;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_inherents::{InherentData, InherentIdentifier, ProvideInherentData};
+use sp_runtime::generic::BlockId;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Our native executor instance.
 native_executor_instance!(
@@ -50,7 +58,7 @@ pub fn new_partial(
 > {
     let inherent_data_providers = sp_inherents::InherentDataProviders::new();
 
-    let (client, backend, keystore, task_manager) =
+    let (client, backend, keystore_container, task_manager) =
         sc_service::new_full_parts::<Block, RuntimeApi, Executor>(&config)?;
     let client = Arc::new(client);
 
@@ -90,7 +98,7 @@ pub fn new_partial(
         backend,
         task_manager,
         import_queue,
-        keystore,
+        keystore_container,
         select_chain,
         transaction_pool,
         inherent_data_providers,
@@ -98,19 +106,25 @@ pub fn new_partial(
     })
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds a new service for a full client, running the auction indexer if `indexer` is given.
+pub fn new_full(
+    config: Configuration,
+    indexer: Option<IndexerConfig>,
+) -> Result<TaskManager, ServiceError> {
     let sc_service::PartialComponents {
         client,
         backend,
         mut task_manager,
         import_queue,
-        keystore,
+        keystore_container,
         select_chain,
         transaction_pool,
         inherent_data_providers,
         other: (block_import, grandpa_link),
     } = new_partial(&config)?;
+    let keystore = keystore_container.keystore();
+
+    persist_transaction_pool(&config, &client, &transaction_pool, &task_manager);
 
     let finality_proof_provider =
         GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
@@ -154,6 +168,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
                 client: client.clone(),
                 pool: pool.clone(),
                 deny_unsafe,
+                command_sink: None,
             };
 
             crate::rpc::create_full(deps)
@@ -176,6 +191,21 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
         config,
     })?;
 
+    if let Some(registry) = prometheus_registry.as_ref() {
+        let metrics = crate::metrics::AuctionMetrics::register(registry)?;
+        task_manager.spawn_handle().spawn_blocking(
+            "auction-metrics",
+            crate::metrics::run(client.clone(), metrics),
+        );
+    }
+
+    if let Some(indexer) = indexer {
+        let indexer = Indexer::open(client.clone(), &indexer).map_err(ServiceError::Other)?;
+        task_manager
+            .spawn_handle()
+            .spawn_blocking("auction-indexer", indexer.run());
+    }
+
     if role.is_authority() {
         let proposer = sc_basic_authorship::ProposerFactory::new(
             client.clone(),
@@ -209,7 +239,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
     // if the node isn't actively participating in consensus then it doesn't
     // need a keystore, regardless of which protocol we use below.
     let keystore = if role.is_authority() {
-        Some(keystore as sp_core::traits::BareCryptoStorePtr)
+        Some(keystore)
     } else {
         None
     };
@@ -254,3 +284,239 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 
     Ok(task_manager)
 }
+
+/// Restores the transactions persisted by a previous run into the pool and keeps them persisted
+/// from then on, if `--pool-persist` is set. Does nothing for an in-memory database.
+fn persist_transaction_pool(
+    config: &Configuration,
+    client: &FullClient,
+    transaction_pool: &sc_transaction_pool::FullPool<Block, FullClient>,
+    task_manager: &TaskManager,
+) {
+    let path = match config.transaction_pool_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    task_manager.spawn_handle().spawn_blocking(
+        "txpool-persist",
+        transaction_pool.persist(path, BlockId::hash(client.info().best_hash)),
+    );
+}
+
+/// Block authoring modes of a development node, see `new_dev`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+    /// Seal a block as soon as a transaction enters the pool, or when requested over RPC.
+    Instant,
+    /// Seal a block only when requested over RPC.
+    Manual,
+}
+
+/// Builds a development service for a full client which replaces Aura and GRANDPA with the
+/// manual seal engine. Besides the blocks authored according to `sealing`, blocks can always be
+/// requested through `engine_createBlock` and finalized through `engine_finalizeBlock`.
+pub fn new_dev(
+    config: Configuration,
+    sealing: Sealing,
+    indexer: Option<IndexerConfig>,
+) -> Result<TaskManager, ServiceError> {
+    let (client, backend, keystore_container, mut task_manager) =
+        sc_service::new_full_parts::<Block, RuntimeApi, Executor>(&config)?;
+    let client = Arc::new(client);
+
+    let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+    let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+        config.transaction_pool.clone(),
+        config.prometheus_registry(),
+        task_manager.spawn_handle(),
+        client.clone(),
+    );
+
+    persist_transaction_pool(&config, &client, &transaction_pool, &task_manager);
+
+    let inherent_data_providers = sp_inherents::InherentDataProviders::new();
+    inherent_data_providers
+        .register_provider(SlotTimestampProvider::new(&client))
+        .map_err(sp_consensus::Error::InherentData)?;
+
+    let import_queue = sc_consensus_manual_seal::import_queue(
+        Box::new(client.clone()),
+        &task_manager.spawn_handle(),
+        config.prometheus_registry(),
+    );
+
+    let (network, network_status_sinks, system_rpc_tx) =
+        sc_service::build_network(sc_service::BuildNetworkParams {
+            config: &config,
+            client: client.clone(),
+            transaction_pool: transaction_pool.clone(),
+            spawn_handle: task_manager.spawn_handle(),
+            import_queue,
+            on_demand: None,
+            block_announce_validator_builder: None,
+            finality_proof_request_builder: None,
+            finality_proof_provider: None,
+        })?;
+
+    if config.offchain_worker.enabled {
+        sc_service::build_offchain_workers(
+            &config,
+            backend.clone(),
+            task_manager.spawn_handle(),
+            client.clone(),
+            network.clone(),
+        );
+    }
+
+    let prometheus_registry = config.prometheus_registry().cloned();
+    let (command_sink, rpc_commands) = mpsc::channel(1024);
+
+    let rpc_extensions_builder = {
+        let client = client.clone();
+        let pool = transaction_pool.clone();
+
+        Box::new(move |deny_unsafe| {
+            let deps = crate::rpc::FullDeps {
+                client: client.clone(),
+                pool: pool.clone(),
+                deny_unsafe,
+                command_sink: Some(command_sink.clone()),
+            };
+
+            crate::rpc::create_full(deps)
+        })
+    };
+
+    sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+        network,
+        client: client.clone(),
+        keystore: keystore_container.keystore(),
+        task_manager: &mut task_manager,
+        transaction_pool: transaction_pool.clone(),
+        telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
+        rpc_extensions_builder,
+        on_demand: None,
+        remote_blockchain: None,
+        backend,
+        network_status_sinks,
+        system_rpc_tx,
+        config,
+    })?;
+
+    if let Some(registry) = prometheus_registry.as_ref() {
+        let metrics = crate::metrics::AuctionMetrics::register(registry)?;
+        task_manager.spawn_handle().spawn_blocking(
+            "auction-metrics",
+            crate::metrics::run(client.clone(), metrics),
+        );
+    }
+
+    if let Some(indexer) = indexer {
+        let indexer = Indexer::open(client.clone(), &indexer).map_err(ServiceError::Other)?;
+        task_manager
+            .spawn_handle()
+            .spawn_blocking("auction-indexer", indexer.run());
+    }
+
+    let commands: Box<dyn Stream<Item = EngineCommand<_>> + Send + Unpin> = match sealing {
+        Sealing::Manual => Box::new(rpc_commands),
+        Sealing::Instant => {
+            let pool_commands = transaction_pool
+                .pool()
+                .validated_pool()
+                .import_notification_stream()
+                .map(|_| EngineCommand::SealNewBlock {
+                    create_empty: false,
+                    finalize: false,
+                    parent_hash: None,
+                    sender: None,
+                });
+
+            Box::new(futures::stream::select(pool_commands, rpc_commands))
+        }
+    };
+
+    let proposer = sc_basic_authorship::ProposerFactory::new(
+        client.clone(),
+        transaction_pool.clone(),
+        prometheus_registry.as_ref(),
+    );
+
+    let authorship = sc_consensus_manual_seal::run_manual_seal(
+        Box::new(client.clone()),
+        proposer,
+        client,
+        transaction_pool.pool().clone(),
+        commands,
+        select_chain,
+        inherent_data_providers,
+    );
+
+    // the authorship task is the only source of blocks, if it fails we take down the service
+    // with it.
+    task_manager
+        .spawn_essential_handle()
+        .spawn_blocking("manual-seal", authorship);
+
+    Ok(task_manager)
+}
+
+/// Timestamp inherent provider for the manual seal engine. The runtime still runs Aura, which only
+/// accepts a single block per slot, so every sealed block is stamped at least one slot after its
+/// predecessor regardless of how fast blocks are requested.
+pub(crate) struct SlotTimestampProvider {
+    last: Mutex<u64>,
+}
+
+impl SlotTimestampProvider {
+    /// Continues from the timestamp of the best block, which may lie ahead of the wall clock
+    /// when a previous run sealed blocks in quick succession.
+    pub(crate) fn new(client: &FullClient) -> Self {
+        let key = StorageKey([twox_128(b"Timestamp"), twox_128(b"Now")].concat());
+        let last = client
+            .storage(&BlockId::Hash(client.info().best_hash), &key)
+            .ok()
+            .flatten()
+            .and_then(|data| u64::decode(&mut &data.0[..]).ok())
+            .unwrap_or_default();
+
+        Self {
+            last: Mutex::new(last),
+        }
+    }
+}
+
+impl ProvideInherentData for SlotTimestampProvider {
+    fn inherent_identifier(&self) -> &'static InherentIdentifier {
+        &sp_timestamp::INHERENT_IDENTIFIER
+    }
+
+    fn provide_inherent_data(
+        &self,
+        inherent_data: &mut InherentData,
+    ) -> Result<(), sp_inherents::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .map_err(|_| "Current time is before unix epoch")?;
+
+        let mut last = self
+            .last
+            .lock()
+            .map_err(|_| "Timestamp provider lock poisoned")?;
+        let timestamp = if *last == 0 {
+            now
+        } else {
+            now.max(*last + SLOT_DURATION)
+        };
+        *last = timestamp;
+
+        inherent_data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
+    }
+
+    fn error_to_string(&self, error: &[u8]) -> Option<String> {
+        sp_timestamp::InherentDataProvider.error_to_string(error)
+    }
+}
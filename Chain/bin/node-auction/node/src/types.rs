@@ -0,0 +1,412 @@
+//! The `export-types` sub-command, which writes the polkadot.js type registry of the runtime.
+//!
+//! Aliases are resolved from the types the runtime is compiled with, structs and enums from the
+//! descriptions kept next to their definitions (see `auction_traits::types`). Every type name the
+//! auction and reputation modules expose through their metadata has to resolve, either to the
+//! registry or to a type polkadot.js knows out of the box, otherwise the command fails instead of
+//! writing an incomplete registry.
+
+use auction_traits::auction::{
+    AuctionCoreInfo, AuctionInfo, AuctionParameters, AuctionUpdate, AuctionUpdateComplete,
+    AuctionUpdateInfo, BerthWindow, NewAuction, OnNewBidResult, QueuedBid, TerminalCapacity,
+    UIAuctionInfo, WonSlot,
+};
+use auction_traits::types::{normalize, DescribeType, TypeDefinition};
+use codec::{Decode, Encode};
+use frame_support::metadata::{
+    DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryType,
+};
+use node_auction_runtime::{
+    auction, reputation, AccountId, AuctionId, Balance, BlockNumber, Currency,
+    GeneralInformationContainer, Hash, Runtime,
+};
+use sc_cli::Result;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::any::{type_name, TypeId};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Modules whose metadata has to be covered by the registry.
+const MODULES: &[&str] = &["AuctionModule", "ReputationModule"];
+
+/// Types polkadot.js knows without a registry entry.
+const BUILTIN: &[&str] = &[
+    "AccountId",
+    "Bytes",
+    "Compact",
+    "H256",
+    "Null",
+    "Option",
+    "Result",
+    "Text",
+    "Vec",
+    "bool",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+];
+
+type AuctionInfoOf = AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+type SlotRecordOf = reputation::SlotRecord<AccountId, Balance, BlockNumber>;
+
+/// The `export-types` command used to generate `types.json` for polkadot.js based UIs and clients.
+#[derive(Debug, StructOpt)]
+pub struct ExportTypesCmd {
+    /// File to write the registry to, defaults to stdout.
+    #[structopt(long, short = "o", parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    /// Compare the registry to an existing `types.json` instead of writing it, fails if they
+    /// differ. Useful to keep a checked-in registry in sync with the runtime.
+    #[structopt(long, parse(from_os_str), conflicts_with = "output")]
+    pub check: Option<PathBuf>,
+}
+
+impl ExportTypesCmd {
+    /// Run the export-types command.
+    pub fn run(&self) -> Result<()> {
+        let registry = registry();
+        let unresolved = unresolved(&registry, &metadata_types()?);
+        if !unresolved.is_empty() {
+            return Err(format!(
+                "The registry doesn't cover the types {}, describe them in `export-types`",
+                unresolved.into_iter().collect::<Vec<_>>().join(", ")
+            )
+            .into());
+        }
+
+        let json = serde_json::to_string_pretty(&Registry(&registry))
+            .map_err(|e| format!("Failed to serialize the registry: {}", e))?
+            + "\n";
+
+        // Compared as text, struct fields are positional so their order matters.
+        if let Some(path) = &self.check {
+            if fs::read_to_string(path)?.trim_end() != json.trim_end() {
+                return Err(format!(
+                    "{} is out of date, regenerate it with `node-auction export-types -o {}`",
+                    path.display(),
+                    path.display()
+                )
+                .into());
+            }
+            return Ok(());
+        }
+
+        match &self.output {
+            Some(path) => fs::write(path, json)?,
+            None => print!("{}", json),
+        }
+        Ok(())
+    }
+}
+
+/// The registry of the runtime, by type name.
+fn registry() -> BTreeMap<String, TypeDefinition> {
+    let mut registry = BTreeMap::new();
+    let mut alias = |name: &str, ty: String| {
+        registry.insert(name.to_string(), TypeDefinition::Alias(ty));
+    };
+
+    alias("Address", primitive::<node_auction_runtime::Address>());
+    alias("LookupSource", primitive::<node_auction_runtime::Address>());
+    alias("AuctionId", primitive::<AuctionId>());
+    alias("Balance", primitive::<Balance>());
+    alias("BlockNumber", primitive::<BlockNumber>());
+    alias("Currency", primitive::<Currency>());
+    alias(
+        "GeneralInformationContainer",
+        primitive::<GeneralInformationContainer>(),
+    );
+    alias("Hash", primitive::<Hash>());
+    alias("Score", primitive::<reputation::Score>());
+
+    // Names the modules give runtime types, e.g. in their event declarations.
+    alias(
+        "BalanceOf",
+        named::<auction::BalanceOf<Runtime>, Balance>("Balance"),
+    );
+    alias(
+        "GenInfo",
+        named::<
+            <Runtime as auction::Trait>::GeneralInformationContainer,
+            GeneralInformationContainer,
+        >("GeneralInformationContainer"),
+    );
+    // The auction RPC names the general information type after its generic parameter.
+    alias("GeneralInfo", "GeneralInformationContainer".into());
+    alias(
+        "ParamsCond",
+        named::<auction::ParamsCond<Runtime>, AuctionParameters<Balance, BlockNumber>>(
+            "AuctionParameters",
+        ),
+    );
+    alias(
+        "Parameters",
+        named::<auction::ParamsCond<Runtime>, AuctionParameters<Balance, BlockNumber>>(
+            "AuctionParameters",
+        ),
+    );
+    alias(
+        "CapacityCond",
+        named::<auction::CapacityCond<Runtime>, TerminalCapacity<GeneralInformationContainer>>(
+            "TerminalCapacity",
+        ),
+    );
+    alias(
+        "Capacity",
+        named::<auction::CapacityCond<Runtime>, TerminalCapacity<GeneralInformationContainer>>(
+            "TerminalCapacity",
+        ),
+    );
+    alias(
+        "NewAuctionCond",
+        named::<
+            auction::NewAuctionCond<Runtime>,
+            NewAuction<AccountId, BlockNumber, GeneralInformationContainer>,
+        >("NewAuction"),
+    );
+    alias(
+        "UpdateCond",
+        named::<
+            auction::UpdateCond<Runtime>,
+            AuctionUpdate<AuctionId, BlockNumber, GeneralInformationContainer>,
+        >("AuctionUpdate"),
+    );
+
+    let mut describe = |name: &str, definition: TypeDefinition| {
+        registry.insert(name.to_string(), definition);
+    };
+    describe_as::<QueuedBid<AccountId, Balance, AuctionId>>(&mut describe);
+    describe_as::<AuctionCoreInfo<GeneralInformationContainer>>(&mut describe);
+    describe(
+        AuctionUpdateComplete::<AuctionInfoOf>::NAME,
+        AuctionUpdateComplete::<AuctionInfoOf>::definition().substitute("A", "AuctionInfo"),
+    );
+    describe_as::<AuctionUpdateInfo<GeneralInformationContainer>>(&mut describe);
+    describe_as::<AuctionUpdate<AuctionId, BlockNumber, GeneralInformationContainer>>(
+        &mut describe,
+    );
+    describe_as::<NewAuction<AccountId, BlockNumber, GeneralInformationContainer>>(&mut describe);
+    describe_as::<AuctionInfoOf>(&mut describe);
+    describe_as::<UIAuctionInfo<AccountId, BlockNumber, GeneralInformationContainer>>(
+        &mut describe,
+    );
+    describe_as::<WonSlot<AccountId, Balance, AuctionId, GeneralInformationContainer>>(
+        &mut describe,
+    );
+    describe_as::<AuctionParameters<Balance, BlockNumber>>(&mut describe);
+    describe_as::<TerminalCapacity<GeneralInformationContainer>>(&mut describe);
+    describe_as::<BerthWindow<GeneralInformationContainer>>(&mut describe);
+    describe_as::<OnNewBidResult<BlockNumber>>(&mut describe);
+    describe_as::<auction::Releases>(&mut describe);
+    describe_as::<reputation::SlotOutcome>(&mut describe);
+    describe_as::<SlotRecordOf>(&mut describe);
+    describe_as::<auction_rpc::Page>(&mut describe);
+
+    // Registered types are referred to by their bare name, polkadot.js drops their generics anyway.
+    let names = registry.keys().cloned().collect::<BTreeSet<_>>();
+    registry
+        .into_iter()
+        .map(|(name, definition)| {
+            let definition = match definition {
+                TypeDefinition::Alias(ty) => TypeDefinition::Alias(sanitize(&ty, &names)),
+                TypeDefinition::Struct(fields) => TypeDefinition::Struct(
+                    fields
+                        .into_iter()
+                        .map(|(field, ty)| (field, sanitize(&ty, &names)))
+                        .collect(),
+                ),
+                definition => definition,
+            };
+            (name, definition)
+        })
+        .collect()
+}
+
+fn describe_as<T: DescribeType>(describe: &mut impl FnMut(&str, TypeDefinition)) {
+    describe(T::NAME, T::definition())
+}
+
+/// The polkadot.js name of a primitive runtime type.
+fn primitive<T: 'static>() -> String {
+    if TypeId::of::<T>() == TypeId::of::<AccountId>() {
+        return "AccountId".into();
+    }
+    if TypeId::of::<T>() == TypeId::of::<()>() {
+        return "Null".into();
+    }
+    // Leaves the bare name, e.g. `H256` for `primitive_types::H256`.
+    let name = type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name).into()
+}
+
+/// `name` if `T` is the type `Named`, which goes by `name` in the registry, the primitive `T` is
+/// otherwise.
+fn named<T: 'static, Named: 'static>(name: &str) -> String {
+    if TypeId::of::<T>() == TypeId::of::<Named>() {
+        name.into()
+    } else {
+        primitive::<T>()
+    }
+}
+
+/// Rewrites a Rust type the way polkadot.js spells it: trait paths such as `T::` and
+/// `<T as Trait>::` are dropped, as are the generic arguments of registered types.
+fn sanitize(ty: &str, registered: &BTreeSet<String>) -> String {
+    let mut ty = ty.to_string();
+    while let Some(start) = ty.find("<T as ") {
+        match ty[start..].find(">::") {
+            Some(end) => ty.replace_range(start..start + end + 3, ""),
+            None => break,
+        }
+    }
+    let ty = normalize(&ty);
+
+    let mut sanitized = String::with_capacity(ty.len());
+    let mut ident = String::new();
+    let mut chars = ty.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            ident.push(c);
+            continue;
+        }
+        if ident == "T" && c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            ident.clear();
+            continue;
+        }
+        if c == '<' && registered.contains(&ident) {
+            // Skips the generic arguments, along with any nested ones.
+            let mut depth = 1;
+            while depth > 0 {
+                match chars.next() {
+                    Some('<') => depth += 1,
+                    Some('>') => depth -= 1,
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            sanitized.push_str(&ident);
+            ident.clear();
+            continue;
+        }
+        sanitized.push_str(&ident);
+        sanitized.push(c);
+        ident.clear();
+    }
+    sanitized.push_str(&ident);
+    sanitized
+}
+
+/// Every type name used by the storage, calls, events and constants of `MODULES`.
+fn metadata_types() -> Result<Vec<String>> {
+    // The metadata is built out of static references, a round trip turns it into owned values.
+    let encoded = Runtime::metadata().encode();
+    let metadata = RuntimeMetadataPrefixed::decode(&mut &encoded[..])
+        .map_err(|e| format!("Failed to decode the runtime metadata: {}", e))?;
+    let modules = match metadata.1 {
+        RuntimeMetadata::V11(metadata) => decoded(metadata.modules),
+        _ => return Err("Unsupported runtime metadata version".into()),
+    };
+
+    let mut types = Vec::new();
+    for module in modules {
+        if !MODULES.contains(&decoded(module.name).as_str()) {
+            continue;
+        }
+        if let Some(storage) = module.storage {
+            for entry in decoded(decoded(storage).entries) {
+                match entry.ty {
+                    StorageEntryType::Plain(value) => types.push(decoded(value)),
+                    StorageEntryType::Map { key, value, .. } => {
+                        types.extend(vec![decoded(key), decoded(value)])
+                    }
+                    StorageEntryType::DoubleMap {
+                        key1, key2, value, ..
+                    } => types.extend(vec![decoded(key1), decoded(key2), decoded(value)]),
+                }
+            }
+        }
+        for call in module.calls.map(decoded).unwrap_or_default() {
+            types.extend(
+                decoded(call.arguments)
+                    .into_iter()
+                    .map(|argument| decoded(argument.ty)),
+            );
+        }
+        for event in module.event.map(decoded).unwrap_or_default() {
+            types.extend(decoded(event.arguments));
+        }
+        for constant in decoded(module.constants) {
+            types.push(decoded(constant.ty));
+        }
+    }
+    Ok(types)
+}
+
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> O {
+    match value {
+        DecodeDifferent::Decoded(value) => value,
+        DecodeDifferent::Encode(_) => unreachable!("Decoded metadata holds decoded values; qed"),
+    }
+}
+
+/// The names in `types` which neither polkadot.js nor the registry know.
+fn unresolved(registry: &BTreeMap<String, TypeDefinition>, types: &[String]) -> BTreeSet<String> {
+    let registered = registry.keys().cloned().collect::<BTreeSet<_>>();
+    types
+        .iter()
+        .flat_map(|ty| {
+            sanitize(ty, &registered)
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|name| !name.is_empty() && !name.starts_with(char::is_numeric))
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .filter(|name| !registered.contains(name) && !BUILTIN.contains(&name.as_str()))
+        .collect()
+}
+
+/// Serializes the registry in the polkadot.js format, keeping struct fields in declaration order.
+struct Registry<'a>(&'a BTreeMap<String, TypeDefinition>);
+
+impl Serialize for Registry<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut types = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, definition) in self.0 {
+            types.serialize_entry(name, &Definition(definition))?;
+        }
+        types.end()
+    }
+}
+
+struct Definition<'a>(&'a TypeDefinition);
+
+impl Serialize for Definition<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.0 {
+            TypeDefinition::Alias(ty) => serializer.serialize_str(ty),
+            TypeDefinition::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (field, ty) in fields {
+                    map.serialize_entry(field, ty)?;
+                }
+                map.end()
+            }
+            TypeDefinition::Enum(variants) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("_enum", variants)?;
+                map.end()
+            }
+        }
+    }
+}
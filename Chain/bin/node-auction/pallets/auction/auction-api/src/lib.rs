@@ -1,10 +1,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::too_many_arguments)]
 
-use auction_traits::auction::{UIAuctionInfo, AuctionInfo};
+use auction_traits::auction::{AuctionInfo, AuctionParameters, BerthWindow, UIAuctionInfo};
 use frame_support::inherent::Vec;
 use parity_scale_codec::Codec;
 sp_api::decl_runtime_apis! {
+    /// Version 2 adds the auction parameter queries, version 3 the terminal occupancy query.
+    /// Version 4 returns the balances of the auction parameters converted for display, like the
+    /// formal queries do.
+    #[api_version(4)]
     pub trait AuctionInformationAPI<AccountId, AuctionId, Balance, BlockNumber, GeneralInfo>
     where
         AccountId: Codec,
@@ -30,5 +34,16 @@ sp_api::decl_runtime_apis! {
         fn auction_query_formal_all_status(
             active: bool,
         ) -> Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>;
+        fn auction_parameters() -> AuctionParameters<u64, BlockNumber>;
+        #[changed_in(4)]
+        fn auction_parameters() -> AuctionParameters<Balance, BlockNumber>;
+        fn auction_parameter_bounds() -> AuctionParameters<u64, BlockNumber>;
+        #[changed_in(4)]
+        fn auction_parameter_bounds() -> AuctionParameters<Balance, BlockNumber>;
+        fn terminal_occupancy(
+            terminal: AccountId,
+            from: GeneralInfo,
+            to: GeneralInfo,
+        ) -> Vec<(AuctionId, BerthWindow<GeneralInfo>)>;
     }
 }
@@ -0,0 +1,119 @@
+//! Errors of the auction RPC.
+//!
+//! Every error is reported with a stable JSON-RPC error code, clients should match on the code
+//! rather than on the message. The codes are part of the RPC's interface, new errors get new codes
+//! and existing codes are never reused.
+//!
+//! | Code | Error                               |
+//! |------|-------------------------------------|
+//! | 9001 | [`Error::UnsupportedApi`]           |
+//! | 9002 | [`Error::UnknownBlock`]             |
+//! | 9003 | [`Error::DecodeFailed`]             |
+//! | 9004 | [`Error::AuctionNotFound`]          |
+//! | 9005 | [`Error::InvalidPage`]              |
+//! | 9006 | [`Error::RuntimeError`]             |
+//! | 9007 | [`Error::FetchFailed`]              |
+
+use crate::Page;
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+
+/// Code of [`Error::UnsupportedApi`].
+pub const UNSUPPORTED_API: i64 = 9001;
+/// Code of [`Error::UnknownBlock`].
+pub const UNKNOWN_BLOCK: i64 = 9002;
+/// Code of [`Error::DecodeFailed`].
+pub const DECODE_FAILED: i64 = 9003;
+/// Code of [`Error::AuctionNotFound`].
+pub const AUCTION_NOT_FOUND: i64 = 9004;
+/// Code of [`Error::InvalidPage`].
+pub const INVALID_PAGE: i64 = 9005;
+/// Code of [`Error::RuntimeError`].
+pub const RUNTIME_ERROR: i64 = 9006;
+/// Code of [`Error::FetchFailed`].
+pub const FETCH_FAILED: i64 = 9007;
+
+/// Everything the auction RPC reports instead of a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The runtime of the queried block predates the method, it provides the auction runtime API
+    /// in a version below `required`, or not at all.
+    UnsupportedApi { method: &'static str, required: u32 },
+    /// The node doesn't know the queried block, or has pruned its state.
+    UnknownBlock(String),
+    /// The runtime answered with data the node can't decode, the runtime and the node disagree on
+    /// the auction types.
+    DecodeFailed(String),
+    /// No auction exists under the given id at the queried block.
+    AuctionNotFound(String),
+    /// The requested page is empty or larger than `MAX_PAGE_SIZE`.
+    InvalidPage(Page),
+    /// The runtime failed to answer for another reason.
+    RuntimeError(String),
+    /// A light client couldn't fetch the block's header or state from the full nodes it's
+    /// connected to, or the proof they sent didn't check out.
+    FetchFailed(String),
+}
+
+impl Error {
+    /// Classifies a failed runtime API call.
+    pub fn runtime(error: sp_blockchain::Error) -> Self {
+        match error {
+            sp_blockchain::Error::CallResultDecode(..) => Error::DecodeFailed(error.to_string()),
+            sp_blockchain::Error::UnknownBlock(block) => Error::UnknownBlock(block),
+            error => Error::RuntimeError(format!("{:?}", error)),
+        }
+    }
+
+    /// The stable code of the error.
+    pub fn code(&self) -> i64 {
+        match self {
+            Error::UnsupportedApi { .. } => UNSUPPORTED_API,
+            Error::UnknownBlock(_) => UNKNOWN_BLOCK,
+            Error::DecodeFailed(_) => DECODE_FAILED,
+            Error::AuctionNotFound(_) => AUCTION_NOT_FOUND,
+            Error::InvalidPage(_) => INVALID_PAGE,
+            Error::RuntimeError(_) => RUNTIME_ERROR,
+            Error::FetchFailed(_) => FETCH_FAILED,
+        }
+    }
+
+    /// A message for humans, the details which vary between occurrences go into the error's data.
+    pub fn message(&self) -> String {
+        match self {
+            Error::UnsupportedApi { method, required } => format!(
+                "The runtime of the block doesn't support `{}`, it needs version {} of the auction \
+                 runtime API",
+                method, required
+            ),
+            Error::UnknownBlock(_) => "The block is unknown or its state has been pruned".into(),
+            Error::DecodeFailed(_) => "The runtime's answer could not be decoded".into(),
+            Error::AuctionNotFound(_) => "The auction does not exist".into(),
+            Error::InvalidPage(_) => {
+                format!("Pages hold between 1 and {} items", crate::MAX_PAGE_SIZE)
+            }
+            Error::RuntimeError(_) => "The runtime failed to answer".into(),
+            Error::FetchFailed(_) => "The state could not be fetched from full nodes".into(),
+        }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(error: Error) -> Self {
+        let data = match &error {
+            Error::UnsupportedApi { .. } => None,
+            Error::UnknownBlock(block) => Some(block.clone().into()),
+            Error::DecodeFailed(details)
+            | Error::RuntimeError(details)
+            | Error::FetchFailed(details) => Some(details.clone().into()),
+            Error::AuctionNotFound(id) => Some(id.clone().into()),
+            Error::InvalidPage(page) => {
+                Some(format!("offset {}, limit {}", page.offset, page.limit).into())
+            }
+        };
+        RpcError {
+            code: ErrorCode::ServerError(error.code()),
+            message: error.message(),
+            data,
+        }
+    }
+}
@@ -1,13 +1,71 @@
+//! JSON-RPC interface to the auction runtime API, the `auctionInformation_*` methods.
+//!
+//! Every method answers for the block `at`, the best block if none is given. Methods fail with
+//! the errors described in the [`error`] module, a block whose runtime predates a method is
+//! reported as such rather than queried, and so is an auction which doesn't exist.
+
 use auction_api::AuctionInformationAPI as AuctionWrapperAPI;
 use auction_traits::auction::*;
 use codec::Codec;
-use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
-use sp_api::ProvideRuntimeApi;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiErrorExt, ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 use std::sync::Arc;
 
+pub mod error;
+pub mod storage;
+#[cfg(test)]
+mod tests;
+
+pub use error::Error;
+pub use storage::{AuctionStorageApi, FullAuctionStorage, LightAuctionStorage};
+
+/// Largest number of items a page may hold.
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/// A part of a listing, `limit` items starting with the `offset`th one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page {
+    pub offset: u32,
+    pub limit: u32,
+}
+
+auction_traits::describe_struct!(Page {
+    offset: u32,
+    limit: u32,
+});
+
+impl Page {
+    /// The part of `items` the page covers, pages past the end of the listing are empty.
+    pub fn apply<T>(self, items: Vec<T>) -> std::result::Result<Vec<T>, Error> {
+        if self.limit == 0 || self.limit > MAX_PAGE_SIZE {
+            return Err(Error::InvalidPage(self));
+        }
+        Ok(items
+            .into_iter()
+            .skip(self.offset as usize)
+            .take(self.limit as usize)
+            .collect())
+    }
+}
+
+/// Applies `page` to a listing, which is left whole without one.
+fn paginate<T>(items: Option<Vec<T>>, page: Option<Page>) -> Result<Option<Vec<T>>> {
+    match (items, page) {
+        (Some(items), Some(page)) => Ok(Some(page.apply(items)?)),
+        (items, _) => Ok(items),
+    }
+}
+
+// Versions of the auction runtime API the methods were introduced in, or last changed in. The
+// parameter queries return balances converted for display since version 4.
+const QUERIES: u32 = 1;
+const PARAMETERS: u32 = 4;
+const OCCUPANCY: u32 = 3;
+
 #[rpc]
 pub trait AuctionInformationAPI<BlockHash, AccountId, AuctionId, Balance, BlockNumber, GeneralInfo>
 {
@@ -18,35 +76,57 @@ pub trait AuctionInformationAPI<BlockHash, AccountId, AuctionId, Balance, BlockN
         &self,
         at: Option<BlockHash>,
         id: AuctionId,
-    ) -> Result<Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>;
+    ) -> Result<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>;
     #[rpc(name = "auctionInformation_queryInformalAll")]
     fn auction_query_informal_all(
         &self,
         at: Option<BlockHash>,
+        page: Option<Page>,
     ) -> Result<Option<Vec<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>>;
     #[rpc(name = "auctionInformation_queryInformalAllStatus")]
     fn auction_query_informal_all_status(
         &self,
         at: Option<BlockHash>,
         active: bool,
+        page: Option<Page>,
     ) -> Result<Option<Vec<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>>;
     #[rpc(name = "auctionInformation_queryFormal")]
     fn auction_query_formal(
         &self,
         at: Option<BlockHash>,
         id: AuctionId,
-    ) -> Result<Option<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>;
+    ) -> Result<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>;
     #[rpc(name = "auctionInformation_queryFormalAll")]
     fn auction_query_formal_all(
         &self,
         at: Option<BlockHash>,
+        page: Option<Page>,
     ) -> Result<Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>>;
     #[rpc(name = "auctionInformation_queryFormalAllStatus")]
     fn auction_query_formal_all_status(
         &self,
         at: Option<BlockHash>,
         active: bool,
+        page: Option<Page>,
     ) -> Result<Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>>;
+    #[rpc(name = "auctionInformation_parameters")]
+    fn auction_parameters(
+        &self,
+        at: Option<BlockHash>,
+    ) -> Result<AuctionParameters<u64, BlockNumber>>;
+    #[rpc(name = "auctionInformation_parameterBounds")]
+    fn auction_parameter_bounds(
+        &self,
+        at: Option<BlockHash>,
+    ) -> Result<AuctionParameters<u64, BlockNumber>>;
+    #[rpc(name = "auctionInformation_terminalOccupancy")]
+    fn terminal_occupancy(
+        &self,
+        at: Option<BlockHash>,
+        terminal: AccountId,
+        from: GeneralInfo,
+        to: GeneralInfo,
+    ) -> Result<Vec<(AuctionId, BerthWindow<GeneralInfo>)>>;
 }
 
 pub struct AuctionInformation<C, M> {
@@ -63,6 +143,41 @@ impl<C, M> AuctionInformation<C, M> {
     }
 }
 
+/// The auction runtime API as named to `has_api_with`. Its identifier and version don't depend on
+/// the type parameters, any types do.
+type AuctionApi<Block> = dyn AuctionWrapperAPI<Block, (), (), (), (), (), Error = ()>;
+
+impl<C, Block> AuctionInformation<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: ApiErrorExt<Error = sp_blockchain::Error>,
+{
+    /// Resolves `at` to a block the node knows, the best block if none is given, and ensures the
+    /// block's runtime provides the auction runtime API in at least version `required`, which
+    /// `method` needs.
+    fn block(
+        &self,
+        api: &C::Api,
+        at: Option<<Block as BlockT>::Hash>,
+        method: &'static str,
+        required: u32,
+    ) -> std::result::Result<BlockId<Block>, Error> {
+        let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+        match self.client.header(BlockId::hash(hash)) {
+            Ok(Some(_)) => {}
+            _ => return Err(Error::UnknownBlock(format!("{:?}", hash))),
+        }
+
+        let at = BlockId::hash(hash);
+        match api.has_api_with::<AuctionApi<Block>, _>(&at, |version| version >= required) {
+            Ok(true) => Ok(at),
+            Ok(false) => Err(Error::UnsupportedApi { method, required }),
+            Err(e) => Err(Error::runtime(e)),
+        }
+    }
+}
+
 impl<C, Block, AccountId, AuctionId, Balance, BlockNumber, GeneralInfo>
     AuctionInformationAPI<
         <Block as BlockT>::Hash,
@@ -78,117 +193,143 @@ where
     C: ProvideRuntimeApi<Block>,
     C: HeaderBackend<Block>,
     C::Api: AuctionWrapperAPI<Block, AccountId, AuctionId, Balance, BlockNumber, GeneralInfo>,
+    C::Api: ApiErrorExt<Error = sp_blockchain::Error>,
     AccountId: Codec,
-    AuctionId: Codec,
+    AuctionId: Codec + std::fmt::Debug,
     Balance: Codec,
     BlockNumber: Codec,
     GeneralInfo: Codec,
 {
     fn auction_exists(&self, at: Option<<Block as BlockT>::Hash>, id: AuctionId) -> Result<bool> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(||
-	    // If the block hash is not supplied assume the best block.
-	    self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_exists(&at, id);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876), // No real reason for this value
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(&api, at, "auctionInformation_exists", QUERIES)?;
+
+        api.auction_exists(&at, id)
+            .map_err(|e| Error::runtime(e).into())
     }
 
     fn auction_query_informal(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         id: AuctionId,
-    ) -> Result<Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>> {
+    ) -> Result<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_query_informal(&at, id);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876),
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(&api, at, "auctionInformation_queryInformal", QUERIES)?;
+
+        let missing = Error::AuctionNotFound(format!("{:?}", id));
+        api.auction_query_informal(&at, id)
+            .map_err(Error::runtime)?
+            .ok_or_else(|| missing.into())
     }
 
     fn auction_query_informal_all(
         &self,
         at: Option<<Block as BlockT>::Hash>,
+        page: Option<Page>,
     ) -> Result<Option<Vec<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_query_informal_all(&at);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876),
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(&api, at, "auctionInformation_queryInformalAll", QUERIES)?;
+
+        let auctions = api
+            .auction_query_informal_all(&at)
+            .map_err(Error::runtime)?;
+        paginate(auctions, page)
     }
 
     fn auction_query_informal_all_status(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         active: bool,
+        page: Option<Page>,
     ) -> Result<Option<Vec<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_query_informal_all_status(&at, active);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876),
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(
+            &api,
+            at,
+            "auctionInformation_queryInformalAllStatus",
+            QUERIES,
+        )?;
+
+        let auctions = api
+            .auction_query_informal_all_status(&at, active)
+            .map_err(Error::runtime)?;
+        paginate(auctions, page)
     }
 
     fn auction_query_formal(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         id: AuctionId,
-    ) -> Result<Option<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>> {
+    ) -> Result<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_query_formal(&at, id);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876),
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(&api, at, "auctionInformation_queryFormal", QUERIES)?;
+
+        let missing = Error::AuctionNotFound(format!("{:?}", id));
+        api.auction_query_formal(&at, id)
+            .map_err(Error::runtime)?
+            .ok_or_else(|| missing.into())
     }
 
     fn auction_query_formal_all(
         &self,
         at: Option<<Block as BlockT>::Hash>,
+        page: Option<Page>,
     ) -> Result<Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_query_formal_all(&at);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876),
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(&api, at, "auctionInformation_queryFormalAll", QUERIES)?;
+
+        let auctions = api.auction_query_formal_all(&at).map_err(Error::runtime)?;
+        paginate(auctions, page)
     }
 
     fn auction_query_formal_all_status(
         &self,
         at: Option<<Block as BlockT>::Hash>,
         active: bool,
+        page: Option<Page>,
     ) -> Result<Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>> {
         let api = self.client.runtime_api();
-        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
-
-        let runtime_api_result = api.auction_query_formal_all_status(&at, active);
-        runtime_api_result.map_err(|e| RpcError {
-            code: ErrorCode::ServerError(9876),
-            message: "Something wrong".into(),
-            data: Some(format!("{:?}", e).into()),
-        })
+        let at = self.block(&api, at, "auctionInformation_queryFormalAllStatus", QUERIES)?;
+
+        let auctions = api
+            .auction_query_formal_all_status(&at, active)
+            .map_err(Error::runtime)?;
+        paginate(auctions, page)
+    }
+
+    fn auction_parameters(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<AuctionParameters<u64, BlockNumber>> {
+        let api = self.client.runtime_api();
+        let at = self.block(&api, at, "auctionInformation_parameters", PARAMETERS)?;
+
+        api.auction_parameters(&at)
+            .map_err(|e| Error::runtime(e).into())
+    }
+
+    fn auction_parameter_bounds(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<AuctionParameters<u64, BlockNumber>> {
+        let api = self.client.runtime_api();
+        let at = self.block(&api, at, "auctionInformation_parameterBounds", PARAMETERS)?;
+
+        api.auction_parameter_bounds(&at)
+            .map_err(|e| Error::runtime(e).into())
+    }
+
+    fn terminal_occupancy(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        terminal: AccountId,
+        from: GeneralInfo,
+        to: GeneralInfo,
+    ) -> Result<Vec<(AuctionId, BerthWindow<GeneralInfo>)>> {
+        let api = self.client.runtime_api();
+        let at = self.block(&api, at, "auctionInformation_terminalOccupancy", OCCUPANCY)?;
+
+        api.terminal_occupancy(&at, terminal, from, to)
+            .map_err(|e| Error::runtime(e).into())
     }
 }
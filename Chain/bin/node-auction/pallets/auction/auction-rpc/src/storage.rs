@@ -0,0 +1,238 @@
+//! JSON-RPC interface to the auctioning pallet's storage, the `auctionStorage_*` methods.
+//!
+//! Unlike the `auctionInformation_*` methods these read the storage items directly instead of
+//! calling the auction runtime API, so they answer the same for every runtime which keeps the
+//! items' layout, and work on light clients. [`FullAuctionStorage`] reads the node's own state,
+//! [`LightAuctionStorage`] fetches the items from full nodes with remote read requests, checking
+//! the read proof of every answer against the header the light client holds.
+//!
+//! Apps which don't trust the node they talk to fetch the proof themselves, `state_getReadProof`
+//! for the keys of the `auction-proof` crate, and verify it with that crate.
+
+use crate::Error;
+use auction_proof::{auction_key, queued_bids_key};
+use auction_traits::auction::{AuctionInfo, QueuedBid};
+use codec::{Codec, Decode};
+use futures::{future::ready, Future, FutureExt, TryFutureExt};
+use jsonrpc_core::Error as RpcError;
+use jsonrpc_derive::rpc;
+use sc_client_api::{
+    backend::Backend,
+    light::{future_header, Fetcher, RemoteBlockchain, RemoteReadRequest},
+    StorageProvider,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Future of an RPC result, as the RPC server expects it.
+pub type FutureResult<T> =
+    Box<dyn jsonrpc_core::futures::Future<Item = T, Error = RpcError> + Send>;
+
+#[rpc]
+pub trait AuctionStorageApi<BlockHash, AccountId, AuctionId, Balance, BlockNumber, GeneralInfo> {
+    /// The auction `id` as stored at block `at`, the best block if `None`.
+    #[rpc(name = "auctionStorage_auction")]
+    fn auction(
+        &self,
+        at: Option<BlockHash>,
+        id: AuctionId,
+    ) -> FutureResult<Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>;
+    /// The bids queued for auction `id` at block `at`, the best block if `None`.
+    #[rpc(name = "auctionStorage_queuedBids")]
+    fn queued_bids(
+        &self,
+        at: Option<BlockHash>,
+        id: AuctionId,
+    ) -> FutureResult<Vec<QueuedBid<AccountId, Balance, AuctionId>>>;
+}
+
+/// Reads the auction storage from the state of a full node.
+pub struct FullAuctionStorage<BE, Block, C> {
+    client: Arc<C>,
+    _marker: PhantomData<(BE, Block)>,
+}
+
+impl<BE, Block, C> FullAuctionStorage<BE, Block, C> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<BE, Block, C> FullAuctionStorage<BE, Block, C>
+where
+    Block: BlockT,
+    BE: Backend<Block>,
+    C: StorageProvider<Block, BE> + HeaderBackend<Block>,
+{
+    fn read<V: Decode>(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        key: Vec<u8>,
+    ) -> Result<Option<V>, Error> {
+        let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+        let unknown = || Error::UnknownBlock(format!("{:?}", hash));
+        match self.client.header(BlockId::hash(hash)) {
+            Ok(Some(_)) => {}
+            _ => return Err(unknown()),
+        }
+
+        // The header being known, a failed read means the block's state has been pruned.
+        let data = self
+            .client
+            .storage(&BlockId::hash(hash), &StorageKey(key))
+            .map_err(|_| unknown())?;
+        decode(data.map(|data| data.0))
+    }
+}
+
+impl<BE, Block, C, AccountId, AuctionId, Balance, BlockNumber, GeneralInfo>
+    AuctionStorageApi<
+        <Block as BlockT>::Hash,
+        AccountId,
+        AuctionId,
+        Balance,
+        BlockNumber,
+        GeneralInfo,
+    > for FullAuctionStorage<BE, Block, C>
+where
+    Block: BlockT,
+    BE: Backend<Block> + Send + Sync + 'static,
+    C: StorageProvider<Block, BE> + HeaderBackend<Block> + Send + Sync + 'static,
+    AccountId: Codec + Send + 'static,
+    AuctionId: Codec + Send + 'static,
+    Balance: Codec + Send + 'static,
+    BlockNumber: Codec + Send + 'static,
+    GeneralInfo: Codec + Send + 'static,
+{
+    fn auction(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        id: AuctionId,
+    ) -> FutureResult<Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>> {
+        boxed(ready(self.read(at, auction_key(&id))))
+    }
+
+    fn queued_bids(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        id: AuctionId,
+    ) -> FutureResult<Vec<QueuedBid<AccountId, Balance, AuctionId>>> {
+        let bids = self.read(at, queued_bids_key(&id));
+        boxed(ready(bids.map(Option::unwrap_or_default)))
+    }
+}
+
+/// Reads the auction storage from full nodes on behalf of a light client.
+pub struct LightAuctionStorage<Block: BlockT, C, F> {
+    client: Arc<C>,
+    remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+    fetcher: Arc<F>,
+}
+
+impl<Block: BlockT, C, F> LightAuctionStorage<Block, C, F> {
+    pub fn new(
+        client: Arc<C>,
+        remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+        fetcher: Arc<F>,
+    ) -> Self {
+        Self {
+            client,
+            remote_blockchain,
+            fetcher,
+        }
+    }
+}
+
+impl<Block, C, F> LightAuctionStorage<Block, C, F>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+    F: Fetcher<Block> + 'static,
+{
+    fn read<V: Decode + Send + 'static>(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        key: Vec<u8>,
+    ) -> impl Future<Output = Result<Option<V>, Error>> + Send + 'static {
+        let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+        let header = future_header(
+            &*self.remote_blockchain,
+            &*self.fetcher,
+            BlockId::hash(hash),
+        );
+        let fetcher = self.fetcher.clone();
+
+        async move {
+            let header = header
+                .await
+                .map_err(|e| Error::FetchFailed(e.to_string()))?
+                .ok_or_else(|| Error::UnknownBlock(format!("{:?}", hash)))?;
+            // The fetcher checks the proof the full node answers with against `header`.
+            let mut values = fetcher
+                .remote_read(RemoteReadRequest {
+                    block: hash,
+                    header,
+                    keys: vec![key.clone()],
+                    retry_count: None,
+                })
+                .await
+                .map_err(|e| Error::FetchFailed(e.to_string()))?;
+            decode(values.remove(&key).flatten())
+        }
+    }
+}
+
+impl<Block, C, F, AccountId, AuctionId, Balance, BlockNumber, GeneralInfo>
+    AuctionStorageApi<
+        <Block as BlockT>::Hash,
+        AccountId,
+        AuctionId,
+        Balance,
+        BlockNumber,
+        GeneralInfo,
+    > for LightAuctionStorage<Block, C, F>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block> + Send + Sync + 'static,
+    F: Fetcher<Block> + 'static,
+    AccountId: Codec + Send + 'static,
+    AuctionId: Codec + Send + 'static,
+    Balance: Codec + Send + 'static,
+    BlockNumber: Codec + Send + 'static,
+    GeneralInfo: Codec + Send + 'static,
+{
+    fn auction(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        id: AuctionId,
+    ) -> FutureResult<Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>> {
+        boxed(self.read(at, auction_key(&id)))
+    }
+
+    fn queued_bids(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+        id: AuctionId,
+    ) -> FutureResult<Vec<QueuedBid<AccountId, Balance, AuctionId>>> {
+        let bids = self.read(at, queued_bids_key(&id));
+        boxed(bids.map_ok(Option::unwrap_or_default))
+    }
+}
+
+/// Decodes a storage value into the item's type.
+fn decode<V: Decode>(data: Option<Vec<u8>>) -> Result<Option<V>, Error> {
+    data.map(|data| V::decode(&mut &data[..]).map_err(|e| Error::DecodeFailed(e.to_string())))
+        .transpose()
+}
+
+/// Turns a read into the future the RPC server expects.
+fn boxed<T: Send + 'static>(
+    read: impl Future<Output = Result<T, Error>> + Send + 'static,
+) -> FutureResult<T> {
+    Box::new(read.map_err(RpcError::from).boxed().compat())
+}
@@ -0,0 +1,131 @@
+use crate::error::*;
+use crate::*;
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+
+#[test]
+fn page_selects_items() {
+    let items: Vec<u32> = (0..10).collect();
+    let page = Page {
+        offset: 3,
+        limit: 4,
+    };
+    assert_eq!(page.apply(items.clone()), Ok(vec![3, 4, 5, 6]));
+    let page = Page {
+        offset: 8,
+        limit: 4,
+    };
+    assert_eq!(page.apply(items.clone()), Ok(vec![8, 9]));
+    let page = Page {
+        offset: 20,
+        limit: 4,
+    };
+    assert_eq!(page.apply(items), Ok(vec![]));
+}
+
+#[test]
+fn page_size_is_bounded() {
+    let empty = Page {
+        offset: 0,
+        limit: 0,
+    };
+    assert_eq!(empty.apply(vec![1u32]), Err(Error::InvalidPage(empty)));
+    let oversized = Page {
+        offset: 0,
+        limit: MAX_PAGE_SIZE + 1,
+    };
+    assert_eq!(
+        oversized.apply(vec![1u32]),
+        Err(Error::InvalidPage(oversized))
+    );
+    let largest = Page {
+        offset: 0,
+        limit: MAX_PAGE_SIZE,
+    };
+    assert_eq!(largest.apply(vec![1u32]), Ok(vec![1]));
+}
+
+#[test]
+fn listing_without_page_is_whole() {
+    assert_eq!(
+        paginate(Some(vec![1u32, 2, 3]), None),
+        Ok(Some(vec![1, 2, 3]))
+    );
+    assert_eq!(
+        paginate::<u32>(
+            None,
+            Some(Page {
+                offset: 0,
+                limit: 1
+            })
+        ),
+        Ok(None)
+    );
+    let error = paginate(
+        Some(vec![1u32]),
+        Some(Page {
+            offset: 0,
+            limit: 0,
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(error.code, ErrorCode::ServerError(INVALID_PAGE));
+}
+
+#[test]
+fn runtime_errors_are_classified() {
+    let decode = Error::runtime(sp_blockchain::Error::CallResultDecode(
+        "auction_parameters",
+        codec::Error::from("Not enough data to fill buffer"),
+    ));
+    assert_eq!(decode.code(), DECODE_FAILED);
+    // A message mentioning decoding is no decode failure.
+    let other = Error::runtime(sp_blockchain::Error::Msg(
+        "Failed to decode result of a call".into(),
+    ));
+    assert_eq!(other.code(), RUNTIME_ERROR);
+    let unknown = Error::runtime(sp_blockchain::Error::UnknownBlock("0x00".into()));
+    assert_eq!(unknown, Error::UnknownBlock("0x00".into()));
+}
+
+#[test]
+fn codes_are_stable() {
+    let errors = vec![
+        (
+            Error::UnsupportedApi {
+                method: "auctionInformation_parameters",
+                required: 2,
+            },
+            9001,
+        ),
+        (Error::UnknownBlock("0x00".into()), 9002),
+        (Error::DecodeFailed(String::new()), 9003),
+        (Error::AuctionNotFound("1".into()), 9004),
+        (
+            Error::InvalidPage(Page {
+                offset: 0,
+                limit: 0,
+            }),
+            9005,
+        ),
+        (Error::RuntimeError(String::new()), 9006),
+        (Error::FetchFailed(String::new()), 9007),
+    ];
+    for (error, code) in errors {
+        let rpc_error: RpcError = error.into();
+        assert_eq!(rpc_error.code, ErrorCode::ServerError(code));
+    }
+}
+
+#[test]
+fn unsupported_api_names_method() {
+    let error: RpcError = Error::UnsupportedApi {
+        method: "auctionInformation_terminalOccupancy",
+        required: 3,
+    }
+    .into();
+    assert!(error
+        .message
+        .contains("auctionInformation_terminalOccupancy"));
+    assert!(error.message.contains('3'));
+    assert_eq!(error.data, None);
+}
@@ -0,0 +1,11 @@
+//! Fuzzes sequences of auction operations, checking the pallet's invariants after every operation.
+//!
+//! Run with `cargo fuzz run auction_operations` from the pallet's directory.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    pallet_auction::invariants::run(data);
+});
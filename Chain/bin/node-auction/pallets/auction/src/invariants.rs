@@ -0,0 +1,565 @@
+// Randomized invariant checking for the auctioning pallet. A byte string is decoded into a sequence
+// of operations (create, update, remove, bid and advancing blocks) which is applied to a small mock
+// runtime, the global invariants of the pallet are checked after every single operation. The same
+// driver backs the randomized tests in `tests.rs` and the fuzz target in `fuzz/`.
+
+use crate::*;
+use frame_support::{impl_outer_origin, parameter_types, traits::OnFinalize, traits::OnInitialize};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+
+impl_outer_origin! {
+    pub enum Origin for InvariantTestRuntime {}
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct InvariantTestRuntime;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: u32 = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    pub const ExistentialDeposit: u64 = 1;
+    // Fees are left out, burning them would change the total issuance.
+    pub const MaximumParameters: AuctionParameters<u64, u64> = AuctionParameters {
+        min_duration: 0,
+        max_horizon: 0,
+        creation_deposit: 300,
+        creation_fee: 0,
+        soft_close_window: 8,
+    };
+    pub const ResaleCutoff: u64 = 0;
+    pub const ResaleFee: Perbill = Perbill::zero();
+    pub const MaxRunnerUps: u32 = 2;
+    pub const ConfirmationPeriod: u64 = 3;
+    // Nothing is burned. The platform account is endowed in genesis, so even fees below the
+    // existential deposit are paid into it rather than burned.
+    pub const TerminalCommission: Perbill = Perbill::from_percent(10);
+    pub const PlatformFee: Perbill = Perbill::from_percent(5);
+    pub const PlatformAccount: u64 = 0;
+    pub const BurnShare: Perbill = Perbill::zero();
+    pub const DefaultSlotDuration: u64 = 3600;
+    pub const MaxBatchLength: u32 = 4;
+    pub const MaxTerminalWindows: u32 = 64;
+}
+
+impl system::Trait for InvariantTestRuntime {
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type ModuleToIndex = ();
+    type AccountData = pallet_balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type DbWeight = ();
+    type BaseCallFilter = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type SystemWeightInfo = ();
+}
+
+impl pallet_balances::Trait for InvariantTestRuntime {
+    type Balance = u64;
+    type Event = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+/// Pays the winning bid out to the creator, the terminal and the platform, like the node's runtime
+/// does.
+pub struct Handler;
+
+impl AuctionHandler<u64, u64, u64, u64> for Handler {
+    fn on_new_bid(
+        _now: u64,
+        _id: u64,
+        _new_bid: (u64, u64),
+        _last_bid: Option<(u64, u64)>,
+    ) -> OnNewBidResult<u64> {
+        OnNewBidResult {
+            accept_bid: true,
+            auction_end: None,
+        }
+    }
+
+    fn on_auction_ended(id: u64, recipients: (u64, u64), winner: Option<(u64, u64)>) -> bool {
+        if let Some(winner) = winner {
+            if let Err(_) = AuctionModule::settle_bid(id, &winner.0, &recipients, winner.1) {
+                sp_runtime::print("ERROR -- INVARIANTS>>ON_AUCTION_ENDED>>SETTLE_BID ");
+                return false;
+            }
+            return true;
+        }
+        false
+    }
+}
+
+/// A clock standing still at the epoch, the scenarios don't resell slots.
+pub struct Epoch;
+
+impl UnixTime for Epoch {
+    fn now() -> core::time::Duration {
+        Default::default()
+    }
+}
+
+impl Trait for InvariantTestRuntime {
+    type Event = ();
+    type Currency = Balances;
+    type AuctionId = u64;
+    type Handler = Handler;
+    type GeneralInformationContainer = u64;
+    type ParametersOrigin = system::EnsureRoot<u64>;
+    type MaximumParameters = MaximumParameters;
+    type UnixTime = Epoch;
+    type ResaleCutoff = ResaleCutoff;
+    type ResaleFee = ResaleFee;
+    type MaxRunnerUps = MaxRunnerUps;
+    type ConfirmationPeriod = ConfirmationPeriod;
+    type TerminalCommission = TerminalCommission;
+    type PlatformFee = PlatformFee;
+    type PlatformAccount = PlatformAccount;
+    type BurnShare = BurnShare;
+    type DefaultSlotDuration = DefaultSlotDuration;
+    type MaxBatchLength = MaxBatchLength;
+    type MaxTerminalWindows = MaxTerminalWindows;
+}
+
+pub type System = system::Module<InvariantTestRuntime>;
+pub type Balances = pallet_balances::Module<InvariantTestRuntime>;
+pub type AuctionModule = Module<InvariantTestRuntime>;
+
+/// Number of accounts taking part, accounts are numbered from 1.
+pub const ACCOUNTS: u64 = 6;
+/// Starting balance of every account, small enough for bids to run out of funds.
+pub const ENDOWMENT: u64 = 1_000;
+
+/// A single step of a generated scenario.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Operation {
+    /// `creator` opens an auction starting `start` blocks from now and lasting `duration` blocks.
+    Create {
+        creator: u64,
+        terminal: u64,
+        start: u64,
+        duration: u64,
+    },
+    /// `who` moves the start and/or end of auction `id` to the given blocks, the start may lie in
+    /// the past.
+    Update {
+        who: u64,
+        id: u64,
+        start: Option<u64>,
+        end: Option<u64>,
+    },
+    /// `who` removes auction `id`.
+    Remove { who: u64, id: u64 },
+    /// `who` bids `value` on auction `id`.
+    Bid { who: u64, id: u64, value: u64 },
+    /// `who` confirms winning auction `id`.
+    Confirm { who: u64, id: u64 },
+    /// Finalizes the current block and initializes the following `blocks` blocks.
+    Advance { blocks: u64 },
+    /// Root changes the creation deposit and the soft-close window.
+    SetParameters {
+        creation_deposit: u64,
+        soft_close_window: u64,
+    },
+}
+
+/// Reads bytes off the input, an exhausted input keeps yielding zeroes.
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    fn byte(&mut self) -> u8 {
+        match self.0.split_first() {
+            Some((first, rest)) => {
+                self.0 = rest;
+                *first
+            }
+            None => 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn account(&mut self) -> u64 {
+        (self.byte() as u64 % ACCOUNTS) + 1
+    }
+
+    /// Auction ids slightly past the current index, so most operations target existing auctions.
+    fn auction_id(&mut self) -> u64 {
+        self.byte() as u64 % (AuctionModule::auctions_index() + 2)
+    }
+
+    fn offset(&mut self) -> u64 {
+        (self.byte() % 16) as u64
+    }
+}
+
+impl Operation {
+    /// Decodes the next operation off `input`. The arguments depend on the state of the runtime,
+    /// so operations have to be applied as soon as they're decoded.
+    fn decode(input: &mut Input) -> Self {
+        match input.byte() % 10 {
+            0 => Operation::Create {
+                creator: input.account(),
+                terminal: input.account(),
+                start: input.offset(),
+                duration: input.offset(),
+            },
+            1 => {
+                let flags = input.byte();
+                let now = System::block_number();
+                Operation::Update {
+                    who: input.account(),
+                    id: input.auction_id(),
+                    // Offsets below 4 move the start into the past.
+                    start: Some((now + input.offset()).saturating_sub(4))
+                        .filter(|_| flags & 1 != 0),
+                    end: Some(now + input.offset()).filter(|_| flags & 2 != 0),
+                }
+            }
+            2 => Operation::Remove {
+                who: input.account(),
+                id: input.auction_id(),
+            },
+            3 | 4 | 5 => Operation::Bid {
+                who: input.account(),
+                id: input.auction_id(),
+                value: u16::from_le_bytes([input.byte(), input.byte()]) as u64 % (ENDOWMENT + 200),
+            },
+            6 | 7 => Operation::Advance {
+                blocks: (input.byte() % 4) as u64 + 1,
+            },
+            8 => Operation::Confirm {
+                who: input.account(),
+                id: input.auction_id(),
+            },
+            _ => Operation::SetParameters {
+                creation_deposit: input.byte() as u64 % 301,
+                soft_close_window: input.byte() as u64 % 9,
+            },
+        }
+    }
+
+    /// Applies the operation, failing dispatches are part of the scenario and are ignored.
+    fn apply(&self) {
+        let now = System::block_number();
+        let _ = match *self {
+            Operation::Create {
+                creator,
+                terminal,
+                start,
+                duration,
+            } => AuctionModule::ext_new_auction(
+                Origin::signed(creator),
+                terminal,
+                1,
+                1,
+                0,
+                now + start,
+                now + start + duration,
+            ),
+            Operation::Update {
+                who,
+                id,
+                start,
+                end,
+            } => AuctionModule::ext_update_auction(
+                Origin::signed(who),
+                id,
+                None,
+                None,
+                None,
+                start,
+                end,
+            ),
+            Operation::Remove { who, id } => {
+                AuctionModule::ext_remove_auction(Origin::signed(who), id)
+            }
+            Operation::Bid { who, id, value } => AuctionModule::bid(Origin::signed(who), id, value),
+            Operation::Confirm { who, id } => AuctionModule::confirm_win(Origin::signed(who), id),
+            Operation::Advance { blocks } => {
+                for _ in 0..blocks {
+                    run_to_next_block();
+                }
+                Ok(())
+            }
+            Operation::SetParameters {
+                creation_deposit,
+                soft_close_window,
+            } => AuctionModule::set_parameters(
+                Origin::root(),
+                AuctionParameters {
+                    creation_deposit,
+                    soft_close_window,
+                    ..Default::default()
+                },
+            ),
+        };
+    }
+}
+
+fn run_to_next_block() {
+    AuctionModule::on_finalize(System::block_number());
+    System::on_finalize(System::block_number());
+    System::set_block_number(System::block_number() + 1);
+    System::on_initialize(System::block_number());
+    AuctionModule::on_initialize(System::block_number());
+}
+
+/// Genesis of the scenarios, every account and the platform account are endowed and no auctions
+/// exist.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = system::GenesisConfig::default()
+        .build_storage::<InvariantTestRuntime>()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<InvariantTestRuntime> {
+        balances: (1..=ACCOUNTS)
+            .chain(Some(PlatformAccount::get()))
+            .map(|account| (account, ENDOWMENT))
+            .collect(),
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+/// Decodes `data` into operations and applies them one by one, checking the invariants after
+/// every operation. Panics with the operations applied so far once an invariant is violated.
+pub fn run(data: &[u8]) {
+    new_test_ext().execute_with(|| {
+        let mut input = Input(data);
+        let mut applied = Vec::new();
+
+        while !input.is_empty() {
+            let operation = Operation::decode(&mut input);
+            operation.apply();
+            applied.push(operation);
+
+            if let Err(violation) = check_invariants() {
+                panic!("{} after {:#?}", violation, applied);
+            }
+        }
+    });
+}
+
+/// Checks the global invariants of the pallet against the current state.
+///
+/// * The reserved balance of every account equals the sum of its leading bids, its queued bids, its
+///   runner-up bids, its bids awaiting confirmation and the creation deposits of its auctions.
+/// * Runner-ups are only kept for live or pending auctions, at most `MaxRunnerUps` of them, ordered
+///   from the highest bid down and below the auction's leading bid.
+/// * Every `SettlementDeadline` entry points to a pending settlement with that deadline, and every
+///   pending settlement is indexed under its deadline.
+/// * Every `AuctionEndTime` entry points to an existing auction ending on that block, and every
+///   auction with an end is indexed under it.
+/// * Every `AuctionStartTime` entry points to an existing auction starting on that block, and every
+///   auction holding queued bids is indexed under its start.
+/// * Every auction other than a resale, every pending settlement other than a resale and every won
+///   slot whose berth window hasn't passed is indexed in the `Timeline` of its terminal, and nothing
+///   else whose window hasn't passed is.
+/// * The total issuance never changes, no balance is minted or burned.
+pub fn check_invariants() -> Result<(), String> {
+    let mut expected_reserved = BTreeMap::<u64, u64>::new();
+
+    for (id, auction) in <Auctions<InvariantTestRuntime>>::iter() {
+        if let Some((bidder, value)) = auction.bid {
+            *expected_reserved.entry(bidder).or_default() += value;
+        }
+        if let Some(end) = auction.end {
+            if !<AuctionEndTime<InvariantTestRuntime>>::contains_key(end, id) {
+                return Err(format!("Auction {} ending on {} is not indexed", id, end));
+            }
+        }
+        if let Some(deposit) = <AuctionDeposits<InvariantTestRuntime>>::get(id) {
+            *expected_reserved.entry(auction.creator).or_default() += deposit;
+        }
+    }
+
+    for (id, queue) in <QueuedBids<InvariantTestRuntime>>::iter() {
+        let auction = <Auctions<InvariantTestRuntime>>::get(id)
+            .ok_or_else(|| format!("Queued bids held for missing auction {}", id))?;
+        if !queue.is_empty()
+            && !<AuctionStartTime<InvariantTestRuntime>>::contains_key(auction.start, id)
+        {
+            return Err(format!("Queued bids of auction {} are not indexed", id));
+        }
+        for queued in queue {
+            *expected_reserved.entry(queued.bid.0).or_default() += queued.bid.1;
+        }
+    }
+
+    for (id, auction) in <PendingSettlements<InvariantTestRuntime>>::iter() {
+        let (bidder, value) = auction
+            .bid
+            .ok_or_else(|| format!("Pending settlement {} has no bid", id))?;
+        *expected_reserved.entry(bidder).or_default() += value;
+        let deadline = auction
+            .end
+            .ok_or_else(|| format!("Pending settlement {} has no deadline", id))?;
+        if !<SettlementDeadline<InvariantTestRuntime>>::contains_key(deadline, id) {
+            return Err(format!("Pending settlement {} is not indexed", id));
+        }
+    }
+
+    for (deadline, id, _) in <SettlementDeadline<InvariantTestRuntime>>::iter() {
+        match <PendingSettlements<InvariantTestRuntime>>::get(id) {
+            Some(auction) if auction.end == Some(deadline) => {}
+            _ => {
+                return Err(format!(
+                    "Settlement {} indexed under {} is not pending by then",
+                    id, deadline
+                ))
+            }
+        }
+    }
+
+    for (id, runner_ups) in <RunnerUps<InvariantTestRuntime>>::iter() {
+        let auction = <Auctions<InvariantTestRuntime>>::get(id)
+            .or_else(|| <PendingSettlements<InvariantTestRuntime>>::get(id))
+            .ok_or_else(|| format!("Runner-ups held for settled auction {}", id))?;
+        if runner_ups.len() > MaxRunnerUps::get() as usize {
+            return Err(format!(
+                "Auction {} holds {} runner-ups",
+                id,
+                runner_ups.len()
+            ));
+        }
+        let leading = auction.bid.map(|(_, value)| value).unwrap_or_default();
+        let mut previous = u64::max_value();
+        for (bidder, value) in runner_ups {
+            if value >= previous || value >= leading {
+                return Err(format!("Runner-ups of auction {} are out of order", id));
+            }
+            previous = value;
+            *expected_reserved.entry(bidder).or_default() += value;
+        }
+    }
+
+    for (end, id, _) in <AuctionEndTime<InvariantTestRuntime>>::iter() {
+        match <Auctions<InvariantTestRuntime>>::get(id) {
+            Some(auction) if auction.end == Some(end) => {}
+            Some(auction) => {
+                return Err(format!(
+                    "Auction {} indexed to end on {} but ends on {:?}",
+                    id, end, auction.end
+                ))
+            }
+            None => return Err(format!("Missing auction {} indexed to end on {}", id, end)),
+        }
+    }
+
+    for (start, id, _) in <AuctionStartTime<InvariantTestRuntime>>::iter() {
+        match <Auctions<InvariantTestRuntime>>::get(id) {
+            Some(auction) if auction.start == start => {}
+            Some(auction) => {
+                return Err(format!(
+                    "Auction {} indexed to start on {} but starts on {}",
+                    id, start, auction.start
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "Missing auction {} indexed to start on {}",
+                    id, start
+                ))
+            }
+        }
+    }
+
+    // Windows which have passed may or may not have been pruned yet.
+    let now = Epoch::now().as_secs();
+    let current = |terminal: &u64, core| AuctionModule::berth_window(terminal, core).end > now;
+    let mut expected_timeline = BTreeSet::new();
+    for (id, auction) in <Auctions<InvariantTestRuntime>>::iter()
+        .chain(<PendingSettlements<InvariantTestRuntime>>::iter())
+        .filter(|(id, _)| !<ResaleOf<InvariantTestRuntime>>::contains_key(id))
+        .filter(|(_, auction)| current(&auction.slot_origin, &auction.core))
+    {
+        expected_timeline.insert((auction.slot_origin, id));
+    }
+    for (id, slot) in <WonSlots<InvariantTestRuntime>>::iter()
+        .filter(|(_, slot)| current(&slot.slot_origin, &slot.core))
+    {
+        expected_timeline.insert((slot.slot_origin, id));
+    }
+    let timeline = <Timeline<InvariantTestRuntime>>::iter()
+        .filter(|(_, _, window)| window.end > now)
+        .map(|(terminal, id, _)| (terminal, id))
+        .collect::<BTreeSet<_>>();
+    if let Some((terminal, id)) = expected_timeline.symmetric_difference(&timeline).next() {
+        return Err(format!(
+            "Timeline of terminal {} is out of sync for auction {}",
+            terminal, id
+        ));
+    }
+
+    for account in 1..=ACCOUNTS {
+        let reserved = Balances::reserved_balance(account);
+        let expected = expected_reserved.get(&account).copied().unwrap_or_default();
+        if reserved != expected {
+            return Err(format!(
+                "Account {} has {} reserved, its bids and deposits add up to {}",
+                account, reserved, expected
+            ));
+        }
+    }
+
+    let issuance = Balances::total_issuance();
+    if issuance != (ACCOUNTS + 1) * ENDOWMENT {
+        return Err(format!("Total issuance changed to {}", issuance));
+    }
+
+    Ok(())
+}
+
+/// Deterministic xorshift generator, used to produce scenarios for the randomized tests without
+/// pulling in a source of randomness.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        // A zero state would only ever yield zeroes.
+        XorShift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                (self.0 >> 32) as u8
+            })
+            .collect()
+    }
+}
@@ -10,12 +10,17 @@ use frame_support::{
     dispatch::Parameter,
     ensure,
     sp_runtime::{
-        traits::{AtLeast32Bit, Bounded, MaybeSerializeDeserialize, Member, One, Printable, Zero},
-        DispatchError, DispatchResult,
+        traits::{
+            AtLeast32Bit, Bounded, MaybeSerializeDeserialize, Member, One, Printable, Saturating,
+            Zero,
+        },
+        DispatchError, DispatchResult, Perbill,
     },
+    storage::{with_transaction, TransactionOutcome},
     traits::{
-        Currency, ExistenceRequirement::AllowDeath, ReservableCurrency, WithdrawReason,
-        WithdrawReasons,
+        Currency, EnsureOrigin, ExistenceRequirement, ExistenceRequirement::AllowDeath,
+        ExistenceRequirement::KeepAlive, Get, Imbalance, ReservableCurrency, UnixTime,
+        WithdrawReason, WithdrawReasons,
     },
     weights::{DispatchInfo, Weight},
     IterableStorageDoubleMap, IterableStorageMap,
@@ -26,6 +31,9 @@ use frame_support::inherent::*;
 
 use sp_std::convert::TryInto;
 
+use parity_scale_codec::{Decode, Encode};
+use sp_runtime::RuntimeDebug;
+
 #[allow(unused_imports)]
 use frame_system::{self as system, ensure_signed};
 
@@ -39,6 +47,15 @@ use auction_traits::auction::*;
 #[cfg(test)]
 mod tests;
 
+// Randomized invariant checking, shared by the tests and the fuzz target.
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod invariants;
+
+////////////////////////////////////////////
+//////////////// Migrations ////////////////
+////////////////////////////////////////////
+mod migration;
+
 ////////////////////////////////////////////
 ///////// Auctioning Module Code ///////////
 ////////////////////////////////////////////
@@ -56,6 +73,56 @@ pub type InfoCond<T> = AuctionInfo<
     <T as Trait>::GeneralInformationContainer,
 >;
 
+// WonSlot condensed into a single type.
+pub type SlotCond<T> = WonSlot<
+    <T as system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as Trait>::AuctionId,
+    <T as Trait>::GeneralInformationContainer,
+>;
+
+// TerminalCapacity condensed into a single type.
+pub type CapacityCond<T> = TerminalCapacity<<T as Trait>::GeneralInformationContainer>;
+
+// AuctionParameters condensed into a single type.
+pub type ParamsCond<T> = AuctionParameters<BalanceOf<T>, <T as system::Trait>::BlockNumber>;
+
+// NewAuction condensed into a single type.
+pub type NewAuctionCond<T> = NewAuction<
+    <T as system::Trait>::AccountId,
+    <T as system::Trait>::BlockNumber,
+    <T as Trait>::GeneralInformationContainer,
+>;
+
+// AuctionUpdate condensed into a single type.
+pub type UpdateCond<T> = AuctionUpdate<
+    <T as Trait>::AuctionId,
+    <T as system::Trait>::BlockNumber,
+    <T as Trait>::GeneralInformationContainer,
+>;
+
+// A value placed in storage that represents the current version of the auction storage. This value
+// is used by the `on_runtime_upgrade` logic to determine which migrations have to be run, see the
+// `migration` module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+    // `AuctionInfo` carries a `parsed_bid` and `QueuedBids` is keyed by block number.
+    V0,
+    // `parsed_bid` is dropped and `QueuedBids` is keyed by auction, indexed by `AuctionStartTime`.
+    V1,
+    // The berth windows of auctions and won slots are indexed per terminal in `Timeline`.
+    V2,
+}
+
+#[cfg(feature = "std")]
+auction_traits::describe_enum!(Releases { V0, V1, V2 });
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V0
+    }
+}
+
 pub trait Trait: system::Trait + Sized {
     // The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
@@ -88,6 +155,42 @@ pub trait Trait: system::Trait + Sized {
         Self::BlockNumber,
         Self::AuctionId,
     >;
+    // Origin allowed to change the auction parameters, e.g. root through sudo or a collective.
+    type ParametersOrigin: EnsureOrigin<Self::Origin>;
+    // Upper bounds of the auction parameters, `set_parameters` rejects anything beyond these. The
+    // lower bound of every parameter is zero.
+    type MaximumParameters: Get<ParamsCond<Self>>;
+    // Source of the current time, which resales are checked against.
+    type UnixTime: UnixTime;
+    // Number of seconds before a won slot's timestamp after which the slot can no longer be
+    // listed or bought.
+    type ResaleCutoff: Get<u64>;
+    // Share of the resale price the seller of a won slot pays to the terminal, zero disables the
+    // fee.
+    type ResaleFee: Get<Perbill>;
+    // Number of outbid bids every auction keeps on hold, highest first, to fall back on when its
+    // winner defaults. Zero refunds outbid bids right away.
+    type MaxRunnerUps: Get<u32>;
+    // Number of blocks the winner of an auction has to confirm the win in, a winner who doesn't
+    // confirm in time defaults. Zero settles auctions as soon as they end.
+    type ConfirmationPeriod: Get<Self::BlockNumber>;
+    // Share of every winning bid paid to the terminal which issued the slot.
+    type TerminalCommission: Get<Perbill>;
+    // Share of every winning bid paid to the platform account.
+    type PlatformFee: Get<Perbill>;
+    // Account receiving the platform fee, e.g. the treasury's account.
+    type PlatformAccount: Get<Self::AccountId>;
+    // Share of every winning bid which is burned. The creator of the auction receives what's left
+    // after the commission, the platform fee and the burn, which can't add up to more than the
+    // whole bid.
+    type BurnShare: Get<Perbill>;
+    // Number of seconds a slot occupies a berth at terminals which haven't declared a capacity.
+    type DefaultSlotDuration: Get<u64>;
+    // Number of entries a batch call takes at most.
+    type MaxBatchLength: Get<u32>;
+    // Number of berth windows which haven't passed yet the timeline of a terminal holds at most.
+    // Every slot offered at a terminal is checked against its timeline, this bounds the check.
+    type MaxTerminalWindows: Get<u32>;
 }
 
 decl_storage! {
@@ -102,34 +205,61 @@ decl_storage! {
         // contained within, it means that an auction is ending on this given block. This improves
         // lookup performance.
         pub AuctionEndTime get(fn auction_end_time): double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) T::AuctionId => Option<bool>;
+        // Each auction holding queued bids is indexed under its starting block. Once the auction
+        // starts, the entry is drained and the auction's queued bids are processed.
+        pub AuctionStartTime get(fn auction_start_time): double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) T::AuctionId => Option<bool>;
         // Storage for queued bids. Bids live in this item untill they can be placed, once placed,
-        // they're removed from this item.
-        pub QueuedBids get(fn queued_bids): map hasher(twox_64_concat) T::BlockNumber => Option<QueuedBid<T::AccountId, BalanceOf<T>, T::AuctionId>>;
+        // they're removed from this item. Every auction can hold a queued bid per bidder.
+        pub QueuedBids get(fn queued_bids): map hasher(twox_64_concat) T::AuctionId => Vec<QueuedBid<T::AccountId, BalanceOf<T>, T::AuctionId>>;
+        // Policy knobs of this module, changed through `set_parameters`. All knobs start out
+        // disabled.
+        pub Parameters get(fn parameters): AuctionParameters<BalanceOf<T>, T::BlockNumber>;
+        // Creation deposits reserved from the creators of the auctions, released once the auction
+        // ends or is removed.
+        pub AuctionDeposits get(fn auction_deposits): map hasher(twox_64_concat) T::AuctionId => Option<BalanceOf<T>>;
+        // Outbid bids kept on hold in case the winner defaults, highest first. Every auction keeps
+        // at most `MaxRunnerUps` of them until it settles.
+        pub RunnerUps get(fn runner_ups): map hasher(twox_64_concat) T::AuctionId => Vec<(T::AccountId, BalanceOf<T>)>;
+        // Ended auctions awaiting the confirmation of their winner. The auction's bid is the bid
+        // awaiting confirmation and its end is the deadline to confirm it by.
+        pub PendingSettlements get(fn pending_settlements): map hasher(twox_64_concat) T::AuctionId => Option<AuctionInfo<T::AccountId, BalanceOf<T>, T::BlockNumber, T::GeneralInformationContainer>>;
+        // Pending settlements indexed under their confirmation deadline.
+        pub SettlementDeadline get(fn settlement_deadline): double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) T::AuctionId => Option<bool>;
+        // Slots won in an auction, keyed by that auction. The winner holds the slot and may resell
+        // it, either at a fixed price or in a new auction.
+        pub WonSlots get(fn won_slots): map hasher(twox_64_concat) T::AuctionId => Option<WonSlot<T::AccountId, BalanceOf<T>, T::AuctionId, T::GeneralInformationContainer>>;
+        // Every holder of a won slot along with the price paid and the block the slot was
+        // acquired on, oldest first.
+        pub SlotHistory get(fn slot_history): map hasher(twox_64_concat) T::AuctionId => Vec<(T::AccountId, BalanceOf<T>, T::BlockNumber)>;
+        // Maps resale auctions to the won slot they resell.
+        pub ResaleOf get(fn resale_of): map hasher(twox_64_concat) T::AuctionId => Option<T::AuctionId>;
+        // Terminals which approve every resale of their slots.
+        pub ResaleApprovalRequired get(fn resale_approval_required): map hasher(blake2_128_concat) T::AccountId => bool;
+        // Berth capacity declared by each terminal. New slots at a terminal without a capacity
+        // aren't checked for conflicts.
+        pub Capacities get(fn capacities): map hasher(blake2_128_concat) T::AccountId => Option<TerminalCapacity<T::GeneralInformationContainer>>;
+        // Berth windows of the slots each terminal has on offer or sold, keyed by the terminal and
+        // by the auction the slot was first offered in. Resale auctions share the entry of the
+        // slot they resell.
+        pub Timeline get(fn timeline): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) T::AuctionId => Option<BerthWindow<T::GeneralInformationContainer>>;
+        // Storage version of this pallet, used by `on_runtime_upgrade` to decide which migrations
+        // have to be applied. New networks start with the latest version.
+        StorageVersion build(|_| Releases::V2): Releases;
     }
         // This section is used to process the genesis information provided into the auctioning
-        // module. This is mainly needed for testing purposes.
+        // module. Terminals are set up first, so the auctions' slots are checked against their
+        // capacities.
         add_extra_genesis {
-            config(_auctions):
-                Vec<(T::AccountId,
-                     T::AccountId,
-                     Vec<T::GeneralInformationContainer>,
-                     T::BlockNumber,
-                     T::BlockNumber)>;
+            config(terminals): Vec<GenesisTerminal<T::AccountId, T::GeneralInformationContainer>>;
+            config(auctions): Vec<GenesisAuction<T::AccountId, BalanceOf<T>, T::BlockNumber, T::GeneralInformationContainer>>;
 
             build(|config: &GenesisConfig<T>| {
-                for (barge, terminal, _core_info, start, end) in &config._auctions {
-                    assert!(
-                        *end > *start,
-                        "Ending block has to be greater than the starting block",
-                    );
-                    assert!(
-                        *barge != *terminal,
-                        "Barge operator cannot be the terminal",
-                    );
+                for terminal in &config.terminals {
+                    <Module<T>>::genesis_terminal(terminal);
+                }
+                for auction in &config.auctions {
+                    <Module<T>>::genesis_auction(auction);
                 }
-                for &(ref barge, ref terminal, ref core_info, ref start, ref end) in config._auctions.iter() {
-                    <Module<T>>::new_auction(barge.clone(), terminal.clone(), AuctionCoreInfo { timestamp: core_info[0], cargo: (core_info[1], core_info[2]), }, *start, Some(*end));
-                    }
             });
     }
 }
@@ -142,6 +272,8 @@ decl_event!(
         BlockNumber = <T as system::Trait>::BlockNumber,
         AuctionId = <T as Trait>::AuctionId,
         GenInfo = <T as Trait>::GeneralInformationContainer,
+        Parameters = ParamsCond<T>,
+        Capacity = CapacityCond<T>,
     {
         // Currency Events
         // Called when funds are reserved (e.g. when placing a bid)
@@ -192,9 +324,71 @@ decl_event!(
         ),
         // Called when an auction is deleted.
         AuctionDeleted(AuctionId),
+        // Called when the auction parameters are changed. (Old, New)
+        ParametersChanged(Parameters, Parameters),
+
+        /// Settlement Events
+        // Called when the winner of an auction has to confirm the win. (Auction, Winner, Bid,
+        // Deadline)
+        ConfirmationRequested(AuctionId, AccountId, Balance, BlockNumber),
+        // Called when the winner of an auction fails to pay. (Auction, Winner, Bid)
+        WinnerDefaulted(AuctionId, AccountId, Balance),
+        // Called when the winner of an auction doesn't confirm the win in time, the bid is
+        // released. (Auction, Winner, Bid)
+        ConfirmationMissed(AuctionId, AccountId, Balance),
+        // Called when the highest runner-up takes the place of a defaulted winner. (Auction,
+        // Bidder, Bid)
+        RunnerUpPromoted(AuctionId, AccountId, Balance),
+        // Called when the bid of a runner-up is released as its auction settles or is removed.
+        // (Auction, Bidder, Bid)
+        RunnerUpReleased(AuctionId, AccountId, Balance),
+        // Called when the creator of an auction is paid its share of the winning bid. (Auction,
+        // Creator, Amount)
+        CreatorPaid(AuctionId, AccountId, Balance),
+        // Called when the terminal is paid its commission on the winning bid. (Auction, Terminal,
+        // Amount)
+        CommissionPaid(AuctionId, AccountId, Balance),
+        // Called when the platform is paid its fee on the winning bid. (Auction, Platform, Amount)
+        PlatformFeePaid(AuctionId, AccountId, Balance),
+        // Called when the burn share of the winning bid is burned. (Auction, Winner, Amount)
+        BidBurned(AuctionId, AccountId, Balance),
+
+        /// Resale Events
+        // Called when the winner of an auction pays and becomes the slot's holder. (Slot, Holder,
+        // Price)
+        SlotWon(AuctionId, AccountId, Balance),
+        // Called when a holder offers a slot at a fixed price. (Slot, Holder, Price)
+        SlotListed(AuctionId, AccountId, Balance),
+        // Called when a holder relists a slot in a new auction. (Slot, Holder, Auction)
+        SlotRelisted(AuctionId, AccountId, AuctionId),
+        // Called when a slot's offer is withdrawn or its resale auction ends without a winner.
+        SlotUnlisted(AuctionId),
+        // Called when a slot changes hands. (Slot, Seller, Buyer, Price)
+        SlotTransferred(AuctionId, AccountId, AccountId, Balance),
+        // Called when the resale fee is withheld from the price of a slot and paid to the
+        // terminal. (Slot, Seller, Terminal, Fee)
+        ResaleFeePaid(AuctionId, AccountId, AccountId, Balance),
+        // Called when a terminal changes whether the resales of its slots need its approval.
+        ResaleApprovalRequired(AccountId, bool),
+        // Called when a terminal approves the resale of a slot by its current holder.
+        ResaleApproved(AuctionId, AccountId),
+        // Called when a slot whose time has passed is cleared from storage.
+        SlotCleared(AuctionId),
+
+        /// Berth Events
+        // Called when a terminal declares its berth capacity, or withdraws it.
+        TerminalCapacitySet(AccountId, Option<Capacity>),
 
         // Other Events
         DummyEvent(),
+
+        /// Auction Events, appended to keep the indices of the events above
+        // Called when a queued bid can't be placed as its auction starts, the bid is refunded.
+        QueuedBidFailed(AuctionId, AccountId, Balance),
+        // Called when a batch of auctions is created. (Creator, First ID, Last ID)
+        AuctionsCreated(AccountId, AuctionId, AuctionId),
+        // Called when a batch of auctions is updated, listing the updated auctions in order.
+        AuctionsUpdated(AccountId, Vec<AuctionId>),
     }
 );
 
@@ -238,6 +432,46 @@ decl_error! {
 
         // Thrown for testing purposes or when no explanation can be given.
         Unexplained,
+
+        // Thrown when an auction's duration is below the minimum duration parameter.
+        AuctionTooShort,
+        // Thrown when an auction's start lies beyond the maximum horizon parameter.
+        AuctionBeyondHorizon,
+        // Thrown when the new auction parameters exceed the maximum parameters.
+        ParameterOutOfBounds,
+        // Thrown when the creation fee can't be paid.
+        FeeNotPaid,
+
+        // Thrown when a won slot which doesn't exist is called.
+        SlotNotExist,
+        // Thrown when a slot is listed twice, or cleared while listed.
+        SlotListed,
+        // Thrown when a slot which isn't offered at a fixed price is bought, or bought at another
+        // price than it's offered at.
+        SlotNotListed,
+        // Thrown when a slot is listed or bought after the resale cutoff.
+        ResaleClosed,
+        // Thrown when a slot is listed without the approval its terminal requires.
+        ResaleNotApproved,
+        // Thrown when the slot information of a resale auction is updated, the slot being resold
+        // fixes it.
+        ResaleSlotImmutable,
+        // Thrown when a slot whose time hasn't passed yet is cleared.
+        SlotNotExpired,
+
+        // Thrown when a win is confirmed for an auction which doesn't await confirmation.
+        SettlementNotExist,
+
+        // Thrown when a slot would exceed the vessels or TEU its terminal handles at once.
+        BerthConflict,
+        // Thrown when a terminal declares a capacity without berths or a slot duration of zero.
+        InvalidCapacity,
+        // Thrown when a batch call is empty or longer than `MaxBatchLength`.
+        InvalidBatchLength,
+
+        // Thrown when a slot is offered at a terminal whose timeline already holds
+        // `MaxTerminalWindows` windows which haven't passed yet.
+        TimelineFull,
     }
 }
 
@@ -255,15 +489,9 @@ decl_module! {
 
             // Queue bid if needed and exit.
             if block_number < auction.start {
-                // We're placing a queued bid.
-                // Check and see if current auction already has queued bids.
-                // TODO(Hamza):: Current queued auctions are just linked to block_numbers, while
-                // searching just through a block_number isn't that bad, perhaps try and actually
-                // have the auction ID's exposed through a double map, this way we can search
-                // focusing just on the auction ID, which saves us a loop.
-                //for (bnum, qbid) in <QueuedBids<T>>::iter() {
-                    //println!("Queued bid under block number: {} :: {:#?}", bnum, qbid);
-                //}
+                // We're placing a queued bid. Queued bids are stored per auction, once the auction
+                // starts the highest of them is placed and the rest are refunded.
+                ensure!(!value.is_zero(), Error::<T>::InvalidBidPrice);
 
                 // Assemble our queued bid.
                 let queued_bid = QueuedBid {
@@ -274,19 +502,42 @@ decl_module! {
                 // Currently bids are queued regardless of the iniatiator's balance. This enables
                 // bidders to cancel each-other's bids out by bidding something higher than the
                 // other. To stop this from happening, we reserve some of the balance now.
-                let reserve_result = Self::reserve_funds(&queued_bid.bid.0, queued_bid.bid.1);
+                //
+                // A bidder can only have a single queued bid per auction, a new queued bid
+                // replaces the old one. The new funds are reserved before the old ones are
+                // released, both within a transaction, so a failure on either side leaves the old
+                // queued bid and its reserve intact.
+                let mut queue = <QueuedBids<T>>::get(id);
+                let previous = queue
+                    .iter()
+                    .position(|q| q.bid.0 == queued_bid.bid.0)
+                    .map(|position| queue.remove(position));
+                let reserve_result = with_transaction(|| {
+                    if let Err(error) = Self::reserve_funds(&queued_bid.bid.0, queued_bid.bid.1) {
+                        sp_runtime::print("ERROR -- BID(QUEUED) >> RESERVE_FUNDS");
+                        return TransactionOutcome::Rollback(Err(error));
+                    }
+                    if let Some(previous) = &previous {
+                        if let Err(error) = Self::unreserve_funds(&previous.bid.0, previous.bid.1) {
+                            sp_runtime::print("ERROR -- BID(QUEUED) >> UNRESERVE_FUNDS");
+                            return TransactionOutcome::Rollback(Err(error));
+                        }
+                    }
+                    TransactionOutcome::Commit(Ok(()))
+                });
 
                 if let Err(error) = reserve_result {
-                    // Funds couldn't be reserved. Print a debug line and throw the error. TODO::
-                    // Make a custom error for this scenario and throw it here!
-                    sp_runtime::print("ERROR -- BID(QUEUED) >> RESERVE_FUNDS");
+                    // Funds couldn't be moved, the debug line was printed above. TODO:: Make a
+                    // custom error for this scenario and throw it here!
                     return Err(error.into());
                 }
 
                 // Note that the reserved balance isn't an exlusive pool of funds, other methods in
                 // this runtime can pull from it. Perhaps something else is needed to make sure
                 // that the unreserving funds are always successful.
-                <QueuedBids<T>>::insert(auction.start, queued_bid.clone());
+                queue.push(queued_bid.clone());
+                <QueuedBids<T>>::insert(id, queue);
+                <AuctionStartTime<T>>::insert(&auction.start, id, true);
                 Self::deposit_event(RawEvent::BidQueued(queued_bid.bid.0, queued_bid.bid.1, queued_bid.auction_id));
                 return Ok(());
             }
@@ -301,18 +552,51 @@ decl_module! {
 
             ensure!(bid_result.accept_bid, Error::<T>::BidNotAccepted);
 
-            // Bid was accepted, time to refund the previous bidder.
-            if let Some((a,b)) = &auction.bid {
-                let (previous_bidder,previous_bid) = (a, b);
-                let unreserve_result = Self::unreserve_funds(previous_bidder, *previous_bid);
-
-                if let Err(error) = unreserve_result {
+            // Bid was accepted. Funds the bidder already holds in this auction, either as the
+            // leading bidder or as a runner-up, are released to count towards the new bid.
+            let mut runner_ups = <RunnerUps<T>>::get(id);
+            let held = match &auction.bid {
+                Some((leader, leading_bid)) if *leader == bidder => Some(*leading_bid),
+                _ => runner_ups
+                    .iter()
+                    .position(|(runner_up, _)| *runner_up == bidder)
+                    .map(|position| runner_ups.remove(position).1),
+            };
+            if let Some(held) = held {
+                if let Err(error) = Self::unreserve_funds(&bidder, held) {
                     // Funds couldn't be unreserved, print a debug line and throw the error.
                     sp_runtime::print("ERROR -- MODULE >> UNRESERVE_FUNDS >> RESERVE");
                     return Err(error.into());
                 }
             }
 
+            // At this point we reserve the funds for the auction, this is also the point where we
+            // know how much is to be reserved.
+            let reserve_result = Self::reserve_funds(&bidder, value);
+
+            if let Err(error) = reserve_result {
+                // Funds couldn't be reserved, the bids stay as they were. The bidder's funds were
+                // released above, so put them back on hold. The bidder has just been refunded
+                // this exact amount, so this can't fail unless something else pulled from their
+                // balance in the meantime.
+                if let Some(held) = held {
+                    if let Err(_) = Self::reserve_funds(&bidder, held) {
+                        sp_runtime::print("ERROR -- MODULE >> RESERVE_FUNDS >> RESTORE");
+                    }
+                }
+                sp_runtime::print("ERROR -- MODULE >> RESERVE_FUNDS >> RESERVE");
+                return Err(error.into());
+            }
+
+            // The outbid bidder stays on hold as the highest runner-up, every other runner-up was
+            // outbid by them before.
+            if let Some((previous_bidder, previous_bid)) = auction.bid.clone() {
+                if previous_bidder != bidder {
+                    runner_ups.insert(0, (previous_bidder, previous_bid));
+                }
+            }
+            Self::store_runner_ups(id, runner_ups);
+
             // In case we're expecting a new end_time, replace it. This in essence extends the
             // auction.
             if let Some(new_end) = bid_result.auction_end {
@@ -325,34 +609,18 @@ decl_module! {
                 auction.end = new_end;
             }
 
-            // At this point we reserve the funds for the auction, this is also the point where we
-            // know how much is to be reserved. At this point we also convert the reserved_balance
-            // to a bid, this because we're using this "converted" balance for display on the
-            // front-end. DO NOT CHANGE THIS- making use of the regular balance causes issues in
-            // regards to value padding, trying to pass a value through serde results in value+ 15
-            // 0's.
-
-            // Keep track of the previously reserved balances, this is for the case where the user
-            // has bid on multiple auctions, we want to save the correct amount of currencies for
-            // this auction.
-            let pre_reserved = T::Currency::reserved_balance(&bidder);
-            let reserve_result = Self::reserve_funds(&bidder, value);
-
-            if let Err(error) = reserve_result {
-                // Funds couldn't be reserved. If it ever does happen at this stage, it's wisest to
-                // move the reserving of funds to before we refund the previous bidder. In that
-                // case, if reservation fails, the previous bidder's bid is still active. As of
-                // now, it's removed- which results in a dead auction.
-                sp_runtime::print("ERROR -- MODULE >> RESERVE_FUNDS >> RESERVE");
-                return Err(error.into());
+            // Bids placed right before the auction's end extend the auction, this gives other
+            // bidders the chance to respond.
+            if let Some(new_end) = Self::soft_close_end(block_number, auction.end) {
+                if let Some(old_end_block) = auction.end {
+                    <AuctionEndTime<T>>::remove(&old_end_block, id);
+                }
+                <AuctionEndTime<T>>::insert(&new_end, id, true);
+                auction.end = Some(new_end);
             }
 
-            // Update current auction's bid and request a new clean Balance type which is then
-            // converted to u64 in preparation for display.
+            // Update current auction's bid.
             auction.bid = Some((bidder.clone(), value));
-            auction.parsed_bid = TryInto::<u64>::try_into(T::Currency::reserved_balance(&bidder) - pre_reserved).ok();
-
-            sp_runtime::print(auction.parsed_bid.unwrap());
 
             <Auctions<T>>::insert(id, auction);
             // Emit bidding event
@@ -361,7 +629,7 @@ decl_module! {
             Ok(())
         }
 
-        #[weight = 10_000]
+        #[weight = <Module<T>>::berth_check_weight()]
         pub fn ext_update_auction(origin,
             id: T::AuctionId,
             time: Option<T::GeneralInformationContainer>,
@@ -371,6 +639,7 @@ decl_module! {
             end: Option<T::BlockNumber>
         ) -> DispatchResult {
             let initiator = ensure_signed(origin)?;
+            Self::ensure_update_fits(id, time, num_teu, start, end)?;
             let event_info = <Module<T>>::update_auction(
                 id,
                 initiator,
@@ -406,7 +675,7 @@ decl_module! {
             Ok(())
     }
 
-       #[weight = 10_000]
+       #[weight = <Module<T>>::berth_check_weight()]
        pub fn ext_new_auction(
            origin,
            terminal: T::AccountId,
@@ -417,15 +686,13 @@ decl_module! {
            end: T::BlockNumber,
        ) -> DispatchResult {
            let initiator = ensure_signed(origin)?;
-           let (auction_id, auction) = <Module<T>>::new_auction(
-               initiator,
-               terminal,
-               AuctionCoreInfo {
-                   timestamp: timestamp,
-                   cargo: (num_con, num_teu)
-               },
-               start,
-               Some(end));
+           Self::ensure_schedule(start, Some(end))?;
+           let core = AuctionCoreInfo {
+               timestamp: timestamp,
+               cargo: (num_con, num_teu)
+           };
+
+           let (auction_id, auction) = Self::create_auction(initiator, terminal, core, start, end)?;
            if let Some(end) = auction.end {
                Self::deposit_event(
                    RawEvent::AuctionCreated(
@@ -456,6 +723,284 @@ decl_module! {
             Ok(())
         }
 
+        #[weight = 10_000]
+        pub fn set_parameters(origin, parameters: ParamsCond<T>) -> DispatchResult {
+            T::ParametersOrigin::ensure_origin(origin)?;
+
+            let max = T::MaximumParameters::get();
+            ensure!(
+                parameters.min_duration <= max.min_duration
+                    && parameters.max_horizon <= max.max_horizon
+                    && parameters.creation_deposit <= max.creation_deposit
+                    && parameters.creation_fee <= max.creation_fee
+                    && parameters.soft_close_window <= max.soft_close_window,
+                Error::<T>::ParameterOutOfBounds
+            );
+
+            let old = <Parameters<T>>::get();
+            <Parameters<T>>::put(parameters.clone());
+            Self::deposit_event(RawEvent::ParametersChanged(old, parameters));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn confirm_win(origin, id: T::AuctionId) -> DispatchResult {
+            let winner = ensure_signed(origin)?;
+            let auction = <PendingSettlements<T>>::get(id).ok_or(Error::<T>::SettlementNotExist)?;
+            ensure!(
+                auction.bid.as_ref().map(|(bidder, _)| bidder) == Some(&winner),
+                Error::<T>::PermissionError
+            );
+
+            <PendingSettlements<T>>::remove(id);
+            if let Some(deadline) = auction.end {
+                <SettlementDeadline<T>>::remove(&deadline, id);
+            }
+            Self::settle(id, auction, <RunnerUps<T>>::take(id), true);
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn set_resale_approval(origin, required: bool) -> DispatchResult {
+            let terminal = ensure_signed(origin)?;
+            if required {
+                <ResaleApprovalRequired<T>>::insert(&terminal, true);
+            } else {
+                <ResaleApprovalRequired<T>>::remove(&terminal);
+            }
+            Self::deposit_event(RawEvent::ResaleApprovalRequired(terminal, required));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn approve_resale(origin, slot_id: T::AuctionId) -> DispatchResult {
+            let terminal = ensure_signed(origin)?;
+            let mut slot = <WonSlots<T>>::get(slot_id).ok_or(Error::<T>::SlotNotExist)?;
+            ensure!(slot.slot_origin == terminal, Error::<T>::PermissionError);
+
+            // The approval holds until the slot changes hands.
+            slot.approved = true;
+            <WonSlots<T>>::insert(slot_id, slot);
+            Self::deposit_event(RawEvent::ResaleApproved(slot_id, terminal));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn list_slot(origin, slot_id: T::AuctionId, price: BalanceOf<T>) -> DispatchResult {
+            let holder = ensure_signed(origin)?;
+            let mut slot = Self::ensure_can_resell(slot_id, &holder)?;
+            ensure!(!price.is_zero(), Error::<T>::InvalidBidPrice);
+
+            slot.price = Some(price);
+            <WonSlots<T>>::insert(slot_id, slot);
+            Self::deposit_event(RawEvent::SlotListed(slot_id, holder, price));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn relist_slot(
+            origin,
+            slot_id: T::AuctionId,
+            start: T::BlockNumber,
+            end: T::BlockNumber,
+        ) -> DispatchResult {
+            let holder = ensure_signed(origin)?;
+            let mut slot = Self::ensure_can_resell(slot_id, &holder)?;
+            Self::ensure_schedule(start, Some(end))?;
+            let deposit = Self::charge_creation(&holder)?;
+
+            // The holder creates the resale auction, so the winning bid is paid out to the holder
+            // rather than the slot's original seller.
+            let (auction_id, auction) = <Module<T>>::new_auction(
+                holder.clone(),
+                slot.slot_origin.clone(),
+                slot.core,
+                start,
+                Some(end),
+            );
+            if !deposit.is_zero() {
+                <AuctionDeposits<T>>::insert(auction_id, deposit);
+            }
+            <ResaleOf<T>>::insert(auction_id, slot_id);
+            slot.resale = Some(auction_id);
+            <WonSlots<T>>::insert(slot_id, slot);
+
+            Self::deposit_event(RawEvent::AuctionCreated(
+                auction_id,
+                auction.creator,
+                auction.slot_origin,
+                auction.start,
+                end,
+                auction.core.timestamp,
+                auction.core.cargo.0,
+                auction.core.cargo.1,
+            ));
+            Self::deposit_event(RawEvent::SlotRelisted(slot_id, holder, auction_id));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn unlist_slot(origin, slot_id: T::AuctionId) -> DispatchResult {
+            let holder = ensure_signed(origin)?;
+            let mut slot = <WonSlots<T>>::get(slot_id).ok_or(Error::<T>::SlotNotExist)?;
+            ensure!(slot.holder == holder, Error::<T>::PermissionError);
+
+            if let Some(auction_id) = slot.resale {
+                // Removing the resale auction refunds its bids and unlists the slot.
+                <Module<T>>::remove_auction(auction_id, holder)?;
+                Self::deposit_event(RawEvent::AuctionDeleted(auction_id));
+            } else {
+                ensure!(slot.price.take().is_some(), Error::<T>::SlotNotListed);
+                <WonSlots<T>>::insert(slot_id, slot);
+                Self::deposit_event(RawEvent::SlotUnlisted(slot_id));
+            }
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn buy_slot(origin, slot_id: T::AuctionId, price: BalanceOf<T>) -> DispatchResult {
+            let buyer = ensure_signed(origin)?;
+            let mut slot = <WonSlots<T>>::get(slot_id).ok_or(Error::<T>::SlotNotExist)?;
+            // The price is given by the buyer as well, so a holder can't raise it in the meantime.
+            ensure!(slot.price == Some(price), Error::<T>::SlotNotListed);
+            ensure!(slot.holder != buyer, Error::<T>::PermissionError);
+            Self::ensure_before_cutoff(&slot)?;
+
+            let seller = slot.holder.clone();
+            Self::pay_resale(slot_id, &buyer, &seller, &slot.slot_origin, price, KeepAlive)?;
+            Self::transfer_slot(slot_id, &mut slot, buyer, price);
+            <WonSlots<T>>::insert(slot_id, slot);
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn clear_slot(origin, slot_id: T::AuctionId) -> DispatchResult {
+            ensure_signed(origin)?;
+            let slot = <WonSlots<T>>::get(slot_id).ok_or(Error::<T>::SlotNotExist)?;
+            ensure!(Self::slot_time(&slot) <= T::UnixTime::now().as_secs(), Error::<T>::SlotNotExpired);
+            ensure!(slot.resale.is_none(), Error::<T>::SlotListed);
+
+            <WonSlots<T>>::remove(slot_id);
+            <SlotHistory<T>>::remove(slot_id);
+            <Timeline<T>>::remove(&slot.slot_origin, slot_id);
+            Self::deposit_event(RawEvent::SlotCleared(slot_id));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn set_terminal_capacity(origin, capacity: Option<CapacityCond<T>>) -> DispatchResult {
+            let terminal = ensure_signed(origin)?;
+            // Slots the terminal already has on offer or sold stay, even if they exceed the new
+            // capacity.
+            match &capacity {
+                Some(capacity) => {
+                    ensure!(
+                        capacity.max_vessels > 0 && capacity.slot_duration > 0,
+                        Error::<T>::InvalidCapacity
+                    );
+                    <Capacities<T>>::insert(&terminal, capacity);
+                }
+                None => <Capacities<T>>::remove(&terminal),
+            }
+            Self::deposit_event(RawEvent::TerminalCapacitySet(terminal, capacity));
+            Ok(())
+        }
+
+        /// Creates every auction of `auctions`, or none of them if one can't be created. The
+        /// auctions get consecutive ids, which `AuctionsCreated` reports as a range.
+        #[weight = (auctions.len() as Weight).saturating_mul(<Module<T>>::berth_check_weight())]
+        pub fn ext_new_auctions_batch(origin, auctions: Vec<NewAuctionCond<T>>) -> DispatchResult {
+            let initiator = ensure_signed(origin)?;
+            Self::ensure_batch_length(auctions.len())?;
+            for auction in &auctions {
+                Self::ensure_schedule(auction.start, Some(auction.end))?;
+            }
+
+            // Berths and funds are checked as the auctions are created, as the earlier auctions
+            // of the batch take their share of them.
+            let first = Self::auctions_index();
+            let result = with_transaction(|| {
+                for auction in auctions {
+                    let created = Self::create_auction(
+                        initiator.clone(),
+                        auction.terminal,
+                        auction.core,
+                        auction.start,
+                        auction.end,
+                    );
+                    if let Err(error) = created {
+                        return TransactionOutcome::Rollback(Err(error));
+                    }
+                }
+                TransactionOutcome::Commit(Ok(()))
+            });
+            result?;
+
+            let last = Self::auctions_index().saturating_sub(One::one());
+            Self::deposit_event(RawEvent::AuctionsCreated(initiator, first, last));
+            Ok(())
+        }
+
+        /// Applies every update of `updates` in order, or none of them if one fails.
+        #[weight = (updates.len() as Weight).saturating_mul(<Module<T>>::berth_check_weight())]
+        pub fn ext_update_auctions_batch(origin, updates: Vec<UpdateCond<T>>) -> DispatchResult {
+            let initiator = ensure_signed(origin)?;
+            Self::ensure_batch_length(updates.len())?;
+            for update in &updates {
+                let auction = <Auctions<T>>::get(update.id).ok_or(Error::<T>::AuctionNotExist)?;
+                ensure!(auction.creator == initiator, <Error<T>>::PermissionError);
+            }
+
+            // An update may move an auction another update of the batch is checked against.
+            let ids = updates.iter().map(|update| update.id).collect::<Vec<_>>();
+            let result = with_transaction(|| {
+                for update in updates {
+                    let updated = Self::ensure_update_fits(
+                        update.id,
+                        update.timestamp,
+                        update.num_teu,
+                        update.start,
+                        update.end,
+                    )
+                    .and_then(|()| {
+                        Self::update_auction(
+                            update.id,
+                            initiator.clone(),
+                            AuctionUpdateInfo {
+                                timestamp: update.timestamp,
+                                num_con: update.num_con,
+                                num_teu: update.num_teu,
+                            },
+                            update.start,
+                            update.end,
+                        )
+                    });
+                    if let Err(error) = updated {
+                        return TransactionOutcome::Rollback(Err(error));
+                    }
+                }
+                TransactionOutcome::Commit(Ok(()))
+            });
+            result?;
+
+            Self::deposit_event(RawEvent::AuctionsUpdated(initiator, ids));
+            Ok(())
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            migration::migrate::<T>()
+        }
+
+        fn integrity_test() {
+            let shares = T::TerminalCommission::get().deconstruct() as u64
+                + T::PlatformFee::get().deconstruct() as u64
+                + T::BurnShare::get().deconstruct() as u64;
+            assert!(
+                shares <= Perbill::one().deconstruct() as u64,
+                "The commission, platform fee and burn share exceed the winning bid",
+            );
+        }
+
         fn on_initialize(now: T::BlockNumber) -> Weight {
             // Logging for the runtime, for testin purposes only
             frame_support::print("--ACTIVE-- Autioning Pallet.");
@@ -556,10 +1101,495 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Pays winning bid `amount` of auction `id` out of the reserve of `winner`. The terminal
+    /// receives its commission, the platform account its fee, the burn share is burned and the
+    /// creator receives the rest, `recipients` being the auction's (creator, terminal). The winner
+    /// of a resale auction pays the resale fee to the terminal and the rest to the seller instead.
+    /// Either every payout happens or none does, in which case what's left of the bid is
+    /// released.
+    pub fn settle_bid(
+        id: T::AuctionId,
+        winner: &T::AccountId,
+        recipients: &(T::AccountId, T::AccountId),
+        amount: BalanceOf<T>,
+    ) -> Result<(), Error<T>> {
+        let result = with_transaction(|| match Self::pay_out(id, winner, recipients, amount) {
+            Ok(()) => TransactionOutcome::Commit(Ok(())),
+            Err(error) => TransactionOutcome::Rollback(Err(error)),
+        });
+
+        if let Err(_) = result {
+            T::Currency::unreserve(winner, amount);
+        }
+        result
+    }
+
+    fn pay_out(
+        id: T::AuctionId,
+        winner: &T::AccountId,
+        recipients: &(T::AccountId, T::AccountId),
+        amount: BalanceOf<T>,
+    ) -> Result<(), Error<T>> {
+        // Just like `transfer_funds`, a reserve which has been pulled from stops the payout.
+        let overdraft = T::Currency::unreserve(winner, amount);
+        ensure!(overdraft.is_zero(), <Error<T>>::AmbitiousTransfer);
+
+        // The resale fee replaces the commission, fee and burn share of the original sale.
+        if let Some(slot_id) = <ResaleOf<T>>::get(id) {
+            let (seller, terminal) = recipients;
+            return Self::transfer_resale(slot_id, winner, seller, terminal, amount, AllowDeath);
+        }
+
+        let commission = T::TerminalCommission::get() * amount;
+        let platform_fee = T::PlatformFee::get() * amount;
+        let burn = T::BurnShare::get() * amount;
+        let creator_share = amount
+            .saturating_sub(commission)
+            .saturating_sub(platform_fee)
+            .saturating_sub(burn);
+
+        if !creator_share.is_zero() {
+            T::Currency::transfer(winner, &recipients.0, creator_share, AllowDeath)
+                .map_err(|_| <Error<T>>::TryTransfer)?;
+            Self::deposit_event(RawEvent::CreatorPaid(
+                id,
+                recipients.0.clone(),
+                creator_share,
+            ));
+        }
+        if !commission.is_zero() {
+            T::Currency::transfer(winner, &recipients.1, commission, AllowDeath)
+                .map_err(|_| <Error<T>>::TryTransfer)?;
+            Self::deposit_event(RawEvent::CommissionPaid(
+                id,
+                recipients.1.clone(),
+                commission,
+            ));
+        }
+        if !platform_fee.is_zero() {
+            let platform = T::PlatformAccount::get();
+            let fee = T::Currency::withdraw(
+                winner,
+                platform_fee,
+                WithdrawReason::Transfer.into(),
+                AllowDeath,
+            )
+            .map_err(|_| <Error<T>>::TryTransfer)?;
+            // A fee below the existential deposit can't open the platform account, such a fee is
+            // burned rather than failing the payout.
+            let deposited = T::Currency::deposit_creating(&platform, platform_fee);
+            if deposited.peek().is_zero() {
+                Self::deposit_event(RawEvent::BidBurned(id, winner.clone(), platform_fee));
+            } else {
+                Self::deposit_event(RawEvent::PlatformFeePaid(id, platform, platform_fee));
+            }
+            drop(fee.offset(deposited));
+        }
+        if !burn.is_zero() {
+            // The burn share is burned by dropping the negative imbalance.
+            let withdraw_result =
+                T::Currency::withdraw(winner, burn, WithdrawReason::Transfer.into(), AllowDeath);
+            if let Err(_) = withdraw_result {
+                return Err(<Error<T>>::TryTransfer);
+            }
+            Self::deposit_event(RawEvent::BidBurned(id, winner.clone(), burn));
+        }
+
+        Ok(())
+    }
+
+    /// Checks an auction's start and end against the minimum duration and maximum horizon
+    /// parameters.
+    pub fn ensure_schedule(
+        start: T::BlockNumber,
+        end: Option<T::BlockNumber>,
+    ) -> Result<(), Error<T>> {
+        let parameters = <Parameters<T>>::get();
+
+        if let Some(end) = end {
+            ensure!(
+                end.saturating_sub(start) >= parameters.min_duration,
+                <Error<T>>::AuctionTooShort
+            );
+        }
+        if !parameters.max_horizon.is_zero() {
+            let horizon =
+                <system::Module<T>>::block_number().saturating_add(parameters.max_horizon);
+            ensure!(start <= horizon, <Error<T>>::AuctionBeyondHorizon);
+        }
+
+        Ok(())
+    }
+
+    /// Ensures a batch call's length is within `MaxBatchLength`, and that it isn't empty.
+    fn ensure_batch_length(length: usize) -> Result<(), Error<T>> {
+        ensure!(
+            length > 0 && length <= T::MaxBatchLength::get() as usize,
+            <Error<T>>::InvalidBatchLength
+        );
+        Ok(())
+    }
+
+    /// Creates an auction of `creator` for a slot of `terminal`, once the slot fits the terminal's
+    /// berths and the creation deposit and fee are paid.
+    fn create_auction(
+        creator: T::AccountId,
+        terminal: T::AccountId,
+        core: AuctionCoreInfo<T::GeneralInformationContainer>,
+        start: T::BlockNumber,
+        end: T::BlockNumber,
+    ) -> Result<(T::AuctionId, InfoCond<T>), Error<T>> {
+        let window = Self::berth_window(&terminal, &core);
+        Self::ensure_berth_available(&terminal, &window, None)?;
+        let deposit = Self::charge_creation(&creator)?;
+
+        let (auction_id, auction) = Self::new_auction(creator, terminal, core, start, Some(end));
+        if !deposit.is_zero() {
+            <AuctionDeposits<T>>::insert(auction_id, deposit);
+        }
+        <Timeline<T>>::insert(&auction.slot_origin, auction_id, window);
+        Ok((auction_id, auction))
+    }
+
+    /// Ensures the updated schedule of auction `id` passes `ensure_schedule` and its updated slot
+    /// still fits the terminal's berths.
+    fn ensure_update_fits(
+        id: T::AuctionId,
+        timestamp: Option<T::GeneralInformationContainer>,
+        num_teu: Option<T::GeneralInformationContainer>,
+        start: Option<T::BlockNumber>,
+        end: Option<T::BlockNumber>,
+    ) -> Result<(), Error<T>> {
+        if start.is_some() || end.is_some() {
+            let auction = <Auctions<T>>::get(id).ok_or(Error::<T>::AuctionNotExist)?;
+            Self::ensure_schedule(start.unwrap_or(auction.start), end.or(auction.end))?;
+        }
+        if (timestamp.is_some() || num_teu.is_some()) && !<ResaleOf<T>>::contains_key(id) {
+            let auction = <Auctions<T>>::get(id).ok_or(Error::<T>::AuctionNotExist)?;
+            let core = AuctionCoreInfo {
+                timestamp: timestamp.unwrap_or(auction.core.timestamp),
+                cargo: (
+                    auction.core.cargo.0,
+                    num_teu.unwrap_or(auction.core.cargo.1),
+                ),
+            };
+            let window = Self::berth_window(&auction.slot_origin, &core);
+            Self::ensure_berth_available(&auction.slot_origin, &window, Some(id))?;
+        }
+        Ok(())
+    }
+
+    /// Reserves the creation deposit and burns the creation fee of a new auction. Returns the
+    /// reserved deposit.
+    fn charge_creation(creator: &T::AccountId) -> Result<BalanceOf<T>, Error<T>> {
+        let parameters = <Parameters<T>>::get();
+
+        if !parameters.creation_deposit.is_zero() {
+            Self::reserve_funds(creator, parameters.creation_deposit)?;
+        }
+        if !parameters.creation_fee.is_zero() {
+            // The fee is burned by dropping the negative imbalance.
+            let withdraw_result = T::Currency::withdraw(
+                creator,
+                parameters.creation_fee,
+                WithdrawReason::Fee.into(),
+                KeepAlive,
+            );
+
+            if let Err(_) = withdraw_result {
+                T::Currency::unreserve(creator, parameters.creation_deposit);
+                return Err(<Error<T>>::FeeNotPaid);
+            }
+        }
+
+        Ok(parameters.creation_deposit)
+    }
+
+    /// Releases the creation deposit of auction `id` back to its creator.
+    fn release_deposit(id: T::AuctionId, creator: &T::AccountId) {
+        if let Some(deposit) = <AuctionDeposits<T>>::take(id) {
+            if let Err(_) = Self::unreserve_funds(creator, deposit) {
+                sp_runtime::print("ERROR -- RELEASE_DEPOSIT>>UNRESERVE_FUNDS ");
+            }
+        }
+    }
+
+    /// Returns the new end of an auction if a bid placed on block `now` falls within the soft-close
+    /// window.
+    fn soft_close_end(now: T::BlockNumber, end: Option<T::BlockNumber>) -> Option<T::BlockNumber> {
+        let window = <Parameters<T>>::get().soft_close_window;
+        match end {
+            Some(end) if !window.is_zero() && now.saturating_add(window) > end => {
+                Some(now.saturating_add(window))
+            }
+            _ => None,
+        }
+    }
+
+    /// A timestamp in seconds, timestamps which don't fit lie in the far future.
+    fn seconds(timestamp: T::GeneralInformationContainer) -> u64 {
+        TryInto::<u64>::try_into(timestamp).unwrap_or_else(|_| u64::max_value())
+    }
+
+    /// The timestamp of a won slot in seconds.
+    fn slot_time(slot: &SlotCond<T>) -> u64 {
+        Self::seconds(slot.core.timestamp)
+    }
+
+    /// The berth window of a slot at `terminal`, which lasts the terminal's slot duration from the
+    /// slot's timestamp on.
+    fn berth_window(
+        terminal: &T::AccountId,
+        core: &AuctionCoreInfo<T::GeneralInformationContainer>,
+    ) -> BerthWindow<T::GeneralInformationContainer> {
+        let duration = <Capacities<T>>::get(terminal)
+            .map_or_else(T::DefaultSlotDuration::get, |capacity| {
+                capacity.slot_duration
+            });
+        let start = Self::seconds(core.timestamp);
+        BerthWindow {
+            start,
+            end: start.saturating_add(duration),
+            teu: core.cargo.1,
+        }
+    }
+
+    /// Adds the slot offered in auction `id` to the timeline of `terminal`.
+    fn index_slot(
+        id: T::AuctionId,
+        terminal: &T::AccountId,
+        core: &AuctionCoreInfo<T::GeneralInformationContainer>,
+    ) {
+        <Timeline<T>>::insert(terminal, id, Self::berth_window(terminal, core));
+    }
+
+    /// Weight of a call offering or moving a single slot. Checking the slot reads every window of
+    /// its terminal's timeline, pruning those which have passed.
+    pub fn berth_check_weight() -> Weight {
+        let windows = T::MaxTerminalWindows::get() as Weight;
+        T::DbWeight::get()
+            .reads_writes(windows.saturating_add(2), windows.saturating_add(1))
+            .saturating_add(10_000)
+    }
+
+    /// Ensures a slot occupying `window` fits within the capacity of `terminal` next to the
+    /// terminal's other slots, leaving out the slot of auction `updated` which is being replaced.
+    /// Windows which have passed are pruned from the terminal's timeline on the way, of those which
+    /// haven't the timeline holds at most `MaxTerminalWindows` along with the slot.
+    pub fn ensure_berth_available(
+        terminal: &T::AccountId,
+        window: &BerthWindow<T::GeneralInformationContainer>,
+        updated: Option<T::AuctionId>,
+    ) -> Result<(), Error<T>> {
+        let now = T::UnixTime::now().as_secs();
+        let (passed, windows): (Vec<_>, Vec<_>) = <Timeline<T>>::iter_prefix(terminal)
+            .filter(|(id, _)| Some(*id) != updated)
+            .partition(|(_, other)| other.end <= now);
+        for (id, _) in passed {
+            <Timeline<T>>::remove(terminal, id);
+        }
+        ensure!(
+            windows.len() < T::MaxTerminalWindows::get() as usize,
+            <Error<T>>::TimelineFull
+        );
+
+        let capacity = match <Capacities<T>>::get(terminal) {
+            Some(capacity) => capacity,
+            None => return Ok(()),
+        };
+        let overlapping = windows
+            .into_iter()
+            .map(|(_, other)| other)
+            .filter(|other| other.start < window.end && window.start < other.end)
+            .collect::<Vec<_>>();
+
+        // The load only rises where a window starts, so checking the starts within the window
+        // covers every point of it.
+        let starts = overlapping
+            .iter()
+            .map(|other| other.start)
+            .filter(|start| *start > window.start)
+            .chain(sp_std::iter::once(window.start));
+        for point in starts {
+            let (vessels, teu) = overlapping
+                .iter()
+                .filter(|other| other.start <= point && point < other.end)
+                .fold((1u32, window.teu), |(vessels, teu), other| {
+                    (vessels.saturating_add(1), teu.saturating_add(other.teu))
+                });
+            ensure!(
+                vessels <= capacity.max_vessels && teu <= capacity.max_teu,
+                <Error<T>>::BerthConflict
+            );
+        }
+        Ok(())
+    }
+
+    /// Ensures the resale cutoff of `slot` hasn't passed.
+    fn ensure_before_cutoff(slot: &SlotCond<T>) -> Result<(), Error<T>> {
+        let cutoff = Self::slot_time(slot).saturating_sub(T::ResaleCutoff::get());
+        ensure!(
+            T::UnixTime::now().as_secs() < cutoff,
+            <Error<T>>::ResaleClosed
+        );
+        Ok(())
+    }
+
+    /// Ensures `holder` may list slot `slot_id` and returns the slot.
+    fn ensure_can_resell(
+        slot_id: T::AuctionId,
+        holder: &T::AccountId,
+    ) -> Result<SlotCond<T>, Error<T>> {
+        let slot = <WonSlots<T>>::get(slot_id).ok_or(<Error<T>>::SlotNotExist)?;
+        ensure!(slot.holder == *holder, <Error<T>>::PermissionError);
+        ensure!(
+            slot.price.is_none() && slot.resale.is_none(),
+            <Error<T>>::SlotListed
+        );
+        Self::ensure_before_cutoff(&slot)?;
+        if <ResaleApprovalRequired<T>>::get(&slot.slot_origin) {
+            ensure!(slot.approved, <Error<T>>::ResaleNotApproved);
+        }
+        Ok(slot)
+    }
+
+    /// Pays `price` for slot `slot_id` from `buyer`, the resale fee to the terminal and the rest
+    /// to `seller`. Either both transfers happen or neither does.
+    fn pay_resale(
+        slot_id: T::AuctionId,
+        buyer: &T::AccountId,
+        seller: &T::AccountId,
+        terminal: &T::AccountId,
+        price: BalanceOf<T>,
+        liveness: ExistenceRequirement,
+    ) -> Result<(), Error<T>> {
+        with_transaction(|| {
+            match Self::transfer_resale(slot_id, buyer, seller, terminal, price, liveness) {
+                Ok(()) => TransactionOutcome::Commit(Ok(())),
+                Err(error) => TransactionOutcome::Rollback(Err(error)),
+            }
+        })
+    }
+
+    fn transfer_resale(
+        slot_id: T::AuctionId,
+        buyer: &T::AccountId,
+        seller: &T::AccountId,
+        terminal: &T::AccountId,
+        price: BalanceOf<T>,
+        liveness: ExistenceRequirement,
+    ) -> Result<(), Error<T>> {
+        let fee = T::ResaleFee::get() * price;
+        T::Currency::transfer(buyer, seller, price.saturating_sub(fee), liveness)
+            .map_err(|_| <Error<T>>::TryTransfer)?;
+        if !fee.is_zero() {
+            T::Currency::transfer(buyer, terminal, fee, liveness)
+                .map_err(|_| <Error<T>>::TryTransfer)?;
+            Self::deposit_event(RawEvent::ResaleFeePaid(
+                slot_id,
+                seller.clone(),
+                terminal.clone(),
+                fee,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Hands `slot` over to `buyer`, who paid `price` for it. Any offer and approval of the
+    /// previous holder lapse. The caller stores the slot.
+    fn transfer_slot(
+        slot_id: T::AuctionId,
+        slot: &mut SlotCond<T>,
+        buyer: T::AccountId,
+        price: BalanceOf<T>,
+    ) {
+        let seller = sp_std::mem::replace(&mut slot.holder, buyer.clone());
+        slot.price = None;
+        slot.resale = None;
+        slot.approved = false;
+
+        let now = <system::Module<T>>::block_number();
+        <SlotHistory<T>>::mutate(slot_id, |history| history.push((buyer.clone(), price, now)));
+        T::Handler::on_slot_transferred(slot_id, seller.clone(), buyer.clone());
+        Self::deposit_event(RawEvent::SlotTransferred(slot_id, seller, buyer, price));
+    }
+
+    /// Withdraws the resale auction of slot `slot_id`, the slot stays with its holder.
+    fn unlist_resale(slot_id: T::AuctionId) {
+        <WonSlots<T>>::mutate(slot_id, |slot| {
+            if let Some(slot) = slot {
+                slot.resale = None;
+            }
+        });
+        Self::deposit_event(RawEvent::SlotUnlisted(slot_id));
+    }
+
+    /// Settles the slot sold in auction `id` once its winner has paid, the resale fee of a resold
+    /// slot having been paid along with the price.
+    fn settle_slot(
+        id: T::AuctionId,
+        auction: &InfoCond<T>,
+        winner: T::AccountId,
+        price: BalanceOf<T>,
+    ) {
+        match <ResaleOf<T>>::take(id) {
+            Some(slot_id) => {
+                if let Some(mut slot) = <WonSlots<T>>::get(slot_id) {
+                    Self::transfer_slot(slot_id, &mut slot, winner, price);
+                    <WonSlots<T>>::insert(slot_id, slot);
+                }
+            }
+            None => {
+                let now = <system::Module<T>>::block_number();
+                <WonSlots<T>>::insert(
+                    id,
+                    WonSlot {
+                        holder: winner.clone(),
+                        slot_origin: auction.slot_origin.clone(),
+                        core: auction.core,
+                        price: None,
+                        resale: None,
+                        approved: false,
+                    },
+                );
+                <SlotHistory<T>>::insert(id, vec![(winner.clone(), price, now)]);
+                Self::deposit_event(RawEvent::SlotWon(id, winner, price));
+            }
+        }
+    }
+
     ////////////////////////////////////////////
     /////////////////// API ////////////////////
     ////////////////////////////////////////////
 
+    /// Returns the current auction parameters, their balances converted for display.
+    pub fn auction_parameters() -> AuctionParameters<u64, T::BlockNumber> {
+        Self::display_parameters(<Parameters<T>>::get())
+    }
+
+    /// Returns the upper bounds of the auction parameters, the lower bound of every parameter is
+    /// zero. Their balances are converted for display.
+    pub fn auction_parameter_bounds() -> AuctionParameters<u64, T::BlockNumber> {
+        Self::display_parameters(T::MaximumParameters::get())
+    }
+
+    /// Returns the berth windows of the slots of `terminal` which overlap the period from `from` up
+    /// to `to`, along with the auctions the slots were first offered in, ordered by their start.
+    /// Windows which have passed are pruned as slots are offered, so past periods may come up
+    /// short.
+    pub fn terminal_occupancy(
+        terminal: T::AccountId,
+        from: T::GeneralInformationContainer,
+        to: T::GeneralInformationContainer,
+    ) -> Vec<(T::AuctionId, BerthWindow<T::GeneralInformationContainer>)> {
+        let (from, to) = (Self::seconds(from), Self::seconds(to));
+        let mut windows = <Timeline<T>>::iter_prefix(&terminal)
+            .filter(|(_, window)| window.start < to && from < window.end)
+            .collect::<Vec<_>>();
+        windows.sort_by_key(|(_, window)| window.start);
+        windows
+    }
+
     #[allow(dead_code)]
     pub fn auction_exists(id: T::AuctionId) -> bool {
         <Auctions<T>>::contains_key(id)
@@ -628,13 +1658,10 @@ impl<T: Trait> Module<T> {
             if let Some(innerbid) = _auction.bid {
                 // There's an active bid, pass it on to the converter.
 
-                // An converted bid with just the bidder's ID intact, this is standard. If the
-                // balance cannot be converted from 128 to 64, just show 0. This function only
-                // deals with the frontent API so nothing breaks, just missing information. DONT
-                // change this otherwise the blockchain will CRASH upon an RPC request of a
-                // unsigned 128 value. -- Unwrapping the value at this point is safe, since once
-                // you're in this statement, there's always a bid
-                let converted_bid = (innerbid.0.clone(), _auction.parsed_bid.unwrap());
+                // An converted bid with just the bidder's ID intact, this is standard. The balance
+                // is converted from 128 to 64 bits since serde can't deal with unsigned 128
+                // values, see `display_balance`.
+                let converted_bid = (innerbid.0.clone(), Self::display_balance(innerbid.1));
 
                 // Return formatted auction with our now compliant bidding information.
                 return Some(UIAuctionInfo {
@@ -671,7 +1698,8 @@ impl<T: Trait> Module<T> {
         let query = <Auctions<T>>::iter()
             .map(|x| {
                 if let Some(inner_bid) = x.1.bid {
-                    let converted_bid = Some((inner_bid.0.clone(), x.1.parsed_bid.unwrap()));
+                    let converted_bid =
+                        Some((inner_bid.0.clone(), Self::display_balance(inner_bid.1)));
 
                     return UIAuctionInfo {
                         slot_owner: x.1.creator,
@@ -716,7 +1744,8 @@ impl<T: Trait> Module<T> {
             })
             .map(|x| {
                 if let Some(inner_bid) = x.1.bid {
-                    let converted_bid = Some((inner_bid.0.clone(), x.1.parsed_bid.unwrap()));
+                    let converted_bid =
+                        Some((inner_bid.0.clone(), Self::display_balance(inner_bid.1)));
 
                     return UIAuctionInfo {
                         slot_owner: x.1.creator,
@@ -763,6 +1792,23 @@ impl<T: Trait> Module<T> {
         TryInto::<u64>::try_into(input).ok()
     }
 
+    /// Converts a balance for display through the RPC, balances which don't fit in a u64 are
+    /// shown as the max value of u64.
+    pub fn display_balance(input: BalanceOf<T>) -> u64 {
+        Self::balance_to_u64(input).unwrap_or_else(u64::max_value)
+    }
+
+    /// Converts the balances of `parameters` for display through the RPC, see `display_balance`.
+    pub fn display_parameters(parameters: ParamsCond<T>) -> AuctionParameters<u64, T::BlockNumber> {
+        AuctionParameters {
+            min_duration: parameters.min_duration,
+            max_horizon: parameters.max_horizon,
+            creation_deposit: Self::display_balance(parameters.creation_deposit),
+            creation_fee: Self::display_balance(parameters.creation_fee),
+            soft_close_window: parameters.soft_close_window,
+        }
+    }
+
     pub fn u64_to_balance_option(input: u64) -> Option<BalanceOf<T>> {
         input.try_into().ok()
     }
@@ -815,13 +1861,128 @@ impl<T: Trait> Module<T> {
     }
 
     fn _on_initialize(now: T::BlockNumber) {
-        for qbid in <QueuedBids<T>>::take(&now) {
-            let pq_result = Self::place_queued_bid(qbid);
+        for (auction_id, _) in <AuctionStartTime<T>>::drain_prefix(&now) {
+            // The auction starts this block, place the highest queued bid. Sorting is stable so
+            // on equal bids, the earliest queued bid wins.
+            let mut queue = <QueuedBids<T>>::take(&auction_id);
+            queue.sort_by(|a, b| b.bid.1.cmp(&a.bid.1));
+
+            let mut placed = false;
+            for qbid in queue {
+                if !placed {
+                    if let Ok(_) = Self::place_queued_bid(qbid.clone()) {
+                        placed = true;
+                        continue;
+                    }
+                    // Something went wrong placing the queued bid, throw an event + log.
+                    sp_runtime::print("ERROR -- _ON_INITIALIZE>>PLACE_QUEUED_BID ");
+                    Self::deposit_event(RawEvent::QueuedBidFailed(
+                        auction_id,
+                        qbid.bid.0.clone(),
+                        qbid.bid.1,
+                    ));
+                }
+                // Every queued bid that isn't placed is refunded.
+                if let Err(_) = Self::unreserve_funds(&qbid.bid.0, qbid.bid.1) {
+                    sp_runtime::print("ERROR -- _ON_INITIALIZE>>UNRESERVE_FUNDS ");
+                }
+            }
+        }
+    }
+
+    /// Refunds and removes all queued bids of auction `id`.
+    fn refund_queued_bids(id: T::AuctionId) {
+        for qbid in <QueuedBids<T>>::take(&id) {
+            if let Err(_) = Self::unreserve_funds(&qbid.bid.0, qbid.bid.1) {
+                sp_runtime::print("ERROR -- REFUND_QUEUED_BIDS>>UNRESERVE_FUNDS ");
+            }
+        }
+    }
+
+    /// Stores the runner-ups of auction `id`, refunding the bids beyond `MaxRunnerUps`.
+    fn store_runner_ups(id: T::AuctionId, mut runner_ups: Vec<(T::AccountId, BalanceOf<T>)>) {
+        let max = T::MaxRunnerUps::get() as usize;
+        if runner_ups.len() > max {
+            for (bidder, value) in runner_ups.split_off(max) {
+                if let Err(_) = Self::unreserve_funds(&bidder, value) {
+                    sp_runtime::print("ERROR -- STORE_RUNNER_UPS>>UNRESERVE_FUNDS ");
+                }
+            }
+        }
+
+        if runner_ups.is_empty() {
+            <RunnerUps<T>>::remove(id);
+        } else {
+            <RunnerUps<T>>::insert(id, runner_ups);
+        }
+    }
+
+    /// Releases the bids of the remaining runner-ups of auction `id`.
+    fn release_runner_ups(id: T::AuctionId, runner_ups: Vec<(T::AccountId, BalanceOf<T>)>) {
+        for (bidder, value) in runner_ups {
+            if let Err(_) = Self::unreserve_funds(&bidder, value) {
+                sp_runtime::print("ERROR -- RELEASE_RUNNER_UPS>>UNRESERVE_FUNDS ");
+            }
+            Self::deposit_event(RawEvent::RunnerUpReleased(id, bidder, value));
+        }
+    }
+
+    /// Takes the highest runner-up off the ladder to take the place of a defaulted winner.
+    fn promote_runner_up(
+        id: T::AuctionId,
+        runner_ups: &mut Vec<(T::AccountId, BalanceOf<T>)>,
+    ) -> Option<(T::AccountId, BalanceOf<T>)> {
+        if runner_ups.is_empty() {
+            return None;
+        }
+        let (bidder, value) = runner_ups.remove(0);
+        Self::deposit_event(RawEvent::RunnerUpPromoted(id, bidder.clone(), value));
+        Some((bidder, value))
+    }
 
-            if let Err(_) = pq_result {
-                // Something went wrong placing the queued bid, for now throw an event + log
-                sp_runtime::print("ERROR -- _ON_INITIALIZE>>PLACE_QUEUED_BID ");
+    /// Settles ended auction `id`, offering the slot to its bidders from the highest bid down
+    /// until one of them pays. With a confirmation period, each bidder first has to confirm the
+    /// win, unless `confirmed` already. An auction which runs out of bidders ends without a
+    /// winner.
+    fn settle(
+        id: T::AuctionId,
+        mut auction: InfoCond<T>,
+        mut runner_ups: Vec<(T::AccountId, BalanceOf<T>)>,
+        mut confirmed: bool,
+    ) {
+        let recipients = (auction.creator.clone(), auction.slot_origin.clone());
+        let period = T::ConfirmationPeriod::get();
+
+        while let Some((winner, price)) = auction.bid.clone() {
+            if !confirmed && !period.is_zero() {
+                let deadline = <system::Module<T>>::block_number().saturating_add(period);
+                auction.end = Some(deadline);
+                <PendingSettlements<T>>::insert(id, auction);
+                <SettlementDeadline<T>>::insert(&deadline, id, true);
+                if !runner_ups.is_empty() {
+                    <RunnerUps<T>>::insert(id, runner_ups);
+                }
+                Self::deposit_event(RawEvent::ConfirmationRequested(id, winner, price, deadline));
+                return;
+            }
+
+            // The handler can still look up whether the auction resells a won slot.
+            if T::Handler::on_auction_ended(id, recipients.clone(), auction.bid.clone()) {
+                Self::settle_slot(id, &auction, winner, price);
+                Self::release_runner_ups(id, runner_ups);
+                return;
             }
+            // The handler released what was left of the defaulted bid.
+            Self::deposit_event(RawEvent::WinnerDefaulted(id, winner, price));
+            auction.bid = Self::promote_runner_up(id, &mut runner_ups);
+            confirmed = false;
+        }
+
+        T::Handler::on_auction_ended(id, recipients, None);
+        if let Some(slot_id) = <ResaleOf<T>>::take(id) {
+            Self::unlist_resale(slot_id);
+        } else {
+            <Timeline<T>>::remove(&auction.slot_origin, id);
         }
     }
 
@@ -829,11 +1990,26 @@ impl<T: Trait> Module<T> {
         for (auction_id, _) in <AuctionEndTime<T>>::drain_prefix(&now) {
             // Drain_prefix removes all keys under the specified blocknumber
             if let Some(auction) = <Auctions<T>>::take(&auction_id) {
-                T::Handler::on_auction_ended(
-                    auction_id,
-                    (auction.creator, auction.slot_origin),
-                    auction.bid.clone(),
-                );
+                // An auction which ends before it even started could still hold queued bids.
+                <AuctionStartTime<T>>::remove(&auction.start, auction_id);
+                Self::refund_queued_bids(auction_id);
+                Self::release_deposit(auction_id, &auction.creator);
+                Self::settle(auction_id, auction, <RunnerUps<T>>::take(auction_id), false);
+            }
+        }
+
+        for (auction_id, _) in <SettlementDeadline<T>>::drain_prefix(&now) {
+            if let Some(mut auction) = <PendingSettlements<T>>::take(&auction_id) {
+                // The winner didn't confirm in time, the next runner-up gets the chance to.
+                if let Some((winner, price)) = auction.bid.take() {
+                    if let Err(_) = Self::unreserve_funds(&winner, price) {
+                        sp_runtime::print("ERROR -- _ON_FINALIZE>>UNRESERVE_FUNDS ");
+                    }
+                    Self::deposit_event(RawEvent::ConfirmationMissed(auction_id, winner, price));
+                }
+                let mut runner_ups = <RunnerUps<T>>::take(auction_id);
+                auction.bid = Self::promote_runner_up(auction_id, &mut runner_ups);
+                Self::settle(auction_id, auction, runner_ups, false);
             }
         }
     }
@@ -881,6 +2057,30 @@ impl<T: Trait> Auction<T::AccountId, T::BlockNumber, T::GeneralInformationContai
         if let Some(_) = &auction.bid {
             return Err(<Error<T>>::CannotUpdateActiveAuction);
         }
+        // A resale auction sells an existing slot, only its schedule can change.
+        if <ResaleOf<T>>::contains_key(id) {
+            ensure!(
+                core_info.timestamp.is_none()
+                    && core_info.num_con.is_none()
+                    && core_info.num_teu.is_none(),
+                <Error<T>>::ResaleSlotImmutable
+            );
+        }
+
+        // All checks happen before the first write, a rejected update leaves storage untouched.
+        if let Some(new_start) = start {
+            // User wants to postpone the auction's start block.
+            // First ensure that the auction hasn't already
+            ensure!(
+                auction.start > <system::Module<T>>::block_number(),
+                <Error<T>>::AuctionAlreadyLive
+            );
+            // Ensure that propsed start hasn't passed already.
+            ensure!(
+                new_start > <system::Module<T>>::block_number(),
+                <Error<T>>::AuctionAlreadyLive
+            );
+        }
 
         // Replace auction's end-time if specified by the origin
         if let Some(new_end) = end {
@@ -891,6 +2091,12 @@ impl<T: Trait> Auction<T::AccountId, T::BlockNumber, T::GeneralInformationContai
             auction.end = Some(new_end.clone());
         }
 
+        // A slot whose window passed may have been pruned from the timeline, it's indexed again
+        // once it moves. Resale auctions aren't indexed, the slot they resell is.
+        let moved = core_info.timestamp.is_some() || core_info.num_teu.is_some();
+        let indexed = !<ResaleOf<T>>::contains_key(id)
+            && (moved || <Timeline<T>>::contains_key(&auction.slot_origin, id));
+
         // Replace auction's data if specified by the origin
         // Option<timestamp> -> Option<num_con> -> Option<num_teu>
         if let Some(timestamp) = core_info.timestamp {
@@ -902,19 +2108,15 @@ impl<T: Trait> Auction<T::AccountId, T::BlockNumber, T::GeneralInformationContai
         if let Some(num_teu) = core_info.num_teu {
             auction.core.cargo.1 = num_teu;
         }
+        if indexed {
+            Self::index_slot(id, &auction.slot_origin, &auction.core);
+        }
 
         if let Some(new_start) = start {
-            // User wants to postpone the auction's start block.
-            // First ensure that the auction hasn't already
-            ensure!(
-                auction.start > <system::Module<T>>::block_number(),
-                <Error<T>>::AuctionAlreadyLive
-            );
-            // Ensure that propsed start hasn't passed already.
-            ensure!(
-                new_start > <system::Module<T>>::block_number(),
-                <Error<T>>::AuctionAlreadyLive
-            );
+            // Move the queued bids along with the auction's start.
+            if <AuctionStartTime<T>>::take(&auction.start, id).is_some() {
+                <AuctionStartTime<T>>::insert(&new_start, id, true);
+            }
 
             // Update auction's start
             auction.start = new_start;
@@ -929,6 +2131,91 @@ impl<T: Trait> Auction<T::AccountId, T::BlockNumber, T::GeneralInformationContai
         })
     }
 
+    /// Sets up `terminal` in the genesis block.
+    fn genesis_terminal(terminal: &GenesisTerminal<T::AccountId, T::GeneralInformationContainer>) {
+        if let Some(capacity) = &terminal.capacity {
+            assert!(
+                capacity.max_vessels > 0 && capacity.slot_duration > 0,
+                "Terminal capacity needs at least one vessel and a slot duration",
+            );
+            <Capacities<T>>::insert(&terminal.account, capacity);
+        }
+        if terminal.resale_approval_required {
+            <ResaleApprovalRequired<T>>::insert(&terminal.account, true);
+        }
+    }
+
+    /// Sets up an auction in the genesis block and places or queues its bids.
+    fn genesis_auction(
+        genesis: &GenesisAuction<
+            T::AccountId,
+            BalanceOf<T>,
+            T::BlockNumber,
+            T::GeneralInformationContainer,
+        >,
+    ) {
+        assert!(
+            genesis.end > genesis.start,
+            "Ending block has to be greater than the starting block",
+        );
+        assert!(
+            genesis.creator != genesis.terminal,
+            "Barge operator cannot be the terminal",
+        );
+        let window = Self::berth_window(&genesis.terminal, &genesis.core);
+        assert!(
+            Self::ensure_berth_available(&genesis.terminal, &window, None).is_ok(),
+            "Genesis auction exceeds the berth capacity or the timeline of its terminal",
+        );
+
+        let (id, mut auction) = Self::new_auction(
+            genesis.creator.clone(),
+            genesis.terminal.clone(),
+            genesis.core,
+            genesis.start,
+            Some(genesis.end),
+        );
+        <Timeline<T>>::insert(&auction.slot_origin, id, window);
+
+        let mut bids = genesis.bids.clone();
+        for (position, (bidder, value)) in bids.iter().enumerate() {
+            assert!(
+                !value.is_zero(),
+                "Genesis bids have to be greater than zero"
+            );
+            assert!(
+                bids[..position].iter().all(|(other, _)| other != bidder),
+                "Genesis auctions take a single bid per bidder",
+            );
+            assert!(
+                Self::reserve_funds(bidder, *value).is_ok(),
+                "Genesis bidders need the free balance for their bids",
+            );
+        }
+        if bids.is_empty() {
+            return;
+        }
+
+        if genesis.start.is_zero() {
+            // The highest bid leads, on equal bids the one listed first.
+            bids.sort_by(|a, b| b.1.cmp(&a.1));
+            let mut bids = bids.into_iter();
+            auction.bid = bids.next();
+            <Auctions<T>>::insert(id, auction);
+            Self::store_runner_ups(id, bids.collect());
+        } else {
+            let queue = bids
+                .into_iter()
+                .map(|bid| QueuedBid {
+                    bid,
+                    auction_id: id,
+                })
+                .collect::<Vec<_>>();
+            <QueuedBids<T>>::insert(id, queue);
+            <AuctionStartTime<T>>::insert(&genesis.start, id, true);
+        }
+    }
+
     fn new_auction(
         barge: T::AccountId,
         terminal: T::AccountId,
@@ -940,7 +2227,6 @@ impl<T: Trait> Auction<T::AccountId, T::BlockNumber, T::GeneralInformationContai
             creator: barge,
             slot_origin: terminal,
             bid: None,
-            parsed_bid: None,
             core: core_info,
             start,
             end,
@@ -970,10 +2256,29 @@ impl<T: Trait> Auction<T::AccountId, T::BlockNumber, T::GeneralInformationContai
             if let Some(end_block) = auction.end {
                 <AuctionEndTime<T>>::remove(&end_block, id);
             }
+            <AuctionStartTime<T>>::remove(&auction.start, id);
+            // The auction won't settle, release the leading bid along with the queued ones.
+            if let Some((bidder, value)) = &auction.bid {
+                if let Err(_) = Self::unreserve_funds(bidder, *value) {
+                    sp_runtime::print("ERROR -- REMOVE_AUCTION>>UNRESERVE_FUNDS ");
+                }
+            }
+            Self::refund_queued_bids(id);
+            Self::release_runner_ups(id, <RunnerUps<T>>::take(id));
+            Self::release_deposit(id, &auction.creator);
+            if let Some(slot_id) = <ResaleOf<T>>::take(id) {
+                Self::unlist_resale(slot_id);
+            } else {
+                <Timeline<T>>::remove(&auction.slot_origin, id);
+            }
         } else {
             _auction_inner = None;
         }
 
         Ok(_auction_inner)
     }
+
+    fn won_slot_time(id: Self::AuctionId) -> Option<u64> {
+        <WonSlots<T>>::get(id).map(|slot| Self::slot_time(&slot))
+    }
 }
@@ -0,0 +1,139 @@
+// Storage migrations for the auctioning pallet. Every storage layout change bumps `Releases` and
+// adds a step to `migrate`, steps are applied in order until the stored version is the latest.
+
+use crate::*;
+use frame_support::{
+    migration::StorageKeyIterator, traits::Get, StorageDoubleMap, StorageMap, StorageValue,
+    Twox64Concat,
+};
+use sp_std::cell::Cell;
+
+/// Name of this pallet's storage prefix, as declared in `decl_storage!`.
+pub const MODULE_PREFIX: &[u8] = b"AuctionModule";
+
+/// Auction information as stored in `Releases::V0`, this contains the `parsed_bid` field which has
+/// since been dropped.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug, Encode, Decode)]
+pub struct AuctionInfoV0<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+    pub creator: AccountId,
+    pub slot_origin: AccountId,
+    pub bid: Option<(AccountId, Balance)>,
+    pub parsed_bid: Option<u64>,
+    pub core: AuctionCoreInfo<GeneralInformationContainer>,
+    pub start: BlockNumber,
+    pub end: Option<BlockNumber>,
+}
+
+impl<AccountId, Balance, BlockNumber, GeneralInformationContainer>
+    AuctionInfoV0<AccountId, Balance, BlockNumber, GeneralInformationContainer>
+{
+    /// Converts the old auction information into the `Releases::V1` format.
+    pub fn upgrade(
+        self,
+    ) -> AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+        AuctionInfo {
+            creator: self.creator,
+            slot_origin: self.slot_origin,
+            bid: self.bid,
+            core: self.core,
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+/// Applies every migration step between the stored version and the latest version. Returns the
+/// weight consumed by the applied steps.
+pub fn migrate<T: Trait>() -> Weight {
+    let mut weight: Weight = T::DbWeight::get().reads(1);
+
+    if StorageVersion::get() == Releases::V0 {
+        weight = weight.saturating_add(migrate_v0_to_v1::<T>());
+        StorageVersion::put(Releases::V1);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+    if StorageVersion::get() == Releases::V1 {
+        weight = weight.saturating_add(migrate_v1_to_v2::<T>());
+        StorageVersion::put(Releases::V2);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+
+    weight
+}
+
+/// Migrates storage from `Releases::V0` to `Releases::V1`.
+///
+/// * `Auctions` loses the `parsed_bid` field.
+/// * `QueuedBids` is rekeyed from the block number the bid is placed on to the auction it's placed
+///   on, the block number moves to the `AuctionStartTime` index. Queued bids of auctions which no
+///   longer exist are refunded.
+pub fn migrate_v0_to_v1<T: Trait>() -> Weight {
+    // `translate` only accepts an `Fn`, keep count of the translated auctions through a cell.
+    let translated = Cell::new(0 as Weight);
+    <Auctions<T>>::translate::<
+        AuctionInfoV0<T::AccountId, BalanceOf<T>, T::BlockNumber, T::GeneralInformationContainer>,
+        _,
+    >(|_, auction| {
+        translated.set(translated.get() + 1);
+        Some(auction.upgrade())
+    });
+
+    let mut reads: Weight = translated.get();
+    let mut writes: Weight = translated.get();
+
+    // Both the old and the new map live under the same prefix, drain the old entries completely
+    // before writing the new ones.
+    let old_queue = StorageKeyIterator::<
+        T::BlockNumber,
+        QueuedBid<T::AccountId, BalanceOf<T>, T::AuctionId>,
+        Twox64Concat,
+    >::new(MODULE_PREFIX, b"QueuedBids")
+    .drain()
+    .collect::<Vec<_>>();
+
+    for (block_number, qbid) in old_queue {
+        reads += 2;
+        writes += 1;
+        if <Auctions<T>>::contains_key(qbid.auction_id) {
+            <QueuedBids<T>>::append(qbid.auction_id, qbid.clone());
+            <AuctionStartTime<T>>::insert(&block_number, qbid.auction_id, true);
+            writes += 2;
+        } else if let Err(_) = <Module<T>>::unreserve_funds(&qbid.bid.0, qbid.bid.1) {
+            sp_runtime::print("ERROR -- MIGRATION>>V0_TO_V1>>UNRESERVE_FUNDS ");
+        }
+    }
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+/// Migrates storage from `Releases::V1` to `Releases::V2`.
+///
+/// * `Timeline` indexes the berth windows of the auctions, the auctions awaiting confirmation and
+///   the won slots. Resale auctions are left out, the slot they resell is indexed already. No
+///   terminal has declared a capacity yet, so every window lasts the default slot duration.
+pub fn migrate_v1_to_v2<T: Trait>() -> Weight {
+    let mut reads: Weight = 0;
+    let mut writes: Weight = 0;
+
+    for (id, auction) in <Auctions<T>>::iter() {
+        reads += 2;
+        if !<ResaleOf<T>>::contains_key(id) {
+            <Module<T>>::index_slot(id, &auction.slot_origin, &auction.core);
+            writes += 1;
+        }
+    }
+    for (id, auction) in <PendingSettlements<T>>::iter() {
+        reads += 2;
+        if !<ResaleOf<T>>::contains_key(id) {
+            <Module<T>>::index_slot(id, &auction.slot_origin, &auction.core);
+            writes += 1;
+        }
+    }
+    for (id, slot) in <WonSlots<T>>::iter() {
+        reads += 1;
+        writes += 1;
+        <Module<T>>::index_slot(id, &slot.slot_origin, &slot.core);
+    }
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
@@ -4,14 +4,15 @@ use crate::*;
 use auction_traits::auction::*;
 use frame_support::{
     assert_err, assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
-    traits::{BalanceStatus, OnFinalize, OnInitialize},
+    traits::{BalanceStatus, LockableCurrency, OnFinalize, OnInitialize, OnRuntimeUpgrade},
+    weights::GetDispatchInfo,
 };
 use frame_system::{self as system};
 use pallet_balances::{self as balances};
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
+    traits::{BadOrigin, BlakeTwo256, IdentityLookup},
     Perbill,
 };
 
@@ -31,9 +32,22 @@ parameter_types! {
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
 
-    pub const ExistentialDeposit: u64 = 1;
     pub const TransferFee: u64 = 0;
     pub const CreationFee: u64 = 0;
+
+    pub const MaximumParameters: AuctionParameters<u64, u64> = AuctionParameters {
+        min_duration: 1000,
+        max_horizon: 10000,
+        creation_deposit: 5000,
+        creation_fee: 1000,
+        soft_close_window: 100,
+    };
+    pub const ResaleCutoff: u64 = 3600;
+    pub const ResaleFee: Perbill = Perbill::from_percent(10);
+    pub const PlatformAccount: AccountId = 9;
+    pub const DefaultSlotDuration: u64 = 3600;
+    pub const MaxBatchLength: u32 = 3;
+    pub const MaxTerminalWindows: u32 = 8;
 }
 
 pub type AccountId = u64;
@@ -98,20 +112,110 @@ impl AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> for Handler {
         _id: AuctionId,
         _recipients: (AccountId, AccountId),
         _winner: Option<(AccountId, Balance)>,
-    ) {
+    ) -> bool {
         if let Some(winner) = _winner {
             // Somebody has won, notify
-            AuctionModule::transfer_funds(&winner.0, &_recipients.0, winner.1);
+            let paid = AuctionModule::settle_bid(_id, &winner.0, &_recipients, winner.1).is_ok();
             AuctionModule::deposit_event(RawEvent::AuctionEndDecided(winner.0, _id));
             println!("The winner: {:?}", winner);
-        } else if let None = _winner {
+            paid
+        } else {
             // Nobody has won, notify
             AuctionModule::deposit_event(RawEvent::AuctionEndUndecided(_id));
             println!("There were no bids, nobody has won");
+            false
         }
     }
 }
 
+thread_local! {
+    static NOW: std::cell::Cell<u64> = std::cell::Cell::new(0);
+    static RUNNER_UPS: std::cell::Cell<u32> = std::cell::Cell::new(0);
+    static CONFIRMATION_PERIOD: std::cell::Cell<u64> = std::cell::Cell::new(0);
+    static SHARES: std::cell::Cell<(u32, u32, u32)> = std::cell::Cell::new((0, 0, 0));
+    static EXISTENTIAL_DEPOSIT: std::cell::Cell<u64> = std::cell::Cell::new(1);
+}
+
+// Clock of the mock runtime in seconds, set by the resale tests.
+pub struct MockTime;
+
+impl MockTime {
+    fn set(secs: u64) {
+        NOW.with(|now| now.set(secs));
+    }
+}
+
+impl UnixTime for MockTime {
+    fn now() -> core::time::Duration {
+        core::time::Duration::from_secs(NOW.with(|now| now.get()))
+    }
+}
+
+// Fallback settings of the mock runtime, both disabled unless a test sets them.
+pub struct MaxRunnerUps;
+
+impl Get<u32> for MaxRunnerUps {
+    fn get() -> u32 {
+        RUNNER_UPS.with(|max| max.get())
+    }
+}
+
+pub struct ConfirmationPeriod;
+
+impl Get<BlockNumber> for ConfirmationPeriod {
+    fn get() -> BlockNumber {
+        CONFIRMATION_PERIOD.with(|period| period.get())
+    }
+}
+
+fn set_fallback(max_runner_ups: u32, confirmation_period: BlockNumber) {
+    RUNNER_UPS.with(|max| max.set(max_runner_ups));
+    CONFIRMATION_PERIOD.with(|period| period.set(confirmation_period));
+}
+
+// Settlement split of the mock runtime in percent, the creator receives the whole bid unless a
+// test sets it.
+pub struct TerminalCommission;
+
+impl Get<Perbill> for TerminalCommission {
+    fn get() -> Perbill {
+        Perbill::from_percent(SHARES.with(|shares| shares.get().0))
+    }
+}
+
+pub struct PlatformFee;
+
+impl Get<Perbill> for PlatformFee {
+    fn get() -> Perbill {
+        Perbill::from_percent(SHARES.with(|shares| shares.get().1))
+    }
+}
+
+pub struct BurnShare;
+
+impl Get<Perbill> for BurnShare {
+    fn get() -> Perbill {
+        Perbill::from_percent(SHARES.with(|shares| shares.get().2))
+    }
+}
+
+fn set_shares(commission: u32, platform_fee: u32, burn: u32) {
+    SHARES.with(|shares| shares.set((commission, platform_fee, burn)));
+}
+
+// Existential deposit of the mock runtime, 1 unless a test raises it above the platform fee.
+pub struct ExistentialDeposit;
+
+impl Get<u64> for ExistentialDeposit {
+    fn get() -> u64 {
+        EXISTENTIAL_DEPOSIT.with(|deposit| deposit.get())
+    }
+}
+
+fn set_existential_deposit(deposit: u64) {
+    EXISTENTIAL_DEPOSIT.with(|existential| existential.set(deposit));
+}
+
 impl balances::Trait for AuctionTestRuntime {
     type Balance = Balance;
     type Event = AuctionTestEvent;
@@ -127,6 +231,20 @@ impl Trait for AuctionTestRuntime {
     type AuctionId = AccountId;
     type Handler = Handler;
     type GeneralInformationContainer = GeneralInformationContainer;
+    type ParametersOrigin = system::EnsureRoot<AccountId>;
+    type MaximumParameters = MaximumParameters;
+    type UnixTime = MockTime;
+    type ResaleCutoff = ResaleCutoff;
+    type ResaleFee = ResaleFee;
+    type MaxRunnerUps = MaxRunnerUps;
+    type ConfirmationPeriod = ConfirmationPeriod;
+    type TerminalCommission = TerminalCommission;
+    type PlatformFee = PlatformFee;
+    type PlatformAccount = PlatformAccount;
+    type BurnShare = BurnShare;
+    type DefaultSlotDuration = DefaultSlotDuration;
+    type MaxBatchLength = MaxBatchLength;
+    type MaxTerminalWindows = MaxTerminalWindows;
 }
 
 pub type System = system::Module<AuctionTestRuntime>;
@@ -159,18 +277,31 @@ fn run_to_block(n: u64) {
 
 pub struct EnvBuilder {
     balances: Vec<(u64, u64)>,
-    auctions: Vec<(
-        AccountId,
-        AccountId,
-        Vec<u64>, //AuctionCoreInfo<<tests::AuctionTestRuntime as Trait>::GeneralInformationContainer>,
-        BlockNumber,
-        BlockNumber,
-    )>,
+    auctions: Vec<GenesisAuction<AccountId, Balance, BlockNumber, GeneralInformationContainer>>,
+}
+
+// A genesis auction of `creator` (Barge) for a slot of `terminal`, without bids.
+fn genesis_auction(
+    creator: AccountId,
+    terminal: AccountId,
+    start: BlockNumber,
+    end: BlockNumber,
+) -> GenesisAuction<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+    GenesisAuction {
+        creator,
+        terminal,
+        core: AuctionCoreInfo {
+            timestamp: 1,
+            cargo: (2, 3),
+        },
+        start,
+        end,
+        bids: vec![],
+    }
 }
 
 impl EnvBuilder {
     pub fn new() -> Self {
-        // Config :: Vec<(T::AccountId | Barge, T::AccountId | Terminal, AuctionCoreInfo, T::BlockNumber | Start, T::BlockNumber | End)>;
         Self {
             balances: vec![
                 (0, 5000000), // Hamza
@@ -185,84 +316,14 @@ impl EnvBuilder {
             ],
             auctions: vec![
                 // Start these auctions from origin
-                (
-                    1,
-                    6,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    0,
-                    60000,
-                ),
-                (
-                    1,
-                    5,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    0,
-                    49,
-                ),
-                (
-                    2,
-                    6,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    0,
-                    51,
-                ),
-                (
-                    3,
-                    7,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    0,
-                    150,
-                ),
-                (
-                    4,
-                    8,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    0,
-                    250,
-                ),
+                genesis_auction(1, 6, 0, 60000),
+                genesis_auction(1, 5, 0, 49),
+                genesis_auction(2, 6, 0, 51),
+                genesis_auction(3, 7, 0, 150),
+                genesis_auction(4, 8, 0, 250),
                 // Start these auctions from block 100+ for the testing of the queues.
-                (
-                    1,
-                    5,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    100,
-                    500,
-                ),
-                (
-                    2,
-                    6,
-                    //AuctionCoreInfo {
-                    //    timestamp: 1594471764,
-                    //    cargo: (22, 22),
-                    //},
-                    vec![1u64, 2u64, 3u64],
-                    140,
-                    600,
-                ),
+                genesis_auction(1, 5, 100, 500),
+                genesis_auction(2, 6, 140, 600),
             ],
         }
     }
@@ -271,6 +332,14 @@ impl EnvBuilder {
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 pub fn new_test_ext() -> sp_io::TestExternalities {
+    new_genesis_ext(vec![], EnvBuilder::new().auctions)
+}
+
+// The mockup's balances along with the given terminals and auctions.
+pub fn new_genesis_ext(
+    terminals: Vec<GenesisTerminal<AccountId, GeneralInformationContainer>>,
+    auctions: Vec<GenesisAuction<AccountId, Balance, BlockNumber, GeneralInformationContainer>>,
+) -> sp_io::TestExternalities {
     let core = EnvBuilder::new();
     let mut t = system::GenesisConfig::default()
         .build_storage::<AuctionTestRuntime>()
@@ -281,7 +350,22 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     .assimilate_storage(&mut t)
     .unwrap();
     GenesisConfig::<AuctionTestRuntime> {
-        _auctions: core.auctions,
+        terminals,
+        auctions,
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    t.into()
+}
+
+// Genesis storage without any auctions, old-format storage snapshots are loaded on top of this.
+pub fn new_test_ext_empty() -> sp_io::TestExternalities {
+    let core = EnvBuilder::new();
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<AuctionTestRuntime>()
+        .unwrap();
+    balances::GenesisConfig::<AuctionTestRuntime> {
+        balances: core.balances,
     }
     .assimilate_storage(&mut t)
     .unwrap();
@@ -540,3 +624,1528 @@ fn new_test_ext_auction_query() {
         }
     })
 }
+
+///////////////////////////////////////////////////////
+// Migration related tests
+///////////////////////////////////////////////////////
+fn auction_v0(
+    creator: AccountId,
+    slot_origin: AccountId,
+    bid: Option<(AccountId, Balance)>,
+    start: BlockNumber,
+    end: BlockNumber,
+) -> migration::AuctionInfoV0<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+    migration::AuctionInfoV0 {
+        creator,
+        slot_origin,
+        parsed_bid: bid.map(|b| b.1),
+        bid,
+        core: AuctionCoreInfo {
+            timestamp: 1594471764,
+            cargo: (22, 22),
+        },
+        start,
+        end: Some(end),
+    }
+}
+
+// Loads a snapshot of `Releases::V0` storage, as found on a network running the original runtime.
+//
+// Auction 0 -- Live, leading bid of 5000 by 3.
+// Auction 1 -- Starts on block 100, queued bid of 4000 by 2.
+// Queued bid of 3000 by 4 on auction 2, which has already been removed.
+fn load_v0_snapshot() {
+    use frame_support::{migration::put_storage_value, Hashable};
+
+    let prefix = migration::MODULE_PREFIX;
+    let auctions = vec![
+        (0u64, auction_v0(1, 5, Some((3, 5000)), 0, 50)),
+        (1u64, auction_v0(1, 6, None, 100, 500)),
+    ];
+    for (id, auction) in auctions {
+        let mut end_key = auction.end.unwrap().twox_64_concat();
+        end_key.extend(id.twox_64_concat());
+        put_storage_value(prefix, b"AuctionEndTime", &end_key, true);
+        put_storage_value(prefix, b"Auctions", &id.twox_64_concat(), auction);
+    }
+    put_storage_value(prefix, b"AuctionsIndex", &[], 3u64);
+
+    let queued_bids = vec![
+        (
+            100u64,
+            QueuedBid {
+                bid: (2u64, 4000u64),
+                auction_id: 1u64,
+            },
+        ),
+        (
+            120u64,
+            QueuedBid {
+                bid: (4u64, 3000u64),
+                auction_id: 2u64,
+            },
+        ),
+    ];
+    for (block_number, qbid) in queued_bids {
+        put_storage_value(prefix, b"QueuedBids", &block_number.twox_64_concat(), qbid);
+    }
+
+    assert_ok!(Balances::reserve(&3, 5000));
+    assert_ok!(Balances::reserve(&2, 4000));
+    assert_ok!(Balances::reserve(&4, 3000));
+}
+
+#[test]
+fn migration_v0_to_v1() {
+    new_test_ext_empty().execute_with(|| {
+        load_v0_snapshot();
+        assert_eq!(StorageVersion::get(), Releases::V0);
+
+        AuctionModule::on_runtime_upgrade();
+        assert_eq!(StorageVersion::get(), Releases::V2);
+
+        assert_eq!(
+            AuctionModule::auctions(0),
+            Some(AuctionInfo {
+                creator: 1,
+                slot_origin: 5,
+                bid: Some((3, 5000)),
+                core: AuctionCoreInfo {
+                    timestamp: 1594471764,
+                    cargo: (22, 22),
+                },
+                start: 0,
+                end: Some(50),
+            })
+        );
+        assert_eq!(AuctionModule::auctions(1).unwrap().start, 100);
+        assert_eq!(AuctionModule::auctions_index(), 3);
+
+        // Queued bids are now keyed by auction.
+        assert_eq!(
+            AuctionModule::queued_bids(1),
+            vec![QueuedBid {
+                bid: (2, 4000),
+                auction_id: 1
+            }]
+        );
+        assert_eq!(AuctionModule::auction_start_time(100, 1), Some(true));
+        // The queued bid of the removed auction is refunded.
+        assert_eq!(AuctionModule::queued_bids(2), vec![]);
+        assert_eq!(Balances::reserved_balance(&4), 0);
+        assert_eq!(Balances::free_balance(&4), 20000);
+    })
+}
+
+#[test]
+fn migration_runs_once() {
+    new_test_ext_empty().execute_with(|| {
+        load_v0_snapshot();
+        AuctionModule::on_runtime_upgrade();
+        let auction = AuctionModule::auctions(0);
+        // A second upgrade must not try to decode the already migrated storage.
+        AuctionModule::on_runtime_upgrade();
+        assert_eq!(AuctionModule::auctions(0), auction);
+        assert_eq!(AuctionModule::queued_bids(1).len(), 1);
+    })
+}
+
+#[test]
+fn migration_v0_to_v1_auctions_continue() {
+    new_test_ext_empty().execute_with(|| {
+        load_v0_snapshot();
+        AuctionModule::on_runtime_upgrade();
+
+        // Migrated auctions keep working, the queued bid is placed once auction 1 starts.
+        run_to_block(100);
+        assert_eq!(AuctionModule::auctions(1).unwrap().bid, Some((2, 4000)));
+        assert_eq!(Balances::reserved_balance(&2), 4000);
+
+        // Auction 0 ended on block 50, its leading bid was transferred to the creator.
+        assert_eq!(AuctionModule::auction_exists(0), false);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+        assert_eq!(Balances::free_balance(&1), 25000);
+    })
+}
+
+#[test]
+fn migration_v1_to_v2_indexes_timeline() {
+    new_test_ext_empty().execute_with(|| {
+        load_v0_snapshot();
+        AuctionModule::on_runtime_upgrade();
+
+        let window = BerthWindow {
+            start: 1594471764,
+            end: 1594471764 + 3600,
+            teu: 22,
+        };
+        assert_eq!(AuctionModule::timeline(5, 0), Some(window));
+        assert_eq!(AuctionModule::timeline(6, 1), Some(window));
+        assert_eq!(AuctionModule::timeline(6, 2), None);
+    })
+}
+
+#[test]
+fn new_genesis_is_latest_version() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(StorageVersion::get(), Releases::V2);
+    })
+}
+
+#[test]
+fn queued_bids_highest_placed() {
+    new_test_ext().execute_with(|| {
+        // Auction 5 starts on block 100, queue multiple bids on it.
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(1), 5, 10000));
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 5, 12000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 5, 11000));
+        // Replacing a queued bid refunds the old one.
+        assert_ok!(AuctionModule::bid(Origin::signed(1), 5, 9000));
+        assert_eq!(Balances::reserved_balance(&1), 9000);
+        assert_eq!(AuctionModule::queued_bids(5).len(), 3);
+
+        run_to_block(100);
+        assert_eq!(AuctionModule::auctions(5).unwrap().bid, Some((2, 12000)));
+        assert_eq!(AuctionModule::queued_bids(5), vec![]);
+        assert_eq!(Balances::reserved_balance(&2), 12000);
+        assert_eq!(Balances::reserved_balance(&1), 0);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+    })
+}
+
+#[test]
+fn failed_queued_bid_replacement_keeps_old_bid() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(1), 5, 10000));
+
+        // The old bid's funds aren't free, so the new bid can't be reserved.
+        assert_noop!(
+            AuctionModule::bid(Origin::signed(1), 5, 15000),
+            Error::<AuctionTestRuntime>::AmbitiousReserve
+        );
+        assert_eq!(Balances::reserved_balance(&1), 10000);
+        assert_eq!(AuctionModule::queued_bids(5)[0].bid, (1, 10000));
+    })
+}
+
+#[test]
+fn queued_bid_failure_refunds() {
+    new_test_ext().execute_with(|| {
+        // Queued bids which no longer hold once the auction starts are reported and refunded.
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(4), 5, 10000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 5, 8000));
+        assert_eq!(Balances::reserved_balance(&4), 10000);
+        assert_eq!(Balances::reserved_balance(&3), 8000);
+        // A standing bid neither queued bid beats, as a migrated auction may carry.
+        <Auctions<AuctionTestRuntime>>::mutate(5, |auction| {
+            auction.as_mut().unwrap().bid = Some((2, 15000));
+        });
+
+        run_to_block(100);
+        assert_eq!(AuctionModule::auctions(5).unwrap().bid, Some((2, 15000)));
+        assert_eq!(AuctionModule::queued_bids(5), vec![]);
+        assert!(has_event(RawEvent::QueuedBidFailed(5, 4, 10000)));
+        assert!(has_event(RawEvent::QueuedBidFailed(5, 3, 8000)));
+        assert_eq!(Balances::reserved_balance(&4), 0);
+        assert_eq!(Balances::free_balance(&4), 20000);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+        assert_eq!(Balances::free_balance(&3), 20000);
+    })
+}
+
+///////////////////////////////////////////////////////
+// Parameter related tests
+///////////////////////////////////////////////////////
+fn test_parameters() -> AuctionParameters<Balance, BlockNumber> {
+    AuctionParameters {
+        min_duration: 10,
+        max_horizon: 500,
+        creation_deposit: 2000,
+        creation_fee: 100,
+        soft_close_window: 20,
+    }
+}
+
+#[test]
+fn set_parameters_requires_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            AuctionModule::set_parameters(Origin::signed(0), test_parameters()),
+            BadOrigin
+        );
+        assert_ok!(AuctionModule::set_parameters(Origin::root(), test_parameters()));
+        assert_eq!(AuctionModule::auction_parameters(), test_parameters());
+        assert!(System::events().iter().any(|e| e.event
+            == AuctionTestEvent::auction_events(RawEvent::ParametersChanged(
+                Default::default(),
+                test_parameters()
+            ))));
+    })
+}
+
+#[test]
+fn set_parameters_bounds() {
+    new_test_ext().execute_with(|| {
+        let mut parameters = test_parameters();
+        parameters.creation_fee = 1001;
+        assert_noop!(
+            AuctionModule::set_parameters(Origin::root(), parameters),
+            Error::<AuctionTestRuntime>::ParameterOutOfBounds
+        );
+        assert_ok!(AuctionModule::set_parameters(
+            Origin::root(),
+            MaximumParameters::get()
+        ));
+        assert_eq!(
+            AuctionModule::auction_parameter_bounds(),
+            MaximumParameters::get()
+        );
+    })
+}
+
+#[test]
+fn parameters_schedule() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AuctionModule::set_parameters(Origin::root(), test_parameters()));
+        run_to_block(1);
+        assert_noop!(
+            AuctionModule::ext_new_auction(Origin::signed(1), 5, 22, 22, 1594471764, 10, 15),
+            Error::<AuctionTestRuntime>::AuctionTooShort
+        );
+        assert_noop!(
+            AuctionModule::ext_new_auction(Origin::signed(1), 5, 22, 22, 1594471764, 502, 600),
+            Error::<AuctionTestRuntime>::AuctionBeyondHorizon
+        );
+        assert_ok!(AuctionModule::ext_new_auction(
+            Origin::signed(1),
+            5,
+            22,
+            22,
+            1594471764,
+            10,
+            20
+        ));
+        // Updates are held to the same rules.
+        assert_noop!(
+            AuctionModule::ext_update_auction(Origin::signed(1), 7, None, None, None, None, Some(19)),
+            Error::<AuctionTestRuntime>::AuctionTooShort
+        );
+    })
+}
+
+#[test]
+fn parameters_creation_deposit_and_fee() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AuctionModule::set_parameters(Origin::root(), test_parameters()));
+        run_to_block(1);
+        assert_ok!(AuctionModule::ext_new_auction(
+            Origin::signed(1),
+            5,
+            22,
+            22,
+            1594471764,
+            10,
+            30
+        ));
+        // The fee is burned and the deposit is reserved.
+        assert_eq!(Balances::free_balance(&1), 17900);
+        assert_eq!(Balances::reserved_balance(&1), 2000);
+        assert_eq!(AuctionModule::auction_deposits(7), Some(2000));
+
+        // The deposit is released once the auction ends.
+        run_to_block(31);
+        assert_eq!(Balances::reserved_balance(&1), 0);
+        assert_eq!(Balances::free_balance(&1), 19900);
+        assert_eq!(AuctionModule::auction_deposits(7), None);
+    })
+}
+
+#[test]
+fn parameters_soft_close() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AuctionModule::set_parameters(Origin::root(), test_parameters()));
+        // Auction 1 ends on block 49, bidding on block 40 extends it to block 60.
+        run_to_block(40);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_eq!(AuctionModule::auctions(1).unwrap().end, Some(60));
+        run_to_block(55);
+        assert_eq!(AuctionModule::auction_exists(1), true);
+        run_to_block(61);
+        assert_eq!(AuctionModule::auction_exists(1), false);
+    })
+}
+
+///////////////////////////////////////////////////////
+// Invariant related tests
+///////////////////////////////////////////////////////
+#[test]
+fn invariants_hold_for_random_operations() {
+    // Every seed is a separate scenario of a few hundred operations, see `invariants::run`.
+    for seed in 0..200 {
+        invariants::run(&invariants::XorShift::new(seed).bytes(600));
+    }
+}
+
+///////////////////////////////////////////////////////
+// Regression tests
+///////////////////////////////////////////////////////
+#[test]
+fn failed_bid_keeps_previous_bid_reserved() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 15000));
+        assert_err!(
+            AuctionModule::bid(Origin::signed(3), 1, 25000),
+            Error::<AuctionTestRuntime>::AmbitiousReserve
+        );
+        assert_eq!(AuctionModule::auctions(1).unwrap().bid, Some((2, 15000)));
+        assert_eq!(Balances::reserved_balance(&2), 15000);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+    })
+}
+
+#[test]
+fn remove_auction_refunds_leading_bid() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::ext_remove_auction(Origin::signed(1), 1));
+        assert_eq!(AuctionModule::auction_exists(1), false);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::free_balance(&2), 20000);
+    })
+}
+
+#[test]
+fn failed_bid_leaves_auction_untouched() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AuctionModule::set_parameters(Origin::root(), test_parameters()));
+        // A bid within the soft-close window which can't be reserved doesn't extend the auction.
+        run_to_block(40);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 15000));
+        run_to_block(55);
+        assert_noop!(
+            AuctionModule::bid(Origin::signed(3), 1, 25000),
+            Error::<AuctionTestRuntime>::AmbitiousReserve
+        );
+        assert_eq!(AuctionModule::auctions(1).unwrap().end, Some(60));
+    })
+}
+
+#[test]
+fn rejected_update_keeps_end_index() {
+    new_test_ext().execute_with(|| {
+        // Auction 1 is live, moving its start is rejected along with the rest of the update.
+        run_to_block(10);
+        assert_ok!(AuctionModule::ext_update_auction(
+            Origin::signed(1),
+            1,
+            None,
+            None,
+            None,
+            Some(20),
+            Some(80)
+        ));
+        assert_eq!(AuctionModule::auctions(1).unwrap().end, Some(49));
+        assert_eq!(AuctionModule::auction_end_time(49, 1), Some(true));
+        assert_eq!(AuctionModule::auction_end_time(80, 1), None);
+    })
+}
+
+#[test]
+fn update_to_passed_start_keeps_end_index() {
+    new_test_ext().execute_with(|| {
+        // Auction 5 starts on block 100, moving its start to a block which has passed is rejected
+        // along with its new end.
+        run_to_block(10);
+        assert_ok!(AuctionModule::ext_update_auction(
+            Origin::signed(1),
+            5,
+            None,
+            None,
+            None,
+            Some(5),
+            Some(600)
+        ));
+        assert_eq!(AuctionModule::auctions(5).unwrap().start, 100);
+        assert_eq!(AuctionModule::auctions(5).unwrap().end, Some(500));
+        assert_eq!(AuctionModule::auction_end_time(500, 5), Some(true));
+        assert_eq!(AuctionModule::auction_end_time(600, 5), None);
+    })
+}
+
+///////////////////////////////////////////////////////
+// Resale related tests
+///////////////////////////////////////////////////////
+const SLOT_TIME: u64 = 100_000;
+
+// Opens an auction for a slot of terminal 5 on block 1 and lets `winner` win it for `price`.
+// Returns the id of the won slot.
+fn win_slot(winner: AccountId, price: Balance) -> AuctionId {
+    run_to_block(1);
+    let id = AuctionModule::auctions_index();
+    assert_ok!(AuctionModule::ext_new_auction(
+        Origin::signed(0),
+        5,
+        22,
+        22,
+        SLOT_TIME,
+        1,
+        10
+    ));
+    assert_ok!(AuctionModule::bid(Origin::signed(winner), id, price));
+    run_to_block(11);
+    id
+}
+
+fn has_event(event: Event<AuctionTestRuntime>) -> bool {
+    System::events()
+        .iter()
+        .any(|e| e.event == AuctionTestEvent::auction_events(event.clone()))
+}
+
+#[test]
+fn won_slot_is_recorded() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        let slot = AuctionModule::won_slots(id).unwrap();
+        assert_eq!(slot.holder, 2);
+        assert_eq!(slot.slot_origin, 5);
+        assert_eq!(slot.core.timestamp, SLOT_TIME);
+        assert_eq!(slot.price, None);
+        assert_eq!(AuctionModule::slot_history(id), vec![(2, 1000, 10)]);
+        assert!(has_event(RawEvent::SlotWon(id, 2, 1000)));
+    })
+}
+
+#[test]
+fn fixed_price_resale() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        assert_noop!(
+            AuctionModule::list_slot(Origin::signed(3), id, 2000),
+            Error::<AuctionTestRuntime>::PermissionError
+        );
+        assert_noop!(
+            AuctionModule::buy_slot(Origin::signed(3), id, 2000),
+            Error::<AuctionTestRuntime>::SlotNotListed
+        );
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), id, 2000));
+        assert_noop!(
+            AuctionModule::list_slot(Origin::signed(2), id, 3000),
+            Error::<AuctionTestRuntime>::SlotListed
+        );
+        // The buyer has to agree to the listed price.
+        assert_noop!(
+            AuctionModule::buy_slot(Origin::signed(3), id, 1999),
+            Error::<AuctionTestRuntime>::SlotNotListed
+        );
+        assert_ok!(AuctionModule::buy_slot(Origin::signed(3), id, 2000));
+
+        // The seller pays the terminal a tenth of the price.
+        assert_eq!(Balances::free_balance(&3), 18000);
+        assert_eq!(Balances::free_balance(&2), 20000 - 1000 + 2000 - 200);
+        assert_eq!(Balances::free_balance(&5), 40200);
+        assert!(has_event(RawEvent::ResaleFeePaid(id, 2, 5, 200)));
+        assert!(has_event(RawEvent::SlotTransferred(id, 2, 3, 2000)));
+
+        let slot = AuctionModule::won_slots(id).unwrap();
+        assert_eq!(slot.holder, 3);
+        assert_eq!(slot.price, None);
+        assert_eq!(
+            AuctionModule::slot_history(id),
+            vec![(2, 1000, 10), (3, 2000, 11)]
+        );
+    })
+}
+
+#[test]
+fn relisted_slot_goes_to_auction_winner() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        let resale = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::relist_slot(Origin::signed(2), id, 12, 20));
+        assert_eq!(AuctionModule::resale_of(resale), Some(id));
+        assert_eq!(AuctionModule::won_slots(id).unwrap().resale, Some(resale));
+        assert!(has_event(RawEvent::SlotRelisted(id, 2, resale)));
+
+        // The slot being resold can't change.
+        assert_err!(
+            AuctionModule::update_auction(
+                resale,
+                2,
+                AuctionUpdateInfo {
+                    timestamp: Some(1),
+                    num_con: None,
+                    num_teu: None,
+                },
+                None,
+                None
+            ),
+            Error::<AuctionTestRuntime>::ResaleSlotImmutable
+        );
+        assert_ok!(AuctionModule::ext_update_auction(
+            Origin::signed(2),
+            resale,
+            None,
+            None,
+            None,
+            None,
+            Some(22)
+        ));
+
+        run_to_block(12);
+        assert_ok!(AuctionModule::bid(Origin::signed(3), resale, 1500));
+        run_to_block(23);
+
+        let slot = AuctionModule::won_slots(id).unwrap();
+        assert_eq!(slot.holder, 3);
+        assert_eq!(slot.resale, None);
+        assert_eq!(AuctionModule::resale_of(resale), None);
+        // The resale auction doesn't make a slot of its own.
+        assert_eq!(AuctionModule::won_slots(resale), None);
+        assert_eq!(Balances::free_balance(&2), 20000 - 1000 + 1500 - 150);
+        assert_eq!(Balances::free_balance(&3), 18500);
+        assert_eq!(AuctionModule::slot_history(id).len(), 2);
+    })
+}
+
+#[test]
+fn resale_auction_pays_only_resale_fee() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        let issuance = Balances::total_issuance();
+        set_shares(10, 5, 5);
+        let resale = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::relist_slot(Origin::signed(2), id, 12, 20));
+        run_to_block(12);
+        assert_ok!(AuctionModule::bid(Origin::signed(3), resale, 1500));
+        run_to_block(21);
+
+        // The seller receives the price less the resale fee, which goes to the terminal. The
+        // commission, platform fee and burn share of a first sale don't apply.
+        assert_eq!(AuctionModule::won_slots(id).unwrap().holder, 3);
+        assert_eq!(Balances::free_balance(&2), 20000 - 1000 + 1500 - 150);
+        assert_eq!(Balances::free_balance(&3), 18500);
+        assert_eq!(Balances::free_balance(&5), 40150);
+        assert_eq!(Balances::total_issuance(), issuance);
+        assert!(has_event(RawEvent::ResaleFeePaid(id, 2, 5, 150)));
+        assert!(!has_event(RawEvent::CommissionPaid(resale, 5, 150)));
+    })
+}
+
+#[test]
+fn resale_purchase_fails_as_a_whole() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), id, 20000));
+        // Buyer 4 can pay the seller its share of the price, but not the fee on top of it without
+        // closing its account, so the seller isn't paid either.
+        assert_noop!(
+            AuctionModule::buy_slot(Origin::signed(4), id, 20000),
+            Error::<AuctionTestRuntime>::TryTransfer
+        );
+        assert_eq!(AuctionModule::won_slots(id).unwrap().holder, 2);
+    })
+}
+
+#[test]
+fn relisted_slot_without_bids_stays() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        let resale = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::relist_slot(Origin::signed(2), id, 12, 20));
+        run_to_block(21);
+        let slot = AuctionModule::won_slots(id).unwrap();
+        assert_eq!(slot.holder, 2);
+        assert_eq!(slot.resale, None);
+        assert_eq!(AuctionModule::resale_of(resale), None);
+        assert!(has_event(RawEvent::SlotUnlisted(id)));
+    })
+}
+
+#[test]
+fn unlist_slot() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        assert_noop!(
+            AuctionModule::unlist_slot(Origin::signed(2), id),
+            Error::<AuctionTestRuntime>::SlotNotListed
+        );
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), id, 2000));
+        assert_ok!(AuctionModule::unlist_slot(Origin::signed(2), id));
+        assert_eq!(AuctionModule::won_slots(id).unwrap().price, None);
+
+        // Unlisting a relisted slot removes its auction and refunds the bids.
+        let resale = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::relist_slot(Origin::signed(2), id, 11, 20));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), resale, 1500));
+        assert_ok!(AuctionModule::unlist_slot(Origin::signed(2), id));
+        assert_eq!(AuctionModule::auction_exists(resale), false);
+        assert_eq!(AuctionModule::resale_of(resale), None);
+        assert_eq!(AuctionModule::won_slots(id).unwrap().resale, None);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+    })
+}
+
+#[test]
+fn resale_requires_terminal_approval() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        assert_ok!(AuctionModule::set_resale_approval(Origin::signed(5), true));
+        assert_noop!(
+            AuctionModule::list_slot(Origin::signed(2), id, 2000),
+            Error::<AuctionTestRuntime>::ResaleNotApproved
+        );
+        assert_noop!(
+            AuctionModule::approve_resale(Origin::signed(6), id),
+            Error::<AuctionTestRuntime>::PermissionError
+        );
+        assert_ok!(AuctionModule::approve_resale(Origin::signed(5), id));
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), id, 2000));
+        assert_ok!(AuctionModule::buy_slot(Origin::signed(3), id, 2000));
+
+        // The approval was given to the previous holder.
+        assert_noop!(
+            AuctionModule::list_slot(Origin::signed(3), id, 3000),
+            Error::<AuctionTestRuntime>::ResaleNotApproved
+        );
+        assert_ok!(AuctionModule::set_resale_approval(Origin::signed(5), false));
+        assert_ok!(AuctionModule::list_slot(Origin::signed(3), id, 3000));
+    })
+}
+
+#[test]
+fn resale_closes_before_slot() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        MockTime::set(SLOT_TIME - ResaleCutoff::get());
+        assert_noop!(
+            AuctionModule::list_slot(Origin::signed(2), id, 2000),
+            Error::<AuctionTestRuntime>::ResaleClosed
+        );
+        assert_noop!(
+            AuctionModule::relist_slot(Origin::signed(2), id, 12, 20),
+            Error::<AuctionTestRuntime>::ResaleClosed
+        );
+
+        MockTime::set(SLOT_TIME - ResaleCutoff::get() - 1);
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), id, 2000));
+        MockTime::set(SLOT_TIME - ResaleCutoff::get());
+        assert_noop!(
+            AuctionModule::buy_slot(Origin::signed(3), id, 2000),
+            Error::<AuctionTestRuntime>::ResaleClosed
+        );
+    })
+}
+
+#[test]
+fn clear_expired_slot() {
+    new_test_ext().execute_with(|| {
+        let id = win_slot(2, 1000);
+        assert_noop!(
+            AuctionModule::clear_slot(Origin::signed(3), id),
+            Error::<AuctionTestRuntime>::SlotNotExpired
+        );
+        MockTime::set(SLOT_TIME);
+        assert_ok!(AuctionModule::clear_slot(Origin::signed(3), id));
+        assert_eq!(AuctionModule::won_slots(id), None);
+        assert_eq!(AuctionModule::slot_history(id), vec![]);
+        assert_noop!(
+            AuctionModule::clear_slot(Origin::signed(3), id),
+            Error::<AuctionTestRuntime>::SlotNotExist
+        );
+    })
+}
+
+///////////////////////////////////////////////////////
+// Fallback related tests
+///////////////////////////////////////////////////////
+#[test]
+fn outbid_bids_kept_as_runner_ups() {
+    new_test_ext().execute_with(|| {
+        set_fallback(2, 0);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 1, 2000));
+        assert_ok!(AuctionModule::bid(Origin::signed(4), 1, 3000));
+        assert_eq!(AuctionModule::runner_ups(1), vec![(3, 2000), (2, 1000)]);
+        assert_eq!(Balances::reserved_balance(&2), 1000);
+
+        // A runner-up outbidding the leader moves its funds along.
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 4000));
+        assert_eq!(AuctionModule::runner_ups(1), vec![(4, 3000), (3, 2000)]);
+        assert_eq!(Balances::reserved_balance(&2), 4000);
+
+        // Bids pushed off the ladder are refunded.
+        assert_ok!(AuctionModule::bid(Origin::signed(0), 1, 5000));
+        assert_eq!(AuctionModule::runner_ups(1), vec![(2, 4000), (4, 3000)]);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+
+        // Once the auction settles, the remaining runner-ups are released.
+        run_to_block(50);
+        assert_eq!(AuctionModule::runner_ups(1), vec![]);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::reserved_balance(&4), 0);
+        assert_eq!(Balances::free_balance(&1), 25000);
+        assert!(has_event(RawEvent::RunnerUpReleased(1, 2, 4000)));
+        assert!(has_event(RawEvent::RunnerUpReleased(1, 4, 3000)));
+    })
+}
+
+#[test]
+fn defaulted_winner_falls_back_to_runner_up() {
+    new_test_ext().execute_with(|| {
+        set_fallback(2, 0);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 1, 2000));
+        // Part of the winning bid is pulled from its reserve, the winner can't pay.
+        Balances::slash_reserved(&3, 1000);
+
+        run_to_block(50);
+        assert!(has_event(RawEvent::WinnerDefaulted(1, 3, 2000)));
+        assert!(has_event(RawEvent::RunnerUpPromoted(1, 2, 1000)));
+        assert_eq!(AuctionModule::won_slots(1).unwrap().holder, 2);
+        assert_eq!(Balances::free_balance(&1), 21000);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+        assert_eq!(Balances::free_balance(&3), 19000);
+    })
+}
+
+#[test]
+fn auction_without_paying_bidder_ends_undecided() {
+    new_test_ext().execute_with(|| {
+        set_fallback(2, 0);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 1, 2000));
+        Balances::slash_reserved(&2, 500);
+        Balances::slash_reserved(&3, 1000);
+
+        run_to_block(50);
+        assert!(has_event(RawEvent::WinnerDefaulted(1, 3, 2000)));
+        assert!(has_event(RawEvent::WinnerDefaulted(1, 2, 1000)));
+        assert!(has_event(RawEvent::AuctionEndUndecided(1)));
+        assert_eq!(AuctionModule::won_slots(1), None);
+        assert_eq!(Balances::free_balance(&1), 20000);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+    })
+}
+
+#[test]
+fn winner_confirms_win() {
+    new_test_ext().execute_with(|| {
+        set_fallback(2, 10);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 1, 2000));
+
+        run_to_block(50);
+        assert!(has_event(RawEvent::ConfirmationRequested(1, 3, 2000, 59)));
+        assert_eq!(
+            AuctionModule::pending_settlements(1).unwrap().bid,
+            Some((3, 2000))
+        );
+        assert_eq!(AuctionModule::settlement_deadline(59, 1), Some(true));
+        assert_eq!(AuctionModule::won_slots(1), None);
+
+        assert_noop!(
+            AuctionModule::confirm_win(Origin::signed(2), 1),
+            Error::<AuctionTestRuntime>::PermissionError
+        );
+        assert_ok!(AuctionModule::confirm_win(Origin::signed(3), 1));
+        assert_eq!(AuctionModule::won_slots(1).unwrap().holder, 3);
+        assert_eq!(AuctionModule::pending_settlements(1), None);
+        assert_eq!(AuctionModule::settlement_deadline(59, 1), None);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::free_balance(&1), 22000);
+        assert!(has_event(RawEvent::RunnerUpReleased(1, 2, 1000)));
+
+        assert_noop!(
+            AuctionModule::confirm_win(Origin::signed(3), 1),
+            Error::<AuctionTestRuntime>::SettlementNotExist
+        );
+    })
+}
+
+#[test]
+fn missed_confirmation_falls_back_to_runner_up() {
+    new_test_ext().execute_with(|| {
+        set_fallback(2, 10);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 1, 2000));
+
+        // Bidder 3 lets the deadline pass, bidder 2 is asked to confirm instead.
+        run_to_block(60);
+        assert!(has_event(RawEvent::ConfirmationMissed(1, 3, 2000)));
+        assert!(has_event(RawEvent::RunnerUpPromoted(1, 2, 1000)));
+        assert!(has_event(RawEvent::ConfirmationRequested(1, 2, 1000, 69)));
+        assert_eq!(Balances::reserved_balance(&3), 0);
+        assert_eq!(Balances::free_balance(&3), 20000);
+
+        assert_ok!(AuctionModule::confirm_win(Origin::signed(2), 1));
+        assert_eq!(AuctionModule::won_slots(1).unwrap().holder, 2);
+        assert_eq!(AuctionModule::settlement_deadline(69, 1), None);
+
+        // Nothing is left pending once the old deadline passes.
+        run_to_block(70);
+        assert_eq!(AuctionModule::won_slots(1).unwrap().holder, 2);
+        assert_eq!(Balances::free_balance(&1), 21000);
+    })
+}
+
+#[test]
+fn remove_auction_releases_runner_ups() {
+    new_test_ext().execute_with(|| {
+        set_fallback(2, 0);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        assert_ok!(AuctionModule::bid(Origin::signed(3), 1, 2000));
+        assert_ok!(AuctionModule::ext_remove_auction(Origin::signed(1), 1));
+        assert_eq!(AuctionModule::runner_ups(1), vec![]);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::reserved_balance(&3), 0);
+        assert!(has_event(RawEvent::RunnerUpReleased(1, 2, 1000)));
+    })
+}
+
+///////////////////////////////////////////////////////
+// Settlement split related tests
+///////////////////////////////////////////////////////
+#[test]
+fn winning_bid_is_split() {
+    new_test_ext().execute_with(|| {
+        set_shares(10, 5, 2);
+        let issuance = Balances::total_issuance();
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 10000));
+        run_to_block(50);
+
+        assert_eq!(Balances::free_balance(&1), 28300);
+        assert_eq!(Balances::free_balance(&5), 41000);
+        assert_eq!(Balances::free_balance(&9), 500);
+        assert_eq!(Balances::free_balance(&2), 10000);
+        assert_eq!(Balances::total_issuance(), issuance - 200);
+        assert!(has_event(RawEvent::CreatorPaid(1, 1, 8300)));
+        assert!(has_event(RawEvent::CommissionPaid(1, 5, 1000)));
+        assert!(has_event(RawEvent::PlatformFeePaid(1, 9, 500)));
+        assert!(has_event(RawEvent::BidBurned(1, 2, 200)));
+        assert_eq!(AuctionModule::won_slots(1).unwrap().holder, 2);
+    })
+}
+
+#[test]
+fn platform_fee_below_existential_deposit_is_burned() {
+    new_test_ext().execute_with(|| {
+        // The platform account holds nothing and a fee of 20 can't open it.
+        set_existential_deposit(500);
+        set_shares(0, 2, 0);
+        let issuance = Balances::total_issuance();
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 1000));
+        run_to_block(50);
+
+        assert!(!has_event(RawEvent::WinnerDefaulted(1, 2, 1000)));
+        assert_eq!(AuctionModule::won_slots(1).unwrap().holder, 2);
+        assert_eq!(Balances::free_balance(&1), 20980);
+        assert_eq!(Balances::free_balance(&9), 0);
+        assert_eq!(Balances::free_balance(&2), 19000);
+        assert_eq!(Balances::total_issuance(), issuance - 20);
+        assert!(has_event(RawEvent::BidBurned(1, 2, 20)));
+        assert!(!has_event(RawEvent::PlatformFeePaid(1, 9, 20)));
+    })
+}
+
+#[test]
+fn failed_payout_pays_nothing() {
+    new_test_ext().execute_with(|| {
+        set_shares(10, 5, 2);
+        run_to_block(1);
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 1, 10000));
+        // The lock lets the creator be paid but not the terminal's commission.
+        Balances::set_lock(
+            *b"testlock",
+            &2,
+            11000,
+            WithdrawReasons::from(WithdrawReason::Transfer),
+        );
+        run_to_block(50);
+
+        assert!(has_event(RawEvent::WinnerDefaulted(1, 2, 10000)));
+        assert!(!has_event(RawEvent::CreatorPaid(1, 1, 8300)));
+        assert_eq!(Balances::free_balance(&1), 20000);
+        assert_eq!(Balances::free_balance(&5), 40000);
+        assert_eq!(Balances::free_balance(&2), 20000);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(AuctionModule::won_slots(1), None);
+    })
+}
+
+///////////////////////////////////////////////////////
+// Berth related tests
+///////////////////////////////////////////////////////
+const BERTH_TIME: u64 = 50_000;
+
+fn capacity(max_vessels: u32, max_teu: u64) -> TerminalCapacity<GeneralInformationContainer> {
+    TerminalCapacity {
+        max_vessels,
+        max_teu,
+        slot_duration: 1000,
+    }
+}
+
+// Opens an auction for a slot of terminal 8 with `teu` TEU at `timestamp`.
+fn offer_berth(timestamp: u64, teu: u64) -> DispatchResult {
+    AuctionModule::ext_new_auction(Origin::signed(1), 8, teu, teu, timestamp, 1, 100)
+}
+
+#[test]
+fn terminal_capacity_is_validated() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            AuctionModule::set_terminal_capacity(Origin::signed(8), Some(capacity(0, 100))),
+            Error::<AuctionTestRuntime>::InvalidCapacity
+        );
+        let instant = TerminalCapacity {
+            slot_duration: 0,
+            ..capacity(1, 100)
+        };
+        assert_noop!(
+            AuctionModule::set_terminal_capacity(Origin::signed(8), Some(instant)),
+            Error::<AuctionTestRuntime>::InvalidCapacity
+        );
+
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(1, 100))
+        ));
+        assert_eq!(AuctionModule::capacities(8), Some(capacity(1, 100)));
+        assert!(has_event(RawEvent::TerminalCapacitySet(
+            8,
+            Some(capacity(1, 100))
+        )));
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            None
+        ));
+        assert_eq!(AuctionModule::capacities(8), None);
+    })
+}
+
+#[test]
+fn overlapping_slots_exceeding_vessels_rejected() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(2, 100))
+        ));
+        assert_ok!(offer_berth(BERTH_TIME, 10));
+        assert_ok!(offer_berth(BERTH_TIME + 500, 10));
+        // Both berths are taken from BERTH_TIME + 500 up to BERTH_TIME + 1000.
+        assert_noop!(
+            offer_berth(BERTH_TIME + 900, 10),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+        assert_noop!(
+            offer_berth(BERTH_TIME - 400, 10),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+        // The first berth frees up at BERTH_TIME + 1000, windows don't include their end.
+        assert_ok!(offer_berth(BERTH_TIME + 1000, 10));
+        assert_ok!(offer_berth(BERTH_TIME - 1000, 10));
+        // Other terminals have capacity to spare.
+        assert_ok!(AuctionModule::ext_new_auction(
+            Origin::signed(1),
+            7,
+            10,
+            10,
+            BERTH_TIME,
+            1,
+            100
+        ));
+    })
+}
+
+#[test]
+fn overlapping_slots_exceeding_teu_rejected() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(3, 30))
+        ));
+        assert_noop!(
+            offer_berth(BERTH_TIME, 31),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+        assert_ok!(offer_berth(BERTH_TIME, 20));
+        assert_noop!(
+            offer_berth(BERTH_TIME + 999, 11),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+        assert_ok!(offer_berth(BERTH_TIME + 999, 10));
+    })
+}
+
+#[test]
+fn update_into_conflict_rejected() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(1, 100))
+        ));
+        let first = AuctionModule::auctions_index();
+        assert_ok!(offer_berth(BERTH_TIME, 10));
+        let second = AuctionModule::auctions_index();
+        assert_ok!(offer_berth(BERTH_TIME + 2000, 10));
+
+        assert_noop!(
+            AuctionModule::ext_update_auction(
+                Origin::signed(1),
+                second,
+                Some(BERTH_TIME + 500),
+                None,
+                None,
+                None,
+                None
+            ),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+        // A slot doesn't conflict with its own window.
+        assert_ok!(AuctionModule::ext_update_auction(
+            Origin::signed(1),
+            first,
+            Some(BERTH_TIME + 500),
+            None,
+            Some(20),
+            None,
+            None
+        ));
+        assert_eq!(
+            AuctionModule::timeline(8, first),
+            Some(BerthWindow {
+                start: BERTH_TIME + 500,
+                end: BERTH_TIME + 1500,
+                teu: 20,
+            })
+        );
+    })
+}
+
+#[test]
+fn timeline_is_bounded_and_pruned() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(8, 1000))
+        ));
+        // The timeline of terminal 8 holds the window of its genesis auction 4 as well.
+        let first = AuctionModule::auctions_index();
+        for n in 0..MaxTerminalWindows::get() as u64 - 1 {
+            assert_ok!(offer_berth(BERTH_TIME + n * 1000, 10));
+        }
+        assert_noop!(
+            offer_berth(BERTH_TIME + 20_000, 10),
+            Error::<AuctionTestRuntime>::TimelineFull
+        );
+
+        // Windows which have passed are pruned as the next slot is offered.
+        MockTime::set(BERTH_TIME + 2000);
+        assert_ok!(offer_berth(BERTH_TIME + 20_000, 10));
+        assert_eq!(AuctionModule::timeline(8, 4), None);
+        assert_eq!(AuctionModule::timeline(8, first), None);
+        assert_eq!(AuctionModule::timeline(8, first + 1), None);
+        assert_eq!(
+            AuctionModule::terminal_occupancy(8, 0, u64::max_value()).len(),
+            MaxTerminalWindows::get() as usize - 2
+        );
+
+        // A pruned slot moved into the future is indexed again.
+        assert_ok!(AuctionModule::ext_update_auction(
+            Origin::signed(1),
+            first,
+            Some(BERTH_TIME + 30_000),
+            None,
+            None,
+            None,
+            None
+        ));
+        assert_eq!(
+            AuctionModule::timeline(8, first),
+            Some(BerthWindow {
+                start: BERTH_TIME + 30_000,
+                end: BERTH_TIME + 31_000,
+                teu: 10,
+            })
+        );
+    })
+}
+
+#[test]
+fn terminal_occupancy_follows_slots() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(2, 100))
+        ));
+        let late = AuctionModule::auctions_index();
+        assert_ok!(offer_berth(BERTH_TIME + 500, 20));
+        let early = AuctionModule::auctions_index();
+        assert_ok!(offer_berth(BERTH_TIME, 10));
+        let window = |start, teu| BerthWindow {
+            start,
+            end: start + 1000,
+            teu,
+        };
+
+        assert_eq!(
+            AuctionModule::terminal_occupancy(8, BERTH_TIME, BERTH_TIME + 2000),
+            vec![
+                (early, window(BERTH_TIME, 10)),
+                (late, window(BERTH_TIME + 500, 20))
+            ]
+        );
+        assert_eq!(
+            AuctionModule::terminal_occupancy(8, BERTH_TIME + 1000, BERTH_TIME + 2000),
+            vec![(late, window(BERTH_TIME + 500, 20))]
+        );
+
+        // A won slot keeps its berth until it's cleared, a removed auction frees it right away.
+        assert_ok!(AuctionModule::bid(Origin::signed(2), early, 1000));
+        assert_ok!(AuctionModule::ext_remove_auction(Origin::signed(1), late));
+        run_to_block(101);
+        assert_eq!(
+            AuctionModule::terminal_occupancy(8, BERTH_TIME, BERTH_TIME + 2000),
+            vec![(early, window(BERTH_TIME, 10))]
+        );
+        MockTime::set(BERTH_TIME);
+        assert_ok!(AuctionModule::clear_slot(Origin::signed(3), early));
+        assert_eq!(
+            AuctionModule::terminal_occupancy(8, BERTH_TIME, BERTH_TIME + 2000),
+            vec![]
+        );
+    })
+}
+
+#[test]
+fn relisted_slot_keeps_its_berth() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(5),
+            Some(capacity(1, 100))
+        ));
+        let id = win_slot(2, 1000);
+        assert_ok!(AuctionModule::relist_slot(Origin::signed(2), id, 12, 20));
+        let resale = AuctionModule::auctions_index() - 1;
+        assert_eq!(AuctionModule::timeline(5, resale), None);
+
+        // The resale auction ends without bids, the slot stays with its holder and so does the
+        // berth.
+        run_to_block(21);
+        assert!(AuctionModule::timeline(5, id).is_some());
+        assert_noop!(
+            AuctionModule::ext_new_auction(Origin::signed(1), 5, 1, 1, SLOT_TIME, 21, 30),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+    })
+}
+
+///////////////////////////////////////////////////////
+// Genesis related tests
+///////////////////////////////////////////////////////
+fn genesis_auction_with_bids(
+    terminal: AccountId,
+    start: BlockNumber,
+    bids: Vec<(AccountId, Balance)>,
+) -> GenesisAuction<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+    GenesisAuction {
+        bids,
+        ..genesis_auction(1, terminal, start, 100)
+    }
+}
+
+#[test]
+fn genesis_bids_are_placed() {
+    set_fallback(1, 0);
+    let auction = genesis_auction_with_bids(5, 0, vec![(2, 500), (3, 800), (4, 300)]);
+    new_genesis_ext(vec![], vec![auction]).execute_with(|| {
+        // The highest bid leads, the next one is kept as runner-up and the rest is refunded.
+        assert_eq!(AuctionModule::auctions(0).unwrap().bid, Some((3, 800)));
+        assert_eq!(AuctionModule::runner_ups(0), vec![(2, 500)]);
+        assert_eq!(Balances::reserved_balance(&3), 800);
+        assert_eq!(Balances::reserved_balance(&2), 500);
+        assert_eq!(Balances::reserved_balance(&4), 0);
+        assert_eq!(Balances::free_balance(&4), 20000);
+
+        run_to_block(101);
+        assert!(has_event(RawEvent::SlotWon(0, 3, 800)));
+        assert_eq!(Balances::reserved_balance(&2), 0);
+    })
+}
+
+#[test]
+fn genesis_bids_are_queued_until_start() {
+    let auction = genesis_auction_with_bids(5, 10, vec![(2, 500), (3, 800)]);
+    new_genesis_ext(vec![], vec![auction]).execute_with(|| {
+        assert_eq!(AuctionModule::auctions(0).unwrap().bid, None);
+        assert_eq!(AuctionModule::queued_bids(0).len(), 2);
+        assert_eq!(AuctionModule::auction_start_time(10, 0), Some(true));
+        assert_eq!(Balances::reserved_balance(&2), 500);
+
+        run_to_block(10);
+        assert_eq!(AuctionModule::auctions(0).unwrap().bid, Some((3, 800)));
+        assert_eq!(AuctionModule::queued_bids(0), vec![]);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+    })
+}
+
+#[test]
+fn genesis_terminals_are_set_up() {
+    let terminal = GenesisTerminal {
+        account: 5,
+        capacity: Some(capacity(1, 100)),
+        resale_approval_required: true,
+    };
+    new_genesis_ext(vec![terminal], vec![genesis_auction(1, 5, 0, 100)]).execute_with(|| {
+        assert_eq!(AuctionModule::capacities(5), Some(capacity(1, 100)));
+        assert!(AuctionModule::resale_approval_required(5));
+        assert!(AuctionModule::timeline(5, 0).is_some());
+        assert!(!AuctionModule::resale_approval_required(6));
+    })
+}
+
+#[test]
+#[should_panic(expected = "Genesis auction exceeds the berth capacity of its terminal")]
+fn genesis_auctions_respect_berth_capacity() {
+    let terminal = GenesisTerminal {
+        account: 5,
+        capacity: Some(capacity(1, 100)),
+        resale_approval_required: false,
+    };
+    new_genesis_ext(
+        vec![terminal],
+        vec![genesis_auction(1, 5, 0, 100), genesis_auction(2, 5, 0, 100)],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Genesis bidders need the free balance for their bids")]
+fn genesis_bids_need_funds() {
+    new_genesis_ext(
+        vec![],
+        vec![genesis_auction_with_bids(5, 0, vec![(2, 30000)])],
+    );
+}
+
+///////////////////////////////////////////////////////
+// Batch related tests
+///////////////////////////////////////////////////////
+type NewAuctionOf = NewAuction<AccountId, BlockNumber, GeneralInformationContainer>;
+
+// An auction for a slot of terminal 8 with `teu` TEU at `timestamp`, as a batch entry.
+fn batch_entry(timestamp: u64, teu: u64) -> NewAuctionOf {
+    NewAuction {
+        terminal: 8,
+        core: AuctionCoreInfo {
+            timestamp,
+            cargo: (teu, teu),
+        },
+        start: 1,
+        end: 100,
+    }
+}
+
+// An update moving the end of auction `id` to `end`, as a batch entry.
+fn batch_update(
+    id: AuctionId,
+    end: BlockNumber,
+) -> AuctionUpdate<AuctionId, BlockNumber, GeneralInformationContainer> {
+    AuctionUpdate {
+        id,
+        timestamp: None,
+        num_con: None,
+        num_teu: None,
+        start: None,
+        end: Some(end),
+    }
+}
+
+#[test]
+fn batch_creates_consecutive_auctions() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let first = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::ext_new_auctions_batch(
+            Origin::signed(1),
+            vec![batch_entry(BERTH_TIME, 10), batch_entry(BERTH_TIME, 20)]
+        ));
+
+        assert_eq!(AuctionModule::auctions_index(), first + 2);
+        assert_eq!(AuctionModule::auctions(first).unwrap().creator, 1);
+        assert_eq!(
+            AuctionModule::auctions(first + 1).unwrap().core.cargo,
+            (20, 20)
+        );
+        assert!(AuctionModule::timeline(8, first + 1).is_some());
+        assert!(has_event(RawEvent::AuctionsCreated(1, first, first + 1)));
+    })
+}
+
+#[test]
+fn batch_length_is_bounded() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_noop!(
+            AuctionModule::ext_new_auctions_batch(Origin::signed(1), vec![]),
+            Error::<AuctionTestRuntime>::InvalidBatchLength
+        );
+        assert_noop!(
+            AuctionModule::ext_new_auctions_batch(
+                Origin::signed(1),
+                vec![batch_entry(BERTH_TIME, 10); 4]
+            ),
+            Error::<AuctionTestRuntime>::InvalidBatchLength
+        );
+        assert_noop!(
+            AuctionModule::ext_update_auctions_batch(Origin::signed(1), vec![]),
+            Error::<AuctionTestRuntime>::InvalidBatchLength
+        );
+    })
+}
+
+#[test]
+fn batch_weight_grows_with_its_length() {
+    let one = Call::<AuctionTestRuntime>::ext_new_auctions_batch(vec![batch_entry(BERTH_TIME, 10)]);
+    let three =
+        Call::<AuctionTestRuntime>::ext_new_auctions_batch(vec![batch_entry(BERTH_TIME, 10); 3]);
+    assert_eq!(
+        three.get_dispatch_info().weight,
+        3 * one.get_dispatch_info().weight
+    );
+}
+
+#[test]
+fn invalid_batch_entry_rejects_the_batch() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_parameters(
+            Origin::root(),
+            test_parameters()
+        ));
+        let short = NewAuction {
+            end: 5,
+            ..batch_entry(BERTH_TIME, 10)
+        };
+        assert_noop!(
+            AuctionModule::ext_new_auctions_batch(
+                Origin::signed(1),
+                vec![batch_entry(BERTH_TIME, 10), short]
+            ),
+            Error::<AuctionTestRuntime>::AuctionTooShort
+        );
+    })
+}
+
+#[test]
+fn failing_batch_entry_rolls_back_the_batch() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(AuctionModule::set_parameters(
+            Origin::root(),
+            test_parameters()
+        ));
+        assert_ok!(AuctionModule::set_terminal_capacity(
+            Origin::signed(8),
+            Some(capacity(1, 100))
+        ));
+
+        // The second slot overlaps the first at a terminal with a single berth, neither auction is
+        // created and neither deposit nor fee is charged.
+        assert_noop!(
+            AuctionModule::ext_new_auctions_batch(
+                Origin::signed(1),
+                vec![
+                    batch_entry(BERTH_TIME, 10),
+                    batch_entry(BERTH_TIME + 500, 10)
+                ]
+            ),
+            Error::<AuctionTestRuntime>::BerthConflict
+        );
+
+        // The creator pays the deposit and fee of every auction.
+        let free = Balances::free_balance(&1);
+        assert_ok!(AuctionModule::ext_new_auctions_batch(
+            Origin::signed(1),
+            vec![
+                batch_entry(BERTH_TIME, 10),
+                batch_entry(BERTH_TIME + 1000, 10)
+            ]
+        ));
+        assert_eq!(Balances::free_balance(&1), free - 2 * (2000 + 100));
+        assert_eq!(Balances::reserved_balance(&1), 2 * 2000);
+    })
+}
+
+#[test]
+fn batch_updates_every_auction() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let first = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::ext_new_auctions_batch(
+            Origin::signed(1),
+            vec![batch_entry(BERTH_TIME, 10), batch_entry(BERTH_TIME, 10)]
+        ));
+
+        assert_noop!(
+            AuctionModule::ext_update_auctions_batch(
+                Origin::signed(2),
+                vec![batch_update(first, 200)]
+            ),
+            Error::<AuctionTestRuntime>::PermissionError
+        );
+        assert_ok!(AuctionModule::ext_update_auctions_batch(
+            Origin::signed(1),
+            vec![batch_update(first, 200), batch_update(first + 1, 300)]
+        ));
+        assert_eq!(AuctionModule::auctions(first).unwrap().end, Some(200));
+        assert_eq!(AuctionModule::auctions(first + 1).unwrap().end, Some(300));
+        assert!(has_event(RawEvent::AuctionsUpdated(
+            1,
+            vec![first, first + 1]
+        )));
+    })
+}
+
+#[test]
+fn failing_batch_update_rolls_back_the_batch() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let first = AuctionModule::auctions_index();
+        assert_ok!(AuctionModule::ext_new_auctions_batch(
+            Origin::signed(1),
+            vec![batch_entry(BERTH_TIME, 10), batch_entry(BERTH_TIME, 10)]
+        ));
+        assert_ok!(AuctionModule::bid(Origin::signed(2), first + 1, 1000));
+
+        // The second auction has a bid, so the end of the first doesn't move either.
+        assert_noop!(
+            AuctionModule::ext_update_auctions_batch(
+                Origin::signed(1),
+                vec![batch_update(first, 200), batch_update(first + 1, 300)]
+            ),
+            Error::<AuctionTestRuntime>::CannotUpdateActiveAuction
+        );
+        assert_eq!(AuctionModule::auctions(first).unwrap().end, Some(100));
+    })
+}
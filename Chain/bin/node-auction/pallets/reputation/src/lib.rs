@@ -0,0 +1,425 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+////////////////////////////////////////////
+///////////////// Imports //////////////////
+////////////////////////////////////////////
+
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::Parameter,
+    ensure,
+    sp_runtime::{
+        traits::{Member, Saturating, Zero},
+        DispatchResult, RuntimeDebug,
+    },
+    traits::{BalanceStatus, Currency, Get, ReservableCurrency, UnixTime},
+};
+
+use parity_scale_codec::{Decode, Encode};
+
+use frame_system::{self as system, ensure_signed};
+
+use auction_traits::auction::Auction;
+
+////////////////////////////////////////////
+////////////////// Tests ///////////////////
+////////////////////////////////////////////
+#[cfg(test)]
+mod tests;
+
+////////////////////////////////////////////
+///////// Reputation Module Code ///////////
+////////////////////////////////////////////
+
+// The currency type used in this module.
+pub type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// Reputation score of an account. Scores saturate at both ends.
+pub type Score = u32;
+
+/// The outcome of a won time-slot as attested by either side of the slot.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum SlotOutcome {
+    /// The barge showed up and the terminal served it during the slot.
+    Honoured,
+    /// The barge didn't show up for the slot, only the terminal can attest to this.
+    NoShow,
+    /// The terminal didn't honour the slot, only the barge can attest to this.
+    Refused,
+}
+
+#[cfg(feature = "std")]
+auction_traits::describe_enum!(SlotOutcome {
+    Honoured,
+    NoShow,
+    Refused
+});
+
+/// A won time-slot awaiting attestation. The deposit is reserved from the barge once the auction
+/// settles and is either released or slashed once the terminal attests.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq))]
+#[derive(Clone, RuntimeDebug, Encode, Decode)]
+pub struct SlotRecord<AccountId, Balance, BlockNumber> {
+    /// Terminal which issued the slot.
+    pub terminal: AccountId,
+    /// Barge which won the slot.
+    pub barge: AccountId,
+    /// Deposit reserved from the barge.
+    pub deposit: Balance,
+    /// Outcome as attested by the terminal.
+    pub terminal_attestation: Option<SlotOutcome>,
+    /// Outcome as attested by the barge.
+    pub barge_attestation: Option<SlotOutcome>,
+    /// Block after which the slot can no longer be attested.
+    pub expiry: BlockNumber,
+}
+
+#[cfg(feature = "std")]
+auction_traits::describe_struct!(SlotRecord<AccountId, Balance, BlockNumber> {
+    terminal: AccountId,
+    barge: AccountId,
+    deposit: Balance,
+    terminal_attestation: Option<SlotOutcome>,
+    barge_attestation: Option<SlotOutcome>,
+    expiry: BlockNumber,
+});
+
+pub trait Trait: system::Trait + Sized {
+    // The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    // Definition of this module's currency, deposits are reserved in this currency.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    // Definition of the auction id number, this has to match the auction module's id.
+    type AuctionId: Parameter + Member + Default + Copy;
+    // Definition of the auction module's general information type.
+    type GeneralInformationContainer: Parameter + Member + Default + Copy;
+    // Definition of the auction module's error type.
+    type AuctionError;
+    // The auction module this module is tied to, used to look up the creator of an auction.
+    type Auction: Auction<
+        Self::AccountId,
+        Self::BlockNumber,
+        Self::GeneralInformationContainer,
+        Self::AuctionError,
+        AuctionId = Self::AuctionId,
+    >;
+    // Deposit reserved from the winning barge, slashed to the terminal on a no-show.
+    type SlotDeposit: Get<BalanceOf<Self>>;
+    // Score of an account which hasn't been attested yet.
+    type InitialScore: Get<Score>;
+    // Score added to an account for each honoured slot.
+    type HonouredReward: Get<Score>;
+    // Score removed from an account for each no-show or refused slot.
+    type NoShowPenalty: Get<Score>;
+    // Number of blocks after settlement during which a slot can be attested.
+    type AttestationPeriod: Get<Self::BlockNumber>;
+    // Source of the current time, a slot can't be reported as missed before its time.
+    type UnixTime: UnixTime;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as ReputationModule {
+        // Won time-slots which are awaiting attestation.
+        pub Slots get(fn slots): map hasher(twox_64_concat) T::AuctionId => Option<SlotRecord<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+        // Each block, the slots expiring on said block are looked up in this item, much like the
+        // auction module's AuctionEndTime.
+        pub SlotExpiry get(fn slot_expiry): double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) T::AuctionId => Option<bool>;
+        // Score per account, accounts without an entry have the initial score.
+        pub Scores get(fn scores): map hasher(blake2_128_concat) T::AccountId => Option<Score>;
+        // Minimum score a bidder needs to bid on an auction, set by the auction's creator.
+        pub RequiredScore get(fn required_score): map hasher(twox_64_concat) T::AuctionId => Option<Score>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+        AuctionId = <T as Trait>::AuctionId,
+    {
+        // Called when a won slot is recorded for attestation. (Auction, Terminal, Barge, Deposit)
+        SlotRecorded(AuctionId, AccountId, AccountId, Balance),
+        // Called when either side of a slot attests to its outcome.
+        SlotAttested(AuctionId, AccountId, SlotOutcome),
+        // Called when a slot expires without being fully attested.
+        SlotExpired(AuctionId),
+        // Called when the deposit of a barge is released.
+        DepositReleased(AuctionId, AccountId, Balance),
+        // Called when the deposit of a barge is slashed to the terminal. (Auction, Barge, Terminal, Deposit)
+        DepositSlashed(AuctionId, AccountId, AccountId, Balance),
+        // Called when the score of an account changes.
+        ScoreChanged(AccountId, Score),
+        // Called when the creator of an auction sets the auction's required score.
+        RequiredScoreSet(AuctionId, Option<Score>),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        // Thrown when an auction which doesn't exist is called.
+        AuctionNotExist,
+        // Thrown when a slot which isn't awaiting attestation is attested.
+        SlotNotExist,
+        // Thrown when any given initiator tries to perform an action on some object which doesn't
+        // belong to said initiator.
+        PermissionError,
+        // Thrown when either side of a slot attests twice.
+        AlreadyAttested,
+        // Thrown when a terminal attests a refusal or a barge attests a no-show.
+        InvalidOutcome,
+        // Thrown when a no-show or a refusal is attested before the slot's time.
+        SlotNotStarted,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        const SlotDeposit: BalanceOf<T> = T::SlotDeposit::get();
+        const InitialScore: Score = T::InitialScore::get();
+        const AttestationPeriod: T::BlockNumber = T::AttestationPeriod::get();
+
+        #[weight = 10_000]
+        pub fn set_required_score(origin, id: T::AuctionId, score: Option<Score>) -> DispatchResult {
+            let initiator = ensure_signed(origin)?;
+            let auction = T::Auction::auction_info(id).ok_or(Error::<T>::AuctionNotExist)?;
+            ensure!(auction.creator == initiator, Error::<T>::PermissionError);
+
+            match score {
+                Some(score) => <RequiredScore<T>>::insert(id, score),
+                None => <RequiredScore<T>>::remove(id),
+            }
+
+            Self::deposit_event(RawEvent::RequiredScoreSet(id, score));
+            Ok(())
+        }
+
+        #[weight = 10_000]
+        pub fn attest(origin, id: T::AuctionId, outcome: SlotOutcome) -> DispatchResult {
+            let initiator = ensure_signed(origin)?;
+            let mut slot = <Slots<T>>::get(id).ok_or(Error::<T>::SlotNotExist)?;
+
+            if initiator == slot.terminal {
+                // The terminal attests whether the barge showed up.
+                ensure!(slot.terminal_attestation.is_none(), Error::<T>::AlreadyAttested);
+                match outcome {
+                    SlotOutcome::Honoured => {
+                        Self::release_deposit(id, &mut slot);
+                        Self::reward(&slot.barge);
+                    }
+                    SlotOutcome::NoShow => {
+                        Self::ensure_slot_started(id)?;
+                        Self::slash_deposit(id, &mut slot);
+                        Self::penalize(&slot.barge);
+                    }
+                    SlotOutcome::Refused => return Err(Error::<T>::InvalidOutcome.into()),
+                }
+                slot.terminal_attestation = Some(outcome);
+            } else if initiator == slot.barge {
+                // The barge attests whether the terminal honoured the slot.
+                ensure!(slot.barge_attestation.is_none(), Error::<T>::AlreadyAttested);
+                match outcome {
+                    SlotOutcome::Honoured => Self::reward(&slot.terminal),
+                    SlotOutcome::Refused => {
+                        Self::ensure_slot_started(id)?;
+                        Self::penalize(&slot.terminal);
+                    }
+                    SlotOutcome::NoShow => return Err(Error::<T>::InvalidOutcome.into()),
+                }
+                slot.barge_attestation = Some(outcome);
+            } else {
+                return Err(Error::<T>::PermissionError.into());
+            }
+
+            Self::deposit_event(RawEvent::SlotAttested(id, initiator, outcome));
+
+            // Once both sides have attested, the slot is done with.
+            if slot.terminal_attestation.is_some() && slot.barge_attestation.is_some() {
+                <SlotExpiry<T>>::remove(&slot.expiry, id);
+                <Slots<T>>::remove(id);
+            } else {
+                <Slots<T>>::insert(id, slot);
+            }
+
+            Ok(())
+        }
+
+        fn on_finalize(now: T::BlockNumber) {
+            Self::_on_finalize(now);
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    ////////////////////////////////////////////
+    /////////////////// API ////////////////////
+    ////////////////////////////////////////////
+
+    /// Returns the score of `who`, accounts which haven't been attested yet have the initial
+    /// score.
+    pub fn score(who: &T::AccountId) -> Score {
+        <Scores<T>>::get(who).unwrap_or_else(T::InitialScore::get)
+    }
+
+    /// Returns whether `who` is allowed to bid on auction `id`. Auctions without a required score
+    /// accept everyone.
+    pub fn meets_required_score(id: T::AuctionId, who: &T::AccountId) -> bool {
+        match <RequiredScore<T>>::get(id) {
+            Some(required) => Self::score(who) >= required,
+            None => true,
+        }
+    }
+
+    /// Reserves the deposit `barge` puts down for the slot it is about to win. This is meant to
+    /// be called from the auction module's handler before the winning bid is transferred, a
+    /// barge which can't afford the deposit is refused the slot.
+    pub fn reserve_slot_deposit(barge: &T::AccountId) -> DispatchResult {
+        T::Currency::reserve(barge, T::SlotDeposit::get())
+    }
+
+    /// Releases the deposit reserved by `reserve_slot_deposit` when the winning bid couldn't be
+    /// transferred after all.
+    pub fn unreserve_slot_deposit(barge: &T::AccountId) {
+        T::Currency::unreserve(barge, T::SlotDeposit::get());
+    }
+
+    /// Records a settled auction as a won slot awaiting attestation. This is meant to be called
+    /// from the auction module's handler once the winning bid has been transferred, the deposit
+    /// of the barge having been reserved with `reserve_slot_deposit`.
+    pub fn on_slot_won(id: T::AuctionId, terminal: T::AccountId, barge: T::AccountId) {
+        let deposit = T::SlotDeposit::get();
+        let expiry =
+            <system::Module<T>>::block_number().saturating_add(T::AttestationPeriod::get());
+        <Slots<T>>::insert(
+            id,
+            SlotRecord {
+                terminal: terminal.clone(),
+                barge: barge.clone(),
+                deposit,
+                terminal_attestation: None,
+                barge_attestation: None,
+                expiry,
+            },
+        );
+        <SlotExpiry<T>>::insert(&expiry, id, true);
+        <RequiredScore<T>>::remove(id);
+
+        Self::deposit_event(RawEvent::SlotRecorded(id, terminal, barge, deposit));
+    }
+
+    /// Moves the slot won in auction `id` over to the barge which bought it on the secondary
+    /// market. The seller's deposit is released and the buyer's reserved instead, the attestation
+    /// period is left as it was.
+    pub fn on_slot_transferred(id: T::AuctionId, barge: T::AccountId) {
+        let mut slot = match <Slots<T>>::get(id) {
+            Some(slot) => slot,
+            None => return,
+        };
+        Self::release_deposit(id, &mut slot);
+
+        slot.deposit = T::SlotDeposit::get();
+        if let Err(_) = T::Currency::reserve(&barge, slot.deposit) {
+            sp_runtime::print("ERROR -- ON_SLOT_TRANSFERRED>>RESERVE");
+            slot.deposit = Zero::zero();
+        }
+        slot.barge = barge.clone();
+        let (terminal, deposit) = (slot.terminal.clone(), slot.deposit);
+        <Slots<T>>::insert(id, slot);
+
+        Self::deposit_event(RawEvent::SlotRecorded(id, terminal, barge, deposit));
+    }
+
+    /// Cleans up after an auction which ended without a winner.
+    pub fn on_auction_closed(id: T::AuctionId) {
+        <RequiredScore<T>>::remove(id);
+    }
+
+    ////////////////////////////////////////////
+    ///////////////// Helpers //////////////////
+    ////////////////////////////////////////////
+
+    // A slot which is no longer held in the auction module has either passed or is gone, in both
+    // cases there's nothing left to wait for.
+    fn ensure_slot_started(id: T::AuctionId) -> DispatchResult {
+        if let Some(time) = T::Auction::won_slot_time(id) {
+            ensure!(
+                T::UnixTime::now().as_secs() >= time,
+                Error::<T>::SlotNotStarted
+            );
+        }
+        Ok(())
+    }
+
+    // Both helpers empty the slot's deposit once it's gone, a slot which stays stored would
+    // otherwise release it again on a resale or on expiry.
+    fn release_deposit(
+        id: T::AuctionId,
+        slot: &mut SlotRecord<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+    ) {
+        if slot.deposit.is_zero() {
+            return;
+        }
+        T::Currency::unreserve(&slot.barge, slot.deposit);
+        Self::deposit_event(RawEvent::DepositReleased(
+            id,
+            slot.barge.clone(),
+            slot.deposit,
+        ));
+        slot.deposit = Zero::zero();
+    }
+
+    fn slash_deposit(
+        id: T::AuctionId,
+        slot: &mut SlotRecord<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+    ) {
+        if slot.deposit.is_zero() {
+            return;
+        }
+        // The deposit compensates the terminal for the unused slot.
+        if let Err(_) = T::Currency::repatriate_reserved(
+            &slot.barge,
+            &slot.terminal,
+            slot.deposit,
+            BalanceStatus::Free,
+        ) {
+            sp_runtime::print("ERROR -- SLASH_DEPOSIT>>REPATRIATE_RESERVED");
+            return;
+        }
+        Self::deposit_event(RawEvent::DepositSlashed(
+            id,
+            slot.barge.clone(),
+            slot.terminal.clone(),
+            slot.deposit,
+        ));
+        slot.deposit = Zero::zero();
+    }
+
+    fn reward(who: &T::AccountId) {
+        let score = Self::score(who).saturating_add(T::HonouredReward::get());
+        <Scores<T>>::insert(who, score);
+        Self::deposit_event(RawEvent::ScoreChanged(who.clone(), score));
+    }
+
+    fn penalize(who: &T::AccountId) {
+        let score = Self::score(who).saturating_sub(T::NoShowPenalty::get());
+        <Scores<T>>::insert(who, score);
+        Self::deposit_event(RawEvent::ScoreChanged(who.clone(), score));
+    }
+
+    fn _on_finalize(now: T::BlockNumber) {
+        for (id, _) in <SlotExpiry<T>>::drain_prefix(&now) {
+            if let Some(mut slot) = <Slots<T>>::take(&id) {
+                // Slots which the terminal didn't attest to are given the benefit of the doubt,
+                // the deposit is released without changing any score. Attested slots only hold
+                // a deposit which couldn't be slashed.
+                Self::release_deposit(id, &mut slot);
+                Self::deposit_event(RawEvent::SlotExpired(id));
+            }
+        }
+    }
+}
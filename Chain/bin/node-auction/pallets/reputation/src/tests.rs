@@ -0,0 +1,550 @@
+// Creating mock runtime here
+
+use crate::*;
+use auction_traits::auction::*;
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
+    traits::{OnFinalize, OnInitialize, UnixTime},
+};
+use frame_system::{self as system};
+use pallet_auction::{self as auction};
+use pallet_balances::{self as balances};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+impl_outer_origin! {
+    pub enum Origin for ReputationTestRuntime {}
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ReputationTestRuntime;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: u32 = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+
+    pub const ExistentialDeposit: u64 = 1;
+
+    pub const SlotDeposit: u64 = 1000;
+    pub const InitialScore: Score = 100;
+    pub const HonouredReward: Score = 1;
+    pub const NoShowPenalty: Score = 10;
+    pub const AttestationPeriod: u64 = 20;
+
+    pub const MaximumParameters: AuctionParameters<u64, u64> = AuctionParameters {
+        min_duration: 0,
+        max_horizon: 0,
+        creation_deposit: 0,
+        creation_fee: 0,
+        soft_close_window: 0,
+    };
+    pub const ResaleCutoff: u64 = 0;
+    pub const ResaleFee: Perbill = Perbill::zero();
+    pub const MaxRunnerUps: u32 = 0;
+    pub const ConfirmationPeriod: u64 = 0;
+    pub const TerminalCommission: Perbill = Perbill::zero();
+    pub const PlatformFee: Perbill = Perbill::zero();
+    pub const PlatformAccount: AccountId = 0;
+    pub const BurnShare: Perbill = Perbill::zero();
+    pub const DefaultSlotDuration: u64 = 3600;
+    pub const MaxBatchLength: u32 = 16;
+    pub const MaxTerminalWindows: u32 = 64;
+}
+
+pub type AccountId = u64;
+pub type Balance = u64;
+pub type BlockNumber = u64;
+pub type AuctionId = u64;
+pub type GeneralInformationContainer = u64;
+
+impl system::Trait for ReputationTestRuntime {
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ReputationTestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type ModuleToIndex = ();
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type DbWeight = ();
+    type BaseCallFilter = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type SystemWeightInfo = ();
+}
+
+impl balances::Trait for ReputationTestRuntime {
+    type Balance = Balance;
+    type Event = ReputationTestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<ReputationTestRuntime>;
+    type WeightInfo = ();
+}
+
+// Ties the auction module to the reputation module, just like the runtime's handler.
+pub struct Handler;
+
+impl AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> for Handler {
+    fn on_new_bid(
+        _now: BlockNumber,
+        id: AuctionId,
+        new_bid: (AccountId, Balance),
+        _last_bid: Option<(AccountId, Balance)>,
+    ) -> OnNewBidResult<BlockNumber> {
+        OnNewBidResult {
+            accept_bid: ReputationModule::meets_required_score(id, &new_bid.0),
+            auction_end: None,
+        }
+    }
+
+    fn on_auction_ended(
+        id: AuctionId,
+        recipients: (AccountId, AccountId),
+        winner: Option<(AccountId, Balance)>,
+    ) -> bool {
+        if let Some(winner) = winner {
+            // A resold slot keeps the record of the auction it was first won in.
+            let resale = AuctionModule::resale_of(id).is_some();
+            if !resale && ReputationModule::reserve_slot_deposit(&winner.0).is_err() {
+                Balances::unreserve(&winner.0, winner.1);
+                return false;
+            }
+            if let Ok(_) = AuctionModule::settle_bid(id, &winner.0, &recipients, winner.1) {
+                if !resale {
+                    ReputationModule::on_slot_won(id, recipients.1, winner.0);
+                }
+                return true;
+            }
+            if !resale {
+                ReputationModule::unreserve_slot_deposit(&winner.0);
+            }
+        } else {
+            ReputationModule::on_auction_closed(id);
+        }
+        false
+    }
+
+    fn on_slot_transferred(slot: AuctionId, _from: AccountId, to: AccountId) {
+        ReputationModule::on_slot_transferred(slot, to);
+    }
+}
+
+thread_local! {
+    static NOW: std::cell::Cell<u64> = std::cell::Cell::new(0);
+}
+
+// Clock of the mock runtime in seconds, starting out at the epoch.
+pub struct Epoch;
+
+impl Epoch {
+    fn set(secs: u64) {
+        NOW.with(|now| now.set(secs));
+    }
+}
+
+impl UnixTime for Epoch {
+    fn now() -> core::time::Duration {
+        core::time::Duration::from_secs(NOW.with(|now| now.get()))
+    }
+}
+
+impl auction::Trait for ReputationTestRuntime {
+    type Event = ReputationTestEvent;
+    type Currency = balances::Module<Self>;
+    type AuctionId = AuctionId;
+    type Handler = Handler;
+    type GeneralInformationContainer = GeneralInformationContainer;
+    type ParametersOrigin = system::EnsureRoot<AccountId>;
+    type MaximumParameters = MaximumParameters;
+    type UnixTime = Epoch;
+    type ResaleCutoff = ResaleCutoff;
+    type ResaleFee = ResaleFee;
+    type MaxRunnerUps = MaxRunnerUps;
+    type ConfirmationPeriod = ConfirmationPeriod;
+    type TerminalCommission = TerminalCommission;
+    type PlatformFee = PlatformFee;
+    type PlatformAccount = PlatformAccount;
+    type BurnShare = BurnShare;
+    type DefaultSlotDuration = DefaultSlotDuration;
+    type MaxBatchLength = MaxBatchLength;
+    type MaxTerminalWindows = MaxTerminalWindows;
+}
+
+impl Trait for ReputationTestRuntime {
+    type Event = ReputationTestEvent;
+    type Currency = balances::Module<Self>;
+    type AuctionId = AuctionId;
+    type GeneralInformationContainer = GeneralInformationContainer;
+    type AuctionError = auction::Error<Self>;
+    type Auction = AuctionModule;
+    type SlotDeposit = SlotDeposit;
+    type InitialScore = InitialScore;
+    type HonouredReward = HonouredReward;
+    type NoShowPenalty = NoShowPenalty;
+    type AttestationPeriod = AttestationPeriod;
+    type UnixTime = Epoch;
+}
+
+pub type System = system::Module<ReputationTestRuntime>;
+pub type Balances = balances::Module<ReputationTestRuntime>;
+pub type AuctionModule = auction::Module<ReputationTestRuntime>;
+pub type ReputationModule = Module<ReputationTestRuntime>;
+
+mod reputation_events {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum ReputationTestEvent for ReputationTestRuntime {
+        reputation_events<T>,
+        auction<T>,
+        system<T>,
+        balances<T>,
+    }
+}
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        AuctionModule::on_finalize(System::block_number());
+        ReputationModule::on_finalize(System::block_number());
+        System::on_finalize(System::block_number());
+        System::set_block_number(System::block_number() + 1);
+
+        AuctionModule::on_initialize(System::block_number());
+        System::on_initialize(System::block_number());
+    }
+}
+
+// Timestamp of the slot auctioned off in auction 0.
+const SLOT_TIME: u64 = 1594471764;
+
+// Barges 1-4, terminal 5. Auction 0 is created by barge 1 for terminal 5 and ends on block 10.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<ReputationTestRuntime>()
+        .unwrap();
+    balances::GenesisConfig::<ReputationTestRuntime> {
+        balances: vec![(1, 20000), (2, 20000), (3, 20000), (4, 500), (5, 40000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    auction::GenesisConfig::<ReputationTestRuntime> {
+        terminals: vec![],
+        auctions: vec![GenesisAuction {
+            creator: 1,
+            terminal: 5,
+            core: AuctionCoreInfo {
+                timestamp: SLOT_TIME,
+                cargo: (22, 22),
+            },
+            start: 0,
+            end: 10,
+            bids: vec![],
+        }],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    t.into()
+}
+
+fn last_event() -> RawEvent<AccountId, Balance, AuctionId> {
+    System::events()
+        .into_iter()
+        .filter_map(|e| {
+            if let ReputationTestEvent::reputation_events(inner) = e.event {
+                Some(inner)
+            } else {
+                None
+            }
+        })
+        .last()
+        .unwrap()
+}
+
+// Lets barge `bidder` win auction 0 with `value`.
+fn win_slot(bidder: AccountId, value: Balance) {
+    run_to_block(1);
+    assert_ok!(AuctionModule::bid(Origin::signed(bidder), 0, value));
+    run_to_block(11);
+}
+
+#[test]
+fn settlement_records_slot() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        assert_eq!(
+            ReputationModule::slots(0),
+            Some(SlotRecord {
+                terminal: 5,
+                barge: 2,
+                deposit: 1000,
+                terminal_attestation: None,
+                barge_attestation: None,
+                expiry: 30,
+            })
+        );
+        assert_eq!(Balances::reserved_balance(&2), 1000);
+        assert_eq!(last_event(), RawEvent::SlotRecorded(0, 5, 2, 1000));
+    })
+}
+
+#[test]
+fn resold_slot_moves_deposit() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), 0, 6000));
+        assert_ok!(AuctionModule::buy_slot(Origin::signed(3), 0, 6000));
+
+        let slot = ReputationModule::slots(0).unwrap();
+        assert_eq!(slot.barge, 3);
+        assert_eq!(slot.expiry, 30);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::reserved_balance(&3), 1000);
+        assert_eq!(last_event(), RawEvent::SlotRecorded(0, 5, 3, 1000));
+
+        // The new holder attests in place of the seller.
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(2), 0, SlotOutcome::Honoured),
+            Error::<ReputationTestRuntime>::PermissionError
+        );
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(3),
+            0,
+            SlotOutcome::Honoured
+        ));
+    })
+}
+
+#[test]
+fn attested_deposit_is_not_released_again() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        // Barge 2 holds funds elsewhere, which the resale mustn't touch.
+        assert_ok!(Balances::reserve(&2, 500));
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(5),
+            0,
+            SlotOutcome::Honoured
+        ));
+        assert_eq!(ReputationModule::slots(0).unwrap().deposit, 0);
+        assert_eq!(Balances::reserved_balance(&2), 500);
+
+        assert_ok!(AuctionModule::list_slot(Origin::signed(2), 0, 6000));
+        assert_ok!(AuctionModule::buy_slot(Origin::signed(3), 0, 6000));
+        assert_eq!(Balances::reserved_balance(&2), 500);
+        assert_eq!(Balances::reserved_balance(&3), 1000);
+    })
+}
+
+#[test]
+fn honoured_slot_releases_deposit() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(5),
+            0,
+            SlotOutcome::Honoured
+        ));
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(ReputationModule::score(&2), 101);
+
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(2),
+            0,
+            SlotOutcome::Honoured
+        ));
+        assert_eq!(ReputationModule::score(&5), 101);
+        // Both sides attested, the slot is done with.
+        assert_eq!(ReputationModule::slots(0), None);
+        assert_eq!(ReputationModule::slot_expiry(30, 0), None);
+    })
+}
+
+#[test]
+fn no_show_slashes_deposit() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        Epoch::set(SLOT_TIME);
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(5),
+            0,
+            SlotOutcome::NoShow
+        ));
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(Balances::free_balance(&2), 14000);
+        assert_eq!(Balances::free_balance(&5), 41000);
+        assert_eq!(ReputationModule::score(&2), 90);
+        assert_eq!(last_event(), RawEvent::ScoreChanged(2, 90));
+    })
+}
+
+#[test]
+fn refused_slot_penalizes_terminal() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        Epoch::set(SLOT_TIME);
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(2),
+            0,
+            SlotOutcome::Refused
+        ));
+        assert_eq!(ReputationModule::score(&5), 90);
+        // The deposit stays reserved until the terminal attests or the slot expires.
+        assert_eq!(Balances::reserved_balance(&2), 1000);
+    })
+}
+
+#[test]
+fn attest_checks() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(5), 0, SlotOutcome::Honoured),
+            Error::<ReputationTestRuntime>::SlotNotExist
+        );
+        win_slot(2, 5000);
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(3), 0, SlotOutcome::Honoured),
+            Error::<ReputationTestRuntime>::PermissionError
+        );
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(5), 0, SlotOutcome::Refused),
+            Error::<ReputationTestRuntime>::InvalidOutcome
+        );
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(2), 0, SlotOutcome::NoShow),
+            Error::<ReputationTestRuntime>::InvalidOutcome
+        );
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(5),
+            0,
+            SlotOutcome::Honoured
+        ));
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(5), 0, SlotOutcome::NoShow),
+            Error::<ReputationTestRuntime>::AlreadyAttested
+        );
+    })
+}
+
+#[test]
+fn missed_slot_not_attested_early() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        Epoch::set(SLOT_TIME - 1);
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(5), 0, SlotOutcome::NoShow),
+            Error::<ReputationTestRuntime>::SlotNotStarted
+        );
+        assert_noop!(
+            ReputationModule::attest(Origin::signed(2), 0, SlotOutcome::Refused),
+            Error::<ReputationTestRuntime>::SlotNotStarted
+        );
+        // An honoured slot can be vouched for at any time.
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(5),
+            0,
+            SlotOutcome::Honoured
+        ));
+
+        Epoch::set(SLOT_TIME);
+        assert_ok!(ReputationModule::attest(
+            Origin::signed(2),
+            0,
+            SlotOutcome::Refused
+        ));
+    })
+}
+
+#[test]
+fn unattested_slot_expires() {
+    new_test_ext().execute_with(|| {
+        win_slot(2, 5000);
+        run_to_block(31);
+        assert_eq!(ReputationModule::slots(0), None);
+        assert_eq!(Balances::reserved_balance(&2), 0);
+        assert_eq!(ReputationModule::score(&2), 100);
+        assert!(System::events()
+            .iter()
+            .any(|e| e.event == ReputationTestEvent::reputation_events(RawEvent::SlotExpired(0))));
+    })
+}
+
+#[test]
+fn unaffordable_deposit_refuses_slot() {
+    new_test_ext().execute_with(|| {
+        // Barge 4 can pay for the slot but not for the deposit.
+        win_slot(4, 400);
+        assert_eq!(ReputationModule::slots(0), None);
+        assert_eq!(Balances::reserved_balance(&4), 0);
+        assert_eq!(Balances::free_balance(&4), 500);
+        assert_eq!(Balances::free_balance(&1), 20000);
+    })
+}
+
+#[test]
+fn required_score_rejects_bidders() {
+    new_test_ext().execute_with(|| {
+        // Only the auction's creator can set the required score.
+        assert_noop!(
+            ReputationModule::set_required_score(Origin::signed(2), 0, Some(95)),
+            Error::<ReputationTestRuntime>::PermissionError
+        );
+        assert_noop!(
+            ReputationModule::set_required_score(Origin::signed(1), 7, Some(95)),
+            Error::<ReputationTestRuntime>::AuctionNotExist
+        );
+        assert_ok!(ReputationModule::set_required_score(
+            Origin::signed(1),
+            0,
+            Some(95)
+        ));
+
+        // Barge 3 has a history of no-shows.
+        <Scores<ReputationTestRuntime>>::insert(3, 90);
+
+        run_to_block(1);
+        assert_noop!(
+            AuctionModule::bid(Origin::signed(3), 0, 5000),
+            auction::Error::<ReputationTestRuntime>::BidNotAccepted
+        );
+        assert_ok!(AuctionModule::bid(Origin::signed(2), 0, 5000));
+
+        // The requirement is dropped once the auction settles.
+        run_to_block(11);
+        assert_eq!(ReputationModule::required_score(0), None);
+    })
+}
+
+#[test]
+fn slot_record_described_in_order() {
+    use auction_traits::types::DescribedEncoding;
+
+    let record = SlotRecord {
+        terminal: 1u64,
+        barge: 2u64,
+        deposit: 3u64,
+        terminal_attestation: Some(SlotOutcome::Honoured),
+        barge_attestation: Some(SlotOutcome::Refused),
+        expiry: 4u64,
+    };
+    assert_eq!(record.described_encoding(), record.encode());
+}
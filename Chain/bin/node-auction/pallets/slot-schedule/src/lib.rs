@@ -0,0 +1,470 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+////////////////////////////////////////////
+///////////////// Imports //////////////////
+////////////////////////////////////////////
+
+use frame_support::{
+    debug, decl_error, decl_event, decl_module, decl_storage, ensure,
+    sp_runtime::{
+        offchain::{http, storage::StorageValueRef, Duration},
+        traits::{IdentifyAccount, Saturating},
+        DispatchResult, RuntimeAppPublic, RuntimeDebug,
+    },
+    storage::IterableStorageDoubleMap,
+    traits::Get,
+};
+
+use parity_scale_codec::Encode;
+
+use frame_system::{
+    self as system, ensure_signed,
+    offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+};
+
+use lite_json::json::JsonValue;
+use pallet_auction as auction;
+use sp_core::crypto::KeyTypeId;
+use sp_std::{convert::TryFrom, prelude::*};
+
+////////////////////////////////////////////
+////////////////// Tests ///////////////////
+////////////////////////////////////////////
+#[cfg(test)]
+mod tests;
+
+////////////////////////////////////////////
+/////// Slot Schedule Module Code //////////
+////////////////////////////////////////////
+
+/// Key type of the terminal keys the offchain worker signs with. A terminal inserts its key
+/// through `author_insertKey`, auctions are opened for the account of the key.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"slot");
+
+/// Maximum number of milliseconds a schedule fetch may take.
+const FETCH_TIMEOUT_MS: u64 = 5_000;
+/// Maximum length of the identifier of a slot in the terminal's own system.
+const MAX_SLOT_ID_LENGTH: usize = 64;
+/// Local storage key of the block the schedules were last fetched on.
+const LAST_FETCH_KEY: &[u8] = b"slot-schedule::last-fetch";
+/// Local storage key prefix of the slots submitted per terminal, along with the block they were
+/// submitted on.
+const PENDING_KEY_PREFIX: &[u8] = b"slot-schedule::pending::";
+/// Number of fetch intervals a submitted slot is waited on. A slot whose auction isn't open by
+/// then, e.g. as its transaction failed on-chain, is submitted again.
+const PENDING_FETCHES: u32 = 2;
+
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Terminal keys as used by runtimes with `MultiSignature` transactions.
+    pub struct TerminalId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for TerminalId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+}
+
+/// A slot of a terminal's schedule, as published by the terminal.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ScheduledSlot {
+    /// Identifier of the slot in the terminal's own system, each slot is imported once.
+    pub id: Vec<u8>,
+    /// UNIX timestamp of the slot.
+    pub timestamp: u64,
+    /// Number of containers which can be handled during the slot.
+    pub containers: u64,
+    /// Number of containers in TEU which can be handled during the slot.
+    pub teu: u64,
+    /// Block the slot's auction starts on.
+    pub start: u64,
+    /// Block the slot's auction ends on.
+    pub end: u64,
+}
+
+pub trait Trait: auction::Trait + CreateSignedTransaction<auction::Call<Self>> {
+    // The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    // Crypto of the terminal keys, see `crypto::TerminalId`.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+    // Number of blocks between two fetches of the schedules. Nonces are looked up on-chain on
+    // every fetch, so the interval should comfortably exceed the time it takes to include the
+    // transactions of the previous fetch.
+    type FetchInterval: Get<Self::BlockNumber>;
+    // Maximum length of a schedule endpoint in bytes.
+    type MaxEndpointLength: Get<u32>;
+    // Maximum number of auctions opened per terminal and fetch, the remainder of a schedule is
+    // picked up by the following fetches.
+    type MaxSlotsPerFetch: Get<u32>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as SlotSchedule {
+        // Schedule endpoint per terminal. The offchain worker of a node fetches the schedules of
+        // the terminals whose keys are in its keystore.
+        pub Endpoints get(fn endpoint): map hasher(blake2_128_concat) T::AccountId => Option<Vec<u8>>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+    {
+        // Called when a terminal sets its schedule endpoint.
+        EndpointSet(AccountId, Vec<u8>),
+        // Called when a terminal removes its schedule endpoint.
+        EndpointRemoved(AccountId),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        // Thrown when an endpoint exceeds the maximum endpoint length.
+        EndpointTooLong,
+        // Thrown when an endpoint isn't an http(s) URL.
+        InvalidEndpoint,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        const FetchInterval: T::BlockNumber = T::FetchInterval::get();
+        const MaxEndpointLength: u32 = T::MaxEndpointLength::get();
+        const MaxSlotsPerFetch: u32 = T::MaxSlotsPerFetch::get();
+
+        #[weight = 10_000]
+        pub fn set_endpoint(origin, endpoint: Option<Vec<u8>>) -> DispatchResult {
+            let terminal = ensure_signed(origin)?;
+
+            match endpoint {
+                Some(endpoint) => {
+                    ensure!(
+                        endpoint.len() <= T::MaxEndpointLength::get() as usize,
+                        Error::<T>::EndpointTooLong
+                    );
+                    ensure!(
+                        endpoint.starts_with(b"http://") || endpoint.starts_with(b"https://"),
+                        Error::<T>::InvalidEndpoint
+                    );
+                    <Endpoints<T>>::insert(&terminal, &endpoint);
+                    Self::deposit_event(RawEvent::EndpointSet(terminal, endpoint));
+                }
+                None => {
+                    <Endpoints<T>>::remove(&terminal);
+                    Self::deposit_event(RawEvent::EndpointRemoved(terminal));
+                }
+            }
+
+            Ok(())
+        }
+
+        fn offchain_worker(now: T::BlockNumber) {
+            Self::import_schedules(now);
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    ////////////////////////////////////////////
+    /////////////////// API ////////////////////
+    ////////////////////////////////////////////
+
+    /// Fetches the schedule of every terminal whose key is in the local keystore and opens an
+    /// auction for each slot which hasn't been imported yet. Does nothing if the schedules were
+    /// fetched less than `FetchInterval` blocks ago.
+    pub fn import_schedules(now: T::BlockNumber) {
+        if !Self::acquire_fetch(now) {
+            return;
+        }
+
+        for (public, terminal) in Self::terminal_keys() {
+            let endpoint = match <Endpoints<T>>::get(&terminal) {
+                Some(endpoint) => endpoint,
+                None => continue,
+            };
+            match Self::import_schedule(now, public, &terminal, &endpoint) {
+                Ok(opened) => {
+                    debug::info!("Opened {} auctions for terminal {:?}", opened, terminal)
+                }
+                Err(e) => debug::warn!("Failed to import the schedule of {:?}: {}", terminal, e),
+            }
+        }
+    }
+
+    /// Fetches the schedule at `endpoint` and opens an auction for each new slot on behalf of
+    /// `terminal`. Slots are new unless their auction is open on-chain or was submitted less than
+    /// `PENDING_FETCHES` fetch intervals ago. Returns the number of auctions opened.
+    pub fn import_schedule(
+        now: T::BlockNumber,
+        public: T::Public,
+        terminal: &T::AccountId,
+        endpoint: &[u8],
+    ) -> Result<u32, &'static str> {
+        let url = sp_std::str::from_utf8(endpoint).map_err(|_| "endpoint is not UTF-8")?;
+        let body = Self::fetch(url).map_err(|_| "failed to fetch the schedule")?;
+        let slots = parse_schedule(&body).ok_or("malformed schedule")?;
+
+        let key = [PENDING_KEY_PREFIX, &terminal.encode()].concat();
+        let storage = StorageValueRef::persistent(&key);
+        let mut pending = storage
+            .get::<Vec<(Vec<u8>, T::BlockNumber)>>()
+            .and_then(|pending| pending)
+            .unwrap_or_default();
+        // Submissions are only remembered until their transactions should have been included,
+        // from then on the slot's auction is looked up on-chain.
+        let wait = T::FetchInterval::get().saturating_mul(PENDING_FETCHES.into());
+        pending.retain(|(_, submitted)| submitted.saturating_add(wait) > now);
+        let open = Self::open_auctions(terminal);
+
+        let signer = Signer::<T, T::AuthorityId>::all_accounts().with_filter(vec![public]);
+        let mut opened = 0;
+        for slot in slots {
+            if opened >= T::MaxSlotsPerFetch::get() {
+                break;
+            }
+            if pending.iter().any(|(id, _)| *id == slot.id) {
+                continue;
+            }
+
+            let call = match Self::new_auction_call(now, terminal, &slot) {
+                Ok(call) => call,
+                Err(e) => {
+                    debug::info!("Skipping slot {:?} of {:?}: {}", slot.id, terminal, e);
+                    continue;
+                }
+            };
+            if open.contains(&call) {
+                continue;
+            }
+            let results = signer.send_signed_transaction(|_| call.clone());
+            if results.iter().any(|(_, result)| result.is_ok()) {
+                pending.push((slot.id, now));
+                opened += 1;
+            } else {
+                debug::warn!("Failed to submit the auction of slot {:?}", slot.id);
+            }
+        }
+
+        storage.set(&pending);
+        Ok(opened)
+    }
+
+    /// Validates `slot` and builds the call opening its auction.
+    pub fn new_auction_call(
+        now: T::BlockNumber,
+        terminal: &T::AccountId,
+        slot: &ScheduledSlot,
+    ) -> Result<auction::Call<T>, &'static str> {
+        let block = |n| T::BlockNumber::try_from(n).map_err(|_| "block number out of range");
+        let info =
+            |n| T::GeneralInformationContainer::try_from(n).map_err(|_| "value out of range");
+
+        ensure!(!slot.id.is_empty(), "empty slot id");
+        ensure!(slot.id.len() <= MAX_SLOT_ID_LENGTH, "slot id too long");
+        ensure!(slot.containers > 0, "slot without containers");
+        let start = block(slot.start)?;
+        let end = block(slot.end)?;
+        ensure!(start > now, "auction starts in the past");
+        ensure!(end > start, "auction ends before it starts");
+        <auction::Module<T>>::ensure_schedule(start, Some(end))?;
+
+        let call = auction::Call::ext_new_auction(
+            terminal.clone(),
+            info(slot.containers)?,
+            info(slot.teu)?,
+            info(slot.timestamp)?,
+            start,
+            end,
+        );
+        Ok(call)
+    }
+
+    ////////////////////////////////////////////
+    ///////////////// Helpers //////////////////
+    ////////////////////////////////////////////
+
+    /// Takes the fetch lock in local storage, which is shared between the concurrent runs of the
+    /// offchain worker. Returns whether the schedules should be fetched.
+    fn acquire_fetch(now: T::BlockNumber) -> bool {
+        let last_fetch = StorageValueRef::persistent(LAST_FETCH_KEY);
+        let result = last_fetch.mutate(|last: Option<Option<T::BlockNumber>>| match last {
+            Some(Some(block)) if now < block.saturating_add(T::FetchInterval::get()) => Err(()),
+            _ => Ok(now),
+        });
+
+        match result {
+            Ok(Ok(_)) => true,
+            // Either fetched recently or another run took the lock in the meantime.
+            _ => false,
+        }
+    }
+
+    /// The calls which would open the auctions currently open for slots of `terminal`. A slot
+    /// whose call is among them doesn't need to be submitted.
+    fn open_auctions(terminal: &T::AccountId) -> Vec<auction::Call<T>> {
+        <auction::Timeline<T>>::iter_prefix(terminal)
+            .filter_map(|(id, _)| <auction::Module<T>>::auctions(id))
+            .filter_map(|auction| {
+                Some(auction::Call::ext_new_auction(
+                    auction.slot_origin,
+                    auction.core.cargo.0,
+                    auction.core.cargo.1,
+                    auction.core.timestamp,
+                    auction.start,
+                    auction.end?,
+                ))
+            })
+            .collect()
+    }
+
+    /// The terminal keys in the local keystore along with their accounts.
+    fn terminal_keys() -> Vec<(T::Public, T::AccountId)> {
+        <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all()
+            .into_iter()
+            .map(|key| {
+                let public: T::Public =
+                    <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(
+                        key,
+                    )
+                    .into();
+                let account = public.clone().into_account();
+                (public, account)
+            })
+            .collect()
+    }
+
+    fn fetch(url: &str) -> Result<Vec<u8>, http::Error> {
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT_MS));
+        let pending = http::Request::get(url)
+            .deadline(deadline)
+            .send()
+            .map_err(|_| http::Error::IoError)?;
+        let response = pending
+            .try_wait(deadline)
+            .map_err(|_| http::Error::DeadlineReached)??;
+
+        if response.code != 200 {
+            debug::warn!("Unexpected status code {} from {}", response.code, url);
+            return Err(http::Error::Unknown);
+        }
+        Ok(response.body().collect())
+    }
+}
+
+////////////////////////////////////////////
+///////////////// Parsing //////////////////
+////////////////////////////////////////////
+
+/// Parses a schedule, which is either a JSON array of objects or CSV with a header line. Both
+/// carry the fields of `ScheduledSlot`, e.g.
+///
+/// ```text
+/// [{"id": "B-1", "timestamp": 1596000000, "containers": 20, "teu": 30, "start": 100, "end": 200}]
+/// ```
+///
+/// ```text
+/// id,timestamp,containers,teu,start,end
+/// B-1,1596000000,20,30,100,200
+/// ```
+///
+/// Unknown JSON fields and CSV columns are ignored. Returns `None` if the schedule is malformed.
+pub fn parse_schedule(body: &[u8]) -> Option<Vec<ScheduledSlot>> {
+    let body = sp_std::str::from_utf8(body).ok()?.trim();
+    if body.starts_with('[') {
+        parse_json(body)
+    } else {
+        parse_csv(body)
+    }
+}
+
+fn parse_json(body: &str) -> Option<Vec<ScheduledSlot>> {
+    let entries = match lite_json::parse_json(body).ok()? {
+        JsonValue::Array(entries) => entries,
+        _ => return None,
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let fields = match entry {
+                JsonValue::Object(fields) => fields,
+                _ => return None,
+            };
+            let field = |name: &str| {
+                fields
+                    .iter()
+                    .find(|(key, _)| key.iter().copied().eq(name.chars()))
+                    .map(|(_, value)| value)
+            };
+            let number = |name| match field(name)? {
+                JsonValue::Number(number)
+                    if number.integer >= 0 && number.fraction == 0 && number.exponent == 0 =>
+                {
+                    Some(number.integer as u64)
+                }
+                _ => None,
+            };
+            let id = match field("id")? {
+                JsonValue::String(chars) => chars.iter().fold(Vec::new(), |mut id, c| {
+                    id.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    id
+                }),
+                _ => return None,
+            };
+
+            Some(ScheduledSlot {
+                id,
+                timestamp: number("timestamp")?,
+                containers: number("containers")?,
+                teu: number("teu")?,
+                start: number("start")?,
+                end: number("end")?,
+            })
+        })
+        .collect()
+}
+
+fn parse_csv(body: &str) -> Option<Vec<ScheduledSlot>> {
+    let mut lines = body.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines.next()?.split(',').map(str::trim).collect::<Vec<_>>();
+    let column = |name| header.iter().position(|column| *column == name);
+    let (id, timestamp, containers, teu, start, end) = (
+        column("id")?,
+        column("timestamp")?,
+        column("containers")?,
+        column("teu")?,
+        column("start")?,
+        column("end")?,
+    );
+
+    lines
+        .map(|line| {
+            let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+            if fields.len() != header.len() {
+                return None;
+            }
+            let number = |index: usize| fields[index].parse::<u64>().ok();
+
+            Some(ScheduledSlot {
+                id: fields[id].as_bytes().to_vec(),
+                timestamp: number(timestamp)?,
+                containers: number(containers)?,
+                teu: number(teu)?,
+                start: number(start)?,
+                end: number(end)?,
+            })
+        })
+        .collect()
+}
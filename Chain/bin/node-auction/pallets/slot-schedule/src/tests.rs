@@ -0,0 +1,519 @@
+// Creating mock runtime here
+
+use crate::*;
+use auction_traits::auction::*;
+use frame_support::{
+    assert_noop, assert_ok, impl_outer_dispatch, impl_outer_event, impl_outer_origin,
+    parameter_types, traits::UnixTime, StorageValue,
+};
+use frame_system::{self as system};
+use pallet_balances::{self as balances};
+use parity_scale_codec::Decode;
+use sp_core::{
+    offchain::{
+        testing::{self, OffchainState, PoolState},
+        OffchainExt, TransactionPoolExt,
+    },
+    sr25519,
+    testing::KeyStore,
+    traits::KeystoreExt,
+    H256,
+};
+use sp_runtime::{
+    testing::{Header, TestXt},
+    traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
+    Perbill,
+};
+use std::sync::Arc;
+
+impl_outer_origin! {
+    pub enum Origin for ScheduleTestRuntime {}
+}
+
+impl_outer_dispatch! {
+    pub enum ScheduleTestCall for ScheduleTestRuntime where origin: Origin {
+        auction::AuctionModule,
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ScheduleTestRuntime;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: u32 = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+
+    pub const ExistentialDeposit: u64 = 1;
+
+    pub const FetchInterval: u64 = 10;
+    pub const MaxEndpointLength: u32 = 64;
+    pub const MaxSlotsPerFetch: u32 = 2;
+
+    pub const MaximumParameters: AuctionParameters<u64, u64> = AuctionParameters {
+        min_duration: 1000,
+        max_horizon: 1000,
+        creation_deposit: 0,
+        creation_fee: 0,
+        soft_close_window: 0,
+    };
+    pub const ResaleCutoff: u64 = 0;
+    pub const ResaleFee: Perbill = Perbill::zero();
+    pub const MaxRunnerUps: u32 = 0;
+    pub const ConfirmationPeriod: u64 = 0;
+    pub const TerminalCommission: Perbill = Perbill::zero();
+    pub const PlatformFee: Perbill = Perbill::zero();
+    pub const PlatformAccount: AccountId = 0;
+    pub const BurnShare: Perbill = Perbill::zero();
+    pub const DefaultSlotDuration: u64 = 3600;
+    pub const MaxBatchLength: u32 = 16;
+    pub const MaxTerminalWindows: u32 = 64;
+}
+
+pub type AccountId = sr25519::Public;
+pub type Balance = u64;
+pub type BlockNumber = u64;
+pub type AuctionId = u64;
+pub type Extrinsic = TestXt<ScheduleTestCall, ()>;
+
+impl system::Trait for ScheduleTestRuntime {
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ScheduleTestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type ModuleToIndex = ();
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type DbWeight = ();
+    type BaseCallFilter = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type SystemWeightInfo = ();
+}
+
+impl balances::Trait for ScheduleTestRuntime {
+    type Balance = Balance;
+    type Event = ScheduleTestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<ScheduleTestRuntime>;
+    type WeightInfo = ();
+}
+
+pub struct Handler;
+
+impl AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> for Handler {
+    fn on_new_bid(
+        _now: BlockNumber,
+        _id: AuctionId,
+        _new_bid: (AccountId, Balance),
+        _last_bid: Option<(AccountId, Balance)>,
+    ) -> OnNewBidResult<BlockNumber> {
+        OnNewBidResult {
+            accept_bid: true,
+            auction_end: None,
+        }
+    }
+
+    fn on_auction_ended(
+        _id: AuctionId,
+        _recipients: (AccountId, AccountId),
+        _winner: Option<(AccountId, Balance)>,
+    ) -> bool {
+        false
+    }
+}
+
+pub struct Epoch;
+
+impl UnixTime for Epoch {
+    fn now() -> core::time::Duration {
+        Default::default()
+    }
+}
+
+impl auction::Trait for ScheduleTestRuntime {
+    type Event = ScheduleTestEvent;
+    type Currency = balances::Module<Self>;
+    type AuctionId = AuctionId;
+    type Handler = Handler;
+    type GeneralInformationContainer = u64;
+    type ParametersOrigin = system::EnsureRoot<AccountId>;
+    type MaximumParameters = MaximumParameters;
+    type UnixTime = Epoch;
+    type ResaleCutoff = ResaleCutoff;
+    type ResaleFee = ResaleFee;
+    type MaxRunnerUps = MaxRunnerUps;
+    type ConfirmationPeriod = ConfirmationPeriod;
+    type TerminalCommission = TerminalCommission;
+    type PlatformFee = PlatformFee;
+    type PlatformAccount = PlatformAccount;
+    type BurnShare = BurnShare;
+    type DefaultSlotDuration = DefaultSlotDuration;
+    type MaxBatchLength = MaxBatchLength;
+    type MaxTerminalWindows = MaxTerminalWindows;
+}
+
+impl system::offchain::SigningTypes for ScheduleTestRuntime {
+    type Public = sr25519::Public;
+    type Signature = sr25519::Signature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for ScheduleTestRuntime
+where
+    ScheduleTestCall: From<LocalCall>,
+{
+    type OverarchingCall = ScheduleTestCall;
+    type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for ScheduleTestRuntime
+where
+    ScheduleTestCall: From<LocalCall>,
+{
+    fn create_transaction<C: AppCrypto<Self::Public, Self::Signature>>(
+        call: ScheduleTestCall,
+        _public: sr25519::Public,
+        _account: AccountId,
+        nonce: u64,
+    ) -> Option<(
+        ScheduleTestCall,
+        <Extrinsic as ExtrinsicT>::SignaturePayload,
+    )> {
+        Some((call, (nonce, ())))
+    }
+}
+
+// The test runtime signs with plain sr25519 signatures instead of `MultiSignature`.
+pub struct TestTerminalId;
+
+impl AppCrypto<sr25519::Public, sr25519::Signature> for TestTerminalId {
+    type RuntimeAppPublic = crypto::Public;
+    type GenericSignature = sr25519::Signature;
+    type GenericPublic = sr25519::Public;
+}
+
+impl Trait for ScheduleTestRuntime {
+    type Event = ScheduleTestEvent;
+    type AuthorityId = TestTerminalId;
+    type FetchInterval = FetchInterval;
+    type MaxEndpointLength = MaxEndpointLength;
+    type MaxSlotsPerFetch = MaxSlotsPerFetch;
+}
+
+pub type System = system::Module<ScheduleTestRuntime>;
+pub type AuctionModule = auction::Module<ScheduleTestRuntime>;
+pub type SlotSchedule = Module<ScheduleTestRuntime>;
+
+mod slot_schedule {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum ScheduleTestEvent for ScheduleTestRuntime {
+        slot_schedule<T>,
+        auction<T>,
+        system<T>,
+        balances<T>,
+    }
+}
+
+const ENDPOINT: &str = "http://localhost:8080/schedule";
+
+const SCHEDULE_JSON: &str = r#"[
+    {"id": "B-1", "timestamp": 1596000000, "containers": 20, "teu": 30, "start": 100, "end": 200},
+    {"id": "B-2", "timestamp": 1596003600, "containers": 10, "teu": 10, "start": 150, "end": 250}
+]"#;
+
+const SCHEDULE_CSV: &str = "
+    teu,id,containers,timestamp,start,end,berth
+    30,B-1,20,1596000000,100,200,north
+    10,B-2,10,1596003600,150,250,south
+";
+
+fn slot(
+    id: &str,
+    timestamp: u64,
+    containers: u64,
+    teu: u64,
+    start: u64,
+    end: u64,
+) -> ScheduledSlot {
+    ScheduledSlot {
+        id: id.as_bytes().to_vec(),
+        timestamp,
+        containers,
+        teu,
+        start,
+        end,
+    }
+}
+
+fn new_auction(terminal: AccountId, slot: &ScheduledSlot) -> ScheduleTestCall {
+    ScheduleTestCall::AuctionModule(auction::Call::ext_new_auction(
+        terminal,
+        slot.containers,
+        slot.teu,
+        slot.timestamp,
+        slot.start,
+        slot.end,
+    ))
+}
+
+// Block 1, with a terminal key in the keystore whose account has its endpoint set. Auctions have
+// to last 10 blocks and start within 500 blocks.
+pub fn new_test_ext() -> (
+    sp_io::TestExternalities,
+    AccountId,
+    Arc<parking_lot::RwLock<OffchainState>>,
+    Arc<parking_lot::RwLock<PoolState>>,
+) {
+    let (offchain, offchain_state) = testing::TestOffchainExt::new();
+    let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+    let keystore = KeyStore::new();
+    let terminal = keystore
+        .write()
+        .sr25519_generate_new(KEY_TYPE, Some("//Terminal"))
+        .unwrap();
+
+    let t = system::GenesisConfig::default()
+        .build_storage::<ScheduleTestRuntime>()
+        .unwrap();
+    let mut ext = sp_io::TestExternalities::from(t);
+    ext.register_extension(OffchainExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    ext.register_extension(KeystoreExt(keystore));
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        auction::Parameters::<ScheduleTestRuntime>::put(AuctionParameters {
+            min_duration: 10,
+            max_horizon: 500,
+            creation_deposit: 0,
+            creation_fee: 0,
+            soft_close_window: 0,
+        });
+        assert_ok!(SlotSchedule::set_endpoint(
+            Origin::signed(terminal),
+            Some(ENDPOINT.as_bytes().to_vec())
+        ));
+    });
+
+    (ext, terminal, offchain_state, pool_state)
+}
+
+fn expect_schedule(state: &Arc<parking_lot::RwLock<OffchainState>>, schedule: &str) {
+    state.write().expect_request(testing::PendingRequest {
+        method: "GET".into(),
+        uri: ENDPOINT.into(),
+        response: Some(schedule.as_bytes().to_vec()),
+        sent: true,
+        ..Default::default()
+    });
+}
+
+fn submitted(state: &Arc<parking_lot::RwLock<PoolState>>) -> Vec<Extrinsic> {
+    state
+        .write()
+        .transactions
+        .drain(..)
+        .map(|tx| Extrinsic::decode(&mut &*tx).unwrap())
+        .collect()
+}
+
+#[test]
+fn json_and_csv_schedules_parse_alike() {
+    let expected = vec![
+        slot("B-1", 1596000000, 20, 30, 100, 200),
+        slot("B-2", 1596003600, 10, 10, 150, 250),
+    ];
+
+    assert_eq!(
+        parse_schedule(SCHEDULE_JSON.as_bytes()),
+        Some(expected.clone())
+    );
+    assert_eq!(parse_schedule(SCHEDULE_CSV.as_bytes()), Some(expected));
+    assert_eq!(parse_schedule(b"[]"), Some(vec![]));
+    assert_eq!(
+        parse_schedule(b"id,timestamp,containers,teu,start,end"),
+        Some(vec![])
+    );
+}
+
+#[test]
+fn malformed_schedules_are_rejected() {
+    let malformed: &[&str] = &[
+        "",
+        "{}",
+        r#"[{"id": "B-1", "timestamp": 1, "containers": 2, "teu": 3, "start": 4}]"#,
+        r#"[{"id": "B-1", "timestamp": 1, "containers": 2.5, "teu": 3, "start": 4, "end": 5}]"#,
+        r#"[{"id": "B-1", "timestamp": 1, "containers": -2, "teu": 3, "start": 4, "end": 5}]"#,
+        r#"[{"id": 1, "timestamp": 1, "containers": 2, "teu": 3, "start": 4, "end": 5}]"#,
+        "id,timestamp,containers,teu,start\nB-1,1,2,3,4",
+        "id,timestamp,containers,teu,start,end\nB-1,1,2,3,4",
+        "id,timestamp,containers,teu,start,end\nB-1,1,two,3,4,5",
+    ];
+
+    for schedule in malformed {
+        assert_eq!(parse_schedule(schedule.as_bytes()), None, "{}", schedule);
+    }
+}
+
+#[test]
+fn set_endpoint_validates_the_endpoint() {
+    let (mut ext, terminal, _, _) = new_test_ext();
+    ext.execute_with(|| {
+        assert_eq!(
+            SlotSchedule::endpoint(terminal),
+            Some(ENDPOINT.as_bytes().to_vec())
+        );
+
+        assert_noop!(
+            SlotSchedule::set_endpoint(Origin::signed(terminal), Some(vec![b'h'; 65])),
+            Error::<ScheduleTestRuntime>::EndpointTooLong
+        );
+        assert_noop!(
+            SlotSchedule::set_endpoint(Origin::signed(terminal), Some(b"ftp://schedule".to_vec())),
+            Error::<ScheduleTestRuntime>::InvalidEndpoint
+        );
+
+        assert_ok!(SlotSchedule::set_endpoint(Origin::signed(terminal), None));
+        assert_eq!(SlotSchedule::endpoint(terminal), None);
+    });
+}
+
+#[test]
+fn offchain_worker_opens_auctions_for_valid_slots() {
+    let (mut ext, terminal, offchain_state, pool_state) = new_test_ext();
+    let schedule = "id,timestamp,containers,teu,start,end
+        B-1,1596000000,20,30,100,200
+        B-2,1596000000,20,30,1,200
+        B-3,1596000000,20,30,100,105
+        B-4,1596000000,20,30,900,1000
+        B-5,1596000000,0,0,100,200
+        B-6,1596003600,10,10,150,250";
+    expect_schedule(&offchain_state, schedule);
+
+    ext.execute_with(|| {
+        SlotSchedule::import_schedules(1);
+
+        // Slots starting in the past, too short, beyond the horizon or without containers are
+        // skipped.
+        let txs = submitted(&pool_state);
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].signature, Some((0, ())));
+        assert_eq!(
+            txs[0].call,
+            new_auction(terminal, &slot("B-1", 1596000000, 20, 30, 100, 200))
+        );
+        assert_eq!(txs[1].signature, Some((1, ())));
+        assert_eq!(
+            txs[1].call,
+            new_auction(terminal, &slot("B-6", 1596003600, 10, 10, 150, 250))
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_imports_each_slot_once() {
+    let (mut ext, terminal, offchain_state, pool_state) = new_test_ext();
+
+    ext.execute_with(|| {
+        expect_schedule(&offchain_state, SCHEDULE_JSON);
+        SlotSchedule::import_schedules(1);
+        assert_eq!(submitted(&pool_state).len(), 2);
+
+        // Nothing is fetched within the fetch interval.
+        SlotSchedule::import_schedules(5);
+        assert!(submitted(&pool_state).is_empty());
+
+        let schedule = "id,timestamp,containers,teu,start,end
+            B-1,1596000000,20,30,100,200
+            B-2,1596003600,10,10,150,250
+            B-3,1596007200,5,5,300,400";
+        expect_schedule(&offchain_state, schedule);
+        SlotSchedule::import_schedules(11);
+        let txs = submitted(&pool_state);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(
+            txs[0].call,
+            new_auction(terminal, &slot("B-3", 1596007200, 5, 5, 300, 400))
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_caps_the_slots_per_fetch() {
+    let (mut ext, terminal, offchain_state, pool_state) = new_test_ext();
+    let schedule = "id,timestamp,containers,teu,start,end
+        B-1,1596000000,20,30,100,200
+        B-2,1596003600,10,10,150,250
+        B-3,1596007200,5,5,300,400";
+
+    ext.execute_with(|| {
+        expect_schedule(&offchain_state, schedule);
+        SlotSchedule::import_schedules(1);
+        assert_eq!(submitted(&pool_state).len(), 2);
+
+        // The remainder is picked up by the next fetch.
+        expect_schedule(&offchain_state, schedule);
+        SlotSchedule::import_schedules(11);
+        let txs = submitted(&pool_state);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(
+            txs[0].call,
+            new_auction(terminal, &slot("B-3", 1596007200, 5, 5, 300, 400))
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_resubmits_slots_which_never_made_it_on_chain() {
+    let (mut ext, terminal, offchain_state, pool_state) = new_test_ext();
+    let (b1, b2) = (
+        slot("B-1", 1596000000, 20, 30, 100, 200),
+        slot("B-2", 1596003600, 10, 10, 150, 250),
+    );
+
+    ext.execute_with(|| {
+        expect_schedule(&offchain_state, SCHEDULE_JSON);
+        SlotSchedule::import_schedules(1);
+        assert_eq!(submitted(&pool_state).len(), 2);
+
+        // Only the auction of B-1 is opened, the transaction of B-2 failed.
+        System::set_block_number(5);
+        assert_ok!(AuctionModule::ext_new_auction(
+            Origin::signed(terminal),
+            terminal,
+            b1.containers,
+            b1.teu,
+            b1.timestamp,
+            b1.start,
+            b1.end
+        ));
+
+        // B-2 is awaited for two fetch intervals and submitted again afterwards.
+        expect_schedule(&offchain_state, SCHEDULE_JSON);
+        SlotSchedule::import_schedules(11);
+        assert!(submitted(&pool_state).is_empty());
+
+        expect_schedule(&offchain_state, SCHEDULE_JSON);
+        SlotSchedule::import_schedules(21);
+        let txs = submitted(&pool_state);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].call, new_auction(terminal, &b2));
+    });
+}
@@ -6,18 +6,20 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::Encode;
 use grandpa::fg_primitives;
 use grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::traits::{
-    BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, NumberFor, Saturating, Verify,
+    AccountIdConversion, BlakeTwo256, Block as BlockT, Extrinsic as ExtrinsicT, IdentifyAccount,
+    IdentityLookup, NumberFor, SaturatedConversion, Saturating, Verify,
 };
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys,
     transaction_validity::{TransactionSource, TransactionValidity},
-    ApplyExtrinsicResult, MultiSignature,
+    ApplyExtrinsicResult, ModuleId, MultiSignature,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -27,8 +29,8 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use balances::Call as BalancesCall;
 pub use frame_support::{
-    construct_runtime, parameter_types,
-    traits::{KeyOwnerProofSystem, Randomness},
+    construct_runtime, debug, parameter_types,
+    traits::{KeyOwnerProofSystem, Randomness, ReservableCurrency},
     weights::{
         constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
         IdentityFee, Weight,
@@ -44,6 +46,8 @@ pub use timestamp::Call as TimestampCall;
 use auction_traits::auction::*;
 
 pub use auction;
+pub use reputation;
+pub use slot_schedule;
 
 /// And index to an auction
 pub type AuctionId = u64;
@@ -110,10 +114,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("node-auction"),
     impl_name: create_runtime_str!("node-auction"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 18,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -276,8 +280,8 @@ pub struct Handler;
 impl AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> for Handler {
     fn on_new_bid(
         _now_: BlockNumber,
-        _id_: AuctionId,
-        _new_bid_: (AccountId, Balance),
+        id_: AuctionId,
+        new_bid_: (AccountId, Balance),
         last_bid_: Option<(AccountId, Balance)>,
     ) -> OnNewBidResult<BlockNumber> {
         if let Some(_bid) = last_bid_ {
@@ -290,38 +294,197 @@ impl AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> for Handler {
             //println!("First bid on auction [{:#?}]", id_);
         }
         OnNewBidResult {
-            accept_bid: true,
+            // Auctions can require a minimum reputation score from their bidders.
+            accept_bid: ReputationModule::meets_required_score(id_, &new_bid_.0),
             auction_end: None,
         }
     }
 
     fn on_auction_ended(
-        _id_: AuctionId,
+        id_: AuctionId,
         recipients_: (AccountId, AccountId),
         winner_: Option<(AccountId, Balance)>,
-    ) {
+    ) -> bool {
         if let Some(winner) = winner_ {
-            // Somebody has won, notify
-            if let Ok(_) = AuctionModule::transfer_funds(&winner.0, &recipients_.0, winner.1) {
-                // Funds have successfully been transferred.
+            // Somebody has won, notify. A resold slot already awaits attestation, it is handed
+            // over to the winner once the auction module settles it.
+            let resale = AuctionModule::resale_of(id_).is_some();
+            if !resale && ReputationModule::reserve_slot_deposit(&winner.0).is_err() {
+                // The winner can't put down the slot deposit, its bid is released and the
+                // auction falls back to its next runner-up.
+                Balances::unreserve(&winner.0, winner.1);
+                sp_runtime::print("<RUNTIME>::<AUCTION_ENDED_W_WINNER_NO_DEPOSIT>");
+                return false;
+            }
+            if let Ok(_) = AuctionModule::settle_bid(id_, &winner.0, &recipients_, winner.1) {
+                // Funds have successfully been transferred, the slot now awaits attestation by
+                // the terminal and the barge.
+                if !resale {
+                    ReputationModule::on_slot_won(id_, recipients_.1, winner.0);
+                }
                 sp_runtime::print("<RUNTIME>::<AUCTION_ENDED_W_WINNER_SUCCESS>");
+                return true;
             } else {
-                // Something went wrong, log it.
+                // Something went wrong, log it. The winner's bid has been released, the auction
+                // falls back to its next runner-up.
+                if !resale {
+                    ReputationModule::unreserve_slot_deposit(&winner.0);
+                }
                 sp_runtime::print("<RUNTIME>::<AUCTION_ENDED_W_WINNER_ERROR>");
             }
         } else if let None = winner_ {
             // Nobody has won, notify
+            ReputationModule::on_auction_closed(id_);
             sp_runtime::print("<RUNTIME>::<AUCTION_ENDED_WO_WINNER>");
         }
+        false
+    }
+
+    fn on_slot_transferred(slot: AuctionId, _from: AccountId, to: AccountId) {
+        ReputationModule::on_slot_transferred(slot, to);
     }
 }
 
+parameter_types! {
+    /// Upper bounds for the governance-controlled auction parameters.
+    pub const MaximumAuctionParameters: AuctionParameters<Balance, BlockNumber> = AuctionParameters {
+        min_duration: 7 * DAYS,
+        max_horizon: 90 * DAYS,
+        creation_deposit: 1 << 50,
+        creation_fee: 1 << 40,
+        soft_close_window: DAYS,
+    };
+    /// Won slots can be resold until six hours before they start.
+    pub const ResaleCutoff: u64 = 6 * 60 * 60;
+    pub const ResaleFee: Perbill = Perbill::from_percent(2);
+    /// The three highest outbid bids stay on hold in case the winner defaults.
+    pub const MaxRunnerUps: u32 = 3;
+    /// Winners pay as soon as the auction ends, without confirming first.
+    pub const ConfirmationPeriod: BlockNumber = 0;
+    /// Winning bids are split between the creator, the terminal (5%) and the platform (2%),
+    /// nothing is burned.
+    pub const TerminalCommission: Perbill = Perbill::from_percent(5);
+    pub const PlatformFee: Perbill = Perbill::from_percent(2);
+    pub const BurnShare: Perbill = Perbill::zero();
+    /// The platform fee goes to the account `pallet-treasury` would use.
+    pub PlatformAccount: AccountId = ModuleId(*b"py/trsry").into_account();
+    /// Slots occupy a berth for four hours unless their terminal declares otherwise.
+    pub const DefaultSlotDuration: u64 = 4 * 60 * 60;
+    /// A terminal publishes a day's schedule in a single batch.
+    pub const MaxBatchLength: u32 = 64;
+    /// Bounds the berth check of a full batch to well within a block's weight.
+    pub const MaxTerminalWindows: u32 = 128;
+}
+
 impl auction::Trait for Runtime {
     type Event = Event;
     type Currency = balances::Module<Self>;
     type AuctionId = AuctionId;
     type GeneralInformationContainer = GeneralInformationContainer;
     type Handler = Handler;
+    type ParametersOrigin = system::EnsureRoot<AccountId>;
+    type MaximumParameters = MaximumAuctionParameters;
+    type UnixTime = Timestamp;
+    type ResaleCutoff = ResaleCutoff;
+    type ResaleFee = ResaleFee;
+    type MaxRunnerUps = MaxRunnerUps;
+    type ConfirmationPeriod = ConfirmationPeriod;
+    type TerminalCommission = TerminalCommission;
+    type PlatformFee = PlatformFee;
+    type PlatformAccount = PlatformAccount;
+    type BurnShare = BurnShare;
+    type DefaultSlotDuration = DefaultSlotDuration;
+    type MaxBatchLength = MaxBatchLength;
+    type MaxTerminalWindows = MaxTerminalWindows;
+}
+
+parameter_types! {
+    pub const SlotDeposit: Balance = 10_000;
+    pub const InitialScore: reputation::Score = 100;
+    pub const HonouredReward: reputation::Score = 1;
+    pub const NoShowPenalty: reputation::Score = 10;
+    pub const AttestationPeriod: BlockNumber = 7 * DAYS;
+}
+
+impl reputation::Trait for Runtime {
+    type Event = Event;
+    type Currency = balances::Module<Self>;
+    type AuctionId = AuctionId;
+    type GeneralInformationContainer = GeneralInformationContainer;
+    type AuctionError = auction::Error<Runtime>;
+    type Auction = AuctionModule;
+    type SlotDeposit = SlotDeposit;
+    type InitialScore = InitialScore;
+    type HonouredReward = HonouredReward;
+    type NoShowPenalty = NoShowPenalty;
+    type AttestationPeriod = AttestationPeriod;
+    type UnixTime = Timestamp;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+    Call: From<LocalCall>,
+{
+    fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+        call: Call,
+        public: <Signature as Verify>::Signer,
+        account: AccountId,
+        nonce: Index,
+    ) -> Option<(Call, <UncheckedExtrinsic as ExtrinsicT>::SignaturePayload)> {
+        // Offchain transactions are mortal, they die within `BlockHashCount` blocks.
+        let period = BlockHashCount::get()
+            .checked_next_power_of_two()
+            .map(|c| c / 2)
+            .unwrap_or(2) as u64;
+        // The offchain worker runs on top of block `n`, with the block number set to `n + 1`.
+        let current_block = System::block_number()
+            .saturated_into::<u64>()
+            .saturating_sub(1);
+        let extra: SignedExtra = (
+            system::CheckSpecVersion::<Runtime>::new(),
+            system::CheckTxVersion::<Runtime>::new(),
+            system::CheckGenesis::<Runtime>::new(),
+            system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+            system::CheckNonce::<Runtime>::from(nonce),
+            system::CheckWeight::<Runtime>::new(),
+            transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+        );
+        let raw_payload = generic::SignedPayload::new(call, extra)
+            .map_err(|e| {
+                debug::warn!("Unable to create signed payload: {:?}", e);
+            })
+            .ok()?;
+        let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+        let (call, extra, _) = raw_payload.deconstruct();
+        Some((call, (account, signature, extra)))
+    }
+}
+
+impl system::offchain::SigningTypes for Runtime {
+    type Public = <Signature as Verify>::Signer;
+    type Signature = Signature;
+}
+
+impl<C> system::offchain::SendTransactionTypes<C> for Runtime
+where
+    Call: From<C>,
+{
+    type OverarchingCall = Call;
+    type Extrinsic = UncheckedExtrinsic;
+}
+
+parameter_types! {
+    pub const ScheduleFetchInterval: BlockNumber = 10 * MINUTES;
+    pub const MaxEndpointLength: u32 = 256;
+    pub const MaxSlotsPerFetch: u32 = 16;
+}
+
+impl slot_schedule::Trait for Runtime {
+    type Event = Event;
+    type AuthorityId = slot_schedule::crypto::TerminalId;
+    type FetchInterval = ScheduleFetchInterval;
+    type MaxEndpointLength = MaxEndpointLength;
+    type MaxSlotsPerFetch = MaxSlotsPerFetch;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -340,6 +503,8 @@ construct_runtime!(
         TransactionPayment: transaction_payment::{Module, Storage},
         Sudo: sudo::{Module, Call, Config<T>, Storage, Event<T>},
                 AuctionModule: auction::{Module, Call, Storage, Event<T>, Config<T>},
+                ReputationModule: reputation::{Module, Call, Storage, Event<T>},
+                SlotSchedule: slot_schedule::{Module, Call, Storage, Event<T>},
     }
 );
 
@@ -538,5 +703,21 @@ impl_runtime_apis! {
         ) -> Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInformationContainer>>> {
             AuctionModule::auction_query_formal_all_status(active)
         }
+
+        fn auction_parameters() -> AuctionParameters<u64, BlockNumber> {
+            AuctionModule::auction_parameters()
+        }
+
+        fn auction_parameter_bounds() -> AuctionParameters<u64, BlockNumber> {
+            AuctionModule::auction_parameter_bounds()
+        }
+
+        fn terminal_occupancy(
+            terminal: AccountId,
+            from: GeneralInformationContainer,
+            to: GeneralInformationContainer,
+        ) -> Vec<(AuctionId, BerthWindow<GeneralInformationContainer>)> {
+            AuctionModule::terminal_occupancy(terminal, from, to)
+        }
     }
 }
@@ -278,6 +278,17 @@ impl sp_transaction_pool::TransactionPool for Transactions {
 		unimplemented!()
 	}
 
+	fn inspect(&self) -> Vec<sp_transaction_pool::TransactionDetails<TxHash<Self>>> {
+		unimplemented!()
+	}
+
+	fn inspect_by_hash(
+		&self,
+		_hash: &TxHash<Self>,
+	) -> Option<sp_transaction_pool::TransactionDetails<TxHash<Self>>> {
+		unimplemented!()
+	}
+
 	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
 		unimplemented!()
 	}
@@ -0,0 +1,114 @@
+//! Verifies storage proofs of the auctioning pallet, so apps can read auction state without
+//! trusting the node serving it.
+//!
+//! A proof is the list of trie nodes `state_getReadProof` returns for the storage keys built by
+//! [`auction_key`] and [`queued_bids_key`]. It's checked against the state root of a block header,
+//! which has to come from a source the app trusts, e.g. a header it saw finalized. The serving node
+//! can neither alter an item nor leave it out: a proof which doesn't cover a key fails to verify
+//! rather than reading as an empty item.
+
+use auction_traits::auction::{AuctionInfo, QueuedBid};
+use codec::{Decode, Encode};
+use sp_core::hashing::{twox_128, twox_64};
+use sp_runtime::traits::{BlakeTwo256, Header as HeaderT};
+use sp_state_machine::{read_proof_check, StorageProof};
+
+#[cfg(test)]
+mod tests;
+
+/// Prefix of the auctioning pallet's storage, as named in `decl_storage`.
+pub const PALLET_PREFIX: &[u8] = b"AuctionModule";
+
+/// Everything that makes a proof unusable.
+#[derive(Debug)]
+pub enum Error {
+    /// The proof isn't rooted in the header's state, or doesn't cover the key.
+    InvalidProof(String),
+    /// The proven value doesn't decode into the expected type.
+    Codec(codec::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidProof(e) => write!(f, "Invalid storage proof: {}", e),
+            Error::Codec(e) => write!(f, "Proven value doesn't decode: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Storage key of the auction `id` in `Auctions`.
+pub fn auction_key<AuctionId: Encode>(id: &AuctionId) -> Vec<u8> {
+    map_key(b"Auctions", id)
+}
+
+/// Storage key of the bids queued for auction `id` in `QueuedBids`.
+pub fn queued_bids_key<AuctionId: Encode>(id: &AuctionId) -> Vec<u8> {
+    map_key(b"QueuedBids", id)
+}
+
+/// Key of a `twox_64_concat` map entry of the auctioning pallet.
+fn map_key<K: Encode>(item: &[u8], key: &K) -> Vec<u8> {
+    let key = key.encode();
+    [
+        &twox_128(PALLET_PREFIX)[..],
+        &twox_128(item)[..],
+        &twox_64(&key)[..],
+        &key[..],
+    ]
+    .concat()
+}
+
+/// Reads the value under `key` from `proof`, checking the proof against the state of `header`.
+/// `None` if the proof shows the key is empty.
+pub fn verify_value<Header>(
+    header: &Header,
+    key: &[u8],
+    proof: Vec<Vec<u8>>,
+) -> Result<Option<Vec<u8>>, Error>
+where
+    Header: HeaderT<Hashing = BlakeTwo256>,
+{
+    let mut values =
+        read_proof_check::<BlakeTwo256, _>(*header.state_root(), StorageProof::new(proof), &[key])
+            .map_err(|e| Error::InvalidProof(e.to_string()))?;
+
+    values
+        .remove(key)
+        .ok_or_else(|| Error::InvalidProof("the proof doesn't cover the key".into()))
+}
+
+/// The auction `id` as proven by `proof` at the state of `header`, `None` if it doesn't exist.
+pub fn verify_auction<Header, AuctionId, AccountId, Balance, BlockNumber, GeneralInfo>(
+    header: &Header,
+    id: &AuctionId,
+    proof: Vec<Vec<u8>>,
+) -> Result<Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>, Error>
+where
+    Header: HeaderT<Hashing = BlakeTwo256>,
+    AuctionId: Encode,
+    AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>: Decode,
+{
+    verify_value(header, &auction_key(id), proof)?
+        .map(|data| Decode::decode(&mut &data[..]).map_err(Error::Codec))
+        .transpose()
+}
+
+/// The bids queued for auction `id` as proven by `proof` at the state of `header`.
+pub fn verify_queued_bids<Header, AuctionId, AccountId, Balance>(
+    header: &Header,
+    id: &AuctionId,
+    proof: Vec<Vec<u8>>,
+) -> Result<Vec<QueuedBid<AccountId, Balance, AuctionId>>, Error>
+where
+    Header: HeaderT<Hashing = BlakeTwo256>,
+    AuctionId: Encode,
+    QueuedBid<AccountId, Balance, AuctionId>: Decode,
+{
+    // `QueuedBids` is a value query, an auction without queued bids has no entry.
+    verify_value(header, &queued_bids_key(id), proof)?.map_or(Ok(Vec::new()), |data| {
+        Decode::decode(&mut &data[..]).map_err(Error::Codec)
+    })
+}
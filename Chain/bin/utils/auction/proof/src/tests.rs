@@ -0,0 +1,131 @@
+use crate::*;
+use auction_traits::auction::AuctionCoreInfo;
+use sp_runtime::{generic, traits::BlakeTwo256};
+use sp_state_machine::{prove_read, InMemoryBackend};
+use std::collections::BTreeMap;
+
+type Header = generic::Header<u32, BlakeTwo256>;
+type Auction = AuctionInfo<u64, u128, u32, u64>;
+type Queued = QueuedBid<u64, u128, u64>;
+
+fn auction() -> Auction {
+    AuctionInfo {
+        creator: 1,
+        slot_origin: 5,
+        bid: Some((2, 1000)),
+        core: AuctionCoreInfo {
+            timestamp: 1594471764,
+            cargo: (22, 22),
+        },
+        start: 0,
+        end: Some(50),
+    }
+}
+
+type Storage = BTreeMap<Vec<u8>, Vec<u8>>;
+
+// The state of a chain holding `auction()` under id 0 and a queued bid for auction 1, along with
+// a header committing to it.
+fn state() -> (Storage, Header) {
+    let queued = vec![Queued {
+        bid: (3, 500),
+        auction_id: 1,
+    }];
+    let mut storage = BTreeMap::new();
+    storage.insert(auction_key(&0u64), auction().encode());
+    storage.insert(queued_bids_key(&1u64), queued.encode());
+    storage.insert(b"unrelated".to_vec(), vec![1, 2, 3]);
+
+    let backend = InMemoryBackend::<BlakeTwo256>::from(storage.clone());
+    let header = Header::new(
+        7,
+        Default::default(),
+        *backend.root(),
+        Default::default(),
+        Default::default(),
+    );
+    (storage, header)
+}
+
+fn prove(storage: &Storage, key: Vec<u8>) -> Vec<Vec<u8>> {
+    let backend = InMemoryBackend::<BlakeTwo256>::from(storage.clone());
+    prove_read(backend, &[key]).unwrap().iter_nodes().collect()
+}
+
+#[test]
+fn keys_follow_the_pallet_layout() {
+    let key = auction_key(&3u64);
+    assert_eq!(&key[..16], &twox_128(b"AuctionModule")[..]);
+    assert_eq!(&key[16..32], &twox_128(b"Auctions")[..]);
+    assert_eq!(&key[32..40], &twox_64(&3u64.encode())[..]);
+    assert_eq!(&key[40..], &3u64.encode()[..]);
+    assert_eq!(
+        &queued_bids_key(&3u64)[16..32],
+        &twox_128(b"QueuedBids")[..]
+    );
+}
+
+#[test]
+fn proven_auction_is_decoded() {
+    let (storage, header) = state();
+    let proof = prove(&storage, auction_key(&0u64));
+    assert_eq!(
+        verify_auction::<_, _, u64, u128, u32, u64>(&header, &0u64, proof).unwrap(),
+        Some(auction())
+    );
+}
+
+#[test]
+fn proven_absence_reads_as_none() {
+    let (storage, header) = state();
+    let proof = prove(&storage, auction_key(&9u64));
+    assert_eq!(
+        verify_auction::<_, _, u64, u128, u32, u64>(&header, &9u64, proof).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn proven_queued_bids_are_decoded() {
+    let (storage, header) = state();
+    let proof = prove(&storage, queued_bids_key(&1u64));
+    let bids = verify_queued_bids::<_, _, u64, u128>(&header, &1u64, proof).unwrap();
+    assert_eq!(
+        bids,
+        vec![Queued {
+            bid: (3, 500),
+            auction_id: 1
+        }]
+    );
+
+    let proof = prove(&storage, queued_bids_key(&0u64));
+    let bids = verify_queued_bids::<_, _, u64, u128>(&header, &0u64, proof).unwrap();
+    assert_eq!(bids, vec![]);
+}
+
+#[test]
+fn proof_of_another_state_is_rejected() {
+    let (storage, _) = state();
+    let proof = prove(&storage, auction_key(&0u64));
+    let other = Header::new(
+        7,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    assert!(matches!(
+        verify_auction::<_, _, u64, u128, u32, u64>(&other, &0u64, proof),
+        Err(Error::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn proof_of_another_key_is_rejected() {
+    let (storage, header) = state();
+    let proof = prove(&storage, b"unrelated".to_vec());
+    assert!(matches!(
+        verify_auction::<_, _, u64, u128, u32, u64>(&header, &0u64, proof),
+        Err(Error::InvalidProof(_))
+    ));
+}
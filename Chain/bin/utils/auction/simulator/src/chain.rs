@@ -0,0 +1,92 @@
+//! What the simulator needs from a chain, along with the signing and storage helpers shared by
+//! the backends.
+
+use auction_traits::auction::AuctionInfo;
+use codec::{Decode, Encode};
+use frame_support::storage::StorageMap;
+use node_auction_runtime::{
+    auction, AccountId, AuctionId, Balance, BlockNumber, Event, GeneralInformationContainer, Hash,
+    Index, Runtime, UncheckedExtrinsic,
+};
+use sp_core::{sr25519, twox_128, Pair};
+use sp_runtime::traits::IdentifyAccount;
+use sp_runtime::MultiSigner;
+use sp_version::RuntimeVersion;
+
+pub type Result<T> = std::result::Result<T, String>;
+pub type AuctionInfoOf = AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer>;
+pub type AccountInfoOf = system::AccountInfo<Index, balances::AccountData<Balance>>;
+pub type EventRecordOf = system::EventRecord<Event, Hash>;
+
+/// A block the chain imported since the last call to `Chain::next_blocks`.
+#[derive(Debug, Clone)]
+pub struct NewBlock {
+    pub number: BlockNumber,
+    pub hash: Hash,
+    /// Hashes of the extrinsics in the block, in order.
+    pub extrinsics: Vec<Hash>,
+    /// Hashes of submitted extrinsics which were thrown out while building the block.
+    pub dropped: Vec<Hash>,
+}
+
+/// A chain the scenario runs against.
+pub trait Chain {
+    /// Hash of the genesis block, part of every signed payload.
+    fn genesis_hash(&mut self) -> Result<Hash>;
+
+    /// Version of the runtime at the best block.
+    fn runtime_version(&mut self) -> Result<RuntimeVersion>;
+
+    /// Number and hash of the best block.
+    fn best(&mut self) -> Result<(BlockNumber, Hash)>;
+
+    /// Raw storage value under `key` at block `at`.
+    fn storage(&mut self, at: Hash, key: Vec<u8>) -> Result<Option<Vec<u8>>>;
+
+    /// Submits a signed extrinsic and returns its hash.
+    fn submit(&mut self, xt: UncheckedExtrinsic) -> Result<Hash>;
+
+    /// Blocks until the chain moves on and returns the new blocks, oldest first.
+    fn next_blocks(&mut self) -> Result<Vec<NewBlock>>;
+
+    /// Reads and decodes the storage value under `key` at block `at`.
+    fn get<V: Decode>(&mut self, at: Hash, key: Vec<u8>) -> Result<Option<V>>
+    where
+        Self: Sized,
+    {
+        self.storage(at, key)?
+            .map(|data| V::decode(&mut &data[..]).map_err(|e| e.to_string()))
+            .transpose()
+    }
+}
+
+/// Derives the key pair of a secret URI such as `//Alice`.
+pub fn pair_from_suri(suri: &str) -> Result<sr25519::Pair> {
+    sr25519::Pair::from_string(suri, None).map_err(|e| format!("Invalid key `{}`: {:?}", suri, e))
+}
+
+pub fn account_id(pair: &sr25519::Pair) -> AccountId {
+    MultiSigner::from(pair.public()).into_account()
+}
+
+pub use node_auction_client::sign;
+
+/// Key of `System::Events`, the storage item itself isn't public.
+pub fn events_key() -> Vec<u8> {
+    let mut key = twox_128(b"System").to_vec();
+    key.extend_from_slice(&twox_128(b"Events"));
+    key
+}
+
+pub fn account_key(who: &AccountId) -> Vec<u8> {
+    <system::Account<Runtime> as StorageMap<AccountId, AccountInfoOf>>::hashed_key_for(who)
+}
+
+pub fn auction_key(id: AuctionId) -> Vec<u8> {
+    <auction::Auctions<Runtime> as StorageMap<AuctionId, AuctionInfoOf>>::hashed_key_for(id)
+}
+
+/// Hash of an extrinsic as reported by the transaction pool.
+pub fn extrinsic_hash<E: Encode>(xt: &E) -> Hash {
+    xt.using_encoded(|encoded| sp_core::blake2_256(encoded).into())
+}
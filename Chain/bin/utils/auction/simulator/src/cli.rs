@@ -0,0 +1,114 @@
+//! Command line interface of the simulator.
+
+use crate::scenario::Strategy;
+use node_auction_runtime::{Balance, BlockNumber};
+use structopt::StructOpt;
+
+/// Simulates terminals and barges trading berthing slots on `node-auction`.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "auction-simulator")]
+pub struct Cli {
+    #[allow(missing_docs)]
+    #[structopt(subcommand)]
+    pub subcommand: Subcommand,
+
+    /// Print the report as JSON instead of text.
+    #[structopt(long, global = true)]
+    pub json: bool,
+}
+
+/// The places a scenario can run.
+#[derive(Debug, StructOpt)]
+pub enum Subcommand {
+    /// Drive a running node, e.g. `node-auction --dev`, through its HTTP RPC endpoint.
+    Rpc(RpcCmd),
+    /// Run against the runtime in-process, on top of an in-memory test client.
+    InProcess(InProcessCmd),
+}
+
+/// The `rpc` command.
+#[derive(Debug, StructOpt)]
+pub struct RpcCmd {
+    /// HTTP RPC endpoint of the node.
+    #[structopt(long, default_value = "http://localhost:9933")]
+    pub url: String,
+
+    /// Secret URI of the account funding the simulated terminals and barges.
+    #[structopt(long, value_name = "SURI", default_value = "//Alice")]
+    pub funder: String,
+
+    /// Balance every simulated account is topped up to before the scenario starts.
+    #[structopt(long, default_value = "1125899906842624")]
+    pub endowment: Balance,
+
+    /// Interval at which the node is polled for new blocks, in milliseconds.
+    #[structopt(long, value_name = "MS", default_value = "250")]
+    pub poll_interval: u64,
+
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub scenario: ScenarioParams,
+}
+
+/// The `in-process` command.
+#[derive(Debug, StructOpt)]
+pub struct InProcessCmd {
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub scenario: ScenarioParams,
+}
+
+/// Parameters shaping the simulated traffic.
+#[derive(Debug, Clone, StructOpt)]
+pub struct ScenarioParams {
+    /// Number of terminals putting slots up for auction.
+    #[structopt(long, default_value = "2")]
+    pub terminals: u32,
+
+    /// Number of barges bidding on the slots.
+    #[structopt(long, default_value = "6")]
+    pub barges: u32,
+
+    /// Number of slots every terminal auctions off.
+    #[structopt(long, default_value = "3")]
+    pub slots: u32,
+
+    /// Blocks between two auctions of the same terminal.
+    #[structopt(long, value_name = "BLOCKS", default_value = "4")]
+    pub interval: BlockNumber,
+
+    /// Blocks between the creation of an auction and its start. Bids placed in between are
+    /// queued.
+    #[structopt(long, value_name = "BLOCKS", default_value = "2")]
+    pub lead: BlockNumber,
+
+    /// Blocks an auction stays open for.
+    #[structopt(long, value_name = "BLOCKS", default_value = "8")]
+    pub duration: BlockNumber,
+
+    /// Bidding strategies handed out to the barges in turn: `incremental`, `sniper` or `max`.
+    #[structopt(long, use_delimiter = true, default_value = "incremental,sniper,max")]
+    pub strategies: Vec<Strategy>,
+
+    /// Most a barge values a slot at. Every barge values every slot at somewhere between half
+    /// this amount and the full amount.
+    #[structopt(long, default_value = "1000000")]
+    pub budget: Balance,
+
+    /// Chance, in percent, that a barge looks at an open auction in a given block.
+    #[structopt(long, default_value = "50")]
+    pub activity: u8,
+
+    /// Blocks to wait past the end of the last auction before giving up on its settlement.
+    #[structopt(long, value_name = "BLOCKS", default_value = "5")]
+    pub grace: BlockNumber,
+
+    /// Seed of the random choices made by the terminals and barges.
+    #[structopt(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Prefix of the derivation paths of the simulated accounts. Change it to run with a fresh
+    /// set of accounts.
+    #[structopt(long, default_value = "simulator")]
+    pub accounts: String,
+}
@@ -0,0 +1,190 @@
+//! Runs scenarios in-process, on top of an in-memory client of the runtime. Blocks are authored
+//! on demand, so a scenario runs as fast as the runtime executes it.
+
+use crate::chain::{extrinsic_hash, Chain, NewBlock, Result};
+use codec::Encode;
+use node_auction_runtime::{
+    opaque::Block, AccountId, AuctionModuleConfig, AuraConfig, BalancesConfig, BlockNumber,
+    BuildStorage, GenesisConfig, GrandpaConfig, Hash, RuntimeApi, SudoConfig, SystemConfig,
+    UncheckedExtrinsic, SLOT_DURATION, WASM_BINARY,
+};
+use sc_block_builder::BlockBuilderProvider;
+use sc_client_api::StorageProvider;
+use sc_executor::native_executor_instance;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::storage::StorageKey;
+use sp_inherents::InherentData;
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{Block as _, Header as _};
+use sp_runtime::OpaqueExtrinsic;
+use sp_version::RuntimeVersion;
+use std::time::{SystemTime, UNIX_EPOCH};
+use substrate_test_client::{
+    client::{Client, LocalCallExecutor},
+    AccountKeyring, ClientBlockImportExt, GenesisInit, NativeExecutor, Storage, TestClientBuilder,
+};
+
+native_executor_instance!(
+    pub Executor,
+    node_auction_runtime::api::dispatch,
+    node_auction_runtime::native_version,
+);
+
+type Backend = substrate_test_client::Backend<Block>;
+type CallExecutor = LocalCallExecutor<Backend, NativeExecutor<Executor>>;
+type InProcessClient = Client<Backend, CallExecutor, Block, RuntimeApi>;
+
+/// Balance of every simulated account at genesis.
+const ENDOWMENT: u128 = 1 << 60;
+
+/// Genesis of the in-process chain: the development setup without its test auctions, with the
+/// simulated accounts endowed.
+#[derive(Default)]
+pub struct SimulationGenesis {
+    endowed: Vec<AccountId>,
+}
+
+impl GenesisInit for SimulationGenesis {
+    fn genesis_storage(&self) -> Storage {
+        let sudo = AccountKeyring::Alice.to_account_id();
+        GenesisConfig {
+            system: Some(SystemConfig {
+                code: WASM_BINARY.to_vec(),
+                changes_trie_config: Default::default(),
+            }),
+            balances: Some(BalancesConfig {
+                balances: self
+                    .endowed
+                    .iter()
+                    .chain(Some(&sudo))
+                    .cloned()
+                    .map(|who| (who, ENDOWMENT))
+                    .collect(),
+            }),
+            // Blocks are authored without consensus, so neither needs authorities.
+            aura: Some(AuraConfig {
+                authorities: Vec::new(),
+            }),
+            grandpa: Some(GrandpaConfig {
+                authorities: Vec::new(),
+            }),
+            sudo: Some(SudoConfig { key: sudo }),
+            auction: Some(AuctionModuleConfig {
+                terminals: Vec::new(),
+                auctions: Vec::new(),
+            }),
+        }
+        .build_storage()
+        .expect("The simulation genesis is valid; qed")
+    }
+}
+
+/// An in-memory chain which authors a block every time the simulation waits for one.
+pub struct InProcessChain {
+    client: InProcessClient,
+    /// Submitted extrinsics waiting for the next block.
+    queue: Vec<(Hash, OpaqueExtrinsic)>,
+    /// Timestamp of the genesis block, blocks are spaced one slot apart from it.
+    genesis_time: u64,
+}
+
+impl InProcessChain {
+    pub fn new(endowed: Vec<AccountId>) -> Self {
+        let mut builder =
+            TestClientBuilder::<Block, CallExecutor, Backend, SimulationGenesis>::with_default_backend();
+        builder.genesis_init_mut().endowed = endowed;
+        let (client, _) = builder.build_with_native_executor::<RuntimeApi, _>(None);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("The system clock is past the epoch; qed")
+            .as_millis() as u64;
+
+        InProcessChain {
+            client,
+            queue: Vec::new(),
+            genesis_time: now - now % SLOT_DURATION,
+        }
+    }
+}
+
+impl Chain for InProcessChain {
+    fn genesis_hash(&mut self) -> Result<Hash> {
+        Ok(self.client.info().genesis_hash)
+    }
+
+    fn runtime_version(&mut self) -> Result<RuntimeVersion> {
+        let best = BlockId::Hash(self.client.info().best_hash);
+        self.client
+            .runtime_version_at(&best)
+            .map_err(|e| e.to_string())
+    }
+
+    fn best(&mut self) -> Result<(BlockNumber, Hash)> {
+        let info = self.client.info();
+        Ok((info.best_number, info.best_hash))
+    }
+
+    fn storage(&mut self, at: Hash, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        self.client
+            .storage(&BlockId::Hash(at), &StorageKey(key))
+            .map(|data| data.map(|data| data.0))
+            .map_err(|e| e.to_string())
+    }
+
+    fn submit(&mut self, xt: UncheckedExtrinsic) -> Result<Hash> {
+        let hash = extrinsic_hash(&xt);
+        let xt = OpaqueExtrinsic::from_bytes(&xt.encode()).map_err(|e| e.to_string())?;
+        self.queue.push((hash, xt));
+        Ok(hash)
+    }
+
+    fn next_blocks(&mut self) -> Result<Vec<NewBlock>> {
+        let number = self.client.info().best_number + 1;
+
+        // Aura demands a new slot for every block, the timestamps are spaced accordingly.
+        let mut inherent_data = InherentData::new();
+        inherent_data
+            .put_data(
+                sp_timestamp::INHERENT_IDENTIFIER,
+                &(self.genesis_time + u64::from(number) * SLOT_DURATION),
+            )
+            .map_err(|e| format!("{:?}", e))?;
+
+        let mut builder = self
+            .client
+            .new_block(Default::default())
+            .map_err(|e| e.to_string())?;
+        for xt in builder
+            .create_inherents(inherent_data)
+            .map_err(|e| e.to_string())?
+        {
+            builder.push(xt).map_err(|e| e.to_string())?;
+        }
+
+        // Extrinsics the runtime refuses to apply, e.g. because of a bad nonce, are thrown out
+        // like the transaction pool would.
+        let mut dropped = Vec::new();
+        for (hash, xt) in self.queue.drain(..) {
+            if let Err(e) = builder.push(xt) {
+                log::debug!("Dropped {:?}: {}", hash, e);
+                dropped.push(hash);
+            }
+        }
+
+        let block = builder.build().map_err(|e| e.to_string())?.block;
+        let hash = block.header().hash();
+        let extrinsics = block.extrinsics().iter().map(extrinsic_hash).collect();
+        self.client
+            .import(BlockOrigin::Own, block)
+            .map_err(|e| e.to_string())?;
+
+        Ok(vec![NewBlock {
+            number,
+            hash,
+            extrinsics,
+            dropped,
+        }])
+    }
+}
@@ -0,0 +1,71 @@
+//! Traffic simulator and load generator for `node-auction`.
+//!
+//! Terminals put their berthing slots up for auction and barges bid on them following
+//! configurable strategies. Every action is a real, signed extrinsic. The same scenario either
+//! drives a running node over RPC or runs in-process on top of an in-memory test client, after
+//! which the throughput, the inclusion latency and the settlement of every auction are reported.
+//!
+//! ```text
+//! node-auction --dev --tmp
+//! auction-simulator rpc --terminals 4 --barges 12 --slots 5
+//! auction-simulator in-process --strategies sniper,max --seed 42 --json
+//! ```
+
+mod chain;
+mod cli;
+mod in_process;
+mod report;
+mod rpc;
+mod scenario;
+mod simulation;
+#[cfg(test)]
+mod tests;
+
+use cli::{Cli, Subcommand};
+use in_process::InProcessChain;
+use rpc::RpcChain;
+use scenario::Scenario;
+use simulation::Simulation;
+use std::process;
+use structopt::StructOpt;
+
+fn main() {
+    env_logger::init();
+
+    let cli = Cli::from_args();
+    let result = match cli.subcommand {
+        Subcommand::Rpc(cmd) => RpcChain::connect(&cmd.url, cmd.poll_interval).and_then(|chain| {
+            let mut simulation = Simulation::new(chain, Scenario::new(&cmd.scenario)?, "rpc")?;
+            simulation.fund(&cmd.funder, cmd.endowment)?;
+            simulation.run()
+        }),
+        Subcommand::InProcess(cmd) => Scenario::new(&cmd.scenario).and_then(|scenario| {
+            let chain = InProcessChain::new(scenario.accounts());
+            Simulation::new(chain, scenario, "in-process")?.run()
+        }),
+    };
+
+    match result {
+        Ok(report) => {
+            if cli.json {
+                match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                }
+            } else {
+                print!("{}", report);
+            }
+            // Wrongly settled auctions fail the run, so the simulator can gate CI jobs.
+            if !report.violations.is_empty() {
+                process::exit(2);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Metrics and settlement results collected over a run.
+
+use node_auction_runtime::{AccountId, AuctionId, Balance, BlockNumber};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// The outcome of a run.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    /// Where the scenario ran, `rpc` or `in-process`.
+    pub backend: String,
+    pub first_block: BlockNumber,
+    pub last_block: BlockNumber,
+    pub elapsed_secs: f64,
+    /// Submitted extrinsics per kind.
+    pub submitted: BTreeMap<String, u32>,
+    /// Extrinsics the node refused to take in, per reason.
+    pub rejected: BTreeMap<String, u32>,
+    pub included: u32,
+    /// Included extrinsics whose dispatch failed, per error. Barges racing each other make some
+    /// of their bids fail, this is expected.
+    pub failed: BTreeMap<String, u32>,
+    /// Extrinsics thrown out after they were accepted.
+    pub dropped: u32,
+    /// Extrinsics still waiting for inclusion when the run ended.
+    pub unresolved: u32,
+    /// Included extrinsics per second of wall clock time.
+    pub throughput: f64,
+    /// Included extrinsics per block.
+    pub per_block: f64,
+    pub latency: Option<Latency>,
+    pub auctions: Vec<Settlement>,
+    /// Every way the chain disagreed with the expected settlement of an auction.
+    pub violations: Vec<String>,
+
+    #[serde(skip)]
+    latencies: Vec<(Duration, BlockNumber)>,
+}
+
+/// Time between submitting extrinsics and seeing them in a block.
+#[derive(Debug, Serialize)]
+pub struct Latency {
+    pub p50_ms: u128,
+    pub p95_ms: u128,
+    pub max_ms: u128,
+    pub mean_blocks: f64,
+    pub max_blocks: BlockNumber,
+}
+
+/// How a simulated auction ended.
+#[derive(Debug, Clone, Serialize)]
+pub struct Settlement {
+    pub id: AuctionId,
+    pub creator: AccountId,
+    /// Block the auction was settled in, `None` if it never was.
+    pub block: Option<BlockNumber>,
+    pub winner: Option<AccountId>,
+    pub price: Option<Balance>,
+    /// Whether the settlement matched the expectations.
+    pub correct: bool,
+}
+
+impl Report {
+    pub fn new(backend: &str) -> Self {
+        Report {
+            backend: backend.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn submitted(&mut self, kind: &str) {
+        *self.submitted.entry(kind.into()).or_default() += 1;
+    }
+
+    pub fn rejected(&mut self, reason: String) {
+        *self.rejected.entry(reason).or_default() += 1;
+    }
+
+    /// Records an included extrinsic, `error` holds its dispatch error if it failed.
+    pub fn included(&mut self, latency: Duration, blocks: BlockNumber, error: Option<String>) {
+        self.included += 1;
+        self.latencies.push((latency, blocks));
+        if let Some(error) = error {
+            *self.failed.entry(error).or_default() += 1;
+        }
+    }
+
+    pub fn dropped(&mut self) {
+        self.dropped += 1;
+    }
+
+    pub fn violation(&mut self, violation: String) {
+        log::warn!("{}", violation);
+        self.violations.push(violation);
+    }
+
+    /// Wraps up the run and computes the aggregated metrics.
+    pub fn finish(
+        &mut self,
+        elapsed: Duration,
+        blocks: (BlockNumber, BlockNumber),
+        unresolved: u32,
+    ) {
+        self.elapsed_secs = elapsed.as_secs_f64();
+        self.first_block = blocks.0;
+        self.last_block = blocks.1;
+        self.unresolved = unresolved;
+
+        if self.elapsed_secs > 0.0 {
+            self.throughput = f64::from(self.included) / self.elapsed_secs;
+        }
+        if self.last_block > self.first_block {
+            self.per_block =
+                f64::from(self.included) / f64::from(self.last_block - self.first_block);
+        }
+
+        if !self.latencies.is_empty() {
+            let mut times = self
+                .latencies
+                .iter()
+                .map(|(time, _)| time.as_millis())
+                .collect::<Vec<_>>();
+            times.sort();
+            let percentile = |p: usize| times[(times.len() - 1) * p / 100];
+            let blocks = self.latencies.iter().map(|(_, blocks)| *blocks);
+
+            self.latency = Some(Latency {
+                p50_ms: percentile(50),
+                p95_ms: percentile(95),
+                max_ms: percentile(100),
+                mean_blocks: blocks.clone().map(f64::from).sum::<f64>()
+                    / self.latencies.len() as f64,
+                max_blocks: blocks.max().unwrap_or_default(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Simulated {} blocks ({} to {}, {}) in {:.1}s",
+            self.last_block.saturating_sub(self.first_block),
+            self.first_block,
+            self.last_block,
+            self.backend,
+            self.elapsed_secs
+        )?;
+
+        writeln!(f, "\nTransactions")?;
+        for (kind, count) in &self.submitted {
+            writeln!(f, "  submitted {:<10} {}", kind, count)?;
+        }
+        writeln!(f, "  included             {}", self.included)?;
+        writeln!(f, "  dropped              {}", self.dropped)?;
+        writeln!(f, "  unresolved           {}", self.unresolved)?;
+        for (reason, count) in &self.rejected {
+            writeln!(f, "  rejected             {} ({})", count, reason)?;
+        }
+        for (error, count) in &self.failed {
+            writeln!(f, "  failed               {} ({})", count, error)?;
+        }
+        writeln!(
+            f,
+            "  throughput           {:.2} tx/s, {:.2} tx/block",
+            self.throughput, self.per_block
+        )?;
+        if let Some(latency) = &self.latency {
+            writeln!(
+                f,
+                "  inclusion latency    p50 {}ms, p95 {}ms, max {}ms ({:.2} blocks avg, {} max)",
+                latency.p50_ms,
+                latency.p95_ms,
+                latency.max_ms,
+                latency.mean_blocks,
+                latency.max_blocks
+            )?;
+        }
+
+        writeln!(f, "\nAuctions")?;
+        for auction in &self.auctions {
+            let outcome = match (&auction.winner, auction.price, auction.block) {
+                (Some(winner), Some(price), Some(block)) => {
+                    format!("won by {} for {} at block {}", winner, price, block)
+                }
+                (None, _, Some(block)) => format!("closed without bids at block {}", block),
+                _ => "never settled".to_string(),
+            };
+            let mark = if auction.correct { "ok" } else { "WRONG" };
+            writeln!(f, "  #{:<6} {:<5} {}", auction.id, mark, outcome)?;
+        }
+
+        if self.violations.is_empty() {
+            writeln!(f, "\nEvery auction settled correctly")
+        } else {
+            writeln!(f, "\nSettlement violations")?;
+            for violation in &self.violations {
+                writeln!(f, "  {}", violation)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Runs scenarios against a live node through its HTTP RPC endpoint.
+
+use crate::chain::{extrinsic_hash, Chain, NewBlock, Result};
+use codec::Encode;
+use futures01::Future;
+use jsonrpc_core_client::{transports::http, RpcError};
+use node_auction_runtime::{opaque::Block, BlockNumber, Hash, Header, UncheckedExtrinsic};
+use sc_rpc_api::{author::AuthorClient, chain::ChainClient, state::StateClient};
+use sp_core::storage::StorageKey;
+use sp_core::Bytes;
+use sp_rpc::{list::ListOrValue, number::NumberOrHex};
+use sp_runtime::generic::SignedBlock;
+use sp_runtime::traits::Header as _;
+use sp_version::RuntimeVersion;
+use std::thread;
+use std::time::Duration;
+use tokio01::runtime::Runtime;
+
+/// A node reached over HTTP. New blocks are found by polling the best header.
+pub struct RpcChain {
+    runtime: Runtime,
+    author: AuthorClient<Hash, Hash>,
+    chain: ChainClient<BlockNumber, Hash, Header, SignedBlock<Block>>,
+    state: StateClient<Hash>,
+    poll_interval: Duration,
+    last: Option<(BlockNumber, Hash)>,
+}
+
+impl RpcChain {
+    pub fn connect(url: &str, poll_interval: u64) -> Result<Self> {
+        let mut runtime = Runtime::new().map_err(|e| e.to_string())?;
+        let author = runtime.block_on(http::connect(url)).map_err(rpc_error)?;
+        let chain = runtime.block_on(http::connect(url)).map_err(rpc_error)?;
+        let state = runtime.block_on(http::connect(url)).map_err(rpc_error)?;
+
+        Ok(RpcChain {
+            runtime,
+            author,
+            chain,
+            state,
+            poll_interval: Duration::from_millis(poll_interval),
+            last: None,
+        })
+    }
+
+    fn block_on<F: Future<Error = RpcError> + Send + 'static>(
+        &mut self,
+        future: F,
+    ) -> Result<F::Item>
+    where
+        F::Item: Send + 'static,
+    {
+        self.runtime.block_on(future).map_err(rpc_error)
+    }
+
+    fn block(&mut self, hash: Hash) -> Result<SignedBlock<Block>> {
+        let block = self.chain.block(Some(hash));
+        self.block_on(block)?
+            .ok_or_else(|| format!("Block {:?} is unknown to the node", hash))
+    }
+}
+
+impl Chain for RpcChain {
+    fn genesis_hash(&mut self) -> Result<Hash> {
+        let hash = self
+            .chain
+            .block_hash(Some(ListOrValue::Value(NumberOrHex::Number(0))));
+        match self.block_on(hash)? {
+            ListOrValue::Value(Some(hash)) => Ok(hash),
+            _ => Err("The node didn't return its genesis hash".into()),
+        }
+    }
+
+    fn runtime_version(&mut self) -> Result<RuntimeVersion> {
+        let version = self.state.runtime_version(None);
+        self.block_on(version)
+    }
+
+    fn best(&mut self) -> Result<(BlockNumber, Hash)> {
+        let header = self.chain.header(None);
+        let header = self
+            .block_on(header)?
+            .ok_or("The node didn't return its best header")?;
+        let best = (*header.number(), header.hash());
+        self.last.get_or_insert(best);
+        Ok(best)
+    }
+
+    fn storage(&mut self, at: Hash, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let data = self.state.storage(StorageKey(key), Some(at));
+        Ok(self.block_on(data)?.map(|data| data.0))
+    }
+
+    fn submit(&mut self, xt: UncheckedExtrinsic) -> Result<Hash> {
+        let hash = self.author.submit_extrinsic(Bytes(xt.encode()));
+        self.block_on(hash)
+    }
+
+    fn next_blocks(&mut self) -> Result<Vec<NewBlock>> {
+        let last = match self.last {
+            Some(last) => last,
+            None => self.best()?,
+        };
+
+        loop {
+            thread::sleep(self.poll_interval);
+            let (number, hash) = self.best()?;
+            if number <= last.0 {
+                continue;
+            }
+
+            // Walk back from the best block to the last block we've seen.
+            let mut blocks = Vec::new();
+            let mut next = hash;
+            for number in (last.0 + 1..=number).rev() {
+                let block = self.block(next)?.block;
+                next = block.header.parent_hash;
+                blocks.push(NewBlock {
+                    number,
+                    hash: block.header.hash(),
+                    extrinsics: block.extrinsics.iter().map(extrinsic_hash).collect(),
+                    // The pool doesn't tell which extrinsics it threw out, those end up as
+                    // unresolved.
+                    dropped: Vec::new(),
+                });
+            }
+            if next != last.1 {
+                log::warn!(
+                    "The node switched forks, blocks before #{} are ignored",
+                    last.0 + 1
+                );
+            }
+
+            blocks.reverse();
+            self.last = Some((number, hash));
+            return Ok(blocks);
+        }
+    }
+}
+
+fn rpc_error(error: RpcError) -> String {
+    format!("RPC error: {}", error)
+}
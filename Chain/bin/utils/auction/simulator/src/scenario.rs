@@ -0,0 +1,295 @@
+//! The simulated port: terminals scheduling slots and barges bidding on them.
+
+use crate::chain::{account_id, pair_from_suri, Result};
+use crate::cli::ScenarioParams;
+use node_auction_runtime::{
+    auction, AccountId, AuctionId, Balance, BlockNumber, Call, GeneralInformationContainer,
+    MILLISECS_PER_BLOCK,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sp_core::sr25519;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// Snipers only bid once an auction is this many blocks away from its end.
+const SNIPE_WINDOW: BlockNumber = 2;
+
+/// How a barge bids on the slots it wants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Outbids the leading bid by a small step whenever it's not leading, up to its valuation.
+    Incremental,
+    /// Stays out of the auction until its very end, then bids like `Incremental`.
+    Sniper,
+    /// Bids its full valuation once, as soon as the auction exists. This usually queues the bid.
+    Max,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "incremental" => Ok(Strategy::Incremental),
+            "sniper" => Ok(Strategy::Sniper),
+            "max" => Ok(Strategy::Max),
+            other => Err(format!(
+                "Unknown strategy `{}`, expected incremental, sniper or max",
+                other
+            )),
+        }
+    }
+}
+
+/// A simulated account.
+pub struct Agent {
+    pub pair: sr25519::Pair,
+    pub account: AccountId,
+}
+
+impl Agent {
+    fn derive(prefix: &str, role: &str, index: u32) -> Result<Self> {
+        let pair = pair_from_suri(&format!("//{}//{}//{}", prefix, role, index))?;
+        Ok(Agent {
+            account: account_id(&pair),
+            pair,
+        })
+    }
+}
+
+/// A slot a terminal puts up for auction once the scenario reaches `due`.
+struct Slot {
+    terminal: usize,
+    due: BlockNumber,
+    num_con: GeneralInformationContainer,
+    num_teu: GeneralInformationContainer,
+    submitted: bool,
+}
+
+/// What the scenario knows about an auction, as last seen on chain.
+#[derive(Debug, Clone)]
+pub struct AuctionView {
+    pub creator: AccountId,
+    pub start: BlockNumber,
+    pub end: Option<BlockNumber>,
+    pub bid: Option<(AccountId, Balance)>,
+}
+
+/// What an extrinsic submitted by the scenario does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxKind {
+    /// A terminal creating the auction of one of its slots.
+    Create,
+    /// A barge bidding on an auction.
+    Bid { barge: usize, auction: AuctionId },
+}
+
+impl TxKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TxKind::Create => "create",
+            TxKind::Bid { .. } => "bid",
+        }
+    }
+}
+
+/// An extrinsic the scenario wants submitted.
+pub struct Intent {
+    pub signer: AccountId,
+    pub kind: TxKind,
+    pub call: Call,
+}
+
+pub struct Scenario {
+    params: ScenarioParams,
+    terminals: Vec<Agent>,
+    barges: Vec<(Agent, Strategy)>,
+    slots: Vec<Slot>,
+    rng: StdRng,
+    /// Block the scenario started at, slots are due relative to it.
+    begin: Option<BlockNumber>,
+    valuations: HashMap<(usize, AuctionId), Balance>,
+    /// Bids which haven't made it into a block yet, barges wait for them before bidding again.
+    pending: HashSet<(usize, AuctionId)>,
+    /// Auctions `Max` barges have already placed their bid on.
+    maxed: HashSet<(usize, AuctionId)>,
+}
+
+impl Scenario {
+    pub fn new(params: &ScenarioParams) -> Result<Self> {
+        if params.terminals == 0 || params.barges == 0 {
+            return Err("A scenario needs at least one terminal and one barge".into());
+        }
+        if params.strategies.is_empty() {
+            return Err("A scenario needs at least one strategy".into());
+        }
+        if params.duration == 0 {
+            return Err("Auctions need to last at least one block".into());
+        }
+
+        let terminals = (0..params.terminals)
+            .map(|index| Agent::derive(&params.accounts, "Terminal", index))
+            .collect::<Result<Vec<_>>>()?;
+        let barges = (0..params.barges)
+            .map(|index| {
+                let strategy = params.strategies[index as usize % params.strategies.len()];
+                Agent::derive(&params.accounts, "Barge", index).map(|agent| (agent, strategy))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut rng = StdRng::seed_from_u64(params.seed);
+        let mut slots = Vec::new();
+        for slot in 0..params.slots {
+            for terminal in 0..params.terminals {
+                let num_con = rng.gen_range(10, 500);
+                slots.push(Slot {
+                    terminal: terminal as usize,
+                    // Terminals are staggered so their auctions don't all land in the same block.
+                    due: slot * params.interval + terminal,
+                    num_con,
+                    num_teu: num_con * rng.gen_range(1, 3),
+                    submitted: false,
+                });
+            }
+        }
+
+        Ok(Scenario {
+            params: params.clone(),
+            terminals,
+            barges,
+            slots,
+            rng,
+            begin: None,
+            valuations: HashMap::new(),
+            pending: HashSet::new(),
+            maxed: HashSet::new(),
+        })
+    }
+
+    /// Every simulated account.
+    pub fn accounts(&self) -> Vec<AccountId> {
+        self.agents().map(|agent| agent.account.clone()).collect()
+    }
+
+    pub fn agents(&self) -> impl Iterator<Item = &Agent> {
+        self.terminals
+            .iter()
+            .chain(self.barges.iter().map(|(agent, _)| agent))
+    }
+
+    pub fn is_terminal(&self, who: &AccountId) -> bool {
+        self.terminals.iter().any(|agent| agent.account == *who)
+    }
+
+    /// Whether every slot has been put up for auction.
+    pub fn scheduled(&self) -> bool {
+        self.slots.iter().all(|slot| slot.submitted)
+    }
+
+    /// Last block the scenario waits for, past it unsettled auctions are reported as such.
+    pub fn deadline(&self, now: BlockNumber) -> BlockNumber {
+        let last_due = self.slots.iter().map(|slot| slot.due).max().unwrap_or(0);
+        self.begin.unwrap_or(now)
+            + last_due
+            + 1
+            + self.params.lead
+            + self.params.duration
+            + self.params.grace
+    }
+
+    /// Called once a bid made it into a block or was thrown out.
+    pub fn bid_resolved(&mut self, barge: usize, auction: AuctionId) {
+        self.pending.remove(&(barge, auction));
+    }
+
+    /// The extrinsics the terminals and barges want to submit now that the chain is at `now`.
+    pub fn plan(
+        &mut self,
+        now: BlockNumber,
+        auctions: &BTreeMap<AuctionId, AuctionView>,
+    ) -> Vec<Intent> {
+        let begin = *self.begin.get_or_insert(now);
+        let mut intents = Vec::new();
+
+        for slot in self.slots.iter_mut() {
+            if slot.submitted || begin + slot.due > now {
+                continue;
+            }
+            slot.submitted = true;
+
+            // The auction makes it into the next block at the earliest.
+            let start = now + 1 + self.params.lead;
+            let terminal = &self.terminals[slot.terminal];
+            intents.push(Intent {
+                signer: terminal.account.clone(),
+                kind: TxKind::Create,
+                call: Call::AuctionModule(auction::Call::ext_new_auction(
+                    terminal.account.clone(),
+                    slot.num_con,
+                    slot.num_teu,
+                    // Rough berthing time of the slot: the auction's end, in milliseconds of
+                    // block time.
+                    u64::from(start + self.params.duration) * MILLISECS_PER_BLOCK,
+                    start,
+                    start + self.params.duration,
+                )),
+            });
+        }
+
+        for (&id, view) in auctions.iter() {
+            if view.end.map_or(false, |end| end < now) {
+                continue;
+            }
+            let price = view.bid.as_ref().map(|bid| bid.1).unwrap_or(0);
+            let live = now >= view.start;
+
+            for barge in 0..self.barges.len() {
+                let (agent, strategy) = &self.barges[barge];
+                let leading = view
+                    .bid
+                    .as_ref()
+                    .map_or(false, |bid| bid.0 == agent.account);
+                if leading || self.pending.contains(&(barge, id)) {
+                    continue;
+                }
+                if self.rng.gen_range(0, 100) >= self.params.activity {
+                    continue;
+                }
+
+                let budget = self.params.budget;
+                let rng = &mut self.rng;
+                let valuation = *self
+                    .valuations
+                    .entry((barge, id))
+                    .or_insert_with(|| rng.gen_range(budget / 2, budget + 1));
+                let step = (valuation / 100).max(1);
+
+                let value = match strategy {
+                    Strategy::Incremental if live => Some(price + step),
+                    Strategy::Sniper
+                        if live && view.end.map_or(true, |end| end - now <= SNIPE_WINDOW) =>
+                    {
+                        Some(price + step)
+                    }
+                    Strategy::Max if !self.maxed.contains(&(barge, id)) => {
+                        self.maxed.insert((barge, id));
+                        Some(valuation)
+                    }
+                    _ => None,
+                };
+
+                if let Some(value) = value.filter(|value| *value > price && *value <= valuation) {
+                    self.pending.insert((barge, id));
+                    intents.push(Intent {
+                        signer: agent.account.clone(),
+                        kind: TxKind::Bid { barge, auction: id },
+                        call: Call::AuctionModule(auction::Call::bid(id, value)),
+                    });
+                }
+            }
+        }
+
+        intents
+    }
+}
@@ -0,0 +1,426 @@
+//! Drives a scenario against a chain and checks how its auctions settle.
+
+use crate::chain::{
+    account_id, account_key, auction_key, events_key, pair_from_suri, sign, AccountInfoOf,
+    AuctionInfoOf, Chain, EventRecordOf, NewBlock, Result,
+};
+use crate::report::{Report, Settlement};
+use crate::scenario::{AuctionView, Intent, Scenario, TxKind};
+use node_auction_runtime::{
+    auction, reputation, AccountId, AuctionId, Balance, BalancesCall, BlockNumber, Call, Event,
+    Hash, Index,
+};
+use sp_core::sr25519;
+use sp_runtime::DispatchError;
+use sp_version::RuntimeVersion;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Instant;
+use system::Phase;
+
+/// Blocks to wait for the funding transfers before giving up.
+const FUNDING_TIMEOUT: u32 = 20;
+
+/// An extrinsic submitted by the scenario which hasn't been seen in a block yet.
+struct Pending {
+    signer: AccountId,
+    kind: TxKind,
+    submitted: Instant,
+    at: BlockNumber,
+}
+
+/// An auction created by one of the simulated terminals.
+struct Tracked {
+    view: AuctionView,
+    slot_origin: AccountId,
+    overdue: bool,
+    settled: bool,
+}
+
+pub struct Simulation<C> {
+    chain: C,
+    scenario: Scenario,
+    genesis: Hash,
+    version: RuntimeVersion,
+    keys: HashMap<AccountId, sr25519::Pair>,
+    /// Next nonce of every account, read from the chain on first use.
+    nonces: HashMap<AccountId, Index>,
+    pending: HashMap<Hash, Pending>,
+    auctions: BTreeMap<AuctionId, Tracked>,
+    best: (BlockNumber, Hash),
+    report: Report,
+}
+
+impl<C: Chain> Simulation<C> {
+    pub fn new(mut chain: C, scenario: Scenario, backend: &str) -> Result<Self> {
+        let genesis = chain.genesis_hash()?;
+        let version = chain.runtime_version()?;
+        let best = chain.best()?;
+        let keys = scenario
+            .agents()
+            .map(|agent| (agent.account.clone(), agent.pair.clone()))
+            .collect();
+
+        Ok(Simulation {
+            chain,
+            scenario,
+            genesis,
+            version,
+            keys,
+            nonces: HashMap::new(),
+            pending: HashMap::new(),
+            auctions: BTreeMap::new(),
+            best,
+            report: Report::new(backend),
+        })
+    }
+
+    /// Tops the simulated accounts up to `endowment` from the `funder` account and waits for the
+    /// transfers to be included.
+    pub fn fund(&mut self, funder: &str, endowment: Balance) -> Result<()> {
+        let funder = pair_from_suri(funder)?;
+        let funder_account = account_id(&funder);
+        self.keys.insert(funder_account.clone(), funder);
+
+        let mut waiting = HashSet::new();
+        for who in self.scenario.accounts() {
+            let free = self
+                .chain
+                .get::<AccountInfoOf>(self.best.1, account_key(&who))?
+                .map_or(0, |info| info.data.free);
+            if free >= endowment {
+                continue;
+            }
+            let call = Call::Balances(BalancesCall::transfer(who, endowment - free));
+            waiting.insert(self.sign_and_submit(&funder_account, call)?);
+        }
+
+        let mut blocks = 0;
+        while !waiting.is_empty() {
+            if blocks > FUNDING_TIMEOUT {
+                return Err(format!(
+                    "{} funding transfers weren't included within {} blocks",
+                    waiting.len(),
+                    FUNDING_TIMEOUT
+                ));
+            }
+            for block in self.chain.next_blocks()? {
+                blocks += 1;
+                self.best = (block.number, block.hash);
+                if block.dropped.iter().any(|hash| waiting.contains(hash)) {
+                    return Err("A funding transfer was dropped".into());
+                }
+                for hash in &block.extrinsics {
+                    waiting.remove(hash);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the scenario until every auction settled or the scenario's deadline passed.
+    pub fn run(mut self) -> Result<Report> {
+        let started = Instant::now();
+        let first = self.best.0;
+
+        loop {
+            let views: BTreeMap<_, _> = self
+                .auctions
+                .iter()
+                .filter(|(_, tracked)| !tracked.settled)
+                .map(|(id, tracked)| (*id, tracked.view.clone()))
+                .collect();
+            for intent in self.scenario.plan(self.best.0, &views) {
+                self.submit(intent)?;
+            }
+
+            let creating = self.pending.values().any(|pending| match pending.kind {
+                TxKind::Create => true,
+                TxKind::Bid { .. } => false,
+            });
+            let settled = self.auctions.values().all(|tracked| tracked.settled);
+            if (self.scenario.scheduled() && !creating && settled)
+                || self.best.0 >= self.scenario.deadline(self.best.0)
+            {
+                break;
+            }
+
+            for block in self.chain.next_blocks()? {
+                self.observe(block)?;
+            }
+        }
+
+        for (id, tracked) in self.auctions.iter().filter(|(_, tracked)| !tracked.settled) {
+            self.report
+                .violation(format!("Auction {} was never settled", id));
+            self.report.auctions.push(Settlement {
+                id: *id,
+                creator: tracked.view.creator.clone(),
+                block: None,
+                winner: None,
+                price: None,
+                correct: false,
+            });
+        }
+        self.report.auctions.sort_by_key(|settlement| settlement.id);
+
+        let unresolved = self.pending.len() as u32;
+        self.report
+            .finish(started.elapsed(), (first, self.best.0), unresolved);
+        Ok(self.report)
+    }
+
+    fn submit(&mut self, intent: Intent) -> Result<()> {
+        match self.sign_and_submit(&intent.signer, intent.call) {
+            Ok(hash) => {
+                self.report.submitted(intent.kind.label());
+                self.pending.insert(
+                    hash,
+                    Pending {
+                        signer: intent.signer,
+                        kind: intent.kind,
+                        submitted: Instant::now(),
+                        at: self.best.0,
+                    },
+                );
+            }
+            Err(e) => {
+                log::debug!("{} rejected: {}", intent.kind.label(), e);
+                self.report.rejected(e);
+                self.resolve(intent.kind);
+            }
+        }
+        Ok(())
+    }
+
+    /// Signs `call` with the next nonce of `signer` and submits it. The nonce is only used up
+    /// once the chain accepted the extrinsic.
+    fn sign_and_submit(&mut self, signer: &AccountId, call: Call) -> Result<Hash> {
+        let nonce = match self.nonces.get(signer) {
+            Some(nonce) => *nonce,
+            None => self
+                .chain
+                .get::<AccountInfoOf>(self.best.1, account_key(signer))?
+                .map_or(0, |info| info.nonce),
+        };
+        let pair = self
+            .keys
+            .get(signer)
+            .ok_or_else(|| format!("No key for {}", signer))?;
+
+        let xt = sign(pair, call, nonce, self.genesis, &self.version);
+        let hash = self.chain.submit(xt)?;
+        self.nonces.insert(signer.clone(), nonce + 1);
+        Ok(hash)
+    }
+
+    fn resolve(&mut self, kind: TxKind) {
+        if let TxKind::Bid { barge, auction } = kind {
+            self.scenario.bid_resolved(barge, auction);
+        }
+    }
+
+    fn observe(&mut self, block: NewBlock) -> Result<()> {
+        self.best = (block.number, block.hash);
+        let events = self
+            .chain
+            .get::<Vec<EventRecordOf>>(block.hash, events_key())?
+            .unwrap_or_default();
+
+        for hash in &block.dropped {
+            if let Some(pending) = self.pending.remove(hash) {
+                self.report.dropped();
+                // Everything the signer submitted after it is stuck now, start over from the
+                // nonce the chain knows about.
+                self.nonces.remove(&pending.signer);
+                self.resolve(pending.kind);
+            }
+        }
+
+        for (index, hash) in block.extrinsics.iter().enumerate() {
+            if let Some(pending) = self.pending.remove(hash) {
+                let error = events
+                    .iter()
+                    .filter(|record| record.phase == Phase::ApplyExtrinsic(index as u32))
+                    .find_map(|record| match &record.event {
+                        Event::system(system::RawEvent::ExtrinsicFailed(error, _)) => {
+                            Some(describe(error))
+                        }
+                        _ => None,
+                    });
+                self.report.included(
+                    pending.submitted.elapsed(),
+                    block.number.saturating_sub(pending.at),
+                    error,
+                );
+                self.resolve(pending.kind);
+            }
+        }
+
+        // Bids placed in this block, the last one of an auction is its leading bid.
+        let mut bids = HashMap::new();
+        for record in &events {
+            match &record.event {
+                Event::auction(auction::RawEvent::AuctionCreated(
+                    id,
+                    creator,
+                    terminal,
+                    start,
+                    end,
+                    ..,
+                )) if self.scenario.is_terminal(creator) => {
+                    self.auctions.insert(
+                        *id,
+                        Tracked {
+                            view: AuctionView {
+                                creator: creator.clone(),
+                                start: *start,
+                                end: Some(*end),
+                                bid: None,
+                            },
+                            slot_origin: terminal.clone(),
+                            overdue: false,
+                            settled: false,
+                        },
+                    );
+                }
+                Event::auction(auction::RawEvent::Bid(id, bidder, value)) => {
+                    bids.insert(*id, (bidder.clone(), *value));
+                }
+                _ => {}
+            }
+        }
+
+        let open = self
+            .auctions
+            .iter()
+            .filter(|(_, tracked)| !tracked.settled)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in open {
+            match self
+                .chain
+                .get::<AuctionInfoOf>(block.hash, auction_key(id))?
+            {
+                Some(info) => {
+                    let overdue = info.end.map_or(false, |end| end < block.number);
+                    let tracked = self.auctions.get_mut(&id).expect("collected above; qed");
+                    tracked.view = AuctionView {
+                        creator: info.creator,
+                        start: info.start,
+                        end: info.end,
+                        bid: info.bid,
+                    };
+                    if overdue && !tracked.overdue {
+                        tracked.overdue = true;
+                        self.report.violation(format!(
+                            "Auction {} is still stored at block {}, past its end",
+                            id, block.number
+                        ));
+                    }
+                }
+                None => self.settle(id, block.number, &events, bids.remove(&id)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the settlement of an auction which disappeared from storage in block `number`.
+    /// `last_bid` is the last bid placed on it in that block.
+    fn settle(
+        &mut self,
+        id: AuctionId,
+        number: BlockNumber,
+        events: &[EventRecordOf],
+        last_bid: Option<(AccountId, Balance)>,
+    ) {
+        let tracked = match self.auctions.get_mut(&id) {
+            Some(tracked) => tracked,
+            None => return,
+        };
+        tracked.settled = true;
+        let creator = tracked.view.creator.clone();
+        let winner = last_bid.or_else(|| tracked.view.bid.clone());
+
+        let mut violations = Vec::new();
+        if tracked.view.end != Some(number) {
+            violations.push(format!(
+                "Auction {} was settled at block {} instead of at its end {:?}",
+                id, number, tracked.view.end
+            ));
+        }
+
+        let recorded = events.iter().find_map(|record| match &record.event {
+            Event::reputation(reputation::RawEvent::SlotRecorded(slot, terminal, barge, _))
+                if *slot == id =>
+            {
+                Some((terminal.clone(), barge.clone()))
+            }
+            _ => None,
+        });
+
+        match &winner {
+            Some((winner, price)) => {
+                // The winning bid is split between the creator, the terminal and the platform, the
+                // payouts have to add up to the whole bid.
+                let paid = events
+                    .iter()
+                    .filter_map(|record| match &record.event {
+                        Event::auction(auction::RawEvent::CreatorPaid(settled, _, value))
+                        | Event::auction(auction::RawEvent::CommissionPaid(settled, _, value))
+                        | Event::auction(auction::RawEvent::PlatformFeePaid(settled, _, value))
+                        | Event::auction(auction::RawEvent::BidBurned(settled, _, value))
+                            if *settled == id =>
+                        {
+                            Some(*value)
+                        }
+                        _ => None,
+                    })
+                    .sum::<Balance>();
+                if paid != *price {
+                    violations.push(format!(
+                        "Auction {}: {} didn't pay the winning bid of {} to {}",
+                        id, winner, price, creator
+                    ));
+                }
+                if recorded != Some((tracked.slot_origin.clone(), winner.clone())) {
+                    violations.push(format!(
+                        "Auction {}: the slot wasn't recorded for {}",
+                        id, winner
+                    ));
+                }
+            }
+            None => {
+                if recorded.is_some() {
+                    violations.push(format!(
+                        "Auction {} closed without bids, yet a slot was recorded",
+                        id
+                    ));
+                }
+            }
+        }
+
+        self.report.auctions.push(Settlement {
+            id,
+            creator,
+            block: Some(number),
+            winner: winner.as_ref().map(|winner| winner.0.clone()),
+            price: winner.map(|winner| winner.1),
+            correct: violations.is_empty(),
+        });
+        for violation in violations {
+            self.report.violation(violation);
+        }
+    }
+}
+
+/// A short description of a dispatch error. Module errors only carry indices once decoded.
+fn describe(error: &DispatchError) -> String {
+    match error {
+        DispatchError::Module { index, error, .. } => {
+            format!("module {} error {}", index, error)
+        }
+        other => format!("{:?}", other),
+    }
+}
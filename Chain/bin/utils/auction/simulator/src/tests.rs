@@ -0,0 +1,85 @@
+use crate::cli::ScenarioParams;
+use crate::in_process::InProcessChain;
+use crate::report::Report;
+use crate::scenario::{Scenario, Strategy};
+use crate::simulation::Simulation;
+use structopt::StructOpt;
+
+fn params(args: &[&str]) -> ScenarioParams {
+    ScenarioParams::from_iter(
+        [
+            "simulator",
+            "--terminals",
+            "2",
+            "--barges",
+            "3",
+            "--slots",
+            "2",
+        ]
+        .iter()
+        .chain(args),
+    )
+}
+
+fn run_in_process(params: &ScenarioParams) -> Report {
+    let scenario = Scenario::new(params).unwrap();
+    let chain = InProcessChain::new(scenario.accounts());
+    Simulation::new(chain, scenario, "in-process")
+        .unwrap()
+        .run()
+        .unwrap()
+}
+
+#[test]
+fn every_auction_settles_in_process() {
+    let report = run_in_process(&params(&["--activity", "100", "--duration", "4"]));
+
+    assert_eq!(report.violations, Vec::<String>::new());
+    assert_eq!(report.auctions.len(), 4);
+    assert!(report.auctions.iter().all(|auction| auction.correct));
+    assert!(report
+        .auctions
+        .iter()
+        .all(|auction| auction.winner.is_some()));
+    assert_eq!(report.submitted.get("create"), Some(&4));
+    assert_eq!(report.dropped, 0);
+}
+
+#[test]
+fn auctions_without_bids_close_in_process() {
+    let report = run_in_process(&params(&["--activity", "0"]));
+
+    assert_eq!(report.violations, Vec::<String>::new());
+    assert_eq!(report.auctions.len(), 4);
+    assert!(report
+        .auctions
+        .iter()
+        .all(|auction| auction.winner.is_none() && auction.block.is_some()));
+    assert_eq!(report.submitted.get("bid"), None);
+}
+
+#[test]
+fn same_seed_gives_same_outcome() {
+    let params = params(&["--seed", "7", "--duration", "3"]);
+    let first = run_in_process(&params);
+    let second = run_in_process(&params);
+
+    let outcome = |report: &Report| {
+        report
+            .auctions
+            .iter()
+            .map(|auction| (auction.id, auction.winner.clone(), auction.price))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(outcome(&first), outcome(&second));
+}
+
+#[test]
+fn strategies_parse() {
+    assert_eq!("Sniper".parse::<Strategy>(), Ok(Strategy::Sniper));
+    assert_eq!(
+        params(&["--strategies", "max,incremental"]).strategies,
+        vec![Strategy::Max, Strategy::Incremental]
+    );
+    assert!("shill".parse::<Strategy>().is_err());
+}
@@ -7,12 +7,13 @@ use serde::{Serialize, Deserialize};
 use sp_std::{
     cmp::{Eq, PartialEq},
     fmt::Debug,
+    vec::Vec,
 };
 /// Bids which are placed prior to the auction's start-time are stored in these QueuedBid structs.
 /// Once the auction starts, the highest bid is automatically inserted, this is done to make sure
 /// that if an auction has to be finished as soon as possible, it should be displayed as early as
 /// the initiator wants it to be displayed
-#[cfg_attr(feature = "std", derive(PartialEq, Eq))]
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
 #[derive(Clone, Copy, RuntimeDebug, Encode, Decode, Default)]
 pub struct QueuedBid<AccountId, Balance, AuctionId> {
     /// The bid to be queued.
@@ -21,6 +22,12 @@ pub struct QueuedBid<AccountId, Balance, AuctionId> {
     pub auction_id: AuctionId,
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(QueuedBid<AccountId, Balance, AuctionId> {
+    bid: (AccountId, Balance),
+    auction_id: AuctionId,
+});
+
 /// Information being sold in the auction, in our case the actual time-slot. For now all we're
 /// storing is the timestamp and the cargo information in a tuple of (number of containers, TEUs). For TEUs we're assuming (1TEU==1Container).
 /// The timestamp is stored in UNIX format :: https://en.wikipedia.org/wiki/Unix_time
@@ -33,6 +40,12 @@ pub struct AuctionCoreInfo<GeneralInformationContainer> {
     pub cargo: (GeneralInformationContainer, GeneralInformationContainer),
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(AuctionCoreInfo<GeneralInformationContainer> {
+    timestamp: GeneralInformationContainer,
+    cargo: (GeneralInformationContainer, GeneralInformationContainer),
+});
+
 /// This struct is used for the AuctionUpdated event, shows the old AuctionInfo
 /// and the new AuctionInfo.
 #[cfg_attr(feature = "std", derive(PartialEq, Eq))]
@@ -42,6 +55,9 @@ pub struct AuctionUpdateComplete<A> {
     pub new: A,
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(AuctionUpdateComplete<A> { old: A, new: A });
+
 /// This struct serves to be convenient for the end-user and for the developer working
 /// on updating the auctions. When initiating an update, the end-user will be able to
 /// see the field names, which is a nice indicator which shows exactly what is being
@@ -54,6 +70,57 @@ pub struct AuctionUpdateInfo<GeneralInformationContainer> {
     pub num_teu: Option<GeneralInformationContainer>,
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(AuctionUpdateInfo<GeneralInformationContainer> {
+    timestamp: Option<GeneralInformationContainer>,
+    num_con: Option<GeneralInformationContainer>,
+    num_teu: Option<GeneralInformationContainer>,
+});
+
+/// An auction to be created by a batch call, the batch's sender being its creator.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, Copy, RuntimeDebug, Encode, Decode)]
+pub struct NewAuction<AccountId, BlockNumber, GeneralInformationContainer> {
+    /// Terminal issuing the slot
+    pub terminal: AccountId,
+    /// Core auction information
+    pub core: AuctionCoreInfo<GeneralInformationContainer>,
+    /// Block the auction starts in.
+    pub start: BlockNumber,
+    /// Block the auction ends in.
+    pub end: BlockNumber,
+}
+
+#[cfg(feature = "std")]
+crate::describe_struct!(NewAuction<AccountId, BlockNumber, GeneralInformationContainer> {
+    terminal: AccountId,
+    core: AuctionCoreInfo<GeneralInformationContainer>,
+    start: BlockNumber,
+    end: BlockNumber,
+});
+
+/// An update of auction `id` made by a batch call, fields left `None` keep their value.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, Copy, RuntimeDebug, Encode, Decode)]
+pub struct AuctionUpdate<AuctionId, BlockNumber, GeneralInformationContainer> {
+    pub id: AuctionId,
+    pub timestamp: Option<GeneralInformationContainer>,
+    pub num_con: Option<GeneralInformationContainer>,
+    pub num_teu: Option<GeneralInformationContainer>,
+    pub start: Option<BlockNumber>,
+    pub end: Option<BlockNumber>,
+}
+
+#[cfg(feature = "std")]
+crate::describe_struct!(AuctionUpdate<AuctionId, BlockNumber, GeneralInformationContainer> {
+    id: AuctionId,
+    timestamp: Option<GeneralInformationContainer>,
+    num_con: Option<GeneralInformationContainer>,
+    num_teu: Option<GeneralInformationContainer>,
+    start: Option<BlockNumber>,
+    end: Option<BlockNumber>,
+});
+
 /// Auction information. The creator of the auction is always the barge. Upon creating the auction,
 /// the barge also states which terminal this auctioned off slot belongs to. This can later be
 /// expanded into verification of slot ownership etc.
@@ -66,8 +133,6 @@ pub struct AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContai
     pub slot_origin: AccountId,
     /// Current bidder and bid price.
     pub bid: Option<(AccountId, Balance)>,
-    /// Current parsed bid price.
-    pub parsed_bid: Option<u64>,
     /// Core auction information
     pub core: AuctionCoreInfo<GeneralInformationContainer>,
     /// Define which block this auction will be started.
@@ -76,6 +141,16 @@ pub struct AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContai
     pub end: Option<BlockNumber>,
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(AuctionInfo<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+    creator: AccountId,
+    slot_origin: AccountId,
+    bid: Option<(AccountId, Balance)>,
+    core: AuctionCoreInfo<GeneralInformationContainer>,
+    start: BlockNumber,
+    end: Option<BlockNumber>,
+});
+
 /// Auction information displayed to the user on request, this contains specific auction data.
 #[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
 #[derive(Clone, Copy, RuntimeDebug, Encode, Decode)]
@@ -98,6 +173,147 @@ pub struct UIAuctionInfo<AccountId, BlockNumber, GeneralInformationContainer> {
     pub auction_end_time: Option<BlockNumber>,
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(UIAuctionInfo<AccountId, BlockNumber, GeneralInformationContainer> {
+    slot_owner: AccountId,
+    slot_origin: AccountId,
+    slot_time: GeneralInformationContainer,
+    slot_num_cargo: GeneralInformationContainer,
+    slot_num_teu: GeneralInformationContainer,
+    auction_is_live: bool,
+    auction_highest_bid: Option<(AccountId, u64)>,
+    auction_end_time: Option<BlockNumber>,
+});
+
+/// Policy knobs of the auctioning pallet. These are stored on-chain and changed through governance,
+/// a value of zero disables the respective knob.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, Copy, RuntimeDebug, Encode, Decode, Default)]
+pub struct AuctionParameters<Balance, BlockNumber> {
+    /// Minimum number of blocks between an auction's start and end.
+    pub min_duration: BlockNumber,
+    /// Maximum number of blocks an auction's start may lie in the future.
+    pub max_horizon: BlockNumber,
+    /// Deposit reserved from the creator for the lifetime of the auction.
+    pub creation_deposit: Balance,
+    /// Fee charged to the creator for creating an auction, this fee is burned.
+    pub creation_fee: Balance,
+    /// Bids placed within this many blocks of the auction's end extend the end to this many blocks
+    /// after the bid.
+    pub soft_close_window: BlockNumber,
+}
+
+#[cfg(feature = "std")]
+crate::describe_struct!(AuctionParameters<Balance, BlockNumber> {
+    min_duration: BlockNumber,
+    max_horizon: BlockNumber,
+    creation_deposit: Balance,
+    creation_fee: Balance,
+    soft_close_window: BlockNumber,
+});
+
+/// A slot won in an auction, which its holder may resell until shortly before the slot's time. The
+/// slot is identified by the auction it was first won in.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, RuntimeDebug, Encode, Decode)]
+pub struct WonSlot<AccountId, Balance, AuctionId, GeneralInformationContainer> {
+    /// Current holder of the slot (Barge)
+    pub holder: AccountId,
+    /// Terminal which originally issued the time-slot
+    pub slot_origin: AccountId,
+    /// Core slot information, resales don't change it
+    pub core: AuctionCoreInfo<GeneralInformationContainer>,
+    /// Fixed price the holder offers the slot for
+    pub price: Option<Balance>,
+    /// Auction the holder relisted the slot in
+    pub resale: Option<AuctionId>,
+    /// Whether the terminal approved a resale by the current holder
+    pub approved: bool,
+}
+
+#[cfg(feature = "std")]
+crate::describe_struct!(WonSlot<AccountId, Balance, AuctionId, GeneralInformationContainer> {
+    holder: AccountId,
+    slot_origin: AccountId,
+    core: AuctionCoreInfo<GeneralInformationContainer>,
+    price: Option<Balance>,
+    resale: Option<AuctionId>,
+    approved: bool,
+});
+
+/// Berth capacity a terminal declares for its slots. Slots whose windows overlap share the
+/// terminal's berths, no point in time may see more vessels or TEU than declared here.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, Copy, RuntimeDebug, Encode, Decode, Default)]
+pub struct TerminalCapacity<GeneralInformationContainer> {
+    /// Number of vessels the terminal handles at once
+    pub max_vessels: u32,
+    /// Number of TEU the terminal handles at once
+    pub max_teu: GeneralInformationContainer,
+    /// Number of seconds a vessel occupies a berth, starting at the slot's timestamp
+    pub slot_duration: u64,
+}
+
+#[cfg(feature = "std")]
+crate::describe_struct!(TerminalCapacity<GeneralInformationContainer> {
+    max_vessels: u32,
+    max_teu: GeneralInformationContainer,
+    slot_duration: u64,
+});
+
+/// The time a slot occupies one of its terminal's berths, from `start` up to but excluding `end`,
+/// both UNIX timestamps.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, Copy, RuntimeDebug, Encode, Decode, Default)]
+pub struct BerthWindow<GeneralInformationContainer> {
+    /// Start of the window
+    pub start: u64,
+    /// End of the window
+    pub end: u64,
+    /// Number of TEU handled in the window
+    pub teu: GeneralInformationContainer,
+}
+
+#[cfg(feature = "std")]
+crate::describe_struct!(BerthWindow<GeneralInformationContainer> {
+    start: u64,
+    end: u64,
+    teu: GeneralInformationContainer,
+});
+
+/// A terminal set up in the genesis block.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, RuntimeDebug, Encode, Decode)]
+pub struct GenesisTerminal<AccountId, GeneralInformationContainer> {
+    /// Account of the terminal
+    pub account: AccountId,
+    /// Berth capacity the terminal declares, if any
+    pub capacity: Option<TerminalCapacity<GeneralInformationContainer>>,
+    /// Whether the resales of the terminal's slots need its approval
+    pub resale_approval_required: bool,
+}
+
+/// An auction set up in the genesis block, along with the bids placed on it. Bids on an auction
+/// starting in the genesis block are placed, the highest one leads and the others are runner-ups.
+/// Bids on an auction starting later are queued. Either way they are reserved from the bidders'
+/// genesis balances.
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Serialize, Deserialize))]
+#[derive(Clone, RuntimeDebug, Encode, Decode)]
+pub struct GenesisAuction<AccountId, Balance, BlockNumber, GeneralInformationContainer> {
+    /// Creator of the auction (Barge)
+    pub creator: AccountId,
+    /// Terminal which issues the time-slot
+    pub terminal: AccountId,
+    /// Core slot information
+    pub core: AuctionCoreInfo<GeneralInformationContainer>,
+    /// Block the auction starts in
+    pub start: BlockNumber,
+    /// Block the auction ends in
+    pub end: BlockNumber,
+    /// Bids on the auction, at most one per bidder
+    pub bids: Vec<(AccountId, Balance)>,
+}
+
 /// Abstraction over a simple auction system.
 pub trait Auction<AccountId, BlockNumber, GeneralInformationContainer, ErrorTypes> {
     /// The id of an AuctionInfo
@@ -141,6 +357,8 @@ pub trait Auction<AccountId, BlockNumber, GeneralInformationContainer, ErrorType
         Option<AuctionInfo<AccountId, Self::Balance, BlockNumber, GeneralInformationContainer>>,
         ErrorTypes,
     >;
+    /// The timestamp in seconds of the slot won in auction `id`, `None` if no such slot is held
+    fn won_slot_time(id: Self::AuctionId) -> Option<u64>;
 }
 
 /// The result of bid handling.
@@ -151,6 +369,12 @@ pub struct OnNewBidResult<BlockNumber> {
     pub auction_end: Option<Option<BlockNumber>>,
 }
 
+#[cfg(feature = "std")]
+crate::describe_struct!(OnNewBidResult<BlockNumber> {
+    accept_bid: bool,
+    auction_end: Option<Option<BlockNumber>>,
+});
+
 /// Hooks for auction to handle bids.
 pub trait AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> {
     /// Called when new bid is received.
@@ -162,10 +386,15 @@ pub trait AuctionHandler<AccountId, Balance, BlockNumber, AuctionId> {
         new_bid: (AccountId, Balance),
         last_bid: Option<(AccountId, Balance)>,
     ) -> OnNewBidResult<BlockNumber>;
-    /// End an auction with `winner`
+    /// End an auction with `winner`, `recipients` being the auction's (creator, terminal).
+    /// Returns whether the winner paid for the slot, after which the winner holds the slot. A
+    /// winner who didn't pay defaults, the handler releases what's left of its bid and is called
+    /// again with the next runner-up, or with no winner once none is left.
     fn on_auction_ended(
         id: AuctionId,
         recipients: (AccountId, AccountId),
         winner: Option<(AccountId, Balance)>,
-    );
+    ) -> bool;
+    /// Called when a won slot, identified by the auction it was first won in, is resold.
+    fn on_slot_transferred(_slot: AuctionId, _from: AccountId, _to: AccountId) {}
 }
@@ -0,0 +1,260 @@
+//! Descriptions of the auction types in the format of the polkadot.js type registry, from which
+//! `node-auction export-types` builds `types.json`.
+//!
+//! The descriptions sit right next to the types they describe and are checked against them at
+//! compile time: a description which misses a field or variant, names one that doesn't exist,
+//! gives a field another type than its definition or lists the variants of an enum in another
+//! order fails to build. The order of a struct's fields can't be checked at compile time, instead
+//! every described struct implements [`DescribedEncoding`], which tests compare against the
+//! struct's own encoding.
+
+pub use codec::Encode;
+
+/// The shape of a type as far as polkadot.js is concerned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeDefinition {
+    /// Another name for an existing type.
+    Alias(String),
+    /// A struct, its fields in declaration order along with their types.
+    Struct(Vec<(String, String)>),
+    /// A fieldless enum, its variants in declaration order.
+    Enum(Vec<String>),
+}
+
+impl TypeDefinition {
+    /// Replaces the generic parameter `param` by `name` in every type the definition refers to.
+    pub fn substitute(self, param: &str, name: &str) -> Self {
+        let replace = |ty: String| replace_ident(&ty, param, name);
+        match self {
+            TypeDefinition::Alias(ty) => TypeDefinition::Alias(replace(ty)),
+            TypeDefinition::Struct(fields) => TypeDefinition::Struct(
+                fields
+                    .into_iter()
+                    .map(|(field, ty)| (field, replace(ty)))
+                    .collect(),
+            ),
+            TypeDefinition::Enum(variants) => TypeDefinition::Enum(variants),
+        }
+    }
+}
+
+/// Replaces every occurrence of the identifier `from` in `ty` by `to`, leaving identifiers which
+/// merely contain `from` alone.
+fn replace_ident(ty: &str, from: &str, to: &str) -> String {
+    let mut replaced = String::with_capacity(ty.len());
+    let mut ident = String::new();
+    for c in ty.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            ident.push(c);
+            continue;
+        }
+        replaced.push_str(if ident == from { to } else { &ident });
+        ident.clear();
+        replaced.push(c);
+    }
+    replaced.push_str(if ident == from { to } else { &ident });
+    replaced
+}
+
+/// A type which can be described to polkadot.js.
+pub trait DescribeType {
+    /// Name of the type in the registry.
+    const NAME: &'static str;
+
+    fn definition() -> TypeDefinition;
+}
+
+/// A described struct, encoded field by field in the order of its description.
+pub trait DescribedEncoding {
+    /// Encodes the fields in the order they are described in. This matches the struct's own
+    /// encoding unless the description lists the fields in another order, given the fields of the
+    /// same type hold distinct values.
+    fn described_encoding(&self) -> Vec<u8>;
+}
+
+/// Formats a stringified Rust type the way polkadot.js spells it, `Option<(A, B)>`.
+pub fn normalize(ty: &str) -> String {
+    ty.split_whitespace().collect::<String>().replace(',', ", ")
+}
+
+/// Describes a struct by listing its fields, `describe_struct!(Name<A, B> { a: A, b: Option<B> })`.
+/// The generic parameters keep their names in the description, so they should be named after the
+/// registry types they stand for.
+#[macro_export]
+macro_rules! describe_struct {
+    ($name:ident $(<$($param:ident),*>)? { $($field:ident: $ty:ty),* $(,)? }) => {
+        impl$(<$($param),*>)? $crate::types::DescribeType for $name$(<$($param),*>)? {
+            const NAME: &'static str = stringify!($name);
+
+            fn definition() -> $crate::types::TypeDefinition {
+                $crate::types::TypeDefinition::Struct(vec![
+                    $((stringify!($field).into(), $crate::types::normalize(stringify!($ty)))),*
+                ])
+            }
+        }
+
+        impl$(<$($param: $crate::types::Encode),*>)? $crate::types::DescribedEncoding
+            for $name$(<$($param),*>)?
+        {
+            fn described_encoding(&self) -> Vec<u8> {
+                let mut encoded = Vec::new();
+                $($crate::types::Encode::encode_to(&self.$field, &mut encoded);)*
+                encoded
+            }
+        }
+
+        const _: () = {
+            #[allow(dead_code)]
+            fn check$(<$($param),*>)?(value: $name$(<$($param),*>)?) {
+                let $name { $($field),* } = value;
+                $(let _: $ty = $field;)*
+            }
+        };
+    };
+}
+
+/// Describes a fieldless enum by listing its variants, which have to be given in declaration order
+/// as polkadot.js encodes them by position.
+#[macro_export]
+macro_rules! describe_enum {
+    ($name:ident { $($variant:ident),* $(,)? }) => {
+        impl $crate::types::DescribeType for $name {
+            const NAME: &'static str = stringify!($name);
+
+            fn definition() -> $crate::types::TypeDefinition {
+                $crate::types::TypeDefinition::Enum(vec![$(stringify!($variant).into()),*])
+            }
+        }
+
+        const _: () = {
+            #[allow(dead_code)]
+            fn check(value: $name) {
+                match value {
+                    $($name::$variant => ()),*
+                }
+            }
+
+            $crate::describe_enum!(@order $name, 0usize, $($variant),*);
+        };
+    };
+    // A variant out of order has another discriminant than its position in the description, which
+    // gives the array another length than its type.
+    (@order $name:ident, $index:expr, $variant:ident $(, $rest:ident)*) => {
+        const _: [(); $index] = [(); $name::$variant as usize];
+        $crate::describe_enum!(@order $name, $index + 1, $($rest),*);
+    };
+    (@order $name:ident, $index:expr,) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auction::*;
+
+    // Fields of the same type hold distinct values, so a description listing them in another order
+    // encodes differently.
+    fn core_info(timestamp: u64) -> AuctionCoreInfo<u64> {
+        AuctionCoreInfo {
+            timestamp,
+            cargo: (timestamp + 1, timestamp + 2),
+        }
+    }
+
+    fn assert_described_order<T: Encode + DescribedEncoding>(value: T) {
+        assert_eq!(value.described_encoding(), value.encode());
+    }
+
+    #[test]
+    fn misordered_fields_are_detected() {
+        #[derive(Encode)]
+        struct Pair {
+            first: u32,
+            second: u32,
+        }
+        crate::describe_struct!(Pair {
+            second: u32,
+            first: u32,
+        });
+
+        let pair = Pair {
+            first: 1,
+            second: 2,
+        };
+        assert_ne!(pair.described_encoding(), pair.encode());
+    }
+
+    #[test]
+    fn described_fields_are_in_order() {
+        assert_described_order(QueuedBid {
+            bid: (1u64, 2u128),
+            auction_id: 3u32,
+        });
+        assert_described_order(core_info(1));
+        assert_described_order(AuctionUpdateComplete {
+            old: core_info(1),
+            new: core_info(4),
+        });
+        assert_described_order(AuctionUpdateInfo {
+            timestamp: Some(1u64),
+            num_con: Some(2),
+            num_teu: Some(3),
+        });
+        assert_described_order(NewAuction {
+            terminal: 1u64,
+            core: core_info(2),
+            start: 5u32,
+            end: 6,
+        });
+        assert_described_order(AuctionUpdate {
+            id: 1u32,
+            timestamp: Some(2u64),
+            num_con: Some(3),
+            num_teu: Some(4),
+            start: Some(5u32),
+            end: Some(6),
+        });
+        assert_described_order(AuctionInfo {
+            creator: 1u64,
+            slot_origin: 2,
+            bid: Some((3, 4u128)),
+            core: core_info(5),
+            start: 8u32,
+            end: Some(9),
+        });
+        assert_described_order(UIAuctionInfo {
+            slot_owner: 1u64,
+            slot_origin: 2,
+            slot_time: 3u64,
+            slot_num_cargo: 4,
+            slot_num_teu: 5,
+            auction_is_live: true,
+            auction_highest_bid: Some((6, 7)),
+            auction_end_time: Some(8u32),
+        });
+        assert_described_order(AuctionParameters {
+            min_duration: 1u32,
+            max_horizon: 2,
+            creation_deposit: 3u128,
+            creation_fee: 4,
+            soft_close_window: 5,
+        });
+        assert_described_order(WonSlot {
+            holder: 1u64,
+            slot_origin: 2,
+            core: core_info(3),
+            price: Some(6u128),
+            resale: Some(7u32),
+            approved: true,
+        });
+        assert_described_order(TerminalCapacity {
+            max_vessels: 1,
+            max_teu: 2u64,
+            slot_duration: 3,
+        });
+        assert_described_order(BerthWindow {
+            start: 1,
+            end: 2,
+            teu: 3u64,
+        });
+    }
+}
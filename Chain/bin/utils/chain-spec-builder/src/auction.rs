@@ -0,0 +1,366 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2019-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Chain specs of `node-auction`, built from fixture files.
+//!
+//! A fixture is either a CSV file with a header row or a JSON file holding an array of records,
+//! told apart by the file's extension. Records have the same fields in both formats:
+//!
+//! - balances: `account`, `balance`
+//! - terminals: `account`, `max_vessels`, `max_teu`, `slot_duration`,
+//!   `resale_approval_required`. The capacity fields are left empty, or `null`, for terminals
+//!   which don't declare a berth capacity.
+//! - auctions: `creator`, `terminal`, `timestamp`, `containers`, `teu`, `start`, `end`
+//! - bids: `auction`, `bidder`, `amount`. `auction` is the position of the auction in the
+//!   auctions fixture, counting from zero.
+//!
+//! Accounts are given by their SS58 address, or by the seed of a development account such as
+//! `//Alice` or `//Alice//stash`. `Specs/fixtures` holds an example of every fixture.
+
+use std::{fs, path::{Path, PathBuf}};
+
+use serde::{Deserialize, de::DeserializeOwned};
+use structopt::StructOpt;
+
+use auction_traits::auction::{AuctionCoreInfo, TerminalCapacity};
+use node_auction::chain_spec::{self, Auction, Terminal};
+use node_auction_runtime::{AccountId, Balance, BlockNumber, GeneralInformationContainer, WASM_BINARY};
+use sp_core::{sr25519, crypto::Ss58Codec};
+
+/// The fixture files describing the genesis state of an auction chain.
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Fixtures {
+	/// Endowed accounts along with their balances.
+	#[structopt(long)]
+	balances: PathBuf,
+	/// Terminals declaring a berth capacity or approving the resales of their slots.
+	#[structopt(long)]
+	terminals: Option<PathBuf>,
+	/// Auctions set up in the genesis block.
+	#[structopt(long)]
+	auctions: Option<PathBuf>,
+	/// Bids on the genesis auctions. Bids on auctions starting in the genesis block are placed,
+	/// the others are queued until their auction starts.
+	#[structopt(long, requires = "auctions")]
+	bids: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct BalanceRecord {
+	account: String,
+	balance: Balance,
+}
+
+#[derive(Deserialize)]
+struct TerminalRecord {
+	account: String,
+	max_vessels: Option<u32>,
+	max_teu: Option<GeneralInformationContainer>,
+	slot_duration: Option<u64>,
+	resale_approval_required: bool,
+}
+
+#[derive(Deserialize)]
+struct AuctionRecord {
+	creator: String,
+	terminal: String,
+	timestamp: GeneralInformationContainer,
+	containers: GeneralInformationContainer,
+	teu: GeneralInformationContainer,
+	start: BlockNumber,
+	end: BlockNumber,
+}
+
+#[derive(Deserialize)]
+struct BidRecord {
+	auction: usize,
+	bidder: String,
+	amount: Balance,
+}
+
+impl Fixtures {
+	fn balances(&self) -> Result<Vec<(AccountId, Balance)>, String> {
+		read::<BalanceRecord>(&self.balances)?
+			.into_iter()
+			.map(|record| Ok((parse_account(&record.account)?, record.balance)))
+			.collect()
+	}
+
+	fn terminals(&self) -> Result<Vec<Terminal>, String> {
+		let path = match &self.terminals {
+			Some(path) => path,
+			None => return Ok(Vec::new()),
+		};
+
+		read::<TerminalRecord>(path)?
+			.into_iter()
+			.map(|record| {
+				let capacity = match (record.max_vessels, record.max_teu, record.slot_duration) {
+					(Some(max_vessels), Some(max_teu), Some(slot_duration)) =>
+						Some(TerminalCapacity { max_vessels, max_teu, slot_duration }),
+					(None, None, None) => None,
+					_ => return Err(format!(
+						"Terminal {} declares only part of a capacity, either give `max_vessels`, \
+						 `max_teu` and `slot_duration` or none of them",
+						record.account,
+					)),
+				};
+
+				Ok(Terminal {
+					account: parse_account(&record.account)?,
+					capacity,
+					resale_approval_required: record.resale_approval_required,
+				})
+			})
+			.collect()
+	}
+
+	fn auctions(&self) -> Result<Vec<Auction>, String> {
+		let path = match &self.auctions {
+			Some(path) => path,
+			None => return Ok(Vec::new()),
+		};
+
+		let mut auctions = read::<AuctionRecord>(path)?
+			.into_iter()
+			.map(|record| Ok(Auction {
+				creator: parse_account(&record.creator)?,
+				terminal: parse_account(&record.terminal)?,
+				core: AuctionCoreInfo {
+					timestamp: record.timestamp,
+					cargo: (record.containers, record.teu),
+				},
+				start: record.start,
+				end: record.end,
+				bids: Vec::new(),
+			}))
+			.collect::<Result<Vec<_>, String>>()?;
+
+		if let Some(path) = &self.bids {
+			for record in read::<BidRecord>(path)? {
+				let auction = auctions.get_mut(record.auction).ok_or_else(|| format!(
+					"Bid of {} on auction {}, which isn't in the auctions fixture",
+					record.bidder,
+					record.auction,
+				))?;
+				auction.bids.push((parse_account(&record.bidder)?, record.amount));
+			}
+		}
+
+		Ok(auctions)
+	}
+}
+
+/// Generates the chain spec of an auction chain with the given authorities and sudo account,
+/// from the genesis state described by `fixtures`.
+pub fn generate_chain_spec(
+	authority_seeds: Vec<String>,
+	sudo_account: String,
+	fixtures: Fixtures,
+) -> Result<String, String> {
+	let authorities = authority_seeds
+		.iter()
+		.map(AsRef::as_ref)
+		.map(chain_spec::authority_keys_from_seed)
+		.collect::<Vec<_>>();
+	let sudo_account = parse_account(&sudo_account)?;
+	let balances = fixtures.balances()?;
+	let terminals = fixtures.terminals()?;
+	let auctions = fixtures.auctions()?;
+
+	let chain_spec = chain_spec::ChainSpec::from_genesis(
+		"Custom",
+		"custom",
+		sc_chain_spec::ChainType::Live,
+		move || chain_spec::testnet_genesis(
+			WASM_BINARY,
+			authorities.clone(),
+			sudo_account.clone(),
+			balances.clone(),
+			terminals.clone(),
+			auctions.clone(),
+			true,
+		),
+		vec![],
+		None,
+		None,
+		None,
+		Default::default(),
+	);
+
+	chain_spec.as_json(false)
+}
+
+fn parse_account(account: &str) -> Result<AccountId, String> {
+	if account.starts_with("//") {
+		Ok(chain_spec::get_account_id_from_seed::<sr25519::Public>(&account[2..]))
+	} else {
+		AccountId::from_string(account)
+			.map_err(|err| format!("Failed to parse account address {}: {:?}", account, err))
+	}
+}
+
+/// Reads the records of the CSV or JSON file at `path`.
+fn read<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, String> {
+	let error = |err: &dyn std::fmt::Display| format!("Failed to read {}: {}", path.display(), err);
+
+	match path.extension().and_then(|extension| extension.to_str()) {
+		Some("csv") => csv::ReaderBuilder::new()
+			.trim(csv::Trim::All)
+			.from_path(path)
+			.map_err(|err| error(&err))?
+			.deserialize()
+			.collect::<Result<_, _>>()
+			.map_err(|err| error(&err)),
+		Some("json") => {
+			let file = fs::File::open(path).map_err(|err| error(&err))?;
+			serde_json::from_reader(file).map_err(|err| error(&err))
+		},
+		_ => Err(format!("{} is neither a CSV nor a JSON file", path.display())),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+		let path = dir.path().join(name);
+		fs::write(&path, content).unwrap();
+		path
+	}
+
+	fn fixtures(dir: &TempDir) -> Fixtures {
+		Fixtures {
+			balances: write(dir, "balances.csv", "account,balance\n//Alice,1000\n"),
+			terminals: None,
+			auctions: None,
+			bids: None,
+		}
+	}
+
+	fn account(seed: &str) -> AccountId {
+		chain_spec::get_account_id_from_seed::<sr25519::Public>(seed)
+	}
+
+	#[test]
+	fn reads_the_example_fixtures() {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../Specs/fixtures");
+		let fixtures = Fixtures {
+			balances: dir.join("balances.csv"),
+			terminals: Some(dir.join("terminals.csv")),
+			auctions: Some(dir.join("auctions.json")),
+			bids: Some(dir.join("bids.csv")),
+		};
+
+		assert_eq!(fixtures.balances().unwrap().len(), 6);
+		let terminals = fixtures.terminals().unwrap();
+		assert_eq!(terminals.len(), 2);
+		assert!(terminals[0].capacity.is_some());
+		let auctions = fixtures.auctions().unwrap();
+		assert_eq!(auctions.len(), 2);
+		assert_eq!(auctions[0].bids.len(), 2);
+		assert_eq!(auctions[1].bids, vec![(account("Eve"), 2000)]);
+	}
+
+	#[test]
+	fn accounts_are_addresses_or_seeds() {
+		let alice = account("Alice");
+		assert_eq!(parse_account("//Alice").unwrap(), alice);
+		assert_eq!(parse_account(&alice.to_ss58check()).unwrap(), alice);
+		assert_eq!(parse_account("//Alice//stash").unwrap(), account("Alice//stash"));
+	}
+
+	#[test]
+	fn bad_account_fails() {
+		let dir = TempDir::new().unwrap();
+		let fixtures = Fixtures {
+			balances: write(&dir, "bad.csv", "account,balance\nAlice,1000\n"),
+			..fixtures(&dir)
+		};
+
+		let error = fixtures.balances().unwrap_err();
+		assert!(error.contains("Failed to parse account address Alice"), "{}", error);
+	}
+
+	#[test]
+	fn bid_on_missing_auction_fails() {
+		let dir = TempDir::new().unwrap();
+		let fixtures = Fixtures {
+			auctions: Some(write(
+				&dir,
+				"auctions.csv",
+				"creator,terminal,timestamp,containers,teu,start,end\n\
+				 //Charlie,//Alice,1592839917,200,200,0,50\n",
+			)),
+			bids: Some(write(&dir, "bids.csv", "auction,bidder,amount\n1,//Eve,1000\n")),
+			..fixtures(&dir)
+		};
+
+		let error = fixtures.auctions().unwrap_err();
+		assert_eq!(error, "Bid of //Eve on auction 1, which isn't in the auctions fixture");
+	}
+
+	#[test]
+	fn empty_capacity_columns_declare_no_capacity() {
+		let dir = TempDir::new().unwrap();
+		let header = "account,max_vessels,max_teu,slot_duration,resale_approval_required\n";
+		let fixtures = Fixtures {
+			terminals: Some(write(
+				&dir,
+				"terminals.csv",
+				&format!("{}//Alice,4,1000,3600,false\n//Bob,,,,true\n", header),
+			)),
+			..fixtures(&dir)
+		};
+
+		let terminals = fixtures.terminals().unwrap();
+		assert_eq!(
+			terminals[0].capacity,
+			Some(TerminalCapacity { max_vessels: 4, max_teu: 1000, slot_duration: 3600 }),
+		);
+		assert_eq!(terminals[1].account, account("Bob"));
+		assert_eq!(terminals[1].capacity, None);
+		assert!(terminals[1].resale_approval_required);
+
+		// A JSON fixture leaves the capacity out with `null`.
+		let fixtures = Fixtures {
+			terminals: Some(write(
+				&dir,
+				"terminals.json",
+				r#"[{"account": "//Bob", "max_vessels": null, "max_teu": null,
+					"slot_duration": null, "resale_approval_required": false}]"#,
+			)),
+			..fixtures(&dir)
+		};
+		assert_eq!(fixtures.terminals().unwrap()[0].capacity, None);
+
+		// A capacity needs all of its columns.
+		let fixtures = Fixtures {
+			terminals: Some(write(
+				&dir,
+				"partial.csv",
+				&format!("{}//Bob,4,,3600,false\n", header),
+			)),
+			..fixtures(&dir)
+		};
+		assert!(fixtures.terminals().unwrap_err().contains("only part of a capacity"));
+	}
+}
@@ -16,6 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod auction;
+
 use std::{fs, path::{Path, PathBuf}};
 
 use ansi_term::Style;
@@ -67,6 +69,22 @@ enum ChainSpecBuilder {
 		#[structopt(long, short)]
 		keystore_path: Option<PathBuf>,
 	},
+	/// Create a new `node-auction` chain spec with the given authorities and
+	/// sudo account, and the balances, terminals, auctions and bids read from
+	/// CSV or JSON fixture files.
+	Auction {
+		/// Authority key seed.
+		#[structopt(long, short, required = true)]
+		authority_seeds: Vec<String>,
+		/// Sudo account address (SS58 format) or development seed (`//Alice`).
+		#[structopt(long, short)]
+		sudo_account: String,
+		#[structopt(flatten)]
+		fixtures: auction::Fixtures,
+		/// The path where the chain spec should be saved.
+		#[structopt(long, short, default_value = "./chain_spec.json")]
+		chain_spec_path: PathBuf,
+	},
 }
 
 impl ChainSpecBuilder {
@@ -77,6 +95,8 @@ impl ChainSpecBuilder {
 				chain_spec_path.as_path(),
 			ChainSpecBuilder::Generate { chain_spec_path, .. } =>
 				chain_spec_path.as_path(),
+			ChainSpecBuilder::Auction { chain_spec_path, .. } =>
+				chain_spec_path.as_path(),
 		}
 	}
 }
@@ -264,6 +284,15 @@ fn main() -> Result<(), String> {
 		ChainSpecBuilder::New { authority_seeds, endowed_accounts, sudo_account, .. } => {
 			(authority_seeds, endowed_accounts, sudo_account)
 		},
+		ChainSpecBuilder::Auction { authority_seeds, sudo_account, fixtures, .. } => {
+			let json = auction::generate_chain_spec(
+				authority_seeds,
+				sudo_account,
+				fixtures,
+			)?;
+
+			return fs::write(chain_spec_path, json).map_err(|err| err.to_string());
+		},
 	};
 
 	let json = generate_chain_spec(
@@ -42,6 +42,16 @@ pub trait ProofProvider<Block: BlockT> {
 		keys: &mut dyn Iterator<Item=&[u8]>,
 	) -> sp_blockchain::Result<StorageProof>;
 
+	/// Reads the storage entries following `start_key` at a given block, in key order,
+	/// returning a range proof covering at least `size_limit` bytes of keys and values and the
+	/// number of entries it covers. `None` starts at the first entry.
+	fn read_range_proof(
+		&self,
+		id: &BlockId<Block>,
+		start_key: Option<&[u8]>,
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)>;
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
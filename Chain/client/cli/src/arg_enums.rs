@@ -174,6 +174,28 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// How to bootstrap the chain when syncing.
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum SyncMode {
+		// Download and import every block.
+		Full,
+		// Download the headers, then the state at a recent finalized block, and import blocks
+		// from there.
+		State,
+	}
+}
+
+impl Into<sc_network::config::SyncMode> for SyncMode {
+	fn into(self) -> sc_network::config::SyncMode {
+		match self {
+			SyncMode::Full => sc_network::config::SyncMode::Full,
+			SyncMode::State => sc_network::config::SyncMode::State,
+		}
+	}
+}
+
 /// Default value for the `--execution-syncing` parameter.
 pub const DEFAULT_EXECUTION_SYNCING: ExecutionStrategy = ExecutionStrategy::NativeElseWasm;
 /// Default value for the `--execution-import-block` parameter.
@@ -212,6 +212,13 @@ macro_rules! substrate_cli_subcommands {
 				}
 			}
 
+			fn keystore_allowed_key_types(&self)
+			-> $crate::Result<::std::option::Option<::std::vec::Vec<::sc_service::config::KeyTypeId>>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.keystore_allowed_key_types()),*
+				}
+			}
+
 			fn database_cache_size(&self) -> $crate::Result<::std::option::Option<usize>> {
 				match self {
 					$($enum::$variant(cmd) => cmd.database_cache_size()),*
@@ -27,9 +27,10 @@ use crate::{
 use names::{Generator, Name};
 use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::config::{
-	BasePath, Configuration, DatabaseConfig, ExtTransport, KeystoreConfig, NetworkConfiguration,
-	NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
-	TaskExecutor, TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
+	BasePath, Configuration, DatabaseConfig, ExtTransport, KeyTypeId, KeystoreConfig,
+	NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode,
+	Role, RpcMethods, TaskExecutor, TelemetryEndpoints, TransactionPoolOptions,
+	WasmExecutionMethod,
 };
 use sc_service::{ChainSpec, TracingReceiver};
 use std::net::SocketAddr;
@@ -152,6 +153,14 @@ pub trait CliConfiguration: Sized {
 			.unwrap_or(Ok(KeystoreConfig::InMemory))
 	}
 
+	/// Get the key types the keystore may sign with.
+	///
+	/// By default this is retrieved from `KeystoreParams` if it is available. Otherwise any key
+	/// type is allowed.
+	fn keystore_allowed_key_types(&self) -> Result<Option<Vec<KeyTypeId>>> {
+		Ok(self.keystore_params().and_then(|x| x.keystore_allowed_key_types.clone()))
+	}
+
 	/// Get the database cache size.
 	///
 	/// By default this is retrieved from `DatabaseParams` if it is available. Otherwise its `None`.
@@ -447,6 +456,7 @@ pub trait CliConfiguration: Sized {
 				node_key,
 			)?,
 			keystore: self.keystore_config(&config_dir)?,
+			keystore_allowed_key_types: self.keystore_allowed_key_types()?,
 			database: self.database_config(&config_dir, database_cache_size, database)?,
 			state_cache_size: self.state_cache_size()?,
 			state_cache_child_ratio: self.state_cache_child_ratio()?,
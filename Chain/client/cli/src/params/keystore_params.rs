@@ -18,10 +18,11 @@
 
 use crate::error::Result;
 use sc_service::config::KeystoreConfig;
+use std::convert::TryFrom;
 use std::fs;
 use std::path::PathBuf;
 use structopt::StructOpt;
-use sp_core::crypto::SecretString;
+use sp_core::crypto::{KeyTypeId, SecretString};
 
 /// default sub directory for the key store
 const DEFAULT_KEYSTORE_CONFIG_PATH: &'static str = "keystore";
@@ -33,6 +34,27 @@ pub struct KeystoreParams {
 	#[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
 	pub keystore_path: Option<PathBuf>,
 
+	/// Use the keys held by a separate signing process, listening on the Unix socket at PATH.
+	#[structopt(
+		long = "keystore-remote",
+		value_name = "PATH",
+		parse(from_os_str),
+		conflicts_with_all = &[
+			"keystore-path", "password-interactive", "password", "password-filename",
+		]
+	)]
+	pub keystore_remote: Option<PathBuf>,
+
+	/// Only sign with the keys of the given key types, e.g. `aura,gran`. Keys of other types can
+	/// still be listed and generated.
+	#[structopt(
+		long = "keystore-allowed-key-types",
+		value_name = "KEY_TYPES",
+		use_delimiter = true,
+		parse(try_from_str = key_type_from_str),
+	)]
+	pub keystore_allowed_key_types: Option<Vec<KeyTypeId>>,
+
 	/// Use interactive shell for entering the password used by the keystore.
 	#[structopt(
 		long = "password-interactive",
@@ -64,9 +86,18 @@ pub fn secret_string_from_str(s: &str) -> std::result::Result<SecretString, Stri
 		.map_err(|_e| "Could not get SecretString".to_string())?)
 }
 
+/// Parse a key type, returning a displayable error.
+pub fn key_type_from_str(s: &str) -> std::result::Result<KeyTypeId, String> {
+	KeyTypeId::try_from(s).map_err(|()| format!("Invalid key type {:?}, expected four characters", s))
+}
+
 impl KeystoreParams {
 	/// Get the keystore configuration for the parameters
 	pub fn keystore_config(&self, base_path: &PathBuf) -> Result<KeystoreConfig> {
+		if let Some(socket) = &self.keystore_remote {
+			return Ok(KeystoreConfig::Remote { socket: socket.clone() });
+		}
+
 		let password = if self.password_interactive {
 			#[cfg(not(target_os = "unknown"))]
 			{
@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::arg_enums::SyncMode;
 use crate::params::node_key_params::NodeKeyParams;
 use sc_network::{
 	config::{NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, TransportConfig},
@@ -88,6 +89,21 @@ pub struct NetworkParams {
 	)]
 	pub max_parallel_downloads: u32,
 
+	/// How to bootstrap the chain when syncing.
+	///
+	/// `Full` downloads and imports every block. `State` downloads the headers, then the state
+	/// at a recent finalized block from peers, and imports blocks from there on. `State` only
+	/// applies to a node with an empty database, and needs peers that keep the state of that
+	/// block.
+	#[structopt(
+		long = "sync",
+		value_name = "SYNC_MODE",
+		possible_values = &SyncMode::variants(),
+		case_insensitive = true,
+		default_value = "Full"
+	)]
+	pub sync: SyncMode,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub node_key_params: NodeKeyParams,
@@ -159,6 +175,7 @@ impl NetworkParams {
 				use_yamux_flow_control: !self.no_yamux_flow_control,
 			},
 			max_parallel_downloads: self.max_parallel_downloads,
+			sync_mode: self.sync.into(),
 			allow_non_globals_in_dht: self.discover_local || is_dev,
 		}
 	}
@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use sc_service::config::TransactionPoolOptions;
+use std::time::Duration;
 use structopt::StructOpt;
 
 /// Parameters used to create the pool configuration.
@@ -29,6 +30,28 @@ pub struct TransactionPoolParams {
 	/// Maximum number of kilobytes of all transactions stored in the pool.
 	#[structopt(long = "pool-kbytes", value_name = "COUNT", default_value = "20480")]
 	pub pool_kbytes: usize,
+
+	/// Maximum number of transactions of a single sender in the transaction pool, 0 for no limit.
+	#[structopt(long = "pool-sender-limit", value_name = "COUNT", default_value = "512")]
+	pub pool_sender_limit: usize,
+
+	/// Maximum number of kilobytes of the transactions of a single sender in the pool.
+	#[structopt(long = "pool-sender-kbytes", value_name = "COUNT", default_value = "1280")]
+	pub pool_sender_kbytes: usize,
+
+	/// Number of seconds a transaction removed from the pool is kept from re-entering it.
+	#[structopt(long = "pool-ban-period", value_name = "SECONDS", default_value = "1800")]
+	pub pool_ban_period: u64,
+
+	/// Don't ban the transactions evicted to keep the pool within its limits, so that they can
+	/// re-enter it as soon as there is room.
+	#[structopt(long = "pool-no-ban-evicted")]
+	pub pool_no_ban_evicted: bool,
+
+	/// Keep the ready and future transactions of the pool across restarts, in a file next to the
+	/// database. They are validated again against the best block on startup.
+	#[structopt(long = "pool-persist")]
+	pub pool_persist: bool,
 }
 
 impl TransactionPoolParams {
@@ -45,6 +68,20 @@ impl TransactionPoolParams {
 		opts.future.count = self.pool_limit / factor;
 		opts.future.total_bytes = self.pool_kbytes * 1024 / factor;
 
+		// sender limits
+		if self.pool_sender_limit == 0 {
+			opts.sender = None;
+		} else if let Some(sender) = opts.sender.as_mut() {
+			sender.limit.count = self.pool_sender_limit;
+			sender.limit.total_bytes = self.pool_sender_kbytes * 1024;
+		}
+
+		// bans
+		opts.ban.ban_time = Duration::from_secs(self.pool_ban_period);
+		opts.ban.ban_evicted = !self.pool_no_ban_evicted;
+
+		opts.persist = self.pool_persist;
+
 		opts
 	}
 }
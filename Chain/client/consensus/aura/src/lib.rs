@@ -63,7 +63,7 @@ use sp_runtime::{
 };
 use sp_runtime::traits::{Block as BlockT, Header, DigestItemFor, Zero, Member};
 use sp_api::ProvideRuntimeApi;
-use sp_core::{traits::BareCryptoStore, crypto::Pair};
+use sp_core::{traits::{BareCryptoStore, BareCryptoStorePtr}, crypto::Pair};
 use sp_inherents::{InherentDataProviders, InherentData};
 use sp_timestamp::{
 	TimestampInherentData, InherentType as TimestampInherent, InherentError as TIError
@@ -74,7 +74,6 @@ use sc_consensus_slots::{
 	CheckedHeader, SlotWorker, SlotInfo, SlotCompatible, StorageChanges, check_equivocation,
 };
 
-use sc_keystore::KeyStorePtr;
 use sp_api::ApiExt;
 
 pub use sp_consensus_aura::{
@@ -147,7 +146,7 @@ pub fn start_aura<B, C, SC, E, I, P, SO, CAW, Error>(
 	sync_oracle: SO,
 	inherent_data_providers: InherentDataProviders,
 	force_authoring: bool,
-	keystore: KeyStorePtr,
+	keystore: BareCryptoStorePtr,
 	can_author_with: CAW,
 ) -> Result<impl Future<Output = ()>, sp_consensus::Error> where
 	B: BlockT,
@@ -192,7 +191,7 @@ struct AuraWorker<C, E, I, P, SO> {
 	client: Arc<C>,
 	block_import: Arc<Mutex<I>>,
 	env: E,
-	keystore: KeyStorePtr,
+	keystore: BareCryptoStorePtr,
 	sync_oracle: SO,
 	force_authoring: bool,
 	_key_type: PhantomData<P>,
@@ -248,10 +247,12 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 	) -> Option<Self::Claim> {
 		let expected_author = slot_author::<P>(slot_number, epoch_data);
 		expected_author.and_then(|p| {
-			self.keystore.read()
-				.key_pair_by_type::<P>(&p, sp_application_crypto::key_types::AURA).ok()
-		}).and_then(|p| {
-			Some(p.public())
+			let key = (p.to_raw_vec(), sp_application_crypto::key_types::AURA);
+			if self.keystore.read().has_keys(&[key]) {
+				Some(p.clone())
+			} else {
+				None
+			}
 		})
 	}
 
@@ -492,14 +493,17 @@ fn check_header<C, B: BlockT, P: Pair>(
 }
 
 /// A verifier for Aura blocks.
-pub struct AuraVerifier<C, P> {
+pub struct AuraVerifier<C, P: Pair> {
 	client: Arc<C>,
 	phantom: PhantomData<P>,
 	inherent_data_providers: sp_inherents::InherentDataProviders,
+	/// Hash of the last verified header and the authorities in effect after it, to check its
+	/// children when its state isn't available, e.g. for headers synced ahead of a state download.
+	last_authorities: Option<(Vec<u8>, Vec<AuthorityId<P>>)>,
 }
 
 impl<C, P> AuraVerifier<C, P>
-	where P: Send + Sync + 'static
+	where P: Pair + Send + Sync + 'static
 {
 	fn check_inherents<B: BlockT>(
 		&self,
@@ -580,8 +584,14 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 			.map_err(|e| format!("Could not extract timestamp and slot: {:?}", e))?;
 		let hash = header.hash();
 		let parent_hash = *header.parent_hash();
-		let authorities = authorities(self.client.as_ref(), &BlockId::Hash(parent_hash))
-			.map_err(|e| format!("Could not fetch authorities at {:?}: {:?}", parent_hash, e))?;
+		let authorities = match authorities(self.client.as_ref(), &BlockId::Hash(parent_hash)) {
+			Ok(authorities) => authorities,
+			Err(e) => match &self.last_authorities {
+				Some((last_hash, authorities)) if last_hash.as_slice() == parent_hash.as_ref() =>
+					authorities.clone(),
+				_ => return Err(format!("Could not fetch authorities at {:?}: {:?}", parent_hash, e)),
+			},
+		};
 
 		// we add one to allow for some small drift.
 		// FIXME #1019 in the future, alter this queue to allow deferring of
@@ -627,18 +637,23 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 				telemetry!(CONSENSUS_TRACE; "aura.checked_and_importing"; "pre_header" => ?pre_header);
 
 				// Look for an authorities-change log.
-				let maybe_keys = pre_header.digest()
+				let authorities_change = pre_header.digest()
 					.logs()
 					.iter()
 					.filter_map(|l| l.try_to::<ConsensusLog<AuthorityId<P>>>(
 						OpaqueDigestItemId::Consensus(&AURA_ENGINE_ID)
 					))
 					.find_map(|l| match l {
-						ConsensusLog::AuthoritiesChange(a) => Some(
-							vec![(well_known_cache_keys::AUTHORITIES, a.encode())]
-						),
+						ConsensusLog::AuthoritiesChange(a) => Some(a),
 						_ => None,
 					});
+				let maybe_keys = authorities_change.as_ref().map(|a|
+					vec![(well_known_cache_keys::AUTHORITIES, a.encode())]
+				);
+				self.last_authorities = Some((
+					hash.as_ref().to_vec(),
+					authorities_change.unwrap_or(authorities),
+				));
 
 				let mut import_block = BlockImportParams::new(origin, pre_header);
 				import_block.post_digests.push(seal);
@@ -842,6 +857,7 @@ pub fn import_queue<B, I, C, P, S>(
 		client,
 		inherent_data_providers,
 		phantom: PhantomData,
+		last_authorities: None,
 	};
 
 	Ok(BasicQueue::new(
@@ -954,6 +970,7 @@ mod tests {
 						client,
 						inherent_data_providers,
 						phantom: Default::default(),
+						last_authorities: None,
 					}
 				},
 				PeersClient::Light(_, _) => unreachable!("No (yet) tests for light client + Aura"),
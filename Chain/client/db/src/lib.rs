@@ -540,6 +540,7 @@ pub struct BlockImportOperation<Block: BlockT> {
 	finalized_blocks: Vec<(BlockId<Block>, Option<Justification>)>,
 	set_head: Option<BlockId<Block>>,
 	commit_state: bool,
+	reset_storage: bool,
 }
 
 impl<Block: BlockT> BlockImportOperation<Block> {
@@ -633,6 +634,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.db_updates = transaction;
 		self.changes_trie_config_update = Some(changes_trie_config);
 		self.commit_state = true;
+		self.reset_storage = true;
 		Ok(root)
 	}
 
@@ -1006,6 +1008,11 @@ impl<Block: BlockT> Backend<Block> {
 						implies existence of blocks with all numbers before it; qed")
 			};
 
+			// blocks imported without state during state sync have nothing to canonicalize.
+			if self.storage.state_db.is_pruned(&hash, new_canonical) {
+				return Ok(())
+			}
+
 			trace!(target: "db", "Canonicalize block #{} ({:?})", new_canonical, hash);
 			let commit = self.storage.state_db.canonicalize_block(&hash)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
@@ -1138,12 +1145,23 @@ impl<Block: BlockT> Backend<Block> {
 				}
 				self.state_usage.tally_writes(ops, bytes);
 				let number_u64 = number.saturated_into::<u64>();
-				let commit = self.storage.state_db.insert_block(
-					&hash,
-					number_u64,
-					&pending_block.header.parent_hash(),
-					changeset,
-				).map_err(|e: sc_state_db::Error<io::Error>|
+				// a whole state imported after genesis doesn't build on the parent's state,
+				// which we may not have.
+				let commit = if operation.reset_storage && number_u64 > 0 {
+					self.storage.state_db.import_block(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				} else {
+					self.storage.state_db.insert_block(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				}.map_err(|e: sc_state_db::Error<io::Error>|
 					sp_blockchain::Error::from(format!("State database error: {:?}", e))
 				)?;
 				apply_state_commit(&mut transaction, commit);
@@ -1299,9 +1317,12 @@ impl<Block: BlockT> Backend<Block> {
 			let lookup_key = utils::number_and_hash_to_lookup_key(f_num, f_hash.clone())?;
 			transaction.set_from_vec(columns::META, meta_keys::FINALIZED_BLOCK, lookup_key);
 
-			let commit = self.storage.state_db.canonicalize_block(&f_hash)
-				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
-			apply_state_commit(transaction, commit);
+			// blocks imported without state during state sync have nothing to canonicalize.
+			if !self.storage.state_db.is_pruned(&f_hash, f_num.saturated_into::<u64>()) {
+				let commit = self.storage.state_db.canonicalize_block(&f_hash)
+					.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
+				apply_state_commit(transaction, commit);
+			}
 
 			if !f_num.is_zero() {
 				let new_changes_trie_cache_ops = self.changes_tries_storage.finalize(
@@ -1389,6 +1410,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			finalized_blocks: Vec::new(),
 			set_head: None,
 			commit_state: false,
+			reset_storage: false,
 		})
 	}
 
@@ -1790,6 +1812,68 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn import_state_after_headers() {
+		let db = Backend::<Block>::new_test(2, 0);
+		let mut hash = insert_header(&db, 0, Default::default(), None, Default::default());
+
+		// headers synced without state
+		let mut headers = Vec::new();
+		for number in 1..4 {
+			let header = Header {
+				number,
+				parent_hash: hash,
+				state_root: Default::default(),
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			hash = header.hash();
+			headers.push(hash);
+
+			let mut op = db.begin_operation().unwrap();
+			op.set_block_data(header, None, None, NewBlockState::Best).unwrap();
+			db.commit_operation(op).unwrap();
+			assert!(!db.have_state_at(&hash, number));
+		}
+
+		let storage = vec![
+			(vec![1, 3, 5], vec![2, 4, 6]),
+			(vec![1, 2, 3], vec![9, 9, 9]),
+		];
+		let imported = {
+			let mut op = db.begin_operation().unwrap();
+			for hash in &headers {
+				op.mark_finalized(BlockId::Hash(*hash), None).unwrap();
+			}
+			let state_root = op.reset_storage(Storage {
+				top: storage.iter().cloned().collect(),
+				children_default: Default::default(),
+			}).unwrap();
+			let header = Header {
+				number: 4,
+				parent_hash: hash,
+				state_root,
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			let imported = header.hash();
+			op.set_block_data(header, None, None, NewBlockState::Final).unwrap();
+			db.commit_operation(op).unwrap();
+			imported
+		};
+
+		assert_eq!(db.blockchain().info().finalized_hash, imported);
+		assert!(db.have_state_at(&imported, 4));
+		assert!(!db.have_state_at(&hash, 3));
+		let state = db.state_at(BlockId::Number(4)).unwrap();
+		assert_eq!(state.storage(&[1, 3, 5]).unwrap(), Some(vec![2, 4, 6]));
+		assert_eq!(state.storage(&[1, 2, 3]).unwrap(), Some(vec![9, 9, 9]));
+
+		// regular import continues on top of the imported state
+		let next = insert_header(&db, 5, imported, None, Default::default());
+		assert!(db.have_state_at(&next, 5));
+	}
+
 	#[test]
 	fn set_state_data() {
 		let db = Backend::<Block>::new_test(2, 0);
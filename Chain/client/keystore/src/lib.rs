@@ -15,6 +15,9 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 //! Keystore (and session key management) for ed25519 based chains like Polkadot.
+//!
+//! Keys are held in the file system [`Store`], or by a separate signing process talking to a
+//! [`remote::RemoteStore`]. Either way, a [`policy::SigningPolicy`] may restrict what they sign.
 
 #![warn(missing_docs)]
 use std::{collections::{HashMap, HashSet}, path::PathBuf, fs::{self, File}, io::{self, Write}, sync::Arc};
@@ -28,6 +31,10 @@ use sp_core::{
 use sp_application_crypto::{AppKey, AppPublic, AppPair, ed25519, sr25519, ecdsa};
 use parking_lot::RwLock;
 
+pub mod policy;
+#[cfg(unix)]
+pub mod remote;
+
 /// Keystore pointer
 pub type KeyStorePtr = Arc<RwLock<Store>>;
 
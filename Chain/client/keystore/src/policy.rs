@@ -0,0 +1,208 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Signing policies.
+//!
+//! A [`SigningPolicy`] decides which signing requests a keystore serves. [`PolicyStore`] puts
+//! one in front of any keystore backend, local or remote.
+
+use std::{collections::HashSet, sync::Arc};
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId, Public},
+	traits::{BareCryptoStore, BareCryptoStorePtr, Error as TraitError},
+	vrf::{VRFTranscriptData, VRFSignature},
+	sr25519, ed25519, ecdsa,
+};
+use parking_lot::RwLock;
+
+/// Decides whether a message may be signed.
+pub trait SigningPolicy: Send + Sync {
+	/// Check whether `message` may be signed with `key`, of type `key_type`.
+	///
+	/// For VRF signatures, `message` is the label of the transcript.
+	///
+	/// Returns the reason of the refusal if it may not.
+	fn check(
+		&self,
+		key_type: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		message: &[u8],
+	) -> Result<(), String>;
+}
+
+/// Policy only signing with the keys of the given key types.
+pub struct AllowedKeyTypes(HashSet<KeyTypeId>);
+
+impl AllowedKeyTypes {
+	/// Create a new instance allowing `key_types`.
+	pub fn new(key_types: impl IntoIterator<Item = KeyTypeId>) -> Self {
+		AllowedKeyTypes(key_types.into_iter().collect())
+	}
+}
+
+impl SigningPolicy for AllowedKeyTypes {
+	fn check(
+		&self,
+		key_type: KeyTypeId,
+		_key: &CryptoTypePublicPair,
+		_message: &[u8],
+	) -> Result<(), String> {
+		if self.0.contains(&key_type) {
+			Ok(())
+		} else {
+			Err(format!("Signing with {:?} keys is not allowed", key_type))
+		}
+	}
+}
+
+/// Keystore enforcing a signing policy on top of another keystore.
+///
+/// Everything but signing is passed to the inner keystore as is.
+pub struct PolicyStore {
+	inner: BareCryptoStorePtr,
+	policy: Box<dyn SigningPolicy>,
+}
+
+impl PolicyStore {
+	/// Create a new instance enforcing `policy` on `inner`.
+	pub fn new(
+		inner: BareCryptoStorePtr,
+		policy: impl SigningPolicy + 'static,
+	) -> BareCryptoStorePtr {
+		Arc::new(RwLock::new(PolicyStore { inner, policy: Box::new(policy) }))
+	}
+
+	fn check(
+		&self,
+		key_type: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		message: &[u8],
+	) -> Result<(), TraitError> {
+		self.policy.check(key_type, key, message).map_err(TraitError::ValidationError)
+	}
+}
+
+impl BareCryptoStore for PolicyStore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.inner.read().sr25519_public_keys(id)
+	}
+
+	fn sr25519_generate_new(
+		&mut self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, TraitError> {
+		self.inner.write().sr25519_generate_new(id, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.inner.read().ed25519_public_keys(id)
+	}
+
+	fn ed25519_generate_new(
+		&mut self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, TraitError> {
+		self.inner.write().ed25519_generate_new(id, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.inner.read().ecdsa_public_keys(id)
+	}
+
+	fn ecdsa_generate_new(
+		&mut self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, TraitError> {
+		self.inner.write().ecdsa_generate_new(id, seed)
+	}
+
+	fn insert_unknown(&mut self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		self.inner.write().insert_unknown(key_type, suri, public)
+	}
+
+	fn password(&self) -> Option<&str> {
+		// The password can't be borrowed out of the lock, and is not needed outside of the
+		// keystore holding it.
+		None
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, TraitError> {
+		self.inner.read().supported_keys(id, keys)
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, TraitError> {
+		self.inner.read().keys(id)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		self.inner.read().has_keys(public_keys)
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Vec<u8>, TraitError> {
+		self.check(id, key, msg)?;
+		self.inner.read().sign_with(id, key, msg)
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<VRFSignature, TraitError> {
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, public.to_raw_vec());
+		self.check(key_type, &key, transcript_data.label)?;
+		self.inner.read().sr25519_vrf_sign(key_type, public, transcript_data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Store;
+	use sp_core::testing::{ED25519, SR25519};
+
+	#[test]
+	fn signing_is_refused_by_policy() {
+		let store = PolicyStore::new(Store::new_in_memory(), AllowedKeyTypes::new(vec![SR25519]));
+		let allowed = store.write().sr25519_generate_new(SR25519, Some("//Alice")).unwrap();
+		let refused = store.write().ed25519_generate_new(ED25519, Some("//Bob")).unwrap();
+
+		let allowed = CryptoTypePublicPair(sr25519::CRYPTO_ID, allowed.to_raw_vec());
+		let refused = CryptoTypePublicPair(ed25519::CRYPTO_ID, refused.to_raw_vec());
+		assert!(store.read().sign_with(SR25519, &allowed, b"message").is_ok());
+		assert!(matches!(
+			store.read().sign_with(ED25519, &refused, b"message"),
+			Err(TraitError::ValidationError(_))
+		));
+
+		// listing keys is not restricted
+		assert_eq!(store.read().ed25519_public_keys(ED25519).len(), 1);
+	}
+}
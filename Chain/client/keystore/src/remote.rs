@@ -0,0 +1,525 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Keystore backed by a separate signing process.
+//!
+//! [`RemoteStore`] holds no keys: it asks a signer listening on a Unix socket to list, generate
+//! and sign with them. Every request is a line of JSON sent over a new connection, answered by
+//! a line of JSON. Key types, crypto types, keys, messages and signatures are hex encoded.
+//!
+//! The keys of every key type are listed once per [`KEYS_CACHE_TTL`], so that looking them up
+//! on every slot doesn't wait for the signer.
+//!
+//! [`StandInSigner`] serves the keys of a local keystore over this protocol, standing in for the
+//! signing process in tests.
+
+use std::{
+	collections::HashMap,
+	convert::TryFrom,
+	fs,
+	io::{self, BufRead, BufReader, Write},
+	os::unix::net::{UnixListener, UnixStream},
+	path::{Path, PathBuf},
+	sync::{Arc, atomic::{AtomicBool, Ordering}},
+	thread,
+	time::{Duration, Instant},
+};
+use parking_lot::RwLock;
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId, Public},
+	traits::{BareCryptoStore, BareCryptoStorePtr, Error as TraitError},
+	vrf::{VRFTranscriptData, VRFSignature},
+	sr25519, ed25519, ecdsa,
+};
+
+/// Time given to the signer to answer a request.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time the keys listed by the signer are used for before they are listed again.
+pub const KEYS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Request sent to the signer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum Request {
+	/// List the keys of a key type.
+	#[serde(rename_all = "camelCase")]
+	Keys {
+		/// Key type of the keys.
+		key_type: String,
+	},
+	/// Sign a message.
+	#[serde(rename_all = "camelCase")]
+	Sign {
+		/// Key type of the key.
+		key_type: String,
+		/// Key to sign with.
+		key: RemoteKey,
+		/// Message to sign.
+		message: String,
+	},
+	/// Generate a new key.
+	#[serde(rename_all = "camelCase")]
+	Generate {
+		/// Key type of the key.
+		key_type: String,
+		/// Crypto type of the key.
+		crypto_type: String,
+		/// Seed to generate the key from, the key is only kept in memory if given.
+		seed: Option<String>,
+	},
+}
+
+/// Response of the signer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Response {
+	/// Keys of the requested key type.
+	Keys(Vec<RemoteKey>),
+	/// SCALE encoded signature.
+	Signature(String),
+	/// The generated key.
+	Generated(RemoteKey),
+	/// The request failed or was refused.
+	Error(String),
+}
+
+/// Key held by the signer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteKey {
+	/// Crypto type of the key.
+	pub crypto_type: String,
+	/// Public key.
+	pub public: String,
+}
+
+impl From<&CryptoTypePublicPair> for RemoteKey {
+	fn from(key: &CryptoTypePublicPair) -> Self {
+		RemoteKey {
+			crypto_type: hex::encode((key.0).0),
+			public: hex::encode(&key.1),
+		}
+	}
+}
+
+impl TryFrom<&RemoteKey> for CryptoTypePublicPair {
+	type Error = String;
+
+	fn try_from(key: &RemoteKey) -> Result<Self, String> {
+		Ok(CryptoTypePublicPair(CryptoTypeId(decode_id(&key.crypto_type)?), decode(&key.public)?))
+	}
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, String> {
+	hex::decode(data).map_err(|e| format!("Invalid hex {:?}: {}", data, e))
+}
+
+fn decode_id(data: &str) -> Result<[u8; 4], String> {
+	<[u8; 4]>::try_from(&decode(data)?[..]).map_err(|_| format!("Invalid identifier {:?}", data))
+}
+
+fn write_line<T: Serialize>(mut stream: &UnixStream, value: &T) -> io::Result<()> {
+	let mut line = serde_json::to_vec(value)?;
+	line.push(b'\n');
+	stream.write_all(&line)
+}
+
+fn read_line<T: DeserializeOwned>(stream: &UnixStream) -> io::Result<T> {
+	let mut line = String::new();
+	BufReader::new(stream).read_line(&mut line)?;
+	serde_json::from_str(&line).map_err(Into::into)
+}
+
+/// Keystore asking a signer listening on a Unix socket.
+///
+/// Keys can neither be inserted nor used for VRF signatures. Keys added to the signer by other
+/// means show up within [`KEYS_CACHE_TTL`].
+pub struct RemoteStore {
+	socket: PathBuf,
+	timeout: Duration,
+	cached_keys: RwLock<HashMap<KeyTypeId, (Instant, Vec<CryptoTypePublicPair>)>>,
+}
+
+impl RemoteStore {
+	/// Create a new instance talking to the signer listening on `socket`.
+	///
+	/// The signer is only connected to when a request is made.
+	pub fn new<T: Into<PathBuf>>(socket: T) -> Self {
+		RemoteStore {
+			socket: socket.into(),
+			timeout: DEFAULT_TIMEOUT,
+			cached_keys: Default::default(),
+		}
+	}
+
+	/// Set the time given to the signer to answer a request.
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	/// Path of the socket of the signer.
+	pub fn socket(&self) -> &Path {
+		&self.socket
+	}
+
+	fn request(&self, request: &Request) -> Result<Response, TraitError> {
+		let exchange = || -> io::Result<Response> {
+			let stream = UnixStream::connect(&self.socket)?;
+			stream.set_read_timeout(Some(self.timeout))?;
+			stream.set_write_timeout(Some(self.timeout))?;
+			write_line(&stream, request)?;
+			read_line(&stream)
+		};
+
+		match exchange() {
+			Ok(Response::Error(e)) => Err(TraitError::Other(e)),
+			Ok(response) => Ok(response),
+			Err(e) => Err(TraitError::Other(
+				format!("Remote signer at {} failed: {}", self.socket.display(), e),
+			)),
+		}
+	}
+
+	fn public_keys<P>(&self, id: KeyTypeId, crypto_type: CryptoTypeId) -> Vec<P>
+		where P: for<'a> TryFrom<&'a [u8]>
+	{
+		let keys = self.keys(id).unwrap_or_else(|e| {
+			log::warn!(target: "keystore", "Failed to list the {:?} keys: {}", id, e);
+			Vec::new()
+		});
+		keys.into_iter()
+			.filter(|key| key.0 == crypto_type)
+			.filter_map(|key| P::try_from(&key.1[..]).ok())
+			.collect()
+	}
+
+	fn generate<P>(
+		&self,
+		id: KeyTypeId,
+		crypto_type: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<P, TraitError> where P: for<'a> TryFrom<&'a [u8]> {
+		let request = Request::Generate {
+			key_type: hex::encode(id.0),
+			crypto_type: hex::encode(crypto_type.0),
+			seed: seed.map(Into::into),
+		};
+		let response = self.request(&request)?;
+		self.cached_keys.write().remove(&id);
+		match response {
+			Response::Generated(key) => CryptoTypePublicPair::try_from(&key)
+				.ok()
+				.filter(|key| key.0 == crypto_type)
+				.and_then(|key| P::try_from(&key.1[..]).ok())
+				.ok_or_else(|| TraitError::Other(
+					format!("Invalid key from remote signer: {:?}", key),
+				)),
+			response => Err(unexpected(response)),
+		}
+	}
+}
+
+fn unexpected(response: Response) -> TraitError {
+	TraitError::Other(format!("Unexpected response from remote signer: {:?}", response))
+}
+
+impl BareCryptoStore for RemoteStore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(id, sr25519::CRYPTO_ID)
+	}
+
+	fn sr25519_generate_new(
+		&mut self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, TraitError> {
+		self.generate(id, sr25519::CRYPTO_ID, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(id, ed25519::CRYPTO_ID)
+	}
+
+	fn ed25519_generate_new(
+		&mut self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, TraitError> {
+		self.generate(id, ed25519::CRYPTO_ID, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(id, ecdsa::CRYPTO_ID)
+	}
+
+	fn ecdsa_generate_new(
+		&mut self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, TraitError> {
+		self.generate(id, ecdsa::CRYPTO_ID, seed)
+	}
+
+	fn insert_unknown(
+		&mut self,
+		_key_type: KeyTypeId,
+		_suri: &str,
+		_public: &[u8],
+	) -> Result<(), ()> {
+		// Secrets are not sent to the signer.
+		Err(())
+	}
+
+	fn password(&self) -> Option<&str> {
+		None
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, TraitError> {
+		let all_keys = self.keys(id)?;
+		Ok(keys.into_iter().filter(|key| all_keys.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, TraitError> {
+		if let Some((listed_at, keys)) = self.cached_keys.read().get(&id) {
+			if listed_at.elapsed() < KEYS_CACHE_TTL {
+				return Ok(keys.clone());
+			}
+		}
+
+		let keys = match self.request(&Request::Keys { key_type: hex::encode(id.0) })? {
+			Response::Keys(keys) => keys.iter()
+				.map(|key| CryptoTypePublicPair::try_from(key).map_err(TraitError::Other))
+				.collect::<Result<Vec<_>, _>>()?,
+			response => return Err(unexpected(response)),
+		};
+		self.cached_keys.write().insert(id, (Instant::now(), keys.clone()));
+		Ok(keys)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		public_keys.iter().all(|(public, id)| {
+			self.keys(*id)
+				.map(|keys| keys.iter().any(|key| &key.1 == public))
+				.unwrap_or(false)
+		})
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Vec<u8>, TraitError> {
+		let request = Request::Sign {
+			key_type: hex::encode(id.0),
+			key: key.into(),
+			message: hex::encode(msg),
+		};
+		match self.request(&request)? {
+			Response::Signature(signature) => decode(&signature).map_err(TraitError::Other),
+			response => Err(unexpected(response)),
+		}
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_key_type: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<VRFSignature, TraitError> {
+		Err(TraitError::Other("VRF signatures are not supported by the remote signer".into()))
+	}
+}
+
+/// Signer serving the keys of a local keystore on a Unix socket.
+///
+/// Requests are served one at a time on a background thread, until the signer is dropped. A
+/// signing policy is applied by serving a [`PolicyStore`](crate::policy::PolicyStore).
+pub struct StandInSigner {
+	socket: PathBuf,
+	stop: Arc<AtomicBool>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl StandInSigner {
+	/// Start serving the keys of `keystore` on `socket`.
+	pub fn start<T: Into<PathBuf>>(socket: T, keystore: BareCryptoStorePtr) -> io::Result<Self> {
+		let socket = socket.into();
+		let listener = UnixListener::bind(&socket)?;
+		let stop = Arc::new(AtomicBool::new(false));
+		let thread = thread::Builder::new()
+			.name("stand-in-signer".into())
+			.spawn({
+				let stop = stop.clone();
+				move || for stream in listener.incoming() {
+					if stop.load(Ordering::SeqCst) {
+						break;
+					}
+					if let Ok(stream) = stream {
+						let _ = serve(&keystore, &stream);
+					}
+				}
+			})?;
+
+		Ok(StandInSigner { socket, stop, thread: Some(thread) })
+	}
+
+	/// Path of the socket the signer listens on.
+	pub fn socket(&self) -> &Path {
+		&self.socket
+	}
+}
+
+impl Drop for StandInSigner {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::SeqCst);
+		// Wake the listener up, so that it notices.
+		let _ = UnixStream::connect(&self.socket);
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+		let _ = fs::remove_file(&self.socket);
+	}
+}
+
+fn serve(keystore: &BareCryptoStorePtr, stream: &UnixStream) -> io::Result<()> {
+	let response = read_line(stream)
+		.map_err(|e| e.to_string())
+		.and_then(|request| handle(keystore, request))
+		.unwrap_or_else(Response::Error);
+	write_line(stream, &response)
+}
+
+fn handle(keystore: &BareCryptoStorePtr, request: Request) -> Result<Response, String> {
+	match request {
+		Request::Keys { key_type } => {
+			let keys = keystore.read()
+				.keys(KeyTypeId(decode_id(&key_type)?))
+				.map_err(|e| e.to_string())?;
+			Ok(Response::Keys(keys.iter().map(Into::into).collect()))
+		},
+		Request::Sign { key_type, key, message } => {
+			let signature = keystore.read()
+				.sign_with(
+					KeyTypeId(decode_id(&key_type)?),
+					&CryptoTypePublicPair::try_from(&key)?,
+					&decode(&message)?,
+				)
+				.map_err(|e| e.to_string())?;
+			Ok(Response::Signature(hex::encode(signature)))
+		},
+		Request::Generate { key_type, crypto_type, seed } => {
+			let key_type = KeyTypeId(decode_id(&key_type)?);
+			let crypto_type = CryptoTypeId(decode_id(&crypto_type)?);
+			let seed = seed.as_deref();
+			let mut keystore = keystore.write();
+			let public = match crypto_type {
+				sr25519::CRYPTO_ID => keystore.sr25519_generate_new(key_type, seed)
+					.map(|public| public.to_raw_vec()),
+				ed25519::CRYPTO_ID => keystore.ed25519_generate_new(key_type, seed)
+					.map(|public| public.to_raw_vec()),
+				ecdsa::CRYPTO_ID => keystore.ecdsa_generate_new(key_type, seed)
+					.map(|public| public.to_raw_vec()),
+				_ => return Err(format!("Unsupported crypto type {:?}", crypto_type)),
+			}.map_err(|e| e.to_string())?;
+			Ok(Response::Generated((&CryptoTypePublicPair(crypto_type, public)).into()))
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Store, policy::{PolicyStore, AllowedKeyTypes}};
+	use sp_core::{Pair, testing::{ED25519, SR25519}};
+	use tempfile::TempDir;
+
+	fn signer(keystore: BareCryptoStorePtr) -> (TempDir, StandInSigner, RemoteStore) {
+		let dir = TempDir::new().unwrap();
+		let signer = StandInSigner::start(dir.path().join("signer.sock"), keystore).unwrap();
+		let remote = RemoteStore::new(signer.socket());
+		(dir, signer, remote)
+	}
+
+	#[test]
+	fn keys_are_listed_and_used_remotely() {
+		let keystore = Store::new_in_memory();
+		let pair = keystore.write()
+			.insert_ephemeral_from_seed_by_type::<sr25519::Pair>("//Alice", SR25519)
+			.unwrap();
+		let (_dir, _signer, mut remote) = signer(keystore);
+
+		assert_eq!(remote.sr25519_public_keys(SR25519), vec![pair.public()]);
+		assert!(remote.has_keys(&[(pair.public().to_raw_vec(), SR25519)]));
+		assert!(!remote.has_keys(&[(pair.public().to_raw_vec(), ED25519)]));
+
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+		let signature = remote.sign_with(SR25519, &key, b"message").unwrap();
+		let signature = sr25519::Signature::try_from(&signature[..]).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"message", &pair.public()));
+
+		let generated = remote.ed25519_generate_new(ED25519, Some("//Bob")).unwrap();
+		assert_eq!(remote.ed25519_public_keys(ED25519), vec![generated]);
+	}
+
+	#[test]
+	fn failures_are_reported() {
+		let policy = AllowedKeyTypes::new(vec![SR25519]);
+		let (_dir, signer, mut remote) = signer(PolicyStore::new(Store::new_in_memory(), policy));
+
+		let public = remote.ed25519_generate_new(ED25519, Some("//Bob")).unwrap();
+		let key = CryptoTypePublicPair(ed25519::CRYPTO_ID, public.to_raw_vec());
+		let unknown = CryptoTypePublicPair(sr25519::CRYPTO_ID, vec![0; 32]);
+		assert!(matches!(
+			remote.sign_with(ED25519, &key, b"message"),
+			Err(TraitError::Other(_))
+		));
+		assert!(matches!(
+			remote.sign_with(SR25519, &unknown, b"message"),
+			Err(TraitError::Other(_))
+		));
+		assert!(remote.insert_unknown(SR25519, "//Alice", &[0; 32]).is_err());
+
+		drop(signer);
+		assert!(remote.keys(ED25519).is_err());
+		assert!(remote.ed25519_public_keys(ED25519).is_empty());
+	}
+
+	#[test]
+	fn keys_are_cached() {
+		let keystore = Store::new_in_memory();
+		let pair = keystore.write()
+			.insert_ephemeral_from_seed_by_type::<sr25519::Pair>("//Alice", SR25519)
+			.unwrap();
+		let (_dir, signer, remote) = signer(keystore);
+
+		assert_eq!(remote.sr25519_public_keys(SR25519), vec![pair.public()]);
+
+		// the listed keys are kept while the signer is away
+		drop(signer);
+		assert_eq!(remote.sr25519_public_keys(SR25519), vec![pair.public()]);
+		assert!(remote.has_keys(&[(pair.public().to_raw_vec(), SR25519)]));
+	}
+}
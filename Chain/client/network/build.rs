@@ -1,7 +1,8 @@
 const PROTOS: &[&str] = &[
 	"src/schema/api.v1.proto",
 	"src/schema/finality.v1.proto",
-	"src/schema/light.v1.proto"
+	"src/schema/light.v1.proto",
+	"src/schema/state.v1.proto"
 ];
 
 fn main() {
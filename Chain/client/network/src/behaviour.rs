@@ -15,7 +15,7 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	config::{ProtocolId, Role}, block_requests, light_client_handler, finality_requests,
+	config::{ProtocolId, Role}, block_requests, light_client_handler, finality_requests, state_requests,
 	peer_info, discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
 	protocol::{message::{self, Roles}, CustomMessageOutcome, NotificationsSink, Protocol},
 	ObservedRole, DhtEvent, ExHashT,
@@ -54,6 +54,8 @@ pub struct Behaviour<B: BlockT, H: ExHashT> {
 	block_requests: block_requests::BlockRequests<B>,
 	/// Finality proof request handling.
 	finality_proof_requests: finality_requests::FinalityProofRequests<B>,
+	/// State request handling.
+	state_requests: state_requests::StateRequests<B>,
 	/// Light client request handling.
 	light_client_handler: light_client_handler::LightClientHandler<B>,
 
@@ -158,6 +160,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		local_public_key: PublicKey,
 		block_requests: block_requests::BlockRequests<B>,
 		finality_proof_requests: finality_requests::FinalityProofRequests<B>,
+		state_requests: state_requests::StateRequests<B>,
 		light_client_handler: light_client_handler::LightClientHandler<B>,
 		disco_config: DiscoveryConfig,
 	) -> Self {
@@ -167,6 +170,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 			discovery: disco_config.finish(),
 			block_requests,
 			finality_proof_requests,
+			state_requests,
 			light_client_handler,
 			events: VecDeque::new(),
 			role,
@@ -320,6 +324,9 @@ Behaviour<B, H> {
 			CustomMessageOutcome::FinalityProofRequest { target, block_hash, request } => {
 				self.finality_proof_requests.send_request(&target, block_hash, request);
 			},
+			CustomMessageOutcome::StateRequest { target, block_hash, start } => {
+				self.state_requests.send_request(&target, block_hash, start);
+			},
 			CustomMessageOutcome::NotificationStreamOpened { remote, protocols, roles, notifications_sink } => {
 				let role = reported_roles_to_observed_role(&self.role, &remote, roles);
 				for engine_id in protocols {
@@ -411,6 +418,17 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<finality_requests::Even
 	}
 }
 
+impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<state_requests::Event<B>> for Behaviour<B, H> {
+	fn inject_event(&mut self, event: state_requests::Event<B>) {
+		match event {
+			state_requests::Event::Response { peer, block_hash, proof } => {
+				let ev = self.substrate.on_state_response(peer, block_hash, proof);
+				self.inject_event(ev);
+			}
+		}
+	}
+}
+
 impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<peer_info::PeerInfoEvent>
 	for Behaviour<B, H> {
 	fn inject_event(&mut self, event: peer_info::PeerInfoEvent) {
@@ -423,6 +423,8 @@ pub struct NetworkConfiguration {
 	pub transport: TransportConfig,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// How to bootstrap the chain when syncing.
+	pub sync_mode: SyncMode,
 	/// Should we insert non-global addresses into the DHT?
 	pub allow_non_globals_in_dht: bool,
 }
@@ -455,6 +457,7 @@ impl NetworkConfiguration {
 				use_yamux_flow_control: false,
 			},
 			max_parallel_downloads: 5,
+			sync_mode: SyncMode::Full,
 			allow_non_globals_in_dht: false,
 		}
 	}
@@ -551,6 +554,33 @@ impl NonReservedPeerMode {
 	}
 }
 
+/// How the chain is bootstrapped when syncing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncMode {
+	/// Download and import every block, with bodies. This is the default.
+	Full,
+	/// Download the headers, then the full state at a recent finalized block, and import
+	/// blocks from there. Only applies to a node that has no state beyond genesis.
+	State,
+}
+
+impl Default for SyncMode {
+	fn default() -> Self {
+		SyncMode::Full
+	}
+}
+
+impl SyncMode {
+	/// Attempt to parse the sync mode from a string.
+	pub fn parse(s: &str) -> Option<Self> {
+		match s {
+			"full" => Some(SyncMode::Full),
+			"state" => Some(SyncMode::State),
+			_ => None,
+		}
+	}
+}
+
 /// The configuration of a node's secret key, describing the type of key
 /// and how it is obtained. A node's identity keypair is the result of
 /// the evaluation of the node key configuration.
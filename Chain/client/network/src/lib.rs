@@ -253,6 +253,7 @@ mod on_demand_layer;
 mod protocol;
 mod schema;
 mod service;
+mod state_requests;
 mod transport;
 mod utils;
 
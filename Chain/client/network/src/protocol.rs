@@ -19,7 +19,7 @@
 use crate::{
 	ExHashT,
 	chain::{Client, FinalityProofProvider},
-	config::{BoxFinalityProofRequestBuilder, ProtocolId, TransactionPool, TransactionImportFuture, TransactionImport},
+	config::{BoxFinalityProofRequestBuilder, ProtocolId, SyncMode, TransactionPool, TransactionImportFuture, TransactionImport},
 	error,
 	utils::interval
 };
@@ -311,6 +311,8 @@ pub struct ProtocolConfig {
 	pub roles: Roles,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// How to bootstrap the chain when syncing.
+	pub sync_mode: SyncMode,
 }
 
 impl Default for ProtocolConfig {
@@ -318,6 +320,7 @@ impl Default for ProtocolConfig {
 		ProtocolConfig {
 			roles: Roles::FULL,
 			max_parallel_downloads: 5,
+			sync_mode: SyncMode::Full,
 		}
 	}
 }
@@ -391,6 +394,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 	) -> error::Result<(Protocol<B, H>, sc_peerset::PeersetHandle)> {
 		let info = chain.info();
 		let sync = ChainSync::new(
+			config.sync_mode,
 			config.roles,
 			chain.clone(),
 			&info,
@@ -1784,6 +1788,27 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		}
 	}
 
+	/// Must be called after a [`CustomMessageOutcome::StateRequest`] has been emitted,
+	/// to notify of the response having arrived.
+	pub fn on_state_response(
+		&mut self,
+		who: PeerId,
+		block_hash: B::Hash,
+		proof: Vec<u8>,
+	) -> CustomMessageOutcome<B> {
+		trace!(target: "sync", "State response from {} for {}", who, block_hash);
+		match self.sync.on_state_data(&who, block_hash, &proof) {
+			Ok(sync::OnStateData::Nothing) => CustomMessageOutcome::None,
+			Ok(sync::OnStateData::Import(origin, block)) =>
+				CustomMessageOutcome::BlockImport(origin, vec![block]),
+			Err(sync::BadPeer(id, repu)) => {
+				self.behaviour.disconnect_peer(&id);
+				self.peerset_handle.report_peer(id, repu);
+				CustomMessageOutcome::None
+			}
+		}
+	}
+
 	fn format_stats(&self) -> String {
 		let mut out = String::new();
 		for (id, stats) in &self.context_data.stats {
@@ -1877,6 +1902,11 @@ pub enum CustomMessageOutcome<B: BlockT> {
 	/// If the request times out, or the peer responds in an invalid way, the peer has to be
 	/// disconnect. This will inform the state machine that the request it has emitted is stale.
 	FinalityProofRequest { target: PeerId, block_hash: B::Hash, request: Vec<u8> },
+	/// A new state request must be emitted.
+	/// Once you have the response, you must call `Protocol::on_state_response`.
+	/// If the request times out, or the peer responds in an invalid way, the peer has to be
+	/// disconnect. This will inform the state machine that the request it has emitted is stale.
+	StateRequest { target: PeerId, block_hash: B::Hash, start: Vec<u8> },
 	/// Peer has a reported a new head of chain.
 	PeerNewBest(PeerId, NumberFor<B>),
 	None,
@@ -1999,6 +2029,14 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 			};
 			self.pending_messages.push_back(event);
 		}
+		if let Some((id, r)) = self.sync.state_request() {
+			let event = CustomMessageOutcome::StateRequest {
+				target: id,
+				block_hash: r.block,
+				start: r.start,
+			};
+			self.pending_messages.push_back(event);
+		}
 		if let Poll::Ready(Some((tx_hash, result))) = self.pending_transactions.poll_next_unpin(cx) {
 			if let Some(peers) = self.pending_transactions_peers.remove(&tx_hash) {
 				peers.into_iter().for_each(|p| self.on_handle_transaction_import(p, result));
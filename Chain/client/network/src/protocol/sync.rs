@@ -32,15 +32,16 @@ use blocks::BlockCollection;
 use sp_blockchain::{Error as ClientError, Info as BlockchainInfo, HeaderMetadata};
 use sp_consensus::{BlockOrigin, BlockStatus,
 	block_validation::{BlockAnnounceValidator, Validation},
-	import_queue::{IncomingBlock, BlockImportResult, BlockImportError}
+	import_queue::{IncomingBlock, BlockImportResult, BlockImportError, ImportedState}
 };
 use crate::{
-	config::BoxFinalityProofRequestBuilder,
+	config::{BoxFinalityProofRequestBuilder, SyncMode},
 	protocol::message::{self, generic::FinalityProofRequest, BlockAnnounce, BlockAttributes, BlockRequest, BlockResponse,
 	FinalityProofResponse, Roles},
 };
 use either::Either;
 use extra_requests::ExtraRequests;
+use state::{StateSync, ImportResult};
 use libp2p::PeerId;
 use log::{debug, trace, warn, info, error};
 use sp_runtime::{
@@ -53,6 +54,9 @@ use std::{fmt, ops::Range, collections::{HashMap, HashSet, VecDeque}, sync::Arc}
 
 mod blocks;
 mod extra_requests;
+mod state;
+
+pub use state::StateRequest;
 
 /// Maximum blocks to request in a single packet.
 const MAX_BLOCKS_TO_REQUEST: usize = 128;
@@ -73,6 +77,10 @@ const MAJOR_SYNC_BLOCKS: u8 = 5;
 /// Number of recently announced blocks to track for each peer.
 const ANNOUNCE_HISTORY_SIZE: usize = 64;
 
+/// How far behind the best block seen a justified block may be to become the target of state
+/// sync. Matches the default GRANDPA justification period, so that one is always in range.
+const STATE_SYNC_TARGET_DISTANCE: u32 = 512;
+
 mod rep {
 	use sc_peerset::ReputationChange as Rep;
 	/// Reputation change when a peer sent us a message that led to a
@@ -106,6 +114,9 @@ mod rep {
 
 	/// Reputation change when a peer sent us invlid ancestry result.
 	pub const UNKNOWN_ANCESTOR:Rep = Rep::new(-(1 << 16), "DB Error");
+
+	/// Reputation change for peers which send us a state proof that fails to verify.
+	pub const BAD_STATE: Rep = Rep::new(-(1 << 29), "Bad state");
 }
 
 enum PendingRequests {
@@ -192,6 +203,14 @@ pub struct ChainSync<B: BlockT> {
 	max_parallel_downloads: u32,
 	/// Total number of downloaded blocks.
 	downloaded_blocks: usize,
+	/// How the chain is bootstrapped. Switches to `SyncMode::Full` once the state has been
+	/// imported.
+	mode: SyncMode,
+	/// State download in progress, once a target block has been picked.
+	state_sync: Option<StateSync<B>>,
+	/// Set once the downloaded state turned out to be unsupported. The headers imported so far
+	/// have no state to build on, so no more blocks are requested or imported.
+	stopped: bool,
 }
 
 /// All the data we have about a Peer that we are trying to sync with
@@ -250,7 +269,9 @@ pub enum PeerSyncState<B: BlockT> {
 	/// Downloading justification for given block hash.
 	DownloadingJustification(B::Hash),
 	/// Downloading finality proof for given block hash.
-	DownloadingFinalityProof(B::Hash)
+	DownloadingFinalityProof(B::Hash),
+	/// Downloading a chunk of the state of the state sync target.
+	DownloadingState,
 }
 
 impl<B: BlockT> PeerSyncState<B> {
@@ -306,6 +327,15 @@ pub enum OnBlockData<B: BlockT> {
 	Request(PeerId, BlockRequest<B>)
 }
 
+/// Result of [`ChainSync::on_state_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnStateData<B: BlockT> {
+	/// The response needs no further handling.
+	Nothing,
+	/// The state is complete and the block should be imported along with it.
+	Import(BlockOrigin, IncomingBlock<B>),
+}
+
 /// Result of [`ChainSync::on_block_announce`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OnBlockAnnounce {
@@ -346,6 +376,7 @@ pub enum OnBlockFinalityProof<B: BlockT> {
 impl<B: BlockT> ChainSync<B> {
 	/// Create a new instance.
 	pub fn new(
+		mode: SyncMode,
 		role: Roles,
 		client: Arc<dyn crate::chain::Client<B>>,
 		info: &BlockchainInfo<B>,
@@ -353,9 +384,19 @@ impl<B: BlockT> ChainSync<B> {
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		max_parallel_downloads: u32,
 	) -> Self {
+		// State sync only bootstraps a node that has no state past genesis, possibly resuming
+		// after a restart during the download of headers.
+		let mode = match mode {
+			SyncMode::State if !role.is_full() => SyncMode::Full,
+			SyncMode::State if !info.best_number.is_zero() && client
+				.block_status(&BlockId::Hash(info.best_hash))
+				.map_or(true, |status| status != BlockStatus::InChainPruned) => SyncMode::Full,
+			mode => mode,
+		};
+
 		let mut required_block_attributes = BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION;
 
-		if role.is_full() {
+		if role.is_full() && mode == SyncMode::Full {
 			required_block_attributes |= BlockAttributes::BODY
 		}
 
@@ -377,6 +418,9 @@ impl<B: BlockT> ChainSync<B> {
 			block_announce_validator,
 			max_parallel_downloads,
 			downloaded_blocks: 0,
+			mode,
+			state_sync: None,
+			stopped: false,
 		}
 	}
 
@@ -641,7 +685,7 @@ impl<B: BlockT> ChainSync<B> {
 
 	/// Get an iterator over all block requests of all peers.
 	pub fn block_requests(&mut self) -> impl Iterator<Item = (&PeerId, BlockRequest<B>)> + '_ {
-		if self.pending_requests.is_empty() {
+		if self.pending_requests.is_empty() || self.state_sync.is_some() || self.stopped {
 			return Either::Left(std::iter::empty())
 		}
 		if self.queue_blocks.len() > MAX_IMPORTING_BLOCKS {
@@ -728,6 +772,13 @@ impl<B: BlockT> ChainSync<B> {
 				self.pending_requests.add(who);
 				if request.is_some() {
 					match &mut peer.state {
+						PeerSyncState::DownloadingNew(_) if self.state_sync.is_some() || self.stopped => {
+							// The blocks following the state sync target are downloaded again
+							// once its state has been imported.
+							self.blocks.clear_peer_download(who);
+							peer.state = PeerSyncState::Available;
+							Vec::new()
+						}
 						PeerSyncState::DownloadingNew(start_block) => {
 							self.blocks.clear_peer_download(who);
 							let start_block = *start_block;
@@ -746,6 +797,7 @@ impl<B: BlockT> ChainSync<B> {
 										origin: block_data.origin,
 										allow_missing_state: true,
 										import_existing: false,
+										state: None,
 									}
 								}).collect()
 						}
@@ -765,6 +817,7 @@ impl<B: BlockT> ChainSync<B> {
 									origin: Some(who.clone()),
 									allow_missing_state: true,
 									import_existing: false,
+									state: None,
 								}
 							}).collect()
 						}
@@ -836,7 +889,8 @@ impl<B: BlockT> ChainSync<B> {
 
 						| PeerSyncState::Available
 						| PeerSyncState::DownloadingJustification(..)
-						| PeerSyncState::DownloadingFinalityProof(..) => Vec::new()
+						| PeerSyncState::DownloadingFinalityProof(..)
+						| PeerSyncState::DownloadingState => Vec::new()
 					}
 				} else {
 					// When request.is_none() this is a block announcement. Just accept blocks.
@@ -850,6 +904,7 @@ impl<B: BlockT> ChainSync<B> {
 							origin: Some(who.clone()),
 							allow_missing_state: true,
 							import_existing: false,
+							state: None,
 						}
 					}).collect()
 				}
@@ -857,6 +912,10 @@ impl<B: BlockT> ChainSync<B> {
 				Vec::new()
 			};
 
+		if self.stopped {
+			return Ok(OnBlockData::Import(BlockOrigin::NetworkInitialSync, Vec::new()))
+		}
+
 		// When doing initial sync we don't request blocks in parallel.
 		// So the only way this can happen is when peers lie about the
 		// common block.
@@ -879,6 +938,10 @@ impl<B: BlockT> ChainSync<B> {
 			debug!(target: "sync", "Ignoring {} blocks that are already queued", orig_len - new_blocks.len());
 		}
 
+		if self.mode == SyncMode::State && self.state_sync.is_none() {
+			self.select_state_sync_target(&mut new_blocks);
+		}
+
 		let origin =
 			if is_recent {
 				BlockOrigin::NetworkBroadcast
@@ -896,6 +959,139 @@ impl<B: BlockT> ChainSync<B> {
 		Ok(OnBlockData::Import(origin, new_blocks))
 	}
 
+	/// Pick the state sync target among freshly downloaded headers: the first one carrying a
+	/// justification within `STATE_SYNC_TARGET_DISTANCE` of the best block seen. The target and
+	/// the headers following it are held back from the import queue, and its state is
+	/// downloaded before it is imported.
+	fn select_state_sync_target(&mut self, new_blocks: &mut Vec<IncomingBlock<B>>) {
+		let best_seen = match self.peers.values().map(|p| p.best_number).max() {
+			Some(number) => number,
+			None => return,
+		};
+		let position = new_blocks.iter().position(|b| {
+			b.justification.is_some() && b.header.as_ref().map_or(false, |h| {
+				h.number().saturating_add(STATE_SYNC_TARGET_DISTANCE.into()) >= best_seen
+			})
+		});
+		if let Some(position) = position {
+			let target = new_blocks.drain(position..).next()
+				.expect("`position` is the index of an element; qed");
+			let header = target.header
+				.expect("`position` is the index of a block with a header; qed");
+			info!("⏳ Downloading state at #{} ({})", header.number(), target.hash);
+			self.blocks.clear();
+			self.state_sync = Some(StateSync::new(header, target.justification));
+		}
+	}
+
+	/// Get the next state request to make, if a state is being downloaded and no request is
+	/// underway.
+	pub fn state_request(&mut self) -> Option<(PeerId, StateRequest<B>)> {
+		let state_sync = self.state_sync.as_ref()?;
+		if state_sync.is_complete()
+			|| self.peers.values().any(|p| p.state == PeerSyncState::DownloadingState)
+		{
+			return None
+		}
+		let number = state_sync.target_number();
+		let (id, peer) = self.peers.iter_mut()
+			.find(|(_, peer)| peer.state.is_available() && peer.best_number >= number)?;
+		trace!(target: "sync", "New state request to {} for #{}", id, number);
+		peer.state = PeerSyncState::DownloadingState;
+		Some((id.clone(), state_sync.next_request()))
+	}
+
+	/// Handle a response from the remote to a state request that we made.
+	///
+	/// Returns the state sync target, to be imported along with its state, once the state is
+	/// complete.
+	pub fn on_state_data(
+		&mut self,
+		who: &PeerId,
+		block_hash: B::Hash,
+		proof: &[u8],
+	) -> Result<OnStateData<B>, BadPeer> {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if peer.state != PeerSyncState::DownloadingState {
+				return Ok(OnStateData::Nothing)
+			}
+			peer.state = PeerSyncState::Available;
+		} else {
+			error!(target: "sync", "💔 Called on_state_data with a bad peer ID");
+			return Ok(OnStateData::Nothing)
+		}
+		self.pending_requests.add(who);
+
+		let state_sync = match &mut self.state_sync {
+			Some(state_sync) if state_sync.target_block() == block_hash => state_sync,
+			_ => return Ok(OnStateData::Nothing),
+		};
+		match state_sync.import(proof) {
+			ImportResult::Import(hash, header, justification, state) => {
+				let number = *header.number();
+				info!("✅ Downloaded state at #{} ({}), {} entries", number, hash, state.len());
+				let block = IncomingBlock {
+					hash,
+					header: Some(header),
+					body: None,
+					justification,
+					origin: Some(who.clone()),
+					allow_missing_state: true,
+					import_existing: false,
+					state: Some(ImportedState { block: hash, state }),
+				};
+				self.queue_blocks.insert(hash);
+				self.on_block_queued(&hash, number);
+				Ok(OnStateData::Import(BlockOrigin::NetworkInitialSync, block))
+			}
+			ImportResult::Continue => Ok(OnStateData::Nothing),
+			ImportResult::Unsupported => {
+				self.stop();
+				Ok(OnStateData::Nothing)
+			}
+			ImportResult::BadResponse => Err(BadPeer(who.clone(), rep::BAD_STATE)),
+		}
+	}
+
+	/// Give up on a state that can't be imported. The headers imported so far have no state, and
+	/// full sync can't start over from genesis on top of them, so syncing stops until the chain is
+	/// purged and the node restarted with `--sync full`.
+	fn stop(&mut self) {
+		error!(
+			"💔 Syncing stopped: the state of the chain can't be downloaded. \
+			Purge the chain and restart the node with `--sync full`.",
+		);
+		self.state_sync = None;
+		self.stopped = true;
+		self.blocks.clear();
+	}
+
+	/// Called once the state sync target has been processed by the import queue. On success,
+	/// regular sync resumes from it; otherwise another target is picked.
+	fn on_state_sync_imported(&mut self, success: bool) {
+		let state_sync = match self.state_sync.take() {
+			Some(state_sync) => state_sync,
+			None => return,
+		};
+		if success {
+			info!("✅ State sync complete at #{} ({})", state_sync.target_number(), state_sync.target_block());
+			self.mode = SyncMode::Full;
+			if self.role.is_full() {
+				self.required_block_attributes |= BlockAttributes::BODY;
+			}
+		} else {
+			warn!(
+				target: "sync",
+				"💔 Failed to import state at #{} ({})",
+				state_sync.target_number(),
+				state_sync.target_block(),
+			);
+			let info = self.client.info();
+			self.best_queued_hash = info.best_hash;
+			self.best_queued_number = info.best_number;
+		}
+	}
+
 	/// Handle a response from the remote to a justification request that we made.
 	///
 	/// `request` must be the original request that triggered `response`.
@@ -1012,6 +1208,10 @@ impl<B: BlockT> ChainSync<B> {
 				has_error = true;
 			}
 
+			if self.state_sync.as_ref().map_or(false, |s| s.target_block() == hash) {
+				self.on_state_sync_imported(result.is_ok());
+			}
+
 			match result {
 				Ok(BlockImportResult::ImportedKnown(_number)) => {}
 				Ok(BlockImportResult::ImportedUnknown(number, aux, who)) => {
@@ -1574,6 +1774,7 @@ mod test {
 		let peer_id = PeerId::random();
 
 		let mut sync = ChainSync::new(
+			SyncMode::Full,
 			Roles::AUTHORITY,
 			client.clone(),
 			&info,
@@ -1639,4 +1840,60 @@ mod test {
 			})
 		);
 	}
+
+	#[test]
+	fn stops_on_unsupported_state() {
+		use codec::Encode;
+		use sc_client_api::ProofProvider;
+		use sp_core::storage::ChildInfo;
+
+		let client = Arc::new(
+			TestClientBuilder::new()
+				.add_extra_child_storage(&ChildInfo::new_default(b"child"), b"key".to_vec(), b"value".to_vec())
+				.build()
+		);
+		let info = client.info();
+		let mut sync = ChainSync::new(
+			SyncMode::State,
+			Roles::FULL,
+			client.clone(),
+			&info,
+			None,
+			Box::new(DefaultBlockAnnounceValidator),
+			1,
+		);
+		let peer_id = PeerId::random();
+		sync.new_peer(peer_id.clone(), info.best_hash, info.best_number).unwrap();
+
+		// The genesis state holds a child trie, which state sync can't import.
+		let genesis = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		sync.state_sync = Some(StateSync::new(genesis, None));
+		let (who, _) = sync.state_request().unwrap();
+		let (proof, _) = client.read_range_proof(&BlockId::Number(0), None, usize::max_value()).unwrap();
+
+		assert_eq!(sync.on_state_data(&who, info.genesis_hash, &proof.encode()), Ok(OnStateData::Nothing));
+		assert!(sync.state_sync.is_none());
+		assert_eq!(sync.mode, SyncMode::State);
+		assert!(!sync.required_block_attributes.contains(BlockAttributes::BODY));
+		assert!(sync.state_request().is_none());
+		assert!(sync.block_requests().next().is_none());
+
+		// Announced blocks aren't imported on top of the stateless headers either.
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		let response = message::generic::BlockResponse {
+			id: 0,
+			blocks: vec![message::generic::BlockData {
+				hash: block.hash(),
+				header: Some(block.header().clone()),
+				body: Some(block.extrinsics().to_vec()),
+				receipt: None,
+				message_queue: None,
+				justification: None,
+			}],
+		};
+		match sync.on_block_data(&peer_id, None, response).unwrap() {
+			OnBlockData::Import(_, blocks) => assert!(blocks.is_empty()),
+			_ => panic!("unexpected block data"),
+		}
+	}
 }
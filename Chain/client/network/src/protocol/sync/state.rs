@@ -0,0 +1,262 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2017-2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Download of the full state of a single block, in chunks that are each checked against the
+//! state root of the block header.
+
+use codec::Decode;
+use log::{debug, error};
+use sp_core::{hexdisplay::HexDisplay, storage::well_known_keys};
+use sp_runtime::{Justification, traits::{Block as BlockT, Header, NumberFor, HashFor}};
+use sp_state_machine::{StorageProof, read_range_proof_check};
+
+/// Request for the next chunk of the state being downloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateRequest<B: BlockT> {
+	/// Block whose state is requested.
+	pub block: B::Hash,
+	/// Key after which entries are requested. Empty to start at the first key.
+	pub start: Vec<u8>,
+}
+
+/// Result of [`StateSync::import`].
+pub enum ImportResult<B: BlockT> {
+	/// The state is complete and the target block can be imported along with it.
+	Import(B::Hash, B::Header, Option<Justification>, Vec<(Vec<u8>, Vec<u8>)>),
+	/// More entries need to be downloaded.
+	Continue,
+	/// The response is invalid.
+	BadResponse,
+	/// The state holds data that can't be imported this way.
+	Unsupported,
+}
+
+/// State download of a single target block.
+pub struct StateSync<B: BlockT> {
+	target_block: B::Hash,
+	target_header: B::Header,
+	target_justification: Option<Justification>,
+	/// Last key received, the next request starts after it.
+	last_key: Vec<u8>,
+	state: Vec<(Vec<u8>, Vec<u8>)>,
+	imported_bytes: u64,
+	complete: bool,
+}
+
+impl<B: BlockT> StateSync<B> {
+	/// Create a new instance downloading the state of the block with the given header.
+	pub fn new(target_header: B::Header, target_justification: Option<Justification>) -> Self {
+		StateSync {
+			target_block: target_header.hash(),
+			target_header,
+			target_justification,
+			last_key: Vec::new(),
+			state: Vec::new(),
+			imported_bytes: 0,
+			complete: false,
+		}
+	}
+
+	/// Validate and import a SCALE-encoded storage proof received for the last request.
+	pub fn import(&mut self, proof: &[u8]) -> ImportResult<B> {
+		if self.complete {
+			return ImportResult::Continue
+		}
+		let proof = match StorageProof::decode(&mut &proof[..]) {
+			Ok(proof) => proof,
+			Err(e) => {
+				debug!(target: "sync", "Error decoding state proof: {:?}", e);
+				return ImportResult::BadResponse
+			}
+		};
+		let start = if self.state.is_empty() { None } else { Some(self.last_key.as_slice()) };
+		let (entries, complete) = match read_range_proof_check::<HashFor<B>>(
+			*self.target_header.state_root(),
+			proof,
+			start,
+		) {
+			Ok(result) => result,
+			Err(e) => {
+				debug!(target: "sync", "Invalid state proof: {:?}", e);
+				return ImportResult::BadResponse
+			}
+		};
+		if entries.is_empty() && !complete {
+			debug!(target: "sync", "Empty state proof after {}", HexDisplay::from(&self.last_key));
+			return ImportResult::BadResponse
+		}
+		if let Some((key, _)) = entries.iter().find(|(key, _)| well_known_keys::is_child_storage_key(key)) {
+			error!(
+				target: "sync",
+				"💔 State sync can't import child storage ({})",
+				HexDisplay::from(key),
+			);
+			self.complete = true;
+			return ImportResult::Unsupported
+		}
+
+		if let Some((key, _)) = entries.last() {
+			self.last_key = key.clone();
+		}
+		self.imported_bytes += entries.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum::<u64>();
+		self.state.extend(entries);
+		debug!(
+			target: "sync",
+			"Imported {} state entries ({} bytes) for #{} ({})",
+			self.state.len(),
+			self.imported_bytes,
+			self.target_header.number(),
+			self.target_block,
+		);
+
+		if complete {
+			self.complete = true;
+			ImportResult::Import(
+				self.target_block,
+				self.target_header.clone(),
+				self.target_justification.take(),
+				std::mem::take(&mut self.state),
+			)
+		} else {
+			ImportResult::Continue
+		}
+	}
+
+	/// Produce the request for the next chunk of the state.
+	pub fn next_request(&self) -> StateRequest<B> {
+		StateRequest {
+			block: self.target_block,
+			start: self.last_key.clone(),
+		}
+	}
+
+	/// Whether the state has been downloaded, or can't be.
+	pub fn is_complete(&self) -> bool {
+		self.complete
+	}
+
+	/// Hash of the target block.
+	pub fn target_block(&self) -> B::Hash {
+		self.target_block
+	}
+
+	/// Number of the target block.
+	pub fn target_number(&self) -> NumberFor<B> {
+		*self.target_header.number()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{ImportResult, StateSync};
+	use assert_matches::assert_matches;
+	use codec::Encode;
+	use sc_client_api::{ProofProvider, StorageProvider};
+	use sp_blockchain::HeaderBackend;
+	use sp_core::storage::{ChildInfo, StorageKey};
+	use sp_runtime::{generic::BlockId, traits::Header};
+	use substrate_test_runtime_client::{
+		runtime::Block, DefaultTestClientBuilderExt, TestClient, TestClientBuilder, TestClientBuilderExt,
+	};
+
+	fn genesis_sync(client: &TestClient) -> StateSync<Block> {
+		let header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		StateSync::new(header, Some(b"justification".to_vec()))
+	}
+
+	fn range_proof(client: &TestClient, start: &[u8], size_limit: usize) -> Vec<u8> {
+		let start = if start.is_empty() { None } else { Some(start) };
+		client.read_range_proof(&BlockId::Number(0), start, size_limit).unwrap().0.encode()
+	}
+
+	#[test]
+	fn imports_state_in_chunks() {
+		let client = TestClientBuilder::new()
+			.add_extra_storage(b"key".to_vec(), vec![1; 4096])
+			.build();
+		let genesis_hash = client.info().genesis_hash;
+		let mut sync = genesis_sync(&client);
+
+		let mut chunks = 0;
+		let mut last_start = None;
+		let state = loop {
+			let request = sync.next_request();
+			assert_eq!(request.block, genesis_hash);
+			// Every request starts after the last key received.
+			assert!(last_start.map_or(true, |start| request.start > start));
+			last_start = Some(request.start.clone());
+			chunks += 1;
+			match sync.import(&range_proof(&client, &request.start, 1024)) {
+				ImportResult::Continue => assert!(!sync.is_complete()),
+				ImportResult::Import(hash, header, justification, state) => {
+					assert_eq!(hash, genesis_hash);
+					assert_eq!(header.hash(), genesis_hash);
+					assert_eq!(justification, Some(b"justification".to_vec()));
+					break state
+				}
+				_ => panic!("unexpected import result"),
+			}
+		};
+
+		assert!(chunks > 1);
+		assert!(sync.is_complete());
+		let expected: Vec<_> = client.storage_pairs(&BlockId::Number(0), &StorageKey(Vec::new()))
+			.unwrap()
+			.into_iter()
+			.map(|(key, value)| (key.0, value.0))
+			.collect();
+		assert_eq!(state, expected);
+	}
+
+	#[test]
+	fn rejects_undecodable_proof() {
+		let client = TestClientBuilder::new().build();
+		let mut sync = genesis_sync(&client);
+		assert_matches!(sync.import(&[1, 2, 3]), ImportResult::BadResponse);
+		assert!(!sync.is_complete());
+	}
+
+	#[test]
+	fn rejects_proof_of_another_state() {
+		let client = TestClientBuilder::new().build();
+		let other = TestClientBuilder::new()
+			.add_extra_storage(b"key".to_vec(), b"value".to_vec())
+			.build();
+		let mut sync = genesis_sync(&client);
+		assert_matches!(
+			sync.import(&range_proof(&other, &[], usize::max_value())),
+			ImportResult::BadResponse
+		);
+		// The request is unchanged, to be retried with another peer.
+		assert!(sync.next_request().start.is_empty());
+		assert!(!sync.is_complete());
+	}
+
+	#[test]
+	fn child_storage_is_unsupported() {
+		let client = TestClientBuilder::new()
+			.add_extra_child_storage(&ChildInfo::new_default(b"child"), b"key".to_vec(), b"value".to_vec())
+			.build();
+		let mut sync = genesis_sync(&client);
+		assert_matches!(
+			sync.import(&range_proof(&client, &[], usize::max_value())),
+			ImportResult::Unsupported
+		);
+		assert!(sync.is_complete());
+	}
+}
@@ -37,7 +37,7 @@ use crate::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
 	on_demand_layer::AlwaysBadChecker,
-	light_client_handler, block_requests, finality_requests,
+	light_client_handler, block_requests, finality_requests, state_requests,
 	protocol::{self, event::Event, NotifsHandlerError, LegacyConnectionKillError, NotificationsSink, Ready, sync::SyncState, PeerInfo, Protocol},
 	transport, ReputationChange,
 };
@@ -240,6 +240,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			protocol::ProtocolConfig {
 				roles: From::from(&params.role),
 				max_parallel_downloads: params.network_config.max_parallel_downloads,
+				sync_mode: params.network_config.sync_mode,
 			},
 			local_peer_id.clone(),
 			params.chain.clone(),
@@ -268,6 +269,10 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				let config = finality_requests::Config::new(&params.protocol_id);
 				finality_requests::FinalityProofRequests::new(config, params.finality_proof_provider.clone())
 			};
+			let state_requests = {
+				let config = state_requests::Config::new(&params.protocol_id);
+				state_requests::StateRequests::new(config, params.chain.clone())
+			};
 			let light_client_handler = {
 				let config = light_client_handler::Config::new(&params.protocol_id);
 				light_client_handler::LightClientHandler::new(
@@ -306,6 +311,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				local_public,
 				block_requests,
 				finality_proof_requests,
+				state_requests,
 				light_client_handler,
 				discovery_config
 			);
@@ -0,0 +1,451 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `NetworkBehaviour` implementation which handles incoming state requests.
+//!
+//! Every request is coming in on a separate connection substream which gets
+//! closed after we have sent the response back. Incoming requests are encoded
+//! as protocol buffers (cf. `state.v1.proto`).
+//!
+//! A response carries a storage proof of the entries following the requested key, up to
+//! `MAX_RESPONSE_DATA` bytes of keys and values, which the requester checks against the
+//! state root of the block.
+
+use bytes::Bytes;
+use codec::{Encode, Decode};
+use crate::{
+	chain::Client,
+	config::ProtocolId,
+	schema,
+};
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use libp2p::{
+	core::{
+		Multiaddr,
+		PeerId,
+		connection::ConnectionId,
+		upgrade::{InboundUpgrade, OutboundUpgrade, ReadOneError, UpgradeInfo},
+		upgrade::{read_one, write_one}
+	},
+	swarm::{
+		NegotiatedSubstream,
+		NetworkBehaviour,
+		NetworkBehaviourAction,
+		NotifyHandler,
+		OneShotHandler,
+		OneShotHandlerConfig,
+		PollParameters,
+		SubstreamProtocol
+	}
+};
+use prost::Message;
+use sp_runtime::{generic::BlockId, traits::Block};
+use std::{
+	collections::VecDeque,
+	io,
+	iter,
+	marker::PhantomData,
+	sync::Arc,
+	time::Duration,
+	task::{Context, Poll}
+};
+
+/// Amount of keys and values, in bytes, after which a response is cut.
+const MAX_RESPONSE_DATA: usize = 1024 * 1024;
+
+// Type alias for convenience.
+pub type Error = Box<dyn std::error::Error + 'static>;
+
+/// Event generated by the state requests behaviour.
+#[derive(Debug)]
+pub enum Event<B: Block> {
+	/// A response to a state request has arrived.
+	Response {
+		peer: PeerId,
+		/// Block hash originally passed to `send_request`.
+		block_hash: B::Hash,
+		/// SCALE-encoded storage proof returned by the remote.
+		proof: Vec<u8>,
+	},
+}
+
+/// Configuration options for `StateRequests`.
+#[derive(Debug, Clone)]
+pub struct Config {
+	max_request_len: usize,
+	max_response_len: usize,
+	inactivity_timeout: Duration,
+	protocol: Bytes,
+}
+
+impl Config {
+	/// Create a fresh configuration with the following options:
+	///
+	/// - max. request size = 1 MiB
+	/// - max. response size = 16 MiB
+	/// - inactivity timeout = 30s
+	pub fn new(id: &ProtocolId) -> Self {
+		let mut c = Config {
+			max_request_len: 1024 * 1024,
+			max_response_len: 16 * 1024 * 1024,
+			inactivity_timeout: Duration::from_secs(30),
+			protocol: Bytes::new(),
+		};
+		c.set_protocol(id);
+		c
+	}
+
+	/// Set protocol to use for upgrade negotiation.
+	pub fn set_protocol(&mut self, id: &ProtocolId) -> &mut Self {
+		let mut v = Vec::new();
+		v.extend_from_slice(b"/");
+		v.extend_from_slice(id.as_bytes());
+		v.extend_from_slice(b"/state/1");
+		self.protocol = v.into();
+		self
+	}
+}
+
+/// The state request handling behaviour.
+pub struct StateRequests<B: Block> {
+	/// This behaviour's configuration.
+	config: Config,
+	/// Blockchain client.
+	chain: Arc<dyn Client<B>>,
+	/// Futures sending back the state request responses.
+	outgoing: FuturesUnordered<BoxFuture<'static, ()>>,
+	/// Events to return as soon as possible from `poll`.
+	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol<B>, Event<B>>>,
+}
+
+impl<B> StateRequests<B>
+where
+	B: Block,
+{
+	/// Initializes the behaviour.
+	pub fn new(cfg: Config, chain: Arc<dyn Client<B>>) -> Self {
+		StateRequests {
+			config: cfg,
+			chain,
+			outgoing: FuturesUnordered::new(),
+			pending_events: VecDeque::new(),
+		}
+	}
+
+	/// Issue a new state request for the entries of the state of `block_hash` following `start`.
+	///
+	/// If the response doesn't arrive in time, or if the remote answers improperly, the target
+	/// will be disconnected.
+	pub fn send_request(&mut self, target: &PeerId, block_hash: B::Hash, start: Vec<u8>) {
+		let protobuf_rq = schema::v1::state::StateRequest {
+			block: block_hash.encode(),
+			start,
+		};
+
+		let mut buf = Vec::with_capacity(protobuf_rq.encoded_len());
+		if let Err(err) = protobuf_rq.encode(&mut buf) {
+			log::warn!("failed to encode state request {:?}: {:?}", protobuf_rq, err);
+			return;
+		}
+
+		log::trace!("enqueueing state request to {:?}: {:?}", target, protobuf_rq);
+		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
+			peer_id: target.clone(),
+			handler: NotifyHandler::Any,
+			event: OutboundProtocol {
+				request: buf,
+				block_hash,
+				max_response_size: self.config.max_response_len,
+				protocol: self.config.protocol.clone(),
+			},
+		});
+	}
+
+	/// Callback, invoked when a new state request has been received from remote.
+	fn on_state_request(&mut self, peer: &PeerId, request: &schema::v1::state::StateRequest)
+		-> Result<schema::v1::state::StateResponse, Error>
+	{
+		let block_hash: B::Hash = Decode::decode(&mut request.block.as_ref())?;
+
+		log::trace!(target: "sync", "State request from {} for {} after {:?}",
+			peer,
+			block_hash,
+			sp_core::hexdisplay::HexDisplay::from(&request.start),
+		);
+
+		let start = if request.start.is_empty() {
+			None
+		} else {
+			Some(request.start.as_slice())
+		};
+		let (proof, count) = self.chain.read_range_proof(
+			&BlockId::Hash(block_hash),
+			start,
+			MAX_RESPONSE_DATA,
+		)?;
+
+		log::trace!(target: "sync", "Sending {} state entries to {}", count, peer);
+
+		Ok(schema::v1::state::StateResponse { proof: proof.encode() })
+	}
+}
+
+impl<B> NetworkBehaviour for StateRequests<B>
+where
+	B: Block
+{
+	type ProtocolsHandler = OneShotHandler<InboundProtocol<B>, OutboundProtocol<B>, NodeEvent<B, NegotiatedSubstream>>;
+	type OutEvent = Event<B>;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		let p = InboundProtocol {
+			max_request_len: self.config.max_request_len,
+			protocol: self.config.protocol.clone(),
+			marker: PhantomData,
+		};
+		let mut cfg = OneShotHandlerConfig::default();
+		cfg.keep_alive_timeout = self.config.inactivity_timeout;
+		OneShotHandler::new(SubstreamProtocol::new(p), cfg)
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_disconnected(&mut self, _peer: &PeerId) {
+	}
+
+	fn inject_event(
+		&mut self,
+		peer: PeerId,
+		_connection: ConnectionId,
+		event: NodeEvent<B, NegotiatedSubstream>
+	) {
+		match event {
+			NodeEvent::Request(request, mut stream) => {
+				match self.on_state_request(&peer, &request) {
+					Ok(res) => {
+						log::trace!("enqueueing state response for peer {}", peer);
+						let mut data = Vec::with_capacity(res.encoded_len());
+						if let Err(e) = res.encode(&mut data) {
+							log::debug!("error encoding state response for peer {}: {}", peer, e)
+						} else {
+							let future = async move {
+								if let Err(e) = write_one(&mut stream, data).await {
+									log::debug!("error writing state response: {}", e)
+								}
+							};
+							self.outgoing.push(future.boxed())
+						}
+					}
+					Err(e) => log::debug!("error handling state request from peer {}: {}", peer, e)
+				}
+			}
+			NodeEvent::Response(response, block_hash) => {
+				let ev = Event::Response {
+					peer,
+					block_hash,
+					proof: response.proof,
+				};
+				self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(ev));
+			}
+		}
+	}
+
+	fn poll(&mut self, cx: &mut Context, _: &mut impl PollParameters)
+		-> Poll<NetworkBehaviourAction<OutboundProtocol<B>, Event<B>>>
+	{
+		if let Some(ev) = self.pending_events.pop_front() {
+			return Poll::Ready(ev);
+		}
+
+		while let Poll::Ready(Some(_)) = self.outgoing.poll_next_unpin(cx) {}
+		Poll::Pending
+	}
+}
+
+/// Output type of inbound and outbound substream upgrades.
+#[derive(Debug)]
+pub enum NodeEvent<B: Block, T> {
+	/// Incoming request from remote and substream to use for the response.
+	Request(schema::v1::state::StateRequest, T),
+	/// Incoming response from remote.
+	Response(schema::v1::state::StateResponse, B::Hash),
+}
+
+/// Substream upgrade protocol.
+///
+/// We attempt to parse an incoming protobuf encoded request (cf. `Request`)
+/// which will be handled by the `StateRequests` behaviour, i.e. the request
+/// will become visible via `inject_node_event` which then dispatches to the
+/// relevant callback to process the message and prepare a response.
+#[derive(Debug, Clone)]
+pub struct InboundProtocol<B> {
+	/// The max. request length in bytes.
+	max_request_len: usize,
+	/// The protocol to use during upgrade negotiation.
+	protocol: Bytes,
+	/// Marker to pin the block type.
+	marker: PhantomData<B>,
+}
+
+impl<B: Block> UpgradeInfo for InboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> InboundUpgrade<T> for InboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			let len = self.max_request_len;
+			let vec = read_one(&mut s, len).await?;
+			match schema::v1::state::StateRequest::decode(&vec[..]) {
+				Ok(r) => Ok(NodeEvent::Request(r, s)),
+				Err(e) => Err(ReadOneError::Io(io::Error::new(io::ErrorKind::Other, e)))
+			}
+		}.boxed()
+	}
+}
+
+/// Substream upgrade protocol.
+///
+/// Sends a request to remote and awaits the response.
+#[derive(Debug, Clone)]
+pub struct OutboundProtocol<B: Block> {
+	/// The serialized protobuf request.
+	request: Vec<u8>,
+	/// Block hash whose state has been requested.
+	block_hash: B::Hash,
+	/// The max. response length in bytes.
+	max_response_size: usize,
+	/// The protocol to use for upgrade negotiation.
+	protocol: Bytes,
+}
+
+impl<B: Block> UpgradeInfo for OutboundProtocol<B> {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<B, T> OutboundUpgrade<T> for OutboundProtocol<B>
+where
+	B: Block,
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<B, T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			write_one(&mut s, &self.request).await?;
+			let vec = read_one(&mut s, self.max_response_size).await?;
+
+			schema::v1::state::StateResponse::decode(&vec[..])
+				.map(|r| NodeEvent::Response(r, self.block_hash))
+				.map_err(|e| {
+					ReadOneError::Io(io::Error::new(io::ErrorKind::Other, e))
+				})
+		}.boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::{HashFor, Header};
+	use sp_state_machine::{StorageProof, read_range_proof_check};
+	use substrate_test_runtime_client::{
+		runtime::Block, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
+	};
+
+	fn state_requests(client: substrate_test_runtime_client::TestClient) -> StateRequests<Block> {
+		let config = Config::new(&ProtocolId::from(&b"test"[..]));
+		StateRequests::new(config, Arc::new(client))
+	}
+
+	fn request(block: &[u8], start: &[u8]) -> schema::v1::state::StateRequest {
+		schema::v1::state::StateRequest { block: block.to_vec(), start: start.to_vec() }
+	}
+
+	#[test]
+	fn protocol_name() {
+		let config = Config::new(&ProtocolId::from(&b"test"[..]));
+		assert_eq!(&config.protocol[..], b"/test/state/1");
+	}
+
+	#[test]
+	fn answers_with_checkable_chunks() {
+		let client = TestClientBuilder::new()
+			.add_extra_storage(b"key1".to_vec(), vec![1; MAX_RESPONSE_DATA])
+			.add_extra_storage(b"key2".to_vec(), vec![2; MAX_RESPONSE_DATA])
+			.build();
+		let genesis = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		let mut requests = state_requests(client);
+		let peer = PeerId::random();
+
+		let mut start = Vec::new();
+		let mut entries = Vec::new();
+		let mut chunks = 0;
+		loop {
+			let response = requests.on_state_request(&peer, &request(&genesis.hash().encode(), &start)).unwrap();
+			let proof = StorageProof::decode(&mut &response.proof[..]).unwrap();
+			let (chunk, complete) = read_range_proof_check::<HashFor<Block>>(
+				*genesis.state_root(),
+				proof,
+				if start.is_empty() { None } else { Some(&start[..]) },
+			).unwrap();
+			chunks += 1;
+			start = chunk.last().map(|(key, _)| key.clone()).unwrap_or_default();
+			entries.extend(chunk);
+			if complete {
+				break
+			}
+		}
+
+		// Responses are cut once they hold `MAX_RESPONSE_DATA` bytes.
+		assert!(chunks > 1);
+		assert!(entries.iter().any(|(key, value)| key == b"key1" && value == &vec![1; MAX_RESPONSE_DATA]));
+		assert!(entries.iter().any(|(key, value)| key == b"key2" && value == &vec![2; MAX_RESPONSE_DATA]));
+	}
+
+	#[test]
+	fn fails_on_unknown_block() {
+		let mut requests = state_requests(TestClientBuilder::new().build());
+		let peer = PeerId::random();
+		assert!(requests.on_state_request(&peer, &request(&[0; 32], &[])).is_err());
+		assert!(requests.on_state_request(&peer, &request(&[1, 2, 3], &[])).is_err());
+	}
+}
@@ -28,13 +28,14 @@ use libp2p::build_multiaddr;
 use log::trace;
 use sc_network::config::FinalityProofProvider;
 use sp_blockchain::{
-	HeaderBackend, Result as ClientResult,
+	HeaderBackend, HeaderMetadata, CachedHeaderMetadata, Result as ClientResult,
 	well_known_cache_keys::{self, Id as CacheKeyId},
 	Info as BlockchainInfo,
 };
 use sc_client_api::{
 	BlockchainEvents, BlockImportNotification, FinalityNotifications, ImportNotifications, FinalityNotification,
-	backend::{TransactionFor, AuxStore, Backend, Finalizer}, BlockBackend,
+	backend::{TransactionFor, AuxStore, Backend, Finalizer}, BlockBackend, ProofProvider,
+	StorageProof, ChangesProof,
 };
 use sc_consensus::LongestChain;
 use sc_block_builder::{BlockBuilder, BlockBuilderProvider};
@@ -48,13 +49,13 @@ use sp_consensus::Error as ConsensusError;
 use sp_consensus::{BlockOrigin, ForkChoiceStrategy, BlockImportParams, BlockCheckParams, JustificationImport};
 use futures::prelude::*;
 use sc_network::{NetworkWorker, NetworkService, config::ProtocolId};
-use sc_network::config::{NetworkConfiguration, TransportConfig, BoxFinalityProofRequestBuilder};
+use sc_network::config::{NetworkConfiguration, TransportConfig, BoxFinalityProofRequestBuilder, SyncMode};
 use libp2p::PeerId;
 use parking_lot::Mutex;
-use sp_core::H256;
+use sp_core::{H256, storage::{ChildInfo, PrefixedStorageKey, Storage, StorageKey}};
 use sc_network::config::ProtocolConfig;
-use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
-use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use sp_runtime::generic::{BlockId, OpaqueDigestItemId, SignedBlock};
+use sp_runtime::traits::{Block as BlockT, BlockIdTo, Header as HeaderT, NumberFor};
 use sp_runtime::Justification;
 use substrate_test_runtime_client::{self, AccountKeyring};
 use sc_service::client::Client;
@@ -553,6 +554,140 @@ pub struct FullPeerConfig {
 	pub keep_blocks: Option<u32>,
 	/// Block announce validator.
 	pub block_announce_validator: Option<Box<dyn BlockAnnounceValidator<Block> + Send + Sync>>,
+	/// How the peer bootstraps the chain.
+	pub sync_mode: SyncMode,
+	/// Storage added to the genesis state of the peer.
+	pub extra_storage: Storage,
+	/// Serve state proofs that don't check against the state root of the requested block.
+	pub bad_state_proofs: bool,
+}
+
+/// Client serving, whatever the requested block, range proofs of the genesis state.
+struct BadStateProofs(Arc<PeersFullClient>);
+
+impl HeaderBackend<Block> for BadStateProofs {
+	fn header(&self, id: BlockId<Block>) -> ClientResult<Option<<Block as BlockT>::Header>> {
+		HeaderBackend::header(&*self.0, id)
+	}
+
+	fn info(&self) -> BlockchainInfo<Block> {
+		self.0.info()
+	}
+
+	fn status(&self, id: BlockId<Block>) -> ClientResult<sp_blockchain::BlockStatus> {
+		self.0.status(id)
+	}
+
+	fn number(&self, hash: H256) -> ClientResult<Option<NumberFor<Block>>> {
+		self.0.number(hash)
+	}
+
+	fn hash(&self, number: NumberFor<Block>) -> ClientResult<Option<H256>> {
+		self.0.hash(number)
+	}
+}
+
+impl ProofProvider<Block> for BadStateProofs {
+	fn read_proof(
+		&self,
+		id: &BlockId<Block>,
+		keys: &mut dyn Iterator<Item=&[u8]>,
+	) -> ClientResult<StorageProof> {
+		self.0.read_proof(id, keys)
+	}
+
+	fn read_child_proof(
+		&self,
+		id: &BlockId<Block>,
+		child_info: &ChildInfo,
+		keys: &mut dyn Iterator<Item=&[u8]>,
+	) -> ClientResult<StorageProof> {
+		self.0.read_child_proof(id, child_info, keys)
+	}
+
+	fn read_range_proof(
+		&self,
+		_id: &BlockId<Block>,
+		start_key: Option<&[u8]>,
+		size_limit: usize,
+	) -> ClientResult<(StorageProof, u32)> {
+		self.0.read_range_proof(&BlockId::Number(0), start_key, size_limit)
+	}
+
+	fn execution_proof(
+		&self,
+		id: &BlockId<Block>,
+		method: &str,
+		call_data: &[u8],
+	) -> ClientResult<(Vec<u8>, StorageProof)> {
+		self.0.execution_proof(id, method, call_data)
+	}
+
+	fn header_proof(&self, id: &BlockId<Block>) -> ClientResult<(<Block as BlockT>::Header, StorageProof)> {
+		self.0.header_proof(id)
+	}
+
+	fn key_changes_proof(
+		&self,
+		first: H256,
+		last: H256,
+		min: H256,
+		max: H256,
+		storage_key: Option<&PrefixedStorageKey>,
+		key: &StorageKey,
+	) -> ClientResult<ChangesProof<<Block as BlockT>::Header>> {
+		self.0.key_changes_proof(first, last, min, max, storage_key, key)
+	}
+}
+
+impl BlockIdTo<Block> for BadStateProofs {
+	type Error = sp_blockchain::Error;
+
+	fn to_hash(&self, block_id: &BlockId<Block>) -> ClientResult<Option<H256>> {
+		self.0.to_hash(block_id)
+	}
+
+	fn to_number(&self, block_id: &BlockId<Block>) -> ClientResult<Option<NumberFor<Block>>> {
+		self.0.to_number(block_id)
+	}
+}
+
+impl BlockBackend<Block> for BadStateProofs {
+	fn block_body(&self, id: &BlockId<Block>) -> ClientResult<Option<Vec<<Block as BlockT>::Extrinsic>>> {
+		self.0.block_body(id)
+	}
+
+	fn block(&self, id: &BlockId<Block>) -> ClientResult<Option<SignedBlock<Block>>> {
+		self.0.block(id)
+	}
+
+	fn block_status(&self, id: &BlockId<Block>) -> ClientResult<sp_consensus::BlockStatus> {
+		self.0.block_status(id)
+	}
+
+	fn justification(&self, id: &BlockId<Block>) -> ClientResult<Option<Justification>> {
+		self.0.justification(id)
+	}
+
+	fn block_hash(&self, number: NumberFor<Block>) -> ClientResult<Option<H256>> {
+		self.0.block_hash(number)
+	}
+}
+
+impl HeaderMetadata<Block> for BadStateProofs {
+	type Error = sp_blockchain::Error;
+
+	fn header_metadata(&self, hash: H256) -> ClientResult<CachedHeaderMetadata<Block>> {
+		self.0.header_metadata(hash)
+	}
+
+	fn insert_header_metadata(&self, hash: H256, header_metadata: CachedHeaderMetadata<Block>) {
+		self.0.insert_header_metadata(hash, header_metadata)
+	}
+
+	fn remove_header_metadata(&self, hash: H256) {
+		self.0.remove_header_metadata(hash)
+	}
 }
 
 pub trait TestNetFactory: Sized {
@@ -620,10 +755,18 @@ pub trait TestNetFactory: Sized {
 
 	/// Add a full peer.
 	fn add_full_peer_with_config(&mut self, config: FullPeerConfig) {
-		let test_client_builder = match config.keep_blocks {
+		let mut test_client_builder = match config.keep_blocks {
 			Some(keep_blocks) => TestClientBuilder::with_pruning_window(keep_blocks),
 			None => TestClientBuilder::with_default_backend(),
 		};
+		for (key, value) in config.extra_storage.top {
+			test_client_builder = test_client_builder.add_extra_storage(key, value);
+		}
+		for (_, child) in config.extra_storage.children_default {
+			for (key, value) in child.data {
+				test_client_builder = test_client_builder.add_extra_child_storage(&child.child_info, key, value);
+			}
+		}
 		let backend = test_client_builder.backend();
 		let (c, longest_chain) = test_client_builder.build_with_longest_chain();
 		let client = Arc::new(c);
@@ -663,12 +806,19 @@ pub trait TestNetFactory: Sized {
 		network_config.transport = TransportConfig::MemoryOnly;
 		network_config.listen_addresses = vec![listen_addr.clone()];
 		network_config.allow_non_globals_in_dht = true;
+		network_config.sync_mode = config.sync_mode;
+
+		let chain: Arc<dyn sc_network::config::Client<Block>> = if config.bad_state_proofs {
+			Arc::new(BadStateProofs(client.clone()))
+		} else {
+			client.clone()
+		};
 
 		let network = NetworkWorker::new(sc_network::config::Params {
 			role: Role::Full,
 			executor: None,
 			network_config,
-			chain: client.clone(),
+			chain,
 			finality_proof_provider: self.make_finality_proof_provider(
 				PeersClient::Full(client.clone(), backend.clone()),
 			),
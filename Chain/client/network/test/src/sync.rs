@@ -22,6 +22,8 @@ use futures::executor::block_on;
 use super::*;
 use sp_consensus::block_validation::Validation;
 use substrate_test_runtime::Header;
+use sc_client_api::StorageProvider;
+use sp_core::storage::{StorageChild, StorageData};
 
 fn test_ancestor_search_when_common_is(n: usize) {
 	let _ = ::env_logger::try_init();
@@ -600,6 +602,135 @@ fn syncs_header_only_forks() {
 	assert!(net.peer(1).has_block(&small_hash));
 }
 
+#[test]
+fn syncs_state_at_finalized_block() {
+	let _ = env_logger::try_init();
+	let mut net = TestNet::new(0);
+	net.add_full_peer_with_config(Default::default());
+	net.peer(0).push_blocks(30, true);
+	net.peer(0).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+	net.add_full_peer_with_config(FullPeerConfig { sync_mode: SyncMode::State, ..Default::default() });
+
+	net.block_until_sync();
+
+	let hash = |net: &mut TestNet, number| {
+		net.peer(0).client().header(&BlockId::Number(number)).unwrap().unwrap().hash()
+	};
+	let (hash_10, hash_20, hash_30) = (hash(&mut net, 10), hash(&mut net, 20), hash(&mut net, 30));
+	let backend = net.peer(1).backend.clone().unwrap();
+	let client = net.peer(1).client().as_full().unwrap();
+	// Blocks before the finalized block are synced as headers only.
+	assert!(!backend.have_state_at(&hash_10, 10));
+	assert!(client.block_body(&BlockId::Hash(hash_10)).unwrap().is_none());
+	// The state of the finalized block is downloaded and blocks are imported on top of it.
+	assert!(backend.have_state_at(&hash_20, 20));
+	assert!(backend.have_state_at(&hash_30, 30));
+	assert!(client.block_body(&BlockId::Hash(hash_30)).unwrap().is_some());
+	assert!(net.peers()[0].blockchain_canon_equals(&net.peers()[1]));
+}
+
+#[test]
+fn syncs_state_in_several_chunks() {
+	let _ = env_logger::try_init();
+	// Four values of 512 KiB don't fit in a single state response.
+	let mut extra_storage = Storage::default();
+	for i in 0..4u8 {
+		extra_storage.top.insert(vec![b'k', i], vec![i; 512 * 1024]);
+	}
+	let mut net = TestNet::new(0);
+	net.add_full_peer_with_config(FullPeerConfig { extra_storage: extra_storage.clone(), ..Default::default() });
+	net.peer(0).push_blocks(30, false);
+	net.peer(0).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::State,
+		extra_storage: extra_storage.clone(),
+		..Default::default()
+	});
+
+	net.block_until_sync();
+
+	let client = net.peer(1).client().as_full().unwrap();
+	for (key, value) in extra_storage.top {
+		let stored = client.storage(&BlockId::Number(30), &StorageKey(key)).unwrap();
+		assert_eq!(stored, Some(StorageData(value)));
+	}
+	assert!(net.peers()[0].blockchain_canon_equals(&net.peers()[1]));
+}
+
+#[test]
+fn syncs_state_from_another_peer_after_bad_proof() {
+	let _ = env_logger::try_init();
+	let mut net = TestNet::new(0);
+	net.add_full_peer_with_config(FullPeerConfig { bad_state_proofs: true, ..Default::default() });
+	net.peer(0).push_blocks(30, false);
+	net.peer(0).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+	net.add_full_peer_with_config(FullPeerConfig { sync_mode: SyncMode::State, ..Default::default() });
+
+	// The only peer serves proofs that don't check against the state root of block 20.
+	let bad_peer = net.peer(0).id().to_base58();
+	block_on(futures::future::poll_fn::<(), _>(|cx| {
+		net.poll(cx);
+		let reputation = net.peer(1).network.network_state().peerset["nodes"][&bad_peer]["reputation"]
+			.as_i64()
+			.unwrap_or(0);
+		if reputation < -(1 << 28) {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}));
+	let hash_20 = net.peer(0).client().header(&BlockId::Number(20)).unwrap().unwrap().hash();
+	assert!(!net.peer(1).backend.clone().unwrap().have_state_at(&hash_20, 20));
+
+	// The state is downloaded again from an honest peer.
+	net.add_full_peer();
+	net.peer(2).push_blocks(30, false);
+	net.peer(2).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+
+	net.block_until_sync();
+
+	let hash_30 = net.peer(0).client().header(&BlockId::Number(30)).unwrap().unwrap().hash();
+	let backend = net.peer(1).backend.clone().unwrap();
+	assert!(backend.have_state_at(&hash_20, 20));
+	assert!(backend.have_state_at(&hash_30, 30));
+	assert!(net.peers()[2].blockchain_canon_equals(&net.peers()[1]));
+}
+
+#[test]
+fn stops_syncing_on_unsupported_state() {
+	let _ = env_logger::try_init();
+	// State sync can't import child tries.
+	let mut extra_storage = Storage::default();
+	let child_info = ChildInfo::new_default(b"child");
+	extra_storage.children_default.insert(child_info.storage_key().to_vec(), StorageChild {
+		data: vec![(b"key".to_vec(), b"value".to_vec())].into_iter().collect(),
+		child_info,
+	});
+	let mut net = TestNet::new(0);
+	net.add_full_peer_with_config(FullPeerConfig { extra_storage: extra_storage.clone(), ..Default::default() });
+	net.peer(0).push_blocks(30, false);
+	net.peer(0).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::State,
+		extra_storage,
+		..Default::default()
+	});
+
+	// Poll for a few seconds and make sure 1 doesn't import any block past the state sync target
+	// nor any block body.
+	let mut delay = futures_timer::Delay::new(Duration::from_secs(5));
+	block_on(futures::future::poll_fn::<(), _>(|cx| {
+		net.poll(cx);
+		Pin::new(&mut delay).poll(cx)
+	}));
+	assert_eq!(net.peer(1).client().info().best_number, 19);
+	let client = net.peer(1).client().as_full().unwrap();
+	for number in 1..20 {
+		assert!(client.block_body(&BlockId::Number(number)).unwrap().is_none());
+	}
+	assert!(net.peer(1).client().header(&BlockId::Number(20)).unwrap().is_none());
+}
+
 #[test]
 fn does_not_sync_announced_old_best_block() {
 	let _ = ::env_logger::try_init();
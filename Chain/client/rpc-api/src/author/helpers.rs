@@ -0,0 +1,95 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool inspection types for author RPC module.
+
+use sp_core::Bytes;
+use sp_transaction_pool::{PoolEvictions, TransactionDetails};
+use serde::{Serialize, Deserialize};
+
+/// Status of the transaction pool, with the details of each of its transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus<Hash> {
+	/// Number of transactions in the ready queue.
+	pub ready: usize,
+	/// Sum of ready transaction bytes.
+	pub ready_bytes: usize,
+	/// Number of transactions in the future queue.
+	pub future: usize,
+	/// Sum of future transaction bytes.
+	pub future_bytes: usize,
+	/// Number of transactions evicted so far, by reason.
+	pub evicted: PoolEvictions,
+	/// The transactions in the pool.
+	pub transactions: Vec<PoolTransaction<Hash>>,
+}
+
+/// Queue a transaction is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PoolTransactionState {
+	/// All the tags the transaction requires are provided, it can be included in a block.
+	Ready,
+	/// The transaction waits for some of the tags it requires.
+	Future,
+}
+
+/// A transaction in the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolTransaction<Hash> {
+	/// The hash of the extrinsic.
+	pub hash: Hash,
+	/// Priority of the transaction.
+	pub priority: u64,
+	/// Block number at which the transaction becomes invalid.
+	pub valid_till: u64,
+	/// Tags provided by the transaction.
+	pub provides: Vec<Bytes>,
+	/// Tags required by the transaction.
+	pub requires: Vec<Bytes>,
+	/// Queue the transaction is in.
+	pub state: PoolTransactionState,
+	/// Required tags that no transaction in the pool provides yet.
+	///
+	/// Empty for ready transactions. A future transaction missing the tag of a lower nonce
+	/// stays in the future queue until a transaction with that nonce is submitted.
+	pub missing_tags: Vec<Bytes>,
+	/// Milliseconds since the transaction entered the pool, including the time it spent in the
+	/// future queue before it became ready.
+	pub in_pool_ms: u64,
+}
+
+impl<Hash> From<TransactionDetails<Hash>> for PoolTransaction<Hash> {
+	fn from(details: TransactionDetails<Hash>) -> Self {
+		let tags = |tags: Vec<Vec<u8>>| tags.into_iter().map(Bytes).collect();
+		PoolTransaction {
+			hash: details.hash,
+			priority: details.priority,
+			valid_till: details.valid_till,
+			provides: tags(details.provides),
+			requires: tags(details.requires),
+			state: if details.is_ready {
+				PoolTransactionState::Ready
+			} else {
+				PoolTransactionState::Future
+			},
+			missing_tags: tags(details.missing_tags),
+			in_pool_ms: details.in_pool_for.as_millis() as u64,
+		}
+	}
+}
@@ -20,12 +20,14 @@
 
 pub mod error;
 pub mod hash;
+pub mod helpers;
 
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use sp_core::Bytes;
 use sp_transaction_pool::TransactionStatus;
 use self::error::{FutureResult, Result};
+use self::helpers::{PoolStatus, PoolTransaction};
 
 pub use self::gen_client::Client as AuthorClient;
 
@@ -70,6 +72,21 @@ pub trait AuthorApi<Hash, BlockHash> {
 	#[rpc(name = "author_pendingExtrinsics")]
 	fn pending_extrinsics(&self) -> Result<Vec<Bytes>>;
 
+	/// Returns the number and size of the transactions in the pool, how many were evicted
+	/// so far to keep it within its limits, by reason, and the details of each transaction.
+	///
+	/// This is an unsafe method, the details of a large pool are expensive to collect.
+	#[rpc(name = "author_poolStatus")]
+	fn pool_status(&self) -> Result<PoolStatus<Hash>>;
+
+	/// Returns the details of given extrinsic if it is in the pool.
+	///
+	/// For an extrinsic in the future queue, this includes the tags it still waits for.
+	#[rpc(name = "author_inspectExtrinsic")]
+	fn inspect_extrinsic(&self,
+		bytes_or_hash: hash::ExtrinsicOrHash<Hash>
+	) -> Result<Option<PoolTransaction<Hash>>>;
+
 	/// Remove given extrinsic from the pool and temporarily ban it to prevent reimporting.
 	#[rpc(name = "author_removeExtrinsic")]
 	fn remove_extrinsic(&self,
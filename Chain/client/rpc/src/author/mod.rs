@@ -81,6 +81,19 @@ impl<P, Client> Author<P, Client> {
 	}
 }
 
+impl<P: TransactionPool, Client> Author<P, Client> {
+	/// Hash of the extrinsic given either by its hash or its encoding.
+	fn hash_of(&self, bytes_or_hash: hash::ExtrinsicOrHash<TxHash<P>>) -> Result<TxHash<P>> {
+		match bytes_or_hash {
+			hash::ExtrinsicOrHash::Hash(h) => Ok(h),
+			hash::ExtrinsicOrHash::Extrinsic(bytes) => {
+				let xt = Decode::decode(&mut &bytes[..])?;
+				Ok(self.pool.hash_of(&xt))
+			},
+		}
+	}
+}
+
 /// Currently we treat all RPC transactions as externals.
 ///
 /// Possibly in the future we could allow opt-in for special treatment
@@ -160,6 +173,29 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 		Ok(self.pool.ready().map(|tx| tx.data().encode().into()).collect())
 	}
 
+	fn pool_status(&self) -> Result<helpers::PoolStatus<TxHash<P>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let status = self.pool.status();
+		Ok(helpers::PoolStatus {
+			ready: status.ready,
+			ready_bytes: status.ready_bytes,
+			future: status.future,
+			future_bytes: status.future_bytes,
+			evicted: status.evicted,
+			transactions: self.pool.inspect().into_iter().map(Into::into).collect(),
+		})
+	}
+
+	fn inspect_extrinsic(
+		&self,
+		bytes_or_hash: hash::ExtrinsicOrHash<TxHash<P>>,
+	) -> Result<Option<helpers::PoolTransaction<TxHash<P>>>> {
+		let hash = self.hash_of(bytes_or_hash)?;
+
+		Ok(self.pool.inspect_by_hash(&hash).map(Into::into))
+	}
+
 	fn remove_extrinsic(
 		&self,
 		bytes_or_hash: Vec<hash::ExtrinsicOrHash<TxHash<P>>>,
@@ -167,13 +203,7 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 		self.deny_unsafe.check_if_safe()?;
 
 		let hashes = bytes_or_hash.into_iter()
-			.map(|x| match x {
-				hash::ExtrinsicOrHash::Hash(h) => Ok(h),
-				hash::ExtrinsicOrHash::Extrinsic(bytes) => {
-					let xt = Decode::decode(&mut &bytes[..])?;
-					Ok(self.pool.hash_of(&xt))
-				},
-			})
+			.map(|x| self.hash_of(x))
 			.collect::<Result<Vec<_>>>()?;
 
 		Ok(
@@ -199,6 +199,56 @@ fn should_return_pending_extrinsics() {
 	);
 }
 
+#[test]
+fn should_return_pool_status() {
+	let p = TestSetup::default().author();
+
+	let ex = uxt(AccountKeyring::Alice, 0);
+	AuthorApi::submit_extrinsic(&p, ex.encode().into()).wait().unwrap();
+	let status = p.pool_status().unwrap();
+	assert_eq!(status.ready, 1);
+	assert_eq!(status.future, 0);
+	assert_eq!(status.evicted.total(), 0);
+	assert_eq!(status.transactions.len(), 1);
+	assert_eq!(status.transactions[0].state, helpers::PoolTransactionState::Ready);
+}
+
+#[test]
+fn should_deny_pool_status_if_unsafe() {
+	let p = Author {
+		deny_unsafe: DenyUnsafe::Yes,
+		..TestSetup::default().author()
+	};
+
+	assert_matches!(p.pool_status(), Err(Error::UnsafeRpcCalled(_)));
+}
+
+#[test]
+fn should_inspect_extrinsic_waiting_for_nonce() {
+	let p = TestSetup::default().author();
+
+	let ex = uxt(AccountKeyring::Alice, 1);
+	let hash = AuthorApi::submit_extrinsic(&p, ex.encode().into()).wait().unwrap();
+
+	// the extrinsic waits for nonce 0
+	let tx = p.inspect_extrinsic(hash::ExtrinsicOrHash::Hash(hash)).unwrap().unwrap();
+	let nonce_tag: Bytes = (sr25519::Public::from(AccountKeyring::Alice), 0u64).encode().into();
+	assert_eq!(tx.hash, hash);
+	assert_eq!(tx.state, helpers::PoolTransactionState::Future);
+	assert_eq!(tx.requires, vec![nonce_tag.clone()]);
+	assert_eq!(tx.missing_tags, vec![nonce_tag]);
+
+	// until it is submitted
+	AuthorApi::submit_extrinsic(&p, uxt(AccountKeyring::Alice, 0).encode().into()).wait().unwrap();
+	let tx = p.inspect_extrinsic(hash::ExtrinsicOrHash::Extrinsic(ex.encode().into()))
+		.unwrap()
+		.unwrap();
+	assert_eq!(tx.state, helpers::PoolTransactionState::Ready);
+	assert!(tx.missing_tags.is_empty());
+
+	assert_eq!(p.inspect_extrinsic(hash::ExtrinsicOrHash::Hash(Default::default())).unwrap(), None);
+}
+
 #[test]
 fn should_remove_extrinsics() {
 	let setup = TestSetup::default();
@@ -35,11 +35,10 @@ use sp_consensus::{
 };
 use futures::{FutureExt, StreamExt, future::ready};
 use jsonrpc_pubsub::manager::SubscriptionManager;
-use sc_keystore::Store as Keystore;
+use sc_keystore::{Store as Keystore, KeyStorePtr, policy::{PolicyStore, SigningPolicy, AllowedKeyTypes}};
 use log::{info, warn, error};
 use sc_network::config::{Role, FinalityProofProvider, OnDemand, BoxFinalityProofRequestBuilder};
 use sc_network::NetworkService;
-use parking_lot::RwLock;
 use sp_runtime::generic::BlockId;
 use sp_runtime::traits::{
 	Block as BlockT, SaturatedConversion, HashFor, Zero, BlockIdTo,
@@ -52,7 +51,7 @@ use sc_telemetry::{telemetry, SUBSTRATE_INFO};
 use sp_transaction_pool::MaintainedTransactionPool;
 use prometheus_endpoint::Registry;
 use sc_client_db::{Backend, DatabaseSettings};
-use sp_core::traits::{CodeExecutor, SpawnNamed};
+use sp_core::traits::{BareCryptoStorePtr, CodeExecutor, SpawnNamed};
 use sp_runtime::BuildStorage;
 use sc_client_api::{
 	BlockBackend, BlockchainEvents,
@@ -157,14 +156,14 @@ pub type TLightCallExecutor<TBl, TExecDisp> = sc_light::GenesisCallExecutor<
 type TFullParts<TBl, TRtApi, TExecDisp> = (
 	TFullClient<TBl, TRtApi, TExecDisp>,
 	Arc<TFullBackend<TBl>>,
-	Arc<RwLock<sc_keystore::Store>>,
+	KeystoreContainer,
 	TaskManager,
 );
 
 type TLightParts<TBl, TRtApi, TExecDisp> = (
 	Arc<TLightClient<TBl, TRtApi, TExecDisp>>,
 	Arc<TLightBackend<TBl>>,
-	Arc<RwLock<sc_keystore::Store>>,
+	KeystoreContainer,
 	TaskManager,
 	Arc<OnDemand<TBl>>,
 );
@@ -186,6 +185,69 @@ pub type TLightClientWithBackend<TBl, TRtApi, TExecDisp, TBackend> = Client<
 	TRtApi,
 >;
 
+/// The keystore of a node, along with the local keystore if the keys are not held remotely.
+pub struct KeystoreContainer {
+	keystore: BareCryptoStorePtr,
+	local: Option<KeyStorePtr>,
+}
+
+impl KeystoreContainer {
+	/// Open the keystore of the given config.
+	pub fn new(config: &KeystoreConfig) -> Result<Self, Error> {
+		let local = match config {
+			KeystoreConfig::Path { path, password } => Keystore::open(
+				path.clone(),
+				password.clone()
+			)?,
+			KeystoreConfig::InMemory => Keystore::new_in_memory(),
+			#[cfg(unix)]
+			KeystoreConfig::Remote { socket } => {
+				info!("Using the remote signer at {}", socket.display());
+				let remote = sc_keystore::remote::RemoteStore::new(socket.clone());
+				return Ok(KeystoreContainer {
+					keystore: Arc::new(parking_lot::RwLock::new(remote)),
+					local: None,
+				});
+			},
+			#[cfg(not(unix))]
+			KeystoreConfig::Remote { .. } => {
+				return Err("Remote signers are only supported on Unix".into());
+			},
+		};
+
+		Ok(KeystoreContainer { keystore: local.clone(), local: Some(local) })
+	}
+
+	/// Open the keystore of the given node config, only signing with the allowed key types.
+	pub fn for_config(config: &Configuration) -> Result<Self, Error> {
+		let container = Self::new(&config.keystore)?;
+		Ok(match &config.keystore_allowed_key_types {
+			Some(key_types) => container.with_signing_policy(AllowedKeyTypes::new(key_types.clone())),
+			None => container,
+		})
+	}
+
+	/// Enforce `policy` on the signatures made through `keystore`.
+	///
+	/// The local keystore is left as is.
+	pub fn with_signing_policy(mut self, policy: impl SigningPolicy + 'static) -> Self {
+		self.keystore = PolicyStore::new(self.keystore, policy);
+		self
+	}
+
+	/// The keystore, whether local or remote.
+	pub fn keystore(&self) -> BareCryptoStorePtr {
+		self.keystore.clone()
+	}
+
+	/// The local keystore, `None` if the keys are held by a remote signer.
+	///
+	/// Only to be used by the components that need direct access to the key pairs.
+	pub fn local_keystore(&self) -> Option<KeyStorePtr> {
+		self.local.clone()
+	}
+}
+
 /// Creates a new full client for the given config.
 pub fn new_full_client<TBl, TRtApi, TExecDisp>(
 	config: &Configuration,
@@ -203,13 +265,7 @@ pub fn new_full_parts<TBl, TRtApi, TExecDisp>(
 	TBl: BlockT,
 	TExecDisp: NativeExecutionDispatch + 'static,
 {
-	let keystore = match &config.keystore {
-		KeystoreConfig::Path { path, password } => Keystore::open(
-			path.clone(),
-			password.clone()
-		)?,
-		KeystoreConfig::InMemory => Keystore::new_in_memory(),
-	};
+	let keystore_container = KeystoreContainer::for_config(config)?;
 
 	let task_manager = {
 		let registry = config.prometheus_config.as_ref().map(|cfg| &cfg.registry);
@@ -242,7 +298,7 @@ pub fn new_full_parts<TBl, TRtApi, TExecDisp>(
 
 		let extensions = sc_client_api::execution_extensions::ExecutionExtensions::new(
 			config.execution_strategies.clone(),
-			Some(keystore.clone()),
+			Some(keystore_container.keystore()),
 		);
 
 		new_client(
@@ -261,7 +317,7 @@ pub fn new_full_parts<TBl, TRtApi, TExecDisp>(
 		)?
 	};
 
-	Ok((client, backend, keystore, task_manager))
+	Ok((client, backend, keystore_container, task_manager))
 }
 
 /// Create the initial parts of a light node.
@@ -277,13 +333,7 @@ pub fn new_light_parts<TBl, TRtApi, TExecDisp>(
 		TaskManager::new(config.task_executor.clone(), registry)?
 	};
 
-	let keystore = match &config.keystore {
-		KeystoreConfig::Path { path, password } => Keystore::open(
-			path.clone(),
-			password.clone()
-		)?,
-		KeystoreConfig::InMemory => Keystore::new_in_memory(),
-	};
+	let keystore_container = KeystoreContainer::for_config(config)?;
 
 	let executor = NativeExecutor::<TExecDisp>::new(
 		config.wasm_method,
@@ -319,7 +369,7 @@ pub fn new_light_parts<TBl, TRtApi, TExecDisp>(
 		config.prometheus_config.as_ref().map(|config| config.registry.clone()),
 	)?);
 
-	Ok((client, backend, keystore, task_manager, on_demand))
+	Ok((client, backend, keystore_container, task_manager, on_demand))
 }
 
 /// Create an instance of db-backed client.
@@ -377,8 +427,8 @@ pub struct SpawnTasksParams<'a, TBl: BlockT, TCl, TExPool, TRpc, Backend> {
 	pub backend: Arc<Backend>,
 	/// A task manager returned by `new_full_parts`/`new_light_parts`.
 	pub task_manager: &'a mut TaskManager,
-	/// A shared keystore returned by `new_full_parts`/`new_light_parts`.
-	pub keystore: Arc<RwLock<Keystore>>,
+	/// The keystore of the container returned by `new_full_parts`/`new_light_parts`.
+	pub keystore: BareCryptoStorePtr,
 	/// An optional, shared data fetcher for light clients.
 	pub on_demand: Option<Arc<OnDemand<TBl>>>,
 	/// A shared transaction pool.
@@ -704,7 +754,7 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 	spawn_handle: SpawnTaskHandle,
 	client: Arc<TCl>,
 	transaction_pool: Arc<TExPool>,
-	keystore: Arc<RwLock<Keystore>>,
+	keystore: BareCryptoStorePtr,
 	on_demand: Option<Arc<OnDemand<TBl>>>,
 	remote_blockchain: Option<Arc<dyn RemoteBlockchain<TBl>>>,
 	rpc_extensions_builder: &(dyn RpcExtensionBuilder<Output = TRpc> + Send),
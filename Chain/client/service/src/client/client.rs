@@ -29,7 +29,7 @@ use codec::{Encode, Decode};
 use hash_db::Prefix;
 use sp_core::{
 	convert_hash,
-	storage::{well_known_keys, ChildInfo, PrefixedStorageKey, StorageData, StorageKey},
+	storage::{well_known_keys, ChildInfo, PrefixedStorageKey, Storage, StorageData, StorageKey},
 	ChangesTrieConfiguration, ExecutionContext, NativeOrEncoded,
 };
 use sc_telemetry::{telemetry, SUBSTRATE_INFO};
@@ -43,13 +43,13 @@ use sp_runtime::{
 };
 use sp_state_machine::{
 	DBValue, Backend as StateBackend, ChangesTrieAnchorBlockId,
-	prove_read, prove_child_read, ChangesTrieRootsStorage, ChangesTrieStorage,
-	ChangesTrieConfigurationRange, key_changes, key_changes_proof,
+	prove_read, prove_child_read, prove_range_read_with_size, ChangesTrieRootsStorage,
+	ChangesTrieStorage, ChangesTrieConfigurationRange, key_changes, key_changes_proof,
 };
 use sc_executor::RuntimeVersion;
 use sp_consensus::{
 	Error as ConsensusError, BlockStatus, BlockImportParams, BlockCheckParams,
-	ImportResult, BlockOrigin, ForkChoiceStrategy, RecordProof,
+	ImportResult, ImportedState, BlockOrigin, ForkChoiceStrategy, RecordProof,
 };
 use sp_blockchain::{
 	self as blockchain,
@@ -613,6 +613,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			fork_choice,
 			intermediates,
 			import_existing,
+			imported_state,
 			..
 		} = import_block;
 
@@ -652,6 +653,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			auxiliary,
 			fork_choice,
 			import_existing,
+			imported_state,
 		);
 
 		if let Ok(ImportResult::Imported(ref aux)) = result {
@@ -688,6 +690,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		aux: Vec<(Vec<u8>, Option<Vec<u8>>)>,
 		fork_choice: ForkChoiceStrategy,
 		import_existing: bool,
+		imported_state: Option<ImportedState<Block>>,
 	) -> sp_blockchain::Result<ImportResult> where
 		Self: ProvideRuntimeApi<Block>,
 		<Self as ProvideRuntimeApi<Block>>::Api: CoreApi<Block, Error = Error> +
@@ -761,6 +764,34 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			None => None,
 		};
 
+		if let Some(imported_state) = imported_state {
+			if imported_state.block != hash {
+				return Err(Error::Msg(format!(
+					"Imported state of {:?} doesn't belong to {:?}",
+					imported_state.block,
+					hash,
+				)));
+			}
+
+			let state_root = operation.op.reset_storage(Storage {
+				top: imported_state.state.into_iter().collect(),
+				children_default: Default::default(),
+			})?;
+			if state_root != *import_headers.post().state_root() {
+				return Err(Error::InvalidStateRoot);
+			}
+
+			if finalized {
+				self.apply_finality_with_block_hash(
+					operation,
+					parent_hash,
+					None,
+					info.best_hash,
+					make_notifications,
+				)?;
+			}
+		}
+
 		let is_new_best = finalized || match fork_choice {
 			ForkChoiceStrategy::LongestChain => import_headers.post().number() > &info.best_number,
 			ForkChoiceStrategy::Custom(v) => v,
@@ -839,11 +870,18 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		let enact_state = match self.block_status(&at)? {
 			BlockStatus::Unknown => return Ok(Some(ImportResult::UnknownParent)),
 			BlockStatus::InChainWithState | BlockStatus::Queued => true,
-			BlockStatus::InChainPruned if import_block.allow_missing_state => false,
+			BlockStatus::InChainPruned
+				if import_block.allow_missing_state || import_block.imported_state.is_some() => false,
 			BlockStatus::InChainPruned => return Ok(Some(ImportResult::MissingState)),
 			BlockStatus::KnownBad => return Ok(Some(ImportResult::KnownBad)),
 		};
 
+		// the state is imported whole, there's no block to execute.
+		if import_block.imported_state.is_some() {
+			import_block.storage_changes = None;
+			return Ok(None)
+		}
+
 		match (enact_state, &mut import_block.storage_changes, &mut import_block.body) {
 			// We have storage changes and should enact the state, so we don't need to do anything
 			// here
@@ -1193,6 +1231,17 @@ impl<B, E, Block, RA> ProofProvider<Block> for Client<B, E, Block, RA> where
 				.map_err(Into::into))
 	}
 
+	fn read_range_proof(
+		&self,
+		id: &BlockId<Block>,
+		start_key: Option<&[u8]>,
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)> {
+		self.state_at(id)
+			.and_then(|state| prove_range_read_with_size(state, size_limit, start_key)
+				.map_err(Into::into))
+	}
+
 	fn execution_proof(
 		&self,
 		id: &BlockId<Block>,
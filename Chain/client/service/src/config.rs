@@ -22,6 +22,7 @@ pub use sc_client_db::{Database, PruningMode, DatabaseSettingsSrc as DatabaseCon
 pub use sc_network::Multiaddr;
 pub use sc_network::config::{ExtTransport, MultiaddrWithPeerId, NetworkConfiguration, Role, NodeKeyConfig};
 pub use sc_executor::WasmExecutionMethod;
+pub use sp_core::crypto::KeyTypeId;
 use sc_client_api::execution_extensions::ExecutionStrategies;
 
 use std::{io, future::Future, path::{PathBuf, Path}, pin::Pin, net::SocketAddr, sync::Arc};
@@ -50,6 +51,8 @@ pub struct Configuration {
 	pub network: NetworkConfiguration,
 	/// Configuration for the keystore.
 	pub keystore: KeystoreConfig,
+	/// Key types the keystore may sign with, `None` for any.
+	pub keystore_allowed_key_types: Option<Vec<KeyTypeId>>,
 	/// Configuration for the database.
 	pub database: DatabaseConfig,
 	/// Size of internal state cache in Bytes
@@ -134,6 +137,11 @@ pub enum KeystoreConfig {
 	},
 	/// In-memory keystore. Recommended for in-browser nodes.
 	InMemory,
+	/// Keys held by a separate signing process. Only supported on Unix.
+	Remote {
+		/// The path of the Unix socket the signer listens on.
+		socket: PathBuf,
+	},
 }
 
 impl KeystoreConfig {
@@ -141,7 +149,7 @@ impl KeystoreConfig {
 	pub fn path(&self) -> Option<&Path> {
 		match self {
 			Self::Path { path, .. } => Some(path),
-			Self::InMemory => None,
+			Self::InMemory | Self::Remote { .. } => None,
 		}
 	}
 }
@@ -186,6 +194,15 @@ impl Configuration {
 	pub fn prometheus_registry<'a>(&'a self) -> Option<&'a Registry> {
 		self.prometheus_config.as_ref().map(|config| &config.registry)
 	}
+
+	/// Returns the file the transactions of the pool are kept in across restarts, if the pool
+	/// should be persisted and the database is on disk.
+	pub fn transaction_pool_path(&self) -> Option<PathBuf> {
+		if !self.transaction_pool.persist {
+			return None;
+		}
+		self.database.path().map(|path| path.with_file_name("txpool"))
+	}
 }
 
 /// Available RPC methods.
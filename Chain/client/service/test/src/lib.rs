@@ -239,6 +239,7 @@ fn node_config<G: RuntimeGenesis + 'static, E: ChainSpecExtension + Clone + 'sta
 			path: root.join("key"),
 			password: None
 		},
+		keystore_allowed_key_types: None,
 		database: DatabaseConfig::RocksDb {
 			path: root.join("db"),
 			cache_size: 128,
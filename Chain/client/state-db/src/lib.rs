@@ -274,6 +274,28 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 		}
 	}
 
+	fn import_block<E: fmt::Debug>(
+		&mut self,
+		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
+	) -> Result<CommitSet<Key>, Error<E>> {
+		if number == 0 {
+			return Err(Error::InvalidBlockNumber);
+		}
+		let mut commit = match self.mode {
+			PruningMode::ArchiveAll => CommitSet::default(),
+			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical =>
+				self.non_canonical.reset_canonicalized(parent_hash, number - 1)?,
+		};
+		let block = self.insert_block(hash, number, parent_hash, changeset)?;
+		commit.data = block.data;
+		commit.meta.inserted.extend(block.meta.inserted);
+		commit.meta.deleted.extend(block.meta.deleted);
+		Ok(commit)
+	}
+
 	fn canonicalize_block<E: fmt::Debug>(
 		&mut self,
 		hash: &BlockHash,
@@ -463,6 +485,19 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 		self.db.write().insert_block(hash, number, parent_hash, changeset)
 	}
 
+	/// Add a new non-canonical block whose state was imported whole instead of built on its
+	/// parent's. The blocks before it are considered canonicalized, there must be no other
+	/// non-canonical blocks.
+	pub fn import_block<E: fmt::Debug>(
+		&self,
+		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
+	) -> Result<CommitSet<Key>, Error<E>> {
+		self.db.write().import_block(hash, number, parent_hash, changeset)
+	}
+
 	/// Finalize a previously inserted block.
 	pub fn canonicalize_block<E: fmt::Debug>(
 		&self,
@@ -597,6 +632,69 @@ mod tests {
 		(db, state_db)
 	}
 
+	#[test]
+	fn import_block_after_gap() {
+		let mut db = make_db(&[]);
+		let state_db = StateDb::new(PruningMode::ArchiveCanonical, false, &db).unwrap();
+		db.commit(
+			&state_db
+				.insert_block::<io::Error>(
+					&H256::from_low_u64_be(0),
+					0,
+					&H256::default(),
+					make_changeset(&[90], &[]),
+				)
+				.unwrap(),
+		);
+		state_db.apply_pending();
+		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(0)).unwrap());
+		state_db.apply_pending();
+
+		// blocks 1 to 4 were imported without state
+		assert!(state_db
+			.insert_block::<io::Error>(
+				&H256::from_low_u64_be(5),
+				5,
+				&H256::from_low_u64_be(4),
+				make_changeset(&[95], &[]),
+			)
+			.is_err());
+		db.commit(
+			&state_db
+				.import_block::<io::Error>(
+					&H256::from_low_u64_be(5),
+					5,
+					&H256::from_low_u64_be(4),
+					make_changeset(&[95], &[]),
+				)
+				.unwrap(),
+		);
+		state_db.apply_pending();
+		assert_eq!(state_db.best_canonical(), Some(4));
+		assert!(!state_db.is_pruned(&H256::from_low_u64_be(5), 5));
+		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(5)).unwrap());
+		state_db.apply_pending();
+		assert_eq!(state_db.best_canonical(), Some(5));
+
+		db.commit(
+			&state_db
+				.insert_block::<io::Error>(
+					&H256::from_low_u64_be(6),
+					6,
+					&H256::from_low_u64_be(5),
+					make_changeset(&[96], &[]),
+				)
+				.unwrap(),
+		);
+		state_db.apply_pending();
+		assert!(db.data_eq(&make_db(&[90, 95])));
+
+		// the state of the new block reopened the overlay
+		let reopened = StateDb::<H256, H256>::new(PruningMode::ArchiveCanonical, false, &db).unwrap();
+		assert_eq!(reopened.best_canonical(), Some(5));
+		assert!(!reopened.is_pruned(&H256::from_low_u64_be(6), 6));
+	}
+
 	#[test]
 	fn full_archive_keeps_everything() {
 		let (db, sdb) = make_test_db(PruningMode::ArchiveAll);
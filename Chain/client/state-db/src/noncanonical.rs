@@ -206,6 +206,19 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		})
 	}
 
+	/// Restart the overlay after `hash`, as if it was the last canonicalized block. Expects no
+	/// blocks in the overlay.
+	pub fn reset_canonicalized<E: fmt::Debug>(&mut self, hash: &BlockHash, number: u64) -> Result<CommitSet<Key>, Error<E>> {
+		if !self.levels.is_empty() || !self.pending_canonicalizations.is_empty() {
+			return Err(Error::InvalidBlockNumber);
+		}
+		let mut commit = CommitSet::default();
+		let last_canonicalized = (hash.clone(), number);
+		commit.meta.inserted.push((to_meta_key(LAST_CANONICAL, &()), last_canonicalized.encode()));
+		self.last_canonicalized = Some(last_canonicalized);
+		Ok(commit)
+	}
+
 	/// Insert a new block into the overlay. If inserted on the second level or lover expects parent to be present in the window.
 	pub fn insert<E: fmt::Debug>(&mut self, hash: &BlockHash, number: u64, parent_hash: &BlockHash, changeset: ChangeSet<Key>) -> Result<CommitSet<Key>, Error<E>> {
 		let mut commit = CommitSet::default();
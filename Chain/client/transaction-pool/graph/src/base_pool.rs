@@ -21,7 +21,7 @@
 //! For a more full-featured pool, have a look at the `pool` module.
 
 use std::{
-	collections::HashSet,
+	collections::{HashMap, HashSet},
 	fmt,
 	hash,
 	sync::Arc,
@@ -37,10 +37,11 @@ use sp_runtime::transaction_validity::{
 	TransactionPriority as Priority,
 	TransactionSource as Source,
 };
-use sp_transaction_pool::{error, PoolStatus, InPoolTransaction};
+use sp_transaction_pool::{error, PoolStatus, PoolEvictions, InPoolTransaction, TransactionDetails};
+use wasm_timer::Instant;
 
 use crate::future::{FutureTransactions, WaitingTransaction};
-use crate::ready::ReadyTransactions;
+use crate::ready::{ReadyTransactions, TransactionRef};
 
 /// Successful import result.
 #[derive(Debug, PartialEq, Eq)]
@@ -228,23 +229,45 @@ pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	/// transactions to future in case they were just stuck in verification.
 	recently_pruned: [HashSet<Tag>; RECENTLY_PRUNED_TAGS],
 	recently_pruned_index: usize,
+	/// Number of transactions evicted by `enforce_limits`.
+	#[cfg_attr(not(target_os = "unknown"), ignore_malloc_size_of = "no heap allocation")]
+	evicted: PoolEvictions,
+	/// Limits of the transactions of a single sender, if any.
+	#[cfg_attr(not(target_os = "unknown"), ignore_malloc_size_of = "insignificant")]
+	sender_limit: Option<SenderLimit>,
+	/// Usage of every sender with transactions in the pool, kept only if `sender_limit` is set.
+	senders: HashMap<Vec<u8>, SenderUsage>,
+	/// Senders whose usage exceeds `sender_limit`.
+	senders_exceeding: HashSet<Vec<u8>>,
+}
+
+/// Number of transactions and bytes of a single sender across both queues.
+#[derive(Debug, Default)]
+#[cfg_attr(not(target_os = "unknown"), derive(parity_util_mem::MallocSizeOf))]
+struct SenderUsage {
+	count: usize,
+	bytes: usize,
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for BasePool<Hash, Ex> {
 	fn default() -> Self {
-		Self::new(false)
+		Self::new(false, None)
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash, Ex> {
-	/// Create new pool given reject_future_transactions flag.
-	pub fn new(reject_future_transactions: bool) -> Self {
+	/// Create new pool given reject_future_transactions flag and the limits of a single sender.
+	pub fn new(reject_future_transactions: bool, sender_limit: Option<SenderLimit>) -> Self {
 		BasePool {
 			reject_future_transactions,
 			future: Default::default(),
 			ready: Default::default(),
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
+			evicted: Default::default(),
+			sender_limit,
+			senders: Default::default(),
+			senders_exceeding: Default::default(),
 		}
 	}
 
@@ -301,6 +324,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			}
 
 			let hash = tx.transaction.hash.clone();
+			self.track_sender(&tx.transaction, true);
 			self.future.import(tx);
 			return Ok(Imported::Future { hash });
 		}
@@ -310,7 +334,8 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 
 	/// Imports transaction to ready queue.
 	///
-	/// NOTE the transaction has to have all requirements satisfied.
+	/// NOTE the transaction has to have all requirements satisfied, and is not counted in the
+	/// usage of its sender yet, unlike the future transactions it promotes.
 	fn import_to_ready(&mut self, tx: WaitingTransaction<Hash, Ex>) -> error::Result<Imported<Hash, Ex>> {
 		let hash = tx.transaction.hash.clone();
 		let mut promoted = vec![];
@@ -332,12 +357,18 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 
 			// import this transaction
 			let current_hash = tx.transaction.hash.clone();
+			let transaction = tx.transaction.clone();
 			match self.ready.import(tx) {
 				Ok(mut replaced) => {
-					if !first {
+					if first {
+						self.track_sender(&transaction, true);
+					} else {
 						promoted.push(current_hash);
 					}
 					// The transactions were removed from the ready pool. We might attempt to re-import them.
+					for tx in &replaced {
+						self.track_sender(tx, false);
+					}
 					removed.append(&mut replaced);
 				},
 				// transaction failed to be imported.
@@ -345,6 +376,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 					debug!(target: "txpool", "[{:?}] Error importing: {:?}", current_hash, e);
 					return Err(e)
 				} else {
+					self.track_sender(&transaction, false);
 					failed.push(current_hash);
 				},
 			}
@@ -359,7 +391,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		if removed.iter().any(|tx| tx.hash == hash) {
 			// We still need to remove all transactions that we promoted
 			// since they depend on each other and will never get to the best iterator.
-			self.ready.remove_subtree(&promoted);
+			for tx in self.ready.remove_subtree(&promoted) {
+				self.track_sender(&tx, false);
+			}
 
 			debug!(target: "txpool", "[{:?}] Cycle detected, bailing.", hash);
 			return Err(error::Error::CycleDetected)
@@ -383,6 +417,23 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.future.all()
 	}
 
+	/// Returns the details of every transaction in the pool, ready and future.
+	pub fn inspect(&self) -> Vec<TransactionDetails<Hash>> {
+		let now = Instant::now();
+		let ready = self.ready.all().into_iter().map(|tx| ready_details(&tx, now));
+		let future = self.future.waiting().map(|tx| future_details(tx, now));
+
+		ready.chain(future).collect()
+	}
+
+	/// Returns the details of the transaction with given hash, if it is in the pool.
+	pub fn inspect_by_hash(&self, hash: &Hash) -> Option<TransactionDetails<Hash>> {
+		let now = Instant::now();
+		self.ready.ref_by_hash(hash)
+			.map(|tx| ready_details(&tx, now))
+			.or_else(|| self.future.waiting_by_hash(hash).map(|tx| future_details(tx, now)))
+	}
+
 	/// Returns pool transactions given list of hashes.
 	///
 	/// Includes both ready and future pool. For every hash in the `hashes`
@@ -407,10 +458,61 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	///
 	/// Removes and returns worst transactions from the queues and all transactions that depend on them.
 	/// Technically the worst transaction should be evaluated by computing the entire pending set.
-	/// We use a simplified approach to remove the transaction that occupies the pool for the longest time.
-	pub fn enforce_limits(&mut self, ready: &Limit, future: &Limit) -> Vec<Arc<Transaction<Hash, Ex>>> {
+	/// We use a simplified approach instead:
+	/// - a sender exceeding its limits loses its most recent transactions first, from the future
+	///   queue before the ready one, so that it can't push out the transactions of others;
+	/// - a queue exceeding its limits loses its lowest priority transactions first, and among
+	///   those the one that occupies the pool for the longest time.
+	pub fn enforce_limits(
+		&mut self,
+		ready: &Limit,
+		future: &Limit,
+	) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = vec![];
 
+		if let Some(sender) = self.sender_limit.clone() {
+			while let Some(exceeding) = self.senders_exceeding.iter().next().cloned() {
+				let is_exceeding = |tx: &Transaction<Hash, Ex>| {
+					sender.sender_of(tx) == Some(exceeding.as_slice())
+				};
+				// find the most recent transaction of the sender
+				let newest_future = self.future
+					.fold(|newest: Option<WaitingTransaction<Hash, Ex>>, current| {
+						if !is_exceeding(&current.transaction) {
+							return newest
+						}
+						match newest {
+							Some(ref tx) if tx.imported_at > current.imported_at => newest,
+							_ => Some(current.clone()),
+						}
+					})
+					.map(|tx| tx.transaction.hash.clone());
+				let newest = match newest_future {
+					Some(hash) => Some(hash),
+					None => self.ready
+						.fold(|newest: Option<TransactionRef<Hash, Ex>>, current| {
+							let transaction = &current.transaction;
+							if !is_exceeding(&transaction.transaction) {
+								return newest
+							}
+							match newest {
+								Some(ref tx) if tx.insertion_id > transaction.insertion_id => newest,
+								_ => Some(transaction.clone()),
+							}
+						})
+						.map(|tx| tx.transaction.hash.clone()),
+				};
+
+				if let Some(newest) = newest {
+					let mut evicted = self.remove_subtree(&[newest]);
+					self.evicted.sender_limit += evicted.len() as u64;
+					removed.append(&mut evicted);
+				} else {
+					break;
+				}
+			}
+		}
+
 		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
 			// find the worst transaction
 			let minimal = self.ready
@@ -418,7 +520,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 					let transaction = &current.transaction;
 					match minimal {
 						None => Some(transaction.clone()),
-						Some(ref tx) if tx.insertion_id > transaction.insertion_id => {
+						Some(ref tx) if (tx.transaction.priority, tx.insertion_id)
+							> (transaction.transaction.priority, transaction.insertion_id) =>
+						{
 							Some(transaction.clone())
 						},
 						other => other,
@@ -426,7 +530,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 				});
 
 			if let Some(minimal) = minimal {
-				removed.append(&mut self.remove_subtree(&[minimal.transaction.hash.clone()]))
+				let mut evicted = self.remove_subtree(&[minimal.transaction.hash.clone()]);
+				self.evicted.ready_limit += evicted.len() as u64;
+				removed.append(&mut evicted);
 			} else {
 				break;
 			}
@@ -438,7 +544,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 				.fold(|minimal, current| {
 					match minimal {
 						None => Some(current.clone()),
-						Some(ref tx) if tx.imported_at > current.imported_at => {
+						Some(ref tx) if (tx.transaction.priority, tx.imported_at)
+							> (current.transaction.priority, current.imported_at) =>
+						{
 							Some(current.clone())
 						},
 						other => other,
@@ -446,7 +554,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 				});
 
 			if let Some(minimal) = minimal {
-				removed.append(&mut self.remove_subtree(&[minimal.transaction.hash.clone()]))
+				let mut evicted = self.remove_subtree(&[minimal.transaction.hash.clone()]);
+				self.evicted.future_limit += evicted.len() as u64;
+				removed.append(&mut evicted);
 			} else {
 				break;
 			}
@@ -455,6 +565,41 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		removed
 	}
 
+	/// Returns true if the transactions of any sender exceed the sender limits.
+	pub fn is_sender_limit_exceeded(&self) -> bool {
+		!self.senders_exceeding.is_empty()
+	}
+
+	/// Updates the usage of the sender of `tx` as the transaction enters or leaves the pool.
+	fn track_sender(&mut self, tx: &Transaction<Hash, Ex>, entered: bool) {
+		let limit = match self.sender_limit {
+			Some(ref limit) => limit,
+			None => return,
+		};
+		let sender = match limit.sender_of(tx) {
+			Some(sender) => sender,
+			None => return,
+		};
+
+		let usage = self.senders.entry(sender.to_vec()).or_default();
+		if entered {
+			usage.count += 1;
+			usage.bytes += tx.bytes;
+		} else {
+			usage.count = usage.count.saturating_sub(1);
+			usage.bytes = usage.bytes.saturating_sub(tx.bytes);
+		}
+
+		if limit.limit.is_exceeded(usage.count, usage.bytes) {
+			self.senders_exceeding.insert(sender.to_vec());
+		} else {
+			if usage.count == 0 {
+				self.senders.remove(sender);
+			}
+			self.senders_exceeding.remove(sender);
+		}
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -466,12 +611,19 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	pub fn remove_subtree(&mut self, hashes: &[Hash]) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = self.ready.remove_subtree(hashes);
 		removed.extend(self.future.remove(hashes));
+		for tx in &removed {
+			self.track_sender(tx, false);
+		}
 		removed
 	}
 
 	/// Removes and returns all transactions from the future queue.
 	pub fn clear_future(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
-		self.future.clear()
+		let removed = self.future.clear();
+		for tx in &removed {
+			self.track_sender(tx, false);
+		}
+		removed
 	}
 
 	/// Prunes transactions that provide given list of tags.
@@ -496,6 +648,14 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			recently_pruned.insert(tag);
 		}
 
+		// the promoted transactions are counted again once they make it to the ready queue
+		for tx in &pruned {
+			self.track_sender(tx, false);
+		}
+		for tx in &to_import {
+			self.track_sender(&tx.transaction, false);
+		}
+
 		let mut promoted = vec![];
 		let mut failed = vec![];
 		for tx in to_import {
@@ -523,10 +683,41 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			ready_bytes: self.ready.bytes(),
 			future: self.future.len(),
 			future_bytes: self.future.bytes(),
+			evicted: self.evicted,
 		}
 	}
 }
 
+fn details<Hash: Clone, Ex>(
+	tx: &Transaction<Hash, Ex>,
+	is_ready: bool,
+	missing_tags: Vec<Tag>,
+	imported_at: Instant,
+	now: Instant,
+) -> TransactionDetails<Hash> {
+	TransactionDetails {
+		hash: tx.hash.clone(),
+		priority: tx.priority,
+		valid_till: tx.valid_till,
+		provides: tx.provides.clone(),
+		requires: tx.requires.clone(),
+		is_ready,
+		missing_tags,
+		source: tx.source,
+		in_pool_for: now.duration_since(imported_at),
+	}
+}
+
+fn ready_details<Hash: Clone, Ex>(tx: &TransactionRef<Hash, Ex>, now: Instant) -> TransactionDetails<Hash> {
+	details(&tx.transaction, true, Vec::new(), tx.imported_at, now)
+}
+
+fn future_details<Hash: Clone, Ex>(tx: &WaitingTransaction<Hash, Ex>, now: Instant) -> TransactionDetails<Hash> {
+	let mut missing_tags = tx.missing_tags.iter().cloned().collect::<Vec<_>>();
+	missing_tags.sort();
+	details(&tx.transaction, false, missing_tags, tx.imported_at, now)
+}
+
 /// Queue limits
 #[derive(Debug, Clone)]
 pub struct Limit {
@@ -543,6 +734,25 @@ impl Limit {
 	}
 }
 
+/// Limits of the transactions of a single sender, across both queues.
+///
+/// The sender of a transaction is identified by the leading bytes of the first tag it provides,
+/// e.g. the account in the `(account, nonce)` tag of `frame_system::CheckNonce`.
+#[derive(Debug, Clone)]
+pub struct SenderLimit {
+	/// Number of leading bytes of the first provided tag that identify the sender.
+	pub tag_prefix: usize,
+	/// Limits of the transactions of each sender.
+	pub limit: Limit,
+}
+
+impl SenderLimit {
+	/// Returns the bytes identifying the sender of the transaction, if it provides any tag.
+	pub fn sender_of<'a, Hash, Ex>(&self, tx: &'a Transaction<Hash, Ex>) -> Option<&'a [u8]> {
+		tx.provides.first().map(|tag| &tag[..self.tag_prefix.min(tag.len())])
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1201,4 +1411,131 @@ source: TransactionSource::External, requires: [03,02], provides: [04], data: [4
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	fn limit(count: usize) -> Limit {
+		Limit { count, total_bytes: 1024 }
+	}
+
+	fn tx(hash: u64, priority: u64, requires: Vec<Tag>, provides: Vec<Tag>) -> Transaction<u64, Vec<u8>> {
+		Transaction {
+			data: vec![hash as u8],
+			bytes: 1,
+			hash,
+			priority,
+			valid_till: 64u64,
+			requires,
+			provides,
+			propagate: true,
+			source: Source::External,
+		}
+	}
+
+	#[test]
+	fn should_evict_lowest_priority_transactions_first() {
+		// given
+		let mut pool = pool();
+		pool.import(tx(1, 5, vec![], vec![vec![1]])).unwrap();
+		pool.import(tx(2, 1, vec![], vec![vec![2]])).unwrap();
+		pool.import(tx(3, 5, vec![], vec![vec![3]])).unwrap();
+
+		// when
+		let removed = pool.enforce_limits(&limit(2), &limit(10));
+
+		// then
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![2]);
+
+		// when
+		let removed = pool.enforce_limits(&limit(1), &limit(10));
+
+		// then the oldest one goes among equal priorities
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![1]);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![3]);
+		assert_eq!(pool.status().evicted, PoolEvictions {
+			ready_limit: 2,
+			future_limit: 0,
+			sender_limit: 0,
+		});
+	}
+
+	#[test]
+	fn should_evict_newest_transactions_of_sender_over_limit() {
+		// given
+		let mut pool = BasePool::new(false, Some(SenderLimit {
+			tag_prefix: 1,
+			limit: limit(2),
+		}));
+		pool.import(tx(10, 5, vec![], vec![vec![1, 0]])).unwrap();
+		pool.import(tx(11, 5, vec![vec![1, 0]], vec![vec![1, 1]])).unwrap();
+		pool.import(tx(12, 5, vec![vec![1, 1]], vec![vec![1, 2]])).unwrap();
+		pool.import(tx(13, 5, vec![vec![1, 4]], vec![vec![1, 5]])).unwrap();
+		pool.import(tx(20, 5, vec![], vec![vec![2, 0]])).unwrap();
+		assert_eq!(pool.future.len(), 1);
+		assert!(pool.is_sender_limit_exceeded());
+
+		// when
+		let removed = pool.enforce_limits(&limit(10), &limit(10));
+
+		// then the future transaction goes first
+		assert_eq!(removed.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![13, 12]);
+		let mut ready = pool.ready().map(|tx| tx.hash).collect::<Vec<_>>();
+		ready.sort();
+		assert_eq!(ready, vec![10, 11, 20]);
+		assert_eq!(pool.status().evicted.sender_limit, 2);
+		assert!(!pool.is_sender_limit_exceeded());
+	}
+
+	#[test]
+	fn should_track_sender_usage_as_transactions_leave_the_pool() {
+		// given
+		let mut pool = BasePool::new(false, Some(SenderLimit {
+			tag_prefix: 1,
+			limit: limit(1),
+		}));
+		pool.import(tx(10, 5, vec![], vec![vec![1, 0]])).unwrap();
+		assert!(!pool.is_sender_limit_exceeded());
+		pool.import(tx(11, 5, vec![vec![1, 0]], vec![vec![1, 1]])).unwrap();
+		assert!(pool.is_sender_limit_exceeded());
+
+		// when
+		pool.prune_tags(vec![vec![1, 0]]);
+
+		// then
+		assert!(!pool.is_sender_limit_exceeded());
+
+		// when
+		pool.import(tx(12, 5, vec![vec![1, 3]], vec![vec![1, 4]])).unwrap();
+		assert!(pool.is_sender_limit_exceeded());
+		pool.clear_future();
+
+		// then
+		assert!(!pool.is_sender_limit_exceeded());
+		assert_eq!(pool.senders.len(), 1);
+	}
+
+	#[test]
+	fn should_inspect_ready_and_future_transactions() {
+		// given
+		let mut pool = pool();
+		pool.import(tx(1, 5, vec![], vec![vec![1]])).unwrap();
+		pool.import(tx(2, 3, vec![vec![3], vec![2]], vec![vec![4]])).unwrap();
+
+		// when
+		let mut details = pool.inspect();
+		details.sort_by_key(|tx| tx.hash);
+
+		// then
+		assert_eq!(details.len(), 2);
+		assert_eq!(details[0].hash, 1);
+		assert!(details[0].is_ready);
+		assert!(details[0].missing_tags.is_empty());
+		assert_eq!(details[1].hash, 2);
+		assert_eq!(details[1].priority, 3);
+		assert!(!details[1].is_ready);
+		assert_eq!(details[1].missing_tags, vec![vec![2], vec![3]]);
+		assert_eq!(details[1].source, Source::External);
+		assert_eq!(details[1].valid_till, 64);
+		assert_eq!(pool.inspect_by_hash(&1).map(|tx| tx.is_ready), Some(true));
+		assert_eq!(pool.inspect_by_hash(&2).map(|tx| tx.missing_tags), Some(vec![vec![2], vec![3]]));
+		assert_eq!(pool.inspect_by_hash(&3), None);
+	}
 }
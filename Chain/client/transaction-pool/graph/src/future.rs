@@ -226,6 +226,16 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 			.fold(None, f)
 	}
 
+	/// Returns iterator over all future transactions, along with what they wait for.
+	pub fn waiting(&self) -> impl Iterator<Item=&WaitingTransaction<Hash, Ex>> {
+		self.waiting.values()
+	}
+
+	/// Returns the future transaction with given hash, along with what it waits for.
+	pub fn waiting_by_hash(&self, hash: &Hash) -> Option<&WaitingTransaction<Hash, Ex>> {
+		self.waiting.get(hash)
+	}
+
 	/// Returns iterator over all future transactions
 	pub fn all(&self) -> impl Iterator<Item=&Transaction<Hash, Ex>> {
 		self.waiting.values().map(|waiting| &*waiting.transaction)
@@ -39,6 +39,6 @@ pub mod watcher;
 
 pub use self::base_pool::Transaction;
 pub use self::pool::{
-	Pool, Options, ChainApi, EventStream, ExtrinsicFor, ExtrinsicHash,
+	Pool, Options, BanPolicy, ChainApi, EventStream, ExtrinsicFor, ExtrinsicHash,
 	BlockHash, NumberFor, TransactionFor, ValidatedTransaction,
 };
@@ -19,6 +19,7 @@
 use std::{
 	collections::HashMap,
 	sync::Arc,
+	time::Duration,
 };
 
 use crate::{base_pool as base, watcher::Watcher};
@@ -107,6 +108,12 @@ pub struct Options {
 	pub future: base::Limit,
 	/// Reject future transactions.
 	pub reject_future_transactions: bool,
+	/// Limits of the transactions of a single sender, `None` to only limit the queues.
+	pub sender: Option<base::SenderLimit>,
+	/// Bans of the transactions leaving the pool.
+	pub ban: BanPolicy,
+	/// Keep the ready and future transactions across restarts, in a file next to the database.
+	pub persist: bool,
 }
 
 impl Default for Options {
@@ -121,6 +128,36 @@ impl Default for Options {
 				total_bytes: 1 * 1024 * 1024,
 			},
 			reject_future_transactions: false,
+			sender: Some(base::SenderLimit {
+				// the size of an `AccountId32`
+				tag_prefix: 32,
+				limit: base::Limit {
+					count: 512,
+					total_bytes: 1280 * 1024,
+				},
+			}),
+			ban: Default::default(),
+			persist: false,
+		}
+	}
+}
+
+/// Which transactions are banned from re-entering the pool, and for how long.
+///
+/// Invalid and stale transactions are always banned.
+#[derive(Debug, Clone)]
+pub struct BanPolicy {
+	/// How long a transaction stays banned.
+	pub ban_time: Duration,
+	/// Ban the transactions evicted to keep the pool within its limits.
+	pub ban_evicted: bool,
+}
+
+impl Default for BanPolicy {
+	fn default() -> Self {
+		BanPolicy {
+			ban_time: Duration::from_secs(60 * 30),
+			ban_evicted: true,
 		}
 	}
 }
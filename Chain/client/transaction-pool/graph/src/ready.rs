@@ -30,6 +30,7 @@ use sp_runtime::transaction_validity::{
 	TransactionTag as Tag,
 };
 use sp_transaction_pool::error;
+use wasm_timer::Instant;
 
 use crate::{
 	base_pool::Transaction,
@@ -46,6 +47,9 @@ pub struct TransactionRef<Hash, Ex> {
 	pub transaction: Arc<Transaction<Hash, Ex>>,
 	/// Unique id when transaction was inserted into the pool.
 	pub insertion_id: u64,
+	/// Time the transaction entered the pool, possibly through the future queue.
+	#[ignore_malloc_size_of = "no heap allocation"]
+	pub imported_at: Instant,
 }
 
 impl<Hash, Ex> Clone for TransactionRef<Hash, Ex> {
@@ -53,6 +57,7 @@ impl<Hash, Ex> Clone for TransactionRef<Hash, Ex> {
 		TransactionRef {
 			transaction: self.transaction.clone(),
 			insertion_id: self.insertion_id,
+			imported_at: self.imported_at,
 		}
 	}
 }
@@ -180,6 +185,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 		self.insertion_id += 1;
 		let insertion_id = self.insertion_id;
 		let hash = tx.transaction.hash.clone();
+		let imported_at = tx.imported_at;
 		let transaction = tx.transaction;
 
 		let (replaced, unlocks) = self.replace_previous(&transaction)?;
@@ -209,6 +215,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 
 		let transaction = TransactionRef {
 			insertion_id,
+			imported_at,
 			transaction
 		};
 
@@ -235,6 +242,20 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			.fold(None, f)
 	}
 
+	/// Returns all ready transactions, in no particular order.
+	pub fn all(&self) -> Vec<TransactionRef<Hash, Ex>> {
+		self.ready
+			.read()
+			.values()
+			.map(|tx| tx.transaction.clone())
+			.collect()
+	}
+
+	/// Returns the ready transaction with given hash, along with when it entered the pool.
+	pub fn ref_by_hash(&self, hash: &Hash) -> Option<TransactionRef<Hash, Ex>> {
+		self.ready.read().get(hash).map(|tx| tx.transaction.clone())
+	}
+
 	/// Returns true if given hash is part of the queue.
 	pub fn contains(&self, hash: &Hash) -> bool {
 		self.ready.read().contains_key(hash)
@@ -720,25 +741,31 @@ mod tests {
 		assert!(TransactionRef {
 			transaction: Arc::new(with_priority(3, 3)),
 			insertion_id: 1,
+			imported_at: Instant::now(),
 		} > TransactionRef {
 			transaction: Arc::new(with_priority(2, 3)),
 			insertion_id: 2,
+			imported_at: Instant::now(),
 		});
 		// lower validity = better
 		assert!(TransactionRef {
 			transaction: Arc::new(with_priority(3, 2)),
 			insertion_id: 1,
+			imported_at: Instant::now(),
 		} > TransactionRef {
 			transaction: Arc::new(with_priority(3, 3)),
 			insertion_id: 2,
+			imported_at: Instant::now(),
 		});
 		// lower insertion_id = better
 		assert!(TransactionRef {
 			transaction: Arc::new(with_priority(3, 3)),
 			insertion_id: 1,
+			imported_at: Instant::now(),
 		} > TransactionRef {
 			transaction: Arc::new(with_priority(3, 3)),
 			insertion_id: 2,
+			imported_at: Instant::now(),
 		});
 	}
 }
@@ -34,7 +34,7 @@ use sp_runtime::{
 	traits::{self, SaturatedConversion},
 	transaction_validity::{TransactionTag as Tag, ValidTransaction, TransactionSource},
 };
-use sp_transaction_pool::{error, PoolStatus};
+use sp_transaction_pool::{error, PoolStatus, TransactionDetails};
 use wasm_timer::Instant;
 use futures::channel::mpsc::{channel, Sender};
 use retain_mut::RetainMut;
@@ -117,14 +117,18 @@ where
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, api: Arc<B>) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::new(
+			options.reject_future_transactions,
+			options.sender.clone(),
+		);
+		let rotator = PoolRotator::new(options.ban.ban_time);
 		ValidatedPool {
 			options,
 			listener: Default::default(),
 			api,
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
-			rotator: Default::default(),
+			rotator,
 		}
 	}
 
@@ -218,19 +222,25 @@ impl<B: ChainApi> ValidatedPool<B> {
 	}
 
 	fn enforce_limits(&self) -> HashSet<ExtrinsicHash<B>> {
-		let status = self.pool.read().status();
+		let (status, sender_exceeded) = {
+			let pool = self.pool.read();
+			(pool.status(), pool.is_sender_limit_exceeded())
+		};
 		let ready_limit = &self.options.ready;
 		let future_limit = &self.options.future;
+		let sender_limit = self.options.sender.as_ref();
 
 		log::debug!(target: "txpool", "Pool Status: {:?}", status);
 		if ready_limit.is_exceeded(status.ready, status.ready_bytes)
 			|| future_limit.is_exceeded(status.future, status.future_bytes)
+			|| sender_exceeded
 		{
 			log::debug!(
 				target: "txpool",
-				"Enforcing limits ({}/{}kB ready, {}/{}kB future",
+				"Enforcing limits ({}/{}kB ready, {}/{}kB future, {:?} per sender)",
 				ready_limit.count, ready_limit.total_bytes / 1024,
 				future_limit.count, future_limit.total_bytes / 1024,
+				sender_limit.map(|sender| (sender.limit.count, sender.limit.total_bytes / 1024)),
 			);
 
 			// clean up the pool
@@ -238,8 +248,9 @@ impl<B: ChainApi> ValidatedPool<B> {
 				let mut pool = self.pool.write();
 				let removed = pool.enforce_limits(ready_limit, future_limit)
 					.into_iter().map(|x| x.hash.clone()).collect::<HashSet<_>>();
-				// ban all removed transactions
-				self.rotator.ban(&Instant::now(), removed.iter().map(|x| x.clone()));
+				if self.options.ban.ban_evicted {
+					self.rotator.ban(&Instant::now(), removed.iter().map(|x| x.clone()));
+				}
 				removed
 			};
 			if !removed.is_empty() {
@@ -591,6 +602,21 @@ impl<B: ChainApi> ValidatedPool<B> {
 		self.pool.read().ready()
 	}
 
+	/// Get the source and extrinsic of every transaction in the future queue.
+	pub fn futures(&self) -> Vec<(TransactionSource, ExtrinsicFor<B>)> {
+		self.pool.read().futures().map(|tx| (tx.source, tx.data.clone())).collect()
+	}
+
+	/// Returns the details of every transaction in the pool.
+	pub fn inspect(&self) -> Vec<TransactionDetails<ExtrinsicHash<B>>> {
+		self.pool.read().inspect()
+	}
+
+	/// Returns the details of the transaction with given hash, if it is in the pool.
+	pub fn inspect_by_hash(&self, hash: &ExtrinsicHash<B>) -> Option<TransactionDetails<ExtrinsicHash<B>>> {
+		self.pool.read().inspect_by_hash(hash)
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> PoolStatus {
 		self.pool.read().status()
@@ -25,6 +25,7 @@
 mod api;
 mod revalidation;
 mod metrics;
+mod persistence;
 
 pub mod error;
 
@@ -34,7 +35,10 @@ pub mod testing;
 pub use sc_transaction_graph as txpool;
 pub use crate::api::{FullChainApi, LightChainApi};
 
-use std::{collections::{HashMap, HashSet}, sync::Arc, pin::Pin};
+use std::{
+	collections::{HashMap, HashSet}, path::PathBuf, pin::Pin, time::Duration,
+	sync::{Arc, atomic::{AtomicBool, Ordering}},
+};
 use futures::{prelude::*, future::{self, ready}, channel::oneshot};
 use parking_lot::Mutex;
 
@@ -44,15 +48,16 @@ use sp_runtime::{
 };
 use sp_core::traits::SpawnNamed;
 use sp_transaction_pool::{
-	TransactionPool, PoolStatus, ImportNotificationStream, TxHash, TransactionFor,
+	TransactionPool, PoolStatus, TransactionDetails, ImportNotificationStream, TxHash, TransactionFor,
 	TransactionStatusStreamFor, MaintainedTransactionPool, PoolFuture, ChainEvent,
 	TransactionSource,
 };
-use sc_transaction_graph::{ChainApi, ExtrinsicHash};
+use sc_transaction_graph::{ChainApi, ExtrinsicHash, ExtrinsicFor};
 use wasm_timer::Instant;
 
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use crate::metrics::MetricsLink as PrometheusMetrics;
+use crate::persistence::Persistence;
 
 type BoxedReadyIterator<Hash, Data> = Box<
 	dyn Iterator<Item=Arc<sc_transaction_graph::base_pool::Transaction<Hash, Data>>> + Send
@@ -64,6 +69,9 @@ type ReadyIteratorFor<PoolApi> = BoxedReadyIterator<
 
 type PolledIterator<PoolApi> = Pin<Box<dyn Future<Output=ReadyIteratorFor<PoolApi>> + Send>>;
 
+/// How often the transactions of a persisted pool are written to disk, if the pool changed.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(30);
+
 /// A transaction pool for a full node.
 pub type FullPool<Block, Client> = BasicPool<FullChainApi<Client, Block>, Block>;
 /// A transaction pool for a light node.
@@ -81,6 +89,8 @@ pub struct BasicPool<PoolApi, Block>
 	revalidation_queue: Arc<revalidation::RevalidationQueue<PoolApi>>,
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
+	persistence: Arc<Mutex<Option<Persistence>>>,
+	persistence_outdated: Arc<AtomicBool>,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -174,6 +184,8 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 				revalidation_strategy: Arc::new(Mutex::new(RevalidationStrategy::Always)),
 				ready_poll: Default::default(),
 				metrics: Default::default(),
+				persistence: Default::default(),
+				persistence_outdated: Default::default(),
 			},
 			background_task,
 			notifier,
@@ -214,6 +226,8 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 			)),
 			ready_poll: Default::default(),
 			metrics: PrometheusMetrics::new(prometheus),
+			persistence: Default::default(),
+			persistence_outdated: Default::default(),
 		}
 	}
 
@@ -221,6 +235,71 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 	pub fn pool(&self) -> &Arc<sc_transaction_graph::Pool<PoolApi>> {
 		&self.pool
 	}
+
+	/// Keep the ready and future transactions in the file at `path` across restarts.
+	///
+	/// The transactions persisted by a previous run are validated again at `at` and submitted
+	/// back to the pool, the ones that are no longer valid are dropped. From then on the file
+	/// is rewritten every `PERSIST_INTERVAL` if a block was finalized meanwhile, and when the
+	/// pool is dropped.
+	///
+	/// The returned future restores the transactions, then writes them until the pool is
+	/// dropped. It does blocking file I/O and should be spawned as a blocking task.
+	pub fn persist(
+		&self,
+		path: PathBuf,
+		at: BlockId<Block>,
+	) -> Pin<Box<dyn Future<Output=()> + Send>> {
+		self.persist_with_interval(path, at, wasm_timer::Interval::new(PERSIST_INTERVAL))
+	}
+
+	fn persist_with_interval(
+		&self,
+		path: PathBuf,
+		at: BlockId<Block>,
+		interval: impl Stream<Item=()> + Send + 'static,
+	) -> Pin<Box<dyn Future<Output=()> + Send>> {
+		let pool = self.pool.clone();
+		let metrics = self.metrics.clone();
+		let slot = Arc::downgrade(&self.persistence);
+		let outdated = self.persistence_outdated.clone();
+
+		async move {
+			let persistence = Persistence::new(path);
+			restore_transactions(&*pool, &persistence, &at, &metrics).await;
+			// Only write the file once its content made it back to the pool.
+			match slot.upgrade() {
+				Some(slot) => *slot.lock() = Some(persistence),
+				None => return,
+			}
+
+			let pool = Arc::downgrade(&pool);
+			futures::pin_mut!(interval);
+			while interval.next().await.is_some() {
+				let (pool, slot) = match (pool.upgrade(), slot.upgrade()) {
+					(Some(pool), Some(slot)) => (pool, slot),
+					_ => return,
+				};
+				if outdated.swap(false, Ordering::Relaxed) {
+					if let Some(persistence) = slot.lock().as_ref() {
+						persist_transactions(&*pool, persistence);
+					}
+				}
+			}
+		}.boxed()
+	}
+}
+
+impl<PoolApi, Block> Drop for BasicPool<PoolApi, Block>
+	where
+		Block: BlockT,
+		PoolApi: ChainApi<Block=Block>,
+{
+	fn drop(&mut self) {
+		if let Some(persistence) = self.persistence.lock().as_ref() {
+			persist_transactions(&*self.pool, persistence);
+		}
+	}
 }
 
 impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
@@ -291,6 +370,14 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		self.pool.validated_pool().status()
 	}
 
+	fn inspect(&self) -> Vec<TransactionDetails<TxHash<Self>>> {
+		self.pool.validated_pool().inspect()
+	}
+
+	fn inspect_by_hash(&self, hash: &TxHash<Self>) -> Option<TransactionDetails<TxHash<Self>>> {
+		self.pool.validated_pool().inspect_by_hash(hash)
+	}
+
 	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
 		self.pool.validated_pool().import_notification_stream()
 	}
@@ -517,6 +604,98 @@ impl<N: Clone + Copy + AtLeast32Bit> RevalidationStatus<N> {
 	}
 }
 
+/// Submit the transactions read from `persistence` back to the pool.
+async fn restore_transactions<Api: ChainApi>(
+	pool: &sc_transaction_graph::Pool<Api>,
+	persistence: &Persistence,
+	at: &BlockId<Api::Block>,
+	metrics: &PrometheusMetrics,
+) {
+	let transactions = match persistence.load::<ExtrinsicFor<Api>>() {
+		Ok(transactions) => transactions,
+		Err(e) => {
+			log::warn!(
+				target: "txpool",
+				"Failed to read the transaction pool from {}: {}",
+				persistence.path().display(),
+				e,
+			);
+			return
+		}
+	};
+	if transactions.is_empty() {
+		return
+	}
+
+	// The transactions are submitted in batches sharing the same source.
+	let mut batches: Vec<(TransactionSource, Vec<ExtrinsicFor<Api>>)> = Vec::new();
+	for (source, xt) in transactions {
+		match batches.iter_mut().find(|(batch_source, _)| *batch_source == source) {
+			Some((_, xts)) => xts.push(xt),
+			None => batches.push((source, vec![xt])),
+		}
+	}
+
+	let mut restored = 0;
+	let mut dropped = 0;
+	for (source, xts) in batches {
+		let count = xts.len() as u64;
+		match pool.submit_at(at, source, xts).await {
+			Ok(results) => for result in results {
+				match result {
+					Ok(_) => restored += 1,
+					Err(e) => {
+						log::trace!(target: "txpool", "Dropping persisted transaction: {:?}", e);
+						dropped += 1;
+					}
+				}
+			},
+			Err(e) => {
+				log::debug!(target: "txpool", "Error restoring persisted transactions: {:?}", e);
+				dropped += count;
+			}
+		}
+	}
+
+	log::info!(
+		target: "txpool",
+		"Restored {} transactions from the previous run, {} no longer valid",
+		restored,
+		dropped,
+	);
+	metrics.report(|metrics| {
+		metrics.persisted_transactions_restored.inc_by(restored);
+		metrics.persisted_transactions_dropped.inc_by(dropped);
+	});
+}
+
+/// Write the ready and future transactions of the pool to `persistence`.
+fn persist_transactions<Api: ChainApi>(
+	pool: &sc_transaction_graph::Pool<Api>,
+	persistence: &Persistence,
+) {
+	let validated_pool = pool.validated_pool();
+	let mut transactions = validated_pool.ready()
+		.map(|tx| (tx.source, tx.data.clone()))
+		.collect::<Vec<_>>();
+	transactions.extend(validated_pool.futures());
+
+	match persistence.save(&transactions) {
+		Ok(()) => log::trace!(
+			target: "txpool",
+			"Persisted {} transactions to {}",
+			transactions.len(),
+			persistence.path().display(),
+		),
+		Err(e) => log::warn!(
+			target: "txpool",
+			"Failed to persist the transaction pool to {}: {}",
+			persistence.path().display(),
+			e,
+		),
+	}
+}
+
 /// Prune the known txs for the given block.
 async fn prune_known_txs_for_block<Block: BlockT, Api: ChainApi<Block = Block>>(
 	block_id: BlockId<Block>,
@@ -697,6 +876,7 @@ impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 			}
 			ChainEvent::Finalized { hash } => {
 				let pool = self.pool.clone();
+				let outdated = self.persistence_outdated.clone();
 				async move {
 					if let Err(e) = pool.validated_pool().on_block_finalized(hash).await {
 						log::warn!(
@@ -705,6 +885,8 @@ impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 							e, hash
 						)
 					}
+
+					outdated.store(true, Ordering::Relaxed);
 				}.boxed()
 			}
 		}
@@ -49,6 +49,8 @@ pub struct Metrics {
 	pub validations_invalid: Counter<U64>,
 	pub block_transactions_pruned: Counter<U64>,
 	pub block_transactions_resubmitted: Counter<U64>,
+	pub persisted_transactions_restored: Counter<U64>,
+	pub persisted_transactions_dropped: Counter<U64>,
 }
 
 impl Metrics {
@@ -82,6 +84,20 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			persisted_transactions_restored: register(
+				Counter::new(
+					"sub_txpool_persisted_transactions_restored",
+					"Total number of persisted transactions that were restored into the pool on startup",
+				)?,
+				registry,
+			)?,
+			persisted_transactions_dropped: register(
+				Counter::new(
+					"sub_txpool_persisted_transactions_dropped",
+					"Total number of persisted transactions that were dropped as no longer valid on startup",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -0,0 +1,127 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-disk copy of the transaction pool.
+//!
+//! The ready and future transactions are written as a whole to a single file, along with their
+//! source. The file holds the extrinsics only: on restore they go through validation again,
+//! which drops the ones that were included or became invalid in the meantime.
+
+use std::{fs, io, path::{Path, PathBuf}};
+
+use codec::{Decode, Encode};
+use sp_runtime::transaction_validity::TransactionSource;
+
+/// Version of the file format, written as its first byte.
+const VERSION: u8 = 1;
+
+/// A file holding the transactions of the pool.
+pub struct Persistence {
+	path: PathBuf,
+}
+
+impl Persistence {
+	/// Create a new instance storing the transactions at `path`.
+	pub fn new(path: PathBuf) -> Self {
+		Persistence { path }
+	}
+
+	/// Path of the file.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Read the persisted transactions.
+	///
+	/// A missing file holds no transactions.
+	pub fn load<Xt: Decode>(&self) -> io::Result<Vec<(TransactionSource, Xt)>> {
+		let data = match fs::read(&self.path) {
+			Ok(data) => data,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+			Err(e) => return Err(e),
+		};
+
+		match data.split_first() {
+			Some((&VERSION, mut transactions)) => Decode::decode(&mut transactions)
+				.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.what())),
+			Some((version, _)) => Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("Unsupported transaction pool file version {}", version),
+			)),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Replace the persisted transactions.
+	///
+	/// The file is written next to the previous one and then renamed over it, so that it is
+	/// never left half-written.
+	pub fn save<Xt: Encode>(&self, transactions: &[(TransactionSource, Xt)]) -> io::Result<()> {
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+
+		let mut data = vec![VERSION];
+		transactions.encode_to(&mut data);
+
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, data)?;
+		fs::rename(&tmp_path, &self.path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn missing_file_is_empty() {
+		let dir = tempfile::tempdir().unwrap();
+		let persistence = Persistence::new(dir.path().join("txpool"));
+
+		assert_eq!(persistence.load::<Vec<u8>>().unwrap(), Vec::new());
+	}
+
+	#[test]
+	fn saved_transactions_are_loaded() {
+		let dir = tempfile::tempdir().unwrap();
+		let persistence = Persistence::new(dir.path().join("chain").join("txpool"));
+		let transactions = vec![
+			(TransactionSource::External, vec![1u8, 2, 3]),
+			(TransactionSource::Local, vec![4u8]),
+		];
+
+		persistence.save(&transactions).unwrap();
+		assert_eq!(persistence.load::<Vec<u8>>().unwrap(), transactions);
+
+		persistence.save::<Vec<u8>>(&[]).unwrap();
+		assert_eq!(persistence.load::<Vec<u8>>().unwrap(), Vec::new());
+	}
+
+	#[test]
+	fn unknown_version_is_rejected() {
+		let dir = tempfile::tempdir().unwrap();
+		let persistence = Persistence::new(dir.path().join("txpool"));
+
+		fs::write(persistence.path(), &[VERSION + 1, 0]).unwrap();
+		assert_eq!(
+			persistence.load::<Vec<u8>>().unwrap_err().kind(),
+			io::ErrorKind::InvalidData,
+		);
+	}
+}
@@ -29,7 +29,7 @@ use substrate_test_runtime_client::{
 	ClientBlockImportExt,
 };
 use substrate_test_runtime_transaction_pool::{TestApi, uxt};
-use futures::{prelude::*, task::Poll};
+use futures::{prelude::*, stream, task::Poll};
 use codec::Encode;
 use std::collections::BTreeSet;
 use sc_client_api::client::BlockchainEvents;
@@ -1091,3 +1091,55 @@ fn pruning_a_transaction_should_remove_it_from_best_transaction() {
 	// returned a second time by the iterator.
 	assert!(iterator.next().is_none());
 }
+
+#[test]
+fn persisted_transactions_should_be_restored() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("txpool");
+
+	let (pool, _guard, _notifier) = maintained_pool();
+	block_on(pool.persist_with_interval(path.clone(), BlockId::number(0), stream::empty()));
+	block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 209))).unwrap();
+	block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 210))).unwrap();
+	block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Bob, 1))).unwrap();
+	assert_eq!(pool.status().ready, 2);
+	assert_eq!(pool.status().future, 1);
+
+	// Dropping the pool writes its transactions to disk.
+	drop(pool);
+
+	// `210` became invalid while the node was down.
+	let (pool, _guard, _notifier) = maintained_pool();
+	pool.api.add_invalid(&uxt(Alice, 210));
+	block_on(pool.persist_with_interval(path, BlockId::number(0), stream::empty()));
+
+	let ready: Vec<_> = pool.ready().map(|a| a.data.transfer().nonce).collect();
+	assert_eq!(ready, vec![209]);
+	assert_eq!(pool.status().future, 1);
+}
+
+#[test]
+fn persisted_transactions_should_be_written_after_finalization() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("txpool");
+	let persisted = || persistence::Persistence::new(path.clone())
+		.load::<Extrinsic>()
+		.unwrap()
+		.len();
+
+	let (pool, _guard, _notifier) = maintained_pool();
+	block_on(pool.persist_with_interval(path.clone(), BlockId::number(0), stream::empty()));
+	block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 209))).unwrap();
+
+	// Nothing is written while no block was finalized.
+	block_on(pool.persist_with_interval(path.clone(), BlockId::number(0), stream::once(ready(()))));
+	assert_eq!(persisted(), 0);
+
+	let header = pool.api.push_block(1, vec![]);
+	block_on(pool.maintain(ChainEvent::Finalized { hash: header.hash() }));
+	assert_eq!(persisted(), 0);
+
+	// The next tick writes the pool.
+	block_on(pool.persist_with_interval(path.clone(), BlockId::number(0), stream::once(ready(()))));
+	assert_eq!(persisted(), 1);
+}
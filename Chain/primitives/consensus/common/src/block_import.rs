@@ -113,6 +113,24 @@ pub struct BlockCheckParams<Block: BlockT> {
 	pub import_existing: bool,
 }
 
+/// Whole state of a block, imported instead of built by executing the block.
+#[derive(PartialEq, Eq, Clone)]
+pub struct ImportedState<Block: BlockT> {
+	/// Hash of the block the state belongs to.
+	pub block: Block::Hash,
+	/// Storage keys and values of the state.
+	pub state: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<Block: BlockT> std::fmt::Debug for ImportedState<Block> {
+	fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+		fmt.debug_struct("ImportedState")
+			.field("block", &self.block)
+			.field("entries", &self.state.len())
+			.finish()
+	}
+}
+
 /// Data required to import a Block.
 #[non_exhaustive]
 pub struct BlockImportParams<Block: BlockT, Transaction> {
@@ -166,6 +184,9 @@ pub struct BlockImportParams<Block: BlockT, Transaction> {
 	pub import_existing: bool,
 	/// Cached full header hash (with post-digests applied).
 	pub post_hash: Option<Block::Hash>,
+	/// The whole state of the block, imported instead of executing the block on the parent's
+	/// state. Requires the header's state root to match.
+	pub imported_state: Option<ImportedState<Block>>,
 }
 
 impl<Block: BlockT, Transaction> BlockImportParams<Block, Transaction> {
@@ -187,6 +208,7 @@ impl<Block: BlockT, Transaction> BlockImportParams<Block, Transaction> {
 			allow_missing_state: false,
 			import_existing: false,
 			post_hash: None,
+			imported_state: None,
 		}
 	}
 
@@ -227,6 +249,7 @@ impl<Block: BlockT, Transaction> BlockImportParams<Block, Transaction> {
 			fork_choice: self.fork_choice,
 			import_existing: self.import_existing,
 			post_hash: self.post_hash,
+			imported_state: self.imported_state,
 		}
 	}
 
@@ -688,6 +688,59 @@ where
 	Ok(proving_backend.extract_proof())
 }
 
+/// Generate a proof of the storage entries following `start_at` in key order, covering at
+/// least `size_limit` bytes of keys and values unless the state ends first. `None` starts at
+/// the first key. Returns the proof and the number of entries it covers.
+pub fn prove_range_read_with_size<B, H>(
+	mut backend: B,
+	size_limit: usize,
+	start_at: Option<&[u8]>,
+) -> Result<(StorageProof, u32), Box<dyn Error>>
+where
+	B: Backend<H>,
+	H: Hasher,
+	H::Out: Ord + Codec,
+{
+	let trie_backend = backend.as_trie_backend()
+		.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<dyn Error>)?;
+	prove_range_read_with_size_on_trie_backend(trie_backend, size_limit, start_at)
+}
+
+/// Generate storage range proof on pre-created trie backend.
+pub fn prove_range_read_with_size_on_trie_backend<S, H>(
+	trie_backend: &TrieBackend<S, H>,
+	size_limit: usize,
+	start_at: Option<&[u8]>,
+) -> Result<(StorageProof, u32), Box<dyn Error>>
+where
+	S: trie_backend_essence::TrieBackendStorage<H>,
+	H: Hasher,
+	H::Out: Ord + Codec,
+{
+	let proving_backend = proving_backend::ProvingBackend::<_, H>::new(trie_backend);
+	let mut count = 0;
+	let mut size = 0;
+	let mut next_key = match start_at {
+		Some(start_at) => proving_backend.next_storage_key(start_at)
+			.map_err(|e| Box::new(e) as Box<dyn Error>)?,
+		None => Some(Vec::new()),
+	};
+	while let Some(key) = next_key {
+		if let Some(value) = proving_backend.storage(&key)
+			.map_err(|e| Box::new(e) as Box<dyn Error>)?
+		{
+			count += 1;
+			size += key.len() + value.len();
+		}
+		if size >= size_limit {
+			break;
+		}
+		next_key = proving_backend.next_storage_key(&key)
+			.map_err(|e| Box::new(e) as Box<dyn Error>)?;
+	}
+	Ok((proving_backend.extract_proof(), count))
+}
+
 /// Check storage read proof, generated by `prove_read` call.
 pub fn read_proof_check<H, I>(
 	root: H::Out,
@@ -735,6 +788,42 @@ where
 	Ok(result)
 }
 
+/// Check storage range proof, generated by `prove_range_read_with_size` call.
+///
+/// Returns the entries following `start_at` that the proof covers, in key order, and whether
+/// they reach the last key of the state. Entries are only returned up to the first trie node
+/// missing from the proof, so a proof can't skip keys.
+pub fn read_range_proof_check<H>(
+	root: H::Out,
+	proof: StorageProof,
+	start_at: Option<&[u8]>,
+) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool), Box<dyn Error>>
+where
+	H: Hasher,
+	H::Out: Ord + Codec,
+{
+	let proving_backend = create_proof_check_backend::<H>(root, proof)?;
+	let mut entries = Vec::new();
+	let mut next_key = match start_at {
+		Some(start_at) => proving_backend.next_storage_key(start_at),
+		None => Ok(Some(Vec::new())),
+	};
+	loop {
+		let key = match next_key {
+			Ok(Some(key)) => key,
+			Ok(None) => return Ok((entries, true)),
+			// the proof ends before the state does.
+			Err(_) => return Ok((entries, false)),
+		};
+		match proving_backend.storage(&key) {
+			Ok(Some(value)) => entries.push((key.clone(), value)),
+			Ok(None) => {},
+			Err(_) => return Ok((entries, false)),
+		}
+		next_key = proving_backend.next_storage_key(&key);
+	}
+}
+
 /// Check storage read proof on pre-created proving backend.
 pub fn read_proof_check_on_proving_backend<H>(
 	proving_backend: &TrieBackend<MemoryDB<H>, H>,
@@ -1286,6 +1375,48 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn prove_range_read_and_proof_check_works() {
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+		let mut expected = remote_backend.pairs();
+		expected.sort();
+
+		// fetch the whole state in chunks of at least one entry
+		let mut fetched = Vec::new();
+		let mut complete = false;
+		while !complete {
+			let start_at = fetched.last().map(|(k, _): &(Vec<u8>, Vec<u8>)| k.clone());
+			let (proof, count) = prove_range_read_with_size(
+				trie_backend::tests::test_trie(),
+				1,
+				start_at.as_ref().map(|k| &k[..]),
+			).unwrap();
+			assert_eq!(count, 1);
+			let (entries, last) = read_range_proof_check::<BlakeTwo256>(
+				remote_root,
+				proof,
+				start_at.as_ref().map(|k| &k[..]),
+			).unwrap();
+			assert!(!entries.is_empty());
+			fetched.extend(entries);
+			complete = last;
+		}
+		assert_eq!(fetched, expected);
+
+		// the whole state fits in a single proof
+		let (proof, count) = prove_range_read_with_size(
+			trie_backend::tests::test_trie(),
+			usize::max_value(),
+			None,
+		).unwrap();
+		assert_eq!(count as usize, expected.len());
+		let (entries, complete) = read_range_proof_check::<BlakeTwo256>(remote_root, proof, None)
+			.unwrap();
+		assert_eq!(entries, expected);
+		assert!(complete);
+	}
+
 	#[test]
 	fn child_storage_uuid() {
 
@@ -22,6 +22,7 @@ use std::{
 	hash::Hash,
 	sync::Arc,
 	pin::Pin,
+	time::Duration,
 };
 use futures::{Future, Stream};
 use serde::{Deserialize, Serialize};
@@ -34,7 +35,8 @@ use sp_runtime::{
 };
 
 /// Transaction pool status.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PoolStatus {
 	/// Number of transactions in the ready queue.
 	pub ready: usize,
@@ -44,6 +46,8 @@ pub struct PoolStatus {
 	pub future: usize,
 	/// Sum of bytes of ready transaction encodings.
 	pub future_bytes: usize,
+	/// Number of transactions evicted so far to keep the pool within its limits.
+	pub evicted: PoolEvictions,
 }
 
 impl PoolStatus {
@@ -53,6 +57,53 @@ impl PoolStatus {
 	}
 }
 
+/// Number of transactions evicted from the pool, by reason.
+///
+/// Transactions that depend on an evicted one are evicted along with it and counted under the
+/// same reason.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolEvictions {
+	/// Evicted because the ready queue exceeded its limits.
+	pub ready_limit: u64,
+	/// Evicted because the future queue exceeded its limits.
+	pub future_limit: u64,
+	/// Evicted because their sender exceeded its limits.
+	pub sender_limit: u64,
+}
+
+impl PoolEvictions {
+	/// Total number of evicted transactions.
+	pub fn total(&self) -> u64 {
+		self.ready_limit + self.future_limit + self.sender_limit
+	}
+}
+
+/// Details of a transaction in the pool, see [`TransactionPool::inspect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionDetails<Hash> {
+	/// Transaction hash.
+	pub hash: Hash,
+	/// Transaction priority (higher = better).
+	pub priority: TransactionPriority,
+	/// Block number at which the transaction becomes invalid.
+	pub valid_till: TransactionLongevity,
+	/// Tags provided by the transaction.
+	pub provides: Vec<TransactionTag>,
+	/// Tags required by the transaction.
+	pub requires: Vec<TransactionTag>,
+	/// Whether the transaction is in the ready queue, or waits in the future one.
+	pub is_ready: bool,
+	/// Required tags that are neither provided by the chain nor by another transaction in the
+	/// pool, empty for a ready transaction.
+	pub missing_tags: Vec<TransactionTag>,
+	/// Source of the transaction.
+	pub source: TransactionSource,
+	/// Time elapsed since the transaction entered the pool, including the time it spent in the
+	/// future queue before it became ready.
+	pub in_pool_for: Duration,
+}
+
 /// Possible transaction status events.
 ///
 /// This events are being emitted by `TransactionPool` watchers,
@@ -231,6 +282,13 @@ pub trait TransactionPool: Send + Sync {
 	/// Returns pool status.
 	fn status(&self) -> PoolStatus;
 
+	// *** RPC
+	/// Returns the details of every transaction in the pool, ready and future.
+	fn inspect(&self) -> Vec<TransactionDetails<TxHash<Self>>>;
+
+	/// Returns the details of the transaction with given hash, if it is in the pool.
+	fn inspect_by_hash(&self, hash: &TxHash<Self>) -> Option<TransactionDetails<TxHash<Self>>>;
+
 	// *** logging / RPC / networking
 	/// Return an event stream of transactions imported to the pool.
 	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>>;
@@ -77,6 +77,7 @@ where
 			DatabaseConfig::Custom(sp_database::as_database(db))
 		},
 		keystore: KeystoreConfig::InMemory,
+		keystore_allowed_key_types: None,
 		default_heap_pages: Default::default(),
 		dev_key_seed: Default::default(),
 		disable_grandpa: Default::default(),
@@ -1,5 +1,7 @@
 /* eslint-disable @typescript-eslint/camelcase */
 
+import types from '../../../types.json';
+
 export default {
     rpc: {
         auctionInformation: {
@@ -18,7 +20,7 @@ export default {
                 type: 'bool'
             },
             queryFormal: {
-                description: 'This function tries a query to the auction storage, if an auction is found under id, it will return said auction in its formal format.',
+                description: 'This function tries a query to the auction storage, if an auction is found under id, it will return said auction in its formal format, otherwise it fails with error 9004.',
                 params: [{
                         name: 'at',
                         type: 'Hash',
@@ -29,15 +31,21 @@ export default {
                         type: 'AuctionId',
                     }
                 ],
-                type: 'Option<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>'
+                type: 'UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>'
             },
             queryFormalAll: {
                 description: 'This function tries a full query to the auction storage, if n auctions are found, n auctions are returned in their formal formats.',
                 params: [{
-                    name: 'at',
-                    type: 'Hash',
-                    isOptional: true
-                }],
+                        name: 'at',
+                        type: 'Hash',
+                        isOptional: true
+                    },
+                    {
+                        name: 'page',
+                        type: 'Page',
+                        isOptional: true
+                    }
+                ],
                 type: 'Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>'
             },
             queryFormalAllStatus: {
@@ -50,12 +58,17 @@ export default {
                     {
                         name: 'active',
                         type: 'bool',
+                    },
+                    {
+                        name: 'page',
+                        type: 'Page',
+                        isOptional: true
                     }
                 ],
                 type: 'Option<Vec<UIAuctionInfo<AccountId, BlockNumber, GeneralInfo>>>'
             },
             queryInformal: {
-                description: 'This function tries a query to the auction storage, if an auction is found under id, it will return said auction in its informal format.',
+                description: 'This function tries a query to the auction storage, if an auction is found under id, it will return said auction in its informal format, otherwise it fails with error 9004.',
                 params: [{
                         name: 'at',
                         type: 'Hash',
@@ -66,15 +79,21 @@ export default {
                         type: 'AuctionId',
                     }
                 ],
-                type: 'Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>'
+                type: 'AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>'
             },
             queryInformalAll: {
                 description: 'This function tries a full query to the auction storage, if n auctions are found, n auctions are returned in their informal formats.',
                 params: [{
-                    name: 'at',
-                    type: 'Hash',
-                    isOptional: true
-                }, ],
+                        name: 'at',
+                        type: 'Hash',
+                        isOptional: true
+                    },
+                    {
+                        name: 'page',
+                        type: 'Page',
+                        isOptional: true
+                    }
+                ],
                 type: 'Option<Vec<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>'
             },
             queryInformalAllStatus: {
@@ -87,9 +106,84 @@ export default {
                     {
                         name: 'active',
                         type: 'bool',
+                    },
+                    {
+                        name: 'page',
+                        type: 'Page',
+                        isOptional: true
                     }
                 ],
                 type: 'Option<Vec<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>>'
+            },
+            parameters: {
+                description: 'This function returns the auction parameters currently in effect, a value of zero disables the respective rule. Balances are converted to u64 for display, like in the formal queries.',
+                params: [{
+                    name: 'at',
+                    type: 'Hash',
+                    isOptional: true
+                }, ],
+                type: 'AuctionParameters'
+            },
+            parameterBounds: {
+                description: 'This function returns the upper bounds governance is allowed to set the auction parameters to. Balances are converted to u64 for display, like in the formal queries.',
+                params: [{
+                    name: 'at',
+                    type: 'Hash',
+                    isOptional: true
+                }, ],
+                type: 'AuctionParameters'
+            },
+            terminalOccupancy: {
+                description: 'This function returns the berth windows of a terminal\'s slots which overlap the period from `from` up to `to`, along with the auctions the slots were first offered in, ordered by their start.',
+                params: [{
+                        name: 'at',
+                        type: 'Hash',
+                        isOptional: true
+                    },
+                    {
+                        name: 'terminal',
+                        type: 'AccountId',
+                    },
+                    {
+                        name: 'from',
+                        type: 'GeneralInfo',
+                    },
+                    {
+                        name: 'to',
+                        type: 'GeneralInfo',
+                    }
+                ],
+                type: 'Vec<(AuctionId, BerthWindow)>'
+            }
+        },
+        auctionStorage: {
+            auction: {
+                description: 'This function reads an auction straight from the auction storage, without going through the runtime API. Light clients fetch it from full nodes along with a proof checked against their headers.',
+                params: [{
+                        name: 'at',
+                        type: 'Hash',
+                        isOptional: true
+                    },
+                    {
+                        name: 'id',
+                        type: 'AuctionId',
+                    }
+                ],
+                type: 'Option<AuctionInfo<AccountId, Balance, BlockNumber, GeneralInfo>>'
+            },
+            queuedBids: {
+                description: 'This function reads the bids queued for an auction which is yet to start straight from the auction storage.',
+                params: [{
+                        name: 'at',
+                        type: 'Hash',
+                        isOptional: true
+                    },
+                    {
+                        name: 'id',
+                        type: 'AuctionId',
+                    }
+                ],
+                type: 'Vec<QueuedBid>'
             }
         },
         //	#[rpc(name = "system_dryRun", alias("system_dryRunAt"))]
@@ -121,47 +215,6 @@ export default {
         }
     },
 
-    types: {
-        Address: "AccountId",
-        LookupSource: "AccountId",
-        AuctionId: "u64",
-        Balance: "u128",
-        BalanceOf: "Balance",
-        GeneralInformationContainer: "u64",
-        GenInfo: "GeneralInformationContainer",
-        Status: "bool",
-        Currency: "Balance",
-        BlockNumber: "u32",
-        Hash: "H256",
-        QueuedBid: {
-            bid: "(AccountId, Balance)",
-            auction_id: "AuctionId"
-        },
-        AuctionCoreInfo: {
-            timestamp: "GeneralInformationContainer",
-            cargo: "(GeneralInformationContainer, GeneralInformationContainer)"
-        },
-        AuctionInfo: {
-            creator: "AccountId",
-            slot_origin: "AccountId",
-            bid: "Option<(AccountId, Balance)>",
-            core: "AuctionCoreInfo<GeneralInformationContainer>",
-            start: "BlockNumber",
-            end: "Option<BlockNumber>"
-        },
-        UIAuctionInfo: {
-            slot_owner: "AccountId",
-            slot_origin: "AccountId",
-            slot_time: "GeneralInformationContainer",
-            slot_num_cargo: "GeneralInformationContainer",
-            slot_num_teu: "GeneralInformationContainer",
-            auction_is_live: "bool",
-            auction_highest_bid: "Option<(AccountId, Balance)>",
-            auction_end_time: "Option<BlockNumber>"
-        },
-        OnNewBidResult: {
-            accept_bid: "bool",
-            auction_end: "Option<Option<BlockNumber>>"
-        }
-    }
+    // Generated from the runtime by `node-auction export-types -o Types/types.json`.
+    types
 };